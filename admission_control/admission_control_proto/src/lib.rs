@@ -7,7 +7,17 @@ use failure::prelude::*;
 use logger::prelude::*;
 use mempool::MempoolAddTransactionStatus;
 use proto_conv::{FromProto, IntoProto};
-use types::vm_error::VMStatus;
+use types::{
+    contract_event::ContractEvent,
+    transaction::{TransactionOutput, TransactionStatus, Version},
+    vm_error::VMStatus,
+    write_set::WriteSet,
+};
+
+/// Maximum number of events in a response of a WatchEvents stream. Each response proves the
+/// events of a GetEventsByEventAccessPath request of this limit, starting after the last event of
+/// the previous response.
+pub const WATCH_EVENTS_PAGE_SIZE: u64 = 100;
 
 /// AC response status of submit_transaction to clients.
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -125,3 +135,68 @@ impl FromProto for SubmitTransactionResponse {
         })
     }
 }
+
+/// Rust structure for SimulateTransactionResponse protobuf definition.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SimulateTransactionResponse {
+    /// Output of the execution of the transaction. It is not committed.
+    pub output: TransactionOutput,
+    /// The latest committed version, whose state the transaction was executed against.
+    pub version: Version,
+}
+
+impl IntoProto for SimulateTransactionResponse {
+    type ProtoType = crate::proto::admission_control::SimulateTransactionResponse;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        match self.output.status() {
+            TransactionStatus::Keep(vm_status) => {
+                proto.set_vm_status(vm_status.clone().into_proto());
+            }
+            TransactionStatus::Discard(vm_status) => {
+                proto.set_vm_status(vm_status.clone().into_proto());
+                proto.set_discarded(true);
+            }
+        }
+        proto.set_gas_used(self.output.gas_used());
+        proto.set_write_set(self.output.write_set().clone().into_proto());
+        proto.set_events(protobuf::RepeatedField::from_vec(
+            self.output
+                .events()
+                .iter()
+                .cloned()
+                .map(ContractEvent::into_proto)
+                .collect::<Vec<_>>(),
+        ));
+        proto.set_version(self.version);
+        proto
+    }
+}
+
+impl FromProto for SimulateTransactionResponse {
+    type ProtoType = crate::proto::admission_control::SimulateTransactionResponse;
+
+    fn from_proto(mut object: Self::ProtoType) -> Result<Self> {
+        let vm_status = VMStatus::from_proto(object.take_vm_status())?;
+        let status = if object.get_discarded() {
+            TransactionStatus::Discard(vm_status)
+        } else {
+            TransactionStatus::Keep(vm_status)
+        };
+        let events = object
+            .take_events()
+            .into_iter()
+            .map(ContractEvent::from_proto)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(SimulateTransactionResponse {
+            output: TransactionOutput::new(
+                WriteSet::from_proto(object.take_write_set())?,
+                events,
+                object.get_gas_used(),
+                status,
+            ),
+            version: object.get_version(),
+        })
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::admission_control_service::AdmissionControlService;
+use crate::{admission_control_service::AdmissionControlService, event_watcher::EventWatchConfig};
 use admission_control_proto::proto::admission_control_grpc;
 use config::config::NodeConfig;
 use debug_interface::{node_debug_service::NodeDebugService, proto::node_debug_interface_grpc};
@@ -95,6 +95,10 @@ impl AdmissionControlNode {
             self.node_config
                 .admission_control
                 .need_to_check_mempool_before_validation,
+            EventWatchConfig::from(&self.node_config.admission_control),
+            self.node_config
+                .admission_control
+                .max_concurrent_simulations,
         );
         let service = admission_control_grpc::create_admission_control(handle);
 
@@ -5,18 +5,29 @@
 //! from external clients (such as wallets) and performs necessary processing before sending them to
 //! next step.
 
-use crate::OP_COUNTERS;
+use crate::{
+    event_watcher::{EventWatchConfig, EventWatcher, EventWatcherPool},
+    request_counter::RequestCounter,
+    OP_COUNTERS,
+};
 use admission_control_proto::{
     proto::{
-        admission_control::{SubmitTransactionRequest, SubmitTransactionResponse},
+        admission_control::{
+            GetLedgerStatisticsRequest, GetLedgerStatisticsResponse, SimulateTransactionRequest,
+            SimulateTransactionResponse, SubmitTransactionRequest, SubmitTransactionResponse,
+            WatchEventsRequest,
+        },
         admission_control_grpc::AdmissionControl,
     },
     AdmissionControlStatus,
 };
 use failure::prelude::*;
-use futures::future::Future;
+use futures::{future::Future, sink::Sink, stream::Stream, sync::mpsc};
 use futures03::executor::block_on;
-use grpc_helpers::provide_grpc_response;
+use grpc_helpers::{
+    create_grpc_invalid_arg_status, default_reply_error_logger, provide_grpc_response,
+};
+use grpcio::{RpcStatus, RpcStatusCode, WriteFlags};
 use logger::prelude::*;
 use mempool::proto::{
     mempool::{AddTransactionWithValidationRequest, HealthCheckRequest},
@@ -31,8 +42,9 @@ use proto_conv::{FromProto, IntoProto};
 use std::sync::Arc;
 use storage_client::StorageRead;
 use types::{
+    ledger_statistics::LedgerStatistics,
     proto::get_with_proof::{UpdateToLatestLedgerRequest, UpdateToLatestLedgerResponse},
-    transaction::SignedTransaction,
+    transaction::{SignedTransaction, MAX_TRANSACTION_SIZE_IN_BYTES},
 };
 use vm_validator::vm_validator::{get_account_state, TransactionValidation};
 
@@ -52,6 +64,16 @@ pub struct AdmissionControlService<M, V> {
     /// Flag indicating whether we need to check mempool before validation, drop txn if check
     /// fails.
     need_to_check_mempool_before_validation: bool,
+    /// Settings of the event streams.
+    event_watch_config: EventWatchConfig,
+    /// Number of running event streams.
+    event_watchers: RequestCounter,
+    /// Serves the event streams.
+    event_watcher_pool: Arc<EventWatcherPool>,
+    /// Maximum number of concurrent transaction simulations, 0 if they are disabled.
+    max_concurrent_simulations: usize,
+    /// Number of running transaction simulations.
+    simulations: RequestCounter,
 }
 
 impl<M: 'static, V> AdmissionControlService<M, V>
@@ -65,12 +87,23 @@ where
         storage_read_client: Arc<dyn StorageRead>,
         vm_validator: Arc<V>,
         need_to_check_mempool_before_validation: bool,
+        event_watch_config: EventWatchConfig,
+        max_concurrent_simulations: usize,
     ) -> Self {
+        let event_watcher_pool = Arc::new(EventWatcherPool::new(
+            Arc::clone(&storage_read_client),
+            event_watch_config,
+        ));
         AdmissionControlService {
             mempool_client,
             storage_read_client,
             vm_validator,
             need_to_check_mempool_before_validation,
+            event_watch_config,
+            event_watchers: RequestCounter::default(),
+            event_watcher_pool,
+            max_concurrent_simulations,
+            simulations: RequestCounter::default(),
         }
     }
 
@@ -190,6 +223,80 @@ where
         }
     }
 
+    /// Execute the transaction through the VM against the latest state. Its output is returned
+    /// to the client but never committed nor sent to Mempool. Returns None if
+    /// max_concurrent_simulations simulations are running already.
+    pub(crate) fn simulate_transaction_inner(
+        &self,
+        req: SimulateTransactionRequest,
+    ) -> Result<Option<SimulateTransactionResponse>> {
+        let raw_txn_bytes_len = req.get_signed_txn().get_raw_txn_bytes().len();
+        if raw_txn_bytes_len > MAX_TRANSACTION_SIZE_IN_BYTES {
+            OP_COUNTERS.inc_by("simulate_txn.rejected.txn_too_large", 1);
+            bail!(
+                "Transaction of {} bytes is larger than {} bytes",
+                raw_txn_bytes_len,
+                MAX_TRANSACTION_SIZE_IN_BYTES
+            );
+        }
+        let signed_txn = SignedTransaction::from_proto(req.get_signed_txn().clone())?;
+        let _slot = match self.simulations.acquire(self.max_concurrent_simulations) {
+            Some(slot) => slot,
+            None => {
+                OP_COUNTERS.inc_by("simulate_txn.rejected.too_many_simulations", 1);
+                return Ok(None);
+            }
+        };
+        let (output, version) = self.vm_validator.simulate_transaction(signed_txn).wait()?;
+        OP_COUNTERS.inc_by("simulate_txn", 1);
+        Ok(Some(
+            admission_control_proto::SimulateTransactionResponse { output, version }.into_proto(),
+        ))
+    }
+
+    /// Start streaming the events requested. Returns the receiving end of the stream, or None if
+    /// too many streams are running already.
+    pub(crate) fn watch_events_inner(
+        &self,
+        req: WatchEventsRequest,
+    ) -> Result<Option<mpsc::Receiver<Result<UpdateToLatestLedgerResponse>>>> {
+        let watcher = EventWatcher::new(Arc::clone(&self.storage_read_client), req)?;
+        let slot = match self
+            .event_watchers
+            .acquire(self.event_watch_config.max_watchers)
+        {
+            Some(slot) => slot,
+            None => {
+                OP_COUNTERS.inc_by("watch_events.rejected.too_many_watchers", 1);
+                return Ok(None);
+            }
+        };
+        let (sender, receiver) = mpsc::channel(1);
+        self.event_watcher_pool.add(watcher, sender, slot)?;
+        OP_COUNTERS.inc_by("watch_events", 1);
+        Ok(Some(receiver))
+    }
+
+    /// Read the ledger counters from Storage. They are returned as is, without proof.
+    pub(crate) fn get_ledger_statistics_inner(
+        &self,
+        req: GetLedgerStatisticsRequest,
+    ) -> Result<GetLedgerStatisticsResponse> {
+        let ledger_statistics = self.storage_read_client.get_ledger_statistics(
+            req.get_start_version(),
+            req.get_end_version(),
+            req.get_limit(),
+        )?;
+        let mut resp = GetLedgerStatisticsResponse::new();
+        resp.set_ledger_statistics(protobuf::RepeatedField::from_vec(
+            ledger_statistics
+                .into_iter()
+                .map(LedgerStatistics::into_proto)
+                .collect::<Vec<_>>(),
+        ));
+        Ok(resp)
+    }
+
     /// Pass the UpdateToLatestLedgerRequest to Storage for read query.
     fn update_to_latest_ledger_inner(
         &self,
@@ -248,4 +355,92 @@ where
         let resp = self.update_to_latest_ledger_inner(req);
         provide_grpc_response(resp, ctx, sink);
     }
+    /// Execute a transaction against the latest state without committing it. The output of the
+    /// transaction is returned, e.g. for clients to estimate the gas it will use.
+    fn simulate_transaction(
+        &mut self,
+        ctx: ::grpcio::RpcContext<'_>,
+        req: SimulateTransactionRequest,
+        sink: ::grpcio::UnarySink<SimulateTransactionResponse>,
+    ) {
+        debug!("[GRPC] AdmissionControl::simulate_transaction");
+        let _timer = SVC_COUNTERS.req(&ctx);
+        if self.max_concurrent_simulations == 0 {
+            let status = RpcStatus::new(
+                RpcStatusCode::Unimplemented,
+                Some("Transaction simulation is disabled".to_string()),
+            );
+            ctx.spawn(sink.fail(status).map_err(default_reply_error_logger));
+            return;
+        }
+        match self.simulate_transaction_inner(req) {
+            Ok(Some(resp)) => provide_grpc_response(Ok(resp), ctx, sink),
+            Ok(None) => {
+                let status = RpcStatus::new(
+                    RpcStatusCode::ResourceExhausted,
+                    Some("Too many transaction simulations".to_string()),
+                );
+                ctx.spawn(sink.fail(status).map_err(default_reply_error_logger));
+            }
+            Err(e) => {
+                let status = create_grpc_invalid_arg_status("simulate_transaction", e);
+                ctx.spawn(sink.fail(status).map_err(default_reply_error_logger));
+            }
+        }
+    }
+
+    /// Stream the events of an access path as they are committed. Each response answers a
+    /// GetEventsByEventAccessPath request, so that clients verify it like any other response.
+    fn watch_events(
+        &mut self,
+        ctx: ::grpcio::RpcContext<'_>,
+        req: WatchEventsRequest,
+        sink: ::grpcio::ServerStreamingSink<UpdateToLatestLedgerResponse>,
+    ) {
+        debug!("[GRPC] AdmissionControl::watch_events");
+        let _timer = SVC_COUNTERS.req(&ctx);
+        let receiver = match self.watch_events_inner(req) {
+            Ok(Some(receiver)) => receiver,
+            Ok(None) => {
+                let status = RpcStatus::new(
+                    RpcStatusCode::ResourceExhausted,
+                    Some("Too many event streams".to_string()),
+                );
+                ctx.spawn(sink.fail(status).map_err(default_reply_error_logger));
+                return;
+            }
+            Err(e) => {
+                let status = create_grpc_invalid_arg_status("watch_events", e);
+                ctx.spawn(sink.fail(status).map_err(default_reply_error_logger));
+                return;
+            }
+        };
+        let responses = receiver.then(|item| match item {
+            Ok(Ok(response)) => Ok((response, WriteFlags::default())),
+            Ok(Err(e)) => Err(grpcio::Error::RpcFailure(RpcStatus::new(
+                RpcStatusCode::Internal,
+                Some(e.to_string()),
+            ))),
+            Err(()) => Err(grpcio::Error::RemoteStopped),
+        });
+        ctx.spawn(
+            sink.send_all(responses)
+                .map(|_| ())
+                .map_err(|e| debug!("[GRPC] Event stream closed: {:?}", e)),
+        );
+    }
+
+    /// Return how much data the ledger and the state hold over a range of versions. AC passes the
+    /// request to Storage, and the counters come without proof.
+    fn get_ledger_statistics(
+        &mut self,
+        ctx: ::grpcio::RpcContext<'_>,
+        req: GetLedgerStatisticsRequest,
+        sink: ::grpcio::UnarySink<GetLedgerStatisticsResponse>,
+    ) {
+        debug!("[GRPC] AdmissionControl::get_ledger_statistics");
+        let _timer = SVC_COUNTERS.req(&ctx);
+        let resp = self.get_ledger_statistics_inner(req);
+        provide_grpc_response(resp, ctx, sink);
+    }
 }
@@ -0,0 +1,283 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Streams of the events of an access path, pushed to clients as they are committed.
+//!
+//! All the streams are served by a single thread, started with the first stream, which reads the
+//! latest ledger version at a fixed interval and sends the new events of each stream to its gRPC
+//! sink through a channel. The events of a stream are only read when something was committed
+//! since it last checked, so idle streams cost no storage read. A stream is dropped as soon as
+//! the client goes away, which is noticed on the next send: a response without event is sent when
+//! no event was committed for a while, so that idle streams are noticed too.
+
+use crate::request_counter::RequestSlot;
+use admission_control_proto::{
+    proto::admission_control::WatchEventsRequest, WATCH_EVENTS_PAGE_SIZE,
+};
+use config::config::AdmissionControlConfig;
+use failure::prelude::*;
+use futures::sync::mpsc;
+use logger::prelude::*;
+use proto_conv::{FromProto, IntoProto};
+use std::{
+    sync::{mpsc as std_mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+use storage_client::StorageRead;
+use types::{
+    access_path::AccessPath,
+    get_with_proof::{RequestItem, ResponseItem},
+    proto::get_with_proof::UpdateToLatestLedgerResponse,
+    transaction::Version,
+};
+
+/// Settings of the event streams.
+#[derive(Clone, Copy, Debug)]
+pub struct EventWatchConfig {
+    /// Interval at which streams check storage for new events.
+    pub poll_interval: Duration,
+    /// A stream without new event sends an empty response after this interval.
+    pub heartbeat_interval: Duration,
+    /// Maximum number of concurrent streams.
+    pub max_watchers: usize,
+}
+
+impl Default for EventWatchConfig {
+    fn default() -> Self {
+        Self::from(&AdmissionControlConfig::default())
+    }
+}
+
+impl From<&AdmissionControlConfig> for EventWatchConfig {
+    fn from(config: &AdmissionControlConfig) -> Self {
+        EventWatchConfig {
+            poll_interval: Duration::from_millis(config.event_watch_poll_interval_ms),
+            heartbeat_interval: Duration::from_millis(config.event_watch_heartbeat_interval_ms),
+            max_watchers: config.max_event_watchers,
+        }
+    }
+}
+
+/// The state of a stream of events.
+pub(crate) struct EventWatcher {
+    storage_read_client: Arc<dyn StorageRead>,
+    access_path: AccessPath,
+    /// Sequence number of the next event to stream.
+    next_event_seq_num: u64,
+    /// Version of the ledger info of the latest response, or the version known by the client
+    /// before the first one.
+    client_known_version: Version,
+    /// Latest version all the events of which were read, if they all fit in a page.
+    checked_version: Option<Version>,
+}
+
+impl EventWatcher {
+    pub fn new(
+        storage_read_client: Arc<dyn StorageRead>,
+        mut req: WatchEventsRequest,
+    ) -> Result<Self> {
+        Ok(EventWatcher {
+            storage_read_client,
+            access_path: AccessPath::from_proto(req.take_access_path())?,
+            next_event_seq_num: req.get_start_event_seq_num(),
+            client_known_version: req.get_client_known_version(),
+            checked_version: None,
+        })
+    }
+
+    /// Read the events committed since the previous call, if anything was committed up to
+    /// latest_version, the latest version of the ledger if known. Returns the response to send to
+    /// the client, if any: one is always returned for a heartbeat.
+    pub fn poll(
+        &mut self,
+        heartbeat: bool,
+        latest_version: Option<Version>,
+    ) -> Result<Option<UpdateToLatestLedgerResponse>> {
+        if !heartbeat && latest_version.is_some() && self.checked_version == latest_version {
+            return Ok(None);
+        }
+
+        let (response_items, ledger_info_with_sigs, validator_change_events) =
+            self.storage_read_client.update_to_latest_ledger(
+                self.client_known_version,
+                vec![RequestItem::GetEventsByEventAccessPath {
+                    access_path: self.access_path.clone(),
+                    start_event_seq_num: self.next_event_seq_num,
+                    ascending: true,
+                    limit: WATCH_EVENTS_PAGE_SIZE,
+                }],
+            )?;
+        let (num_events, last_event_seq_num) = match response_items.first() {
+            Some(ResponseItem::GetEventsByEventAccessPath {
+                events_with_proof, ..
+            }) => (
+                events_with_proof.len() as u64,
+                events_with_proof
+                    .last()
+                    .map(|event_with_proof| event_with_proof.event.sequence_number()),
+            ),
+            _ => bail!("Storage did not answer GetEventsByEventAccessPath"),
+        };
+        let version = ledger_info_with_sigs.ledger_info().version();
+        // A full page may be followed by more events: read them on the next call.
+        self.checked_version = if num_events < WATCH_EVENTS_PAGE_SIZE {
+            Some(version)
+        } else {
+            None
+        };
+        if num_events == 0 && !heartbeat {
+            return Ok(None);
+        }
+
+        if let Some(last_event_seq_num) = last_event_seq_num {
+            self.next_event_seq_num = last_event_seq_num + 1;
+        }
+        self.client_known_version = version;
+        Ok(Some(
+            types::get_with_proof::UpdateToLatestLedgerResponse::new(
+                response_items,
+                ledger_info_with_sigs,
+                validator_change_events,
+            )
+            .into_proto(),
+        ))
+    }
+}
+
+/// A stream served by the EventWatcherPool.
+struct EventStream {
+    watcher: EventWatcher,
+    sender: mpsc::Sender<Result<UpdateToLatestLedgerResponse>>,
+    /// The item which didn't fit in the channel yet, if any.
+    pending: Option<Result<UpdateToLatestLedgerResponse>>,
+    last_sent: Instant,
+    /// Counts the stream among the running ones until it is dropped.
+    _slot: RequestSlot,
+}
+
+impl EventStream {
+    /// Send the new events of the stream, if any. Returns false once the stream is over, i.e. when
+    /// the receiver was dropped or after an error was sent as the last item.
+    fn serve(&mut self, latest_version: Option<Version>, heartbeat_interval: Duration) -> bool {
+        if self.pending.is_none() {
+            let heartbeat = self.last_sent.elapsed() >= heartbeat_interval;
+            self.pending = self.watcher.poll(heartbeat, latest_version).transpose();
+        }
+        let item = match self.pending.take() {
+            Some(item) => item,
+            None => return true,
+        };
+        let is_err = item.is_err();
+        match self.sender.try_send(item) {
+            Ok(()) => {
+                self.last_sent = Instant::now();
+                !is_err
+            }
+            Err(e) if e.is_full() => {
+                self.pending = Some(e.into_inner());
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// Serves all the event streams from a single thread, started with the first stream.
+pub(crate) struct EventWatcherPool {
+    storage_read_client: Arc<dyn StorageRead>,
+    config: EventWatchConfig,
+    /// Hands the new streams to the thread, once started.
+    new_streams: Mutex<Option<std_mpsc::Sender<EventStream>>>,
+}
+
+impl EventWatcherPool {
+    pub fn new(storage_read_client: Arc<dyn StorageRead>, config: EventWatchConfig) -> Self {
+        EventWatcherPool {
+            storage_read_client,
+            config,
+            new_streams: Mutex::new(None),
+        }
+    }
+
+    /// Stream the events of watcher to sender until the receiver is dropped, e.g. when the client
+    /// goes away, or until an error occurs, which is sent as the last item. The stream holds slot
+    /// until it is over.
+    pub fn add(
+        &self,
+        watcher: EventWatcher,
+        sender: mpsc::Sender<Result<UpdateToLatestLedgerResponse>>,
+        slot: RequestSlot,
+    ) -> Result<()> {
+        let stream = EventStream {
+            watcher,
+            sender,
+            pending: None,
+            last_sent: Instant::now(),
+            _slot: slot,
+        };
+        let mut new_streams = self.new_streams.lock().unwrap();
+        // The thread is (re)started if it isn't running, e.g. if it panicked.
+        let stream = match new_streams.as_ref().map(|sender| sender.send(stream)) {
+            Some(Ok(())) => return Ok(()),
+            Some(Err(std_mpsc::SendError(stream))) => stream,
+            None => stream,
+        };
+        let (sender, receiver) = std_mpsc::channel();
+        let storage_read_client = Arc::clone(&self.storage_read_client);
+        let config = self.config;
+        thread::Builder::new()
+            .name("ac-event-watcher".to_string())
+            .spawn(move || Self::run(receiver, storage_read_client, config))?;
+        sender
+            .send(stream)
+            .map_err(|_| format_err!("The event watcher thread stopped"))?;
+        *new_streams = Some(sender);
+        Ok(())
+    }
+
+    /// Serve the streams received from new_streams until they are all over and the pool is
+    /// dropped.
+    fn run(
+        new_streams: std_mpsc::Receiver<EventStream>,
+        storage_read_client: Arc<dyn StorageRead>,
+        config: EventWatchConfig,
+    ) {
+        let mut streams: Vec<EventStream> = vec![];
+        let mut latest_version = 0;
+        loop {
+            // Wait for a stream when there is none to serve.
+            if streams.is_empty() {
+                match new_streams.recv() {
+                    Ok(stream) => streams.push(stream),
+                    Err(_) => return,
+                }
+            }
+            streams.extend(new_streams.try_iter());
+            // The latest version is read once for all the streams. Should it fail, each stream
+            // reads its events and ends with the error.
+            let known_version =
+                match storage_read_client.update_to_latest_ledger(latest_version, vec![]) {
+                    Ok((_, ledger_info_with_sigs, _)) => {
+                        latest_version = ledger_info_with_sigs.ledger_info().version();
+                        Some(latest_version)
+                    }
+                    Err(e) => {
+                        warn!("Failed to read the latest ledger version: {:?}", e);
+                        None
+                    }
+                };
+            streams = streams
+                .into_iter()
+                .filter_map(|mut stream| {
+                    if stream.serve(known_version, config.heartbeat_interval) {
+                        Some(stream)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            thread::sleep(config.poll_interval);
+        }
+    }
+}
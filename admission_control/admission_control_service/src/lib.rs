@@ -6,14 +6,19 @@
 //! Admission Control
 //!
 //! Admission Control (AC) is the public API end point taking public gRPC requests from clients.
-//! AC serves two types of request from clients:
+//! AC serves four types of request from clients:
 //! 1. SubmitTransaction, to submit transaction to associated validator.
 //! 2. UpdateToLatestLedger, to query storage, e.g. account state, transaction log, and proofs.
+//! 3. SimulateTransaction, to execute a transaction without committing it.
+//! 4. WatchEvents, to stream the events of an access path as they are committed.
 
 /// Wrapper to run AC in a separate process.
 pub mod admission_control_node;
 /// AC gRPC service.
 pub mod admission_control_service;
+/// Streams of events pushed to clients.
+pub mod event_watcher;
+mod request_counter;
 use lazy_static::lazy_static;
 use metrics::OpMetrics;
 
@@ -0,0 +1,38 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bounds the number of requests of a kind served at the same time, e.g. the event streams.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// Counts the running requests of a kind, to bound their number.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RequestCounter(Arc<AtomicUsize>);
+
+/// A running request, counted until dropped.
+pub(crate) struct RequestSlot(Arc<AtomicUsize>);
+
+impl RequestCounter {
+    /// Count a new request, unless max_requests requests are running already.
+    pub fn acquire(&self, max_requests: usize) -> Option<RequestSlot> {
+        if self.0.fetch_add(1, Ordering::SeqCst) >= max_requests {
+            self.0.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(RequestSlot(Arc::clone(&self.0)))
+    }
+
+    /// The number of running requests.
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for RequestSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
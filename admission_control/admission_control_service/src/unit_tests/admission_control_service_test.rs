@@ -3,35 +3,51 @@
 
 use crate::{
     admission_control_service::{
-        AdmissionControlService, SubmitTransactionRequest,
-        SubmitTransactionResponse as ProtoSubmitTransactionResponse,
+        AdmissionControlService, GetLedgerStatisticsRequest, SimulateTransactionRequest,
+        SubmitTransactionRequest, SubmitTransactionResponse as ProtoSubmitTransactionResponse,
+        WatchEventsRequest,
     },
+    event_watcher::EventWatchConfig,
     unit_tests::LocalMockMempool,
 };
-use admission_control_proto::{AdmissionControlStatus, SubmitTransactionResponse};
+use admission_control_proto::{
+    AdmissionControlStatus, SimulateTransactionResponse, SubmitTransactionResponse,
+};
 
 use assert_matches::assert_matches;
 use crypto::{ed25519::*, hash::CryptoHash, test_utils::TEST_SEED, SigningKey};
+use futures::stream::Stream;
 use mempool::proto::shared::mempool_status::MempoolAddTransactionStatusCode;
-use proto_conv::FromProto;
+use proto_conv::{FromProto, IntoProto};
 use protobuf::{Message, UnknownFields};
 use rand::SeedableRng;
-use std::sync::Arc;
+use std::{sync::Arc, thread, time::Duration};
 use storage_service::mocks::mock_storage_client::MockStorageReadClient;
 use types::{
+    access_path::AccessPath,
     account_address::{AccountAddress, ADDRESS_LENGTH},
+    get_with_proof::UpdateToLatestLedgerResponse,
+    ledger_statistics::LedgerStatistics,
     test_helpers::transaction_test_helpers::get_test_signed_txn,
-    transaction::RawTransactionBytes,
+    transaction::{RawTransactionBytes, TransactionStatus, MAX_TRANSACTION_SIZE_IN_BYTES},
     vm_error::{ExecutionStatus, VMStatus, VMValidationStatus},
 };
 use vm_validator::mocks::mock_vm_validator::MockVMValidator;
 
 fn create_ac_service_for_ut() -> AdmissionControlService<LocalMockMempool, MockVMValidator> {
+    create_ac_service_with_event_watch_config(EventWatchConfig::default())
+}
+
+fn create_ac_service_with_event_watch_config(
+    event_watch_config: EventWatchConfig,
+) -> AdmissionControlService<LocalMockMempool, MockVMValidator> {
     AdmissionControlService::new(
         Some(Arc::new(LocalMockMempool::new())),
         Arc::new(MockStorageReadClient),
         Arc::new(MockVMValidator),
         false,
+        event_watch_config,
+        1,
     )
 }
 
@@ -277,3 +293,128 @@ fn test_submit_txn_inner_mempool() {
         AdmissionControlStatus::Accepted,
     );
 }
+
+#[test]
+fn test_simulate_txn_inner() {
+    let ac_service = create_ac_service_for_ut();
+    let mut req = SimulateTransactionRequest::new();
+    let keypair = compat::generate_keypair(None);
+    req.set_signed_txn(get_test_signed_txn(
+        AccountAddress::random(),
+        0,
+        keypair.0.clone(),
+        keypair.1.clone(),
+        None,
+    ));
+    let response = SimulateTransactionResponse::from_proto(
+        ac_service
+            .simulate_transaction_inner(req.clone())
+            .unwrap()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        response.output.status(),
+        &TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed))
+    );
+
+    // Only one simulation may run at a time.
+    let slot = ac_service.simulations.acquire(1).unwrap();
+    assert!(ac_service
+        .simulate_transaction_inner(req.clone())
+        .unwrap()
+        .is_none());
+    drop(slot);
+    assert_eq!(ac_service.simulations.count(), 0);
+
+    let invalid_sig_add = AccountAddress::new([1; ADDRESS_LENGTH]);
+    req.set_signed_txn(get_test_signed_txn(
+        invalid_sig_add,
+        0,
+        keypair.0.clone(),
+        keypair.1.clone(),
+        None,
+    ));
+    let response = SimulateTransactionResponse::from_proto(
+        ac_service
+            .simulate_transaction_inner(req.clone())
+            .unwrap()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        response.output.status(),
+        &TransactionStatus::Discard(VMStatus::Validation(VMValidationStatus::InvalidSignature))
+    );
+
+    // Oversized transactions are rejected before being executed.
+    req.mut_signed_txn()
+        .set_raw_txn_bytes(vec![0; MAX_TRANSACTION_SIZE_IN_BYTES + 1]);
+    assert!(ac_service.simulate_transaction_inner(req).is_err());
+}
+
+#[test]
+fn test_watch_events_inner() {
+    // Every response is a heartbeat, so that the stream notices right away it was dropped.
+    let ac_service = create_ac_service_with_event_watch_config(EventWatchConfig {
+        poll_interval: Duration::from_millis(1),
+        heartbeat_interval: Duration::from_millis(0),
+        max_watchers: 2,
+    });
+    let mut req = WatchEventsRequest::new();
+    req.set_access_path(AccessPath::new_for_received_event(AccountAddress::random()).into_proto());
+
+    // The streams are served by the same thread.
+    let receivers: Vec<_> = (0..2)
+        .map(|_| ac_service.watch_events_inner(req.clone()).unwrap().unwrap())
+        .collect();
+    assert!(ac_service
+        .watch_events_inner(req.clone())
+        .unwrap()
+        .is_none());
+
+    let mut streams: Vec<_> = receivers
+        .into_iter()
+        .map(|receiver| receiver.wait())
+        .collect();
+    for responses in &mut streams {
+        let response = UpdateToLatestLedgerResponse::<Ed25519Signature>::from_proto(
+            responses.next().unwrap().unwrap().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(response.ledger_info_with_sigs.ledger_info().version(), 7);
+        let (events_with_proof, _) = response.response_items[0]
+            .clone()
+            .into_get_events_by_access_path_response()
+            .unwrap();
+        assert!(events_with_proof.is_empty());
+    }
+
+    // A dropped stream frees its slot, while the other one keeps being served.
+    streams.pop();
+    while ac_service.event_watchers.count() > 1 {
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert!(streams[0].next().unwrap().is_ok());
+    assert!(ac_service.watch_events_inner(req).unwrap().is_some());
+}
+
+#[test]
+fn test_get_ledger_statistics_inner() {
+    let ac_service = create_ac_service_for_ut();
+    let mut req = GetLedgerStatisticsRequest::new();
+    req.set_start_version(0);
+    req.set_end_version(7);
+    req.set_limit(10);
+
+    let mut response = ac_service.get_ledger_statistics_inner(req).unwrap();
+    let ledger_statistics = response
+        .take_ledger_statistics()
+        .into_iter()
+        .map(LedgerStatistics::from_proto)
+        .collect::<failure::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(ledger_statistics.len(), 1);
+    assert_eq!(ledger_statistics[0].version, 7);
+    assert_eq!(ledger_statistics[0].num_accounts(), 2);
+}
@@ -14,6 +14,10 @@ use std::{
 
 /// Delimiter used to ser/deserialize account data.
 pub const DELIMITER: &str = ";";
+/// Delimiter between the sub-wallets of the recovery data.
+pub const SUB_WALLET_DELIMITER: &str = ",";
+/// Delimiter between the name, account level and number of keys of a sub-wallet.
+pub const SUB_WALLET_FIELD_DELIMITER: &str = ":";
 
 /// Recover wallet from the path specified.
 pub fn recover<P: AsRef<Path>>(path: &P) -> Result<WalletLibrary> {
@@ -22,26 +26,69 @@ pub fn recover<P: AsRef<Path>>(path: &P) -> Result<WalletLibrary> {
 
     let mut line = String::new();
     let _ = buffered.read_line(&mut line)?;
+    recover_from_str(&line)
+}
+
+/// Write wallet seed to file.
+pub fn write_recovery<P: AsRef<Path>>(wallet: &WalletLibrary, path: &P) -> Result<()> {
+    let mut output = File::create(path)?;
+    writeln!(output, "{}", recovery_to_string(wallet))?;
+
+    Ok(())
+}
+
+/// Recover wallet from a line of recovery data, as written by `write_recovery`.
+pub(crate) fn recover_from_str(line: &str) -> Result<WalletLibrary> {
     let parts: Vec<&str> = line.split(DELIMITER).collect();
-    ensure!(parts.len() == 2, format!("Invalid entry '{}'", line));
+    ensure!(
+        parts.len() == 2 || parts.len() == 3,
+        format!("Invalid entry '{}'", line)
+    );
 
     let mnemonic = Mnemonic::from(&parts[0].to_string()[..])?;
     let mut wallet = WalletLibrary::new_from_mnemonic(mnemonic);
     wallet.generate_addresses(parts[1].trim().to_string().parse::<u64>()?)?;
+    if let Some(sub_wallets) = parts.get(2) {
+        for sub_wallet in sub_wallets.trim().split(SUB_WALLET_DELIMITER) {
+            let fields: Vec<&str> = sub_wallet.split(SUB_WALLET_FIELD_DELIMITER).collect();
+            ensure!(
+                fields.len() == 3,
+                format!("Invalid sub-wallet '{}'", sub_wallet)
+            );
+            wallet.add_sub_wallet(fields[0], fields[1].parse()?, fields[2].parse()?)?;
+        }
+    }
 
     Ok(wallet)
 }
 
-/// Write wallet seed to file.
-pub fn write_recovery<P: AsRef<Path>>(wallet: &WalletLibrary, path: &P) -> Result<()> {
-    let mut output = File::create(path)?;
-    writeln!(
-        output,
+/// Serialize the recovery data of the wallet: its mnemonic and the number of derived keys,
+/// followed by the name, account level and number of derived keys of each sub-wallet, if any.
+/// Wallets without sub-wallets keep the format of earlier versions.
+pub(crate) fn recovery_to_string(wallet: &WalletLibrary) -> String {
+    let mut recovery = format!(
         "{}{}{}",
         wallet.mnemonic().to_string(),
         DELIMITER,
         wallet.key_leaf()
-    )?;
-
-    Ok(())
+    );
+    if !wallet.sub_wallets().is_empty() {
+        let sub_wallets: Vec<_> = wallet
+            .sub_wallets()
+            .iter()
+            .map(|(name, sub_wallet)| {
+                format!(
+                    "{}{}{}{}{}",
+                    name,
+                    SUB_WALLET_FIELD_DELIMITER,
+                    sub_wallet.account,
+                    SUB_WALLET_FIELD_DELIMITER,
+                    sub_wallet.key_leaf
+                )
+            })
+            .collect();
+        recovery.push_str(DELIMITER);
+        recovery.push_str(&sub_wallets.join(SUB_WALLET_DELIMITER));
+    }
+    recovery
 }
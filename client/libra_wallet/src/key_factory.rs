@@ -14,18 +14,27 @@
 //!
 //! Note further that the Key Derivation Function (KDF) chosen in the derivation of Child
 //! Private Keys adheres to [HKDF RFC 5869](https://tools.ietf.org/html/rfc5869).
+//!
+//! The keys of named sub-wallets are derived differently, along BIP-44 like paths, using the
+//! hardened-only [SLIP-0010](https://github.com/satoshilabs/slips/blob/master/slip-0010.md)
+//! derivation of ed25519 keys from a standard BIP-39 seed (see `HdKeyFactory`). The keys of the
+//! default wallet are unchanged, so that existing addresses are still recovered from their
+//! mnemonic.
 
 use byteorder::{ByteOrder, LittleEndian};
-use crypto::{hmac::Hmac as CryptoHmac, pbkdf2::pbkdf2, sha3::Sha3};
+use crypto::{hmac::Hmac as CryptoHmac, pbkdf2::pbkdf2, sha2::Sha512, sha3::Sha3};
 use ed25519_dalek;
-use libra_crypto::{hash::HashValue, hkdf::Hkdf};
+use libra_crypto::{hash::HashValue, hkdf::Hkdf, slip0010::Slip0010};
 use serde::{Deserialize, Serialize};
 use sha3::Sha3_256;
-use std::{convert::TryFrom, ops::AddAssign};
+use std::{convert::TryFrom, fmt, ops::AddAssign};
 use tiny_keccak::Keccak;
 use types::account_address::AccountAddress;
 
-use crate::{error::Result, mnemonic::Mnemonic};
+use crate::{
+    error::{Result, WalletError},
+    mnemonic::Mnemonic,
+};
 
 /// Master is a set of raw bytes that are used for child key derivation
 pub struct Master([u8; 32]);
@@ -185,6 +194,83 @@ impl Seed {
     }
 }
 
+/// Purpose level of BIP-44 derivation paths.
+pub const BIP44_PURPOSE: u32 = 44;
+
+/// Coin type level of the derivation paths of sub-wallets. Libra has no coin type registered in
+/// [SLIP-0044](https://github.com/satoshilabs/slips/blob/master/slip-0044.md) yet, so this value
+/// is specific to LibraWallet and may change once one is registered.
+pub const LIBRA_COIN_TYPE: u32 = 9999;
+
+/// Position of a key in the BIP-44 like hierarchy of sub-wallets:
+/// `m/44/<coin type>/<account>/0/<index>`.
+///
+/// Every level is hardened, as SLIP-0010 only supports hardened derivation of ed25519 keys. The
+/// change level is always 0: Libra accounts are not UTXOs, and don't need change addresses.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct DerivationPath {
+    /// Account level, i.e. the sub-wallet.
+    pub account: u32,
+    /// Index of the key within the account.
+    pub index: u32,
+}
+
+impl DerivationPath {
+    /// Constructor from the account and the index of the key within the account
+    pub fn new(account: u32, index: u32) -> Self {
+        Self { account, index }
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "m/{}/{}/{}/0/{}",
+            BIP44_PURPOSE, LIBRA_COIN_TYPE, self.account, self.index
+        )
+    }
+}
+
+/// Seed of BIP-39: the output of PBKDF2-HMAC-SHA512 on the Mnemonic, salted with "mnemonic" and
+/// an optional passphrase. Other BIP-39 wallets derive the same seed from the same mnemonic.
+pub struct Bip39Seed([u8; 64]);
+
+impl Bip39Seed {
+    const SALT_PREFIX: &'static str = "mnemonic";
+
+    /// Generate the Seed of a Mnemonic and passphrase, as specified by BIP-39
+    pub fn new(mnemonic: &Mnemonic, passphrase: &str) -> Self {
+        let mut mac = CryptoHmac::new(Sha512::new(), mnemonic.to_string().as_bytes());
+        let mut output = [0u8; 64];
+        let salt = format!("{}{}", Bip39Seed::SALT_PREFIX, passphrase);
+        pbkdf2(&mut mac, salt.as_bytes(), 2048, &mut output);
+        Bip39Seed(output)
+    }
+}
+
+/// Derives the keys of sub-wallets along `DerivationPath`s, following SLIP-0010
+pub struct HdKeyFactory {
+    seed: Bip39Seed,
+}
+
+impl HdKeyFactory {
+    /// Instantiate a new HdKeyFactory from a BIP-39 Seed
+    pub fn new(seed: Bip39Seed) -> Self {
+        Self { seed }
+    }
+
+    /// Derive the PrivateKey at a certain DerivationPath
+    pub fn private_child(&self, path: &DerivationPath) -> Result<ExtendedPrivKey> {
+        let key = Slip0010::derive_from_path(&path.to_string(), &self.seed.0)
+            .map_err(|e| WalletError::LibraWalletGeneric(format!("{}", e)))?;
+        Ok(ExtendedPrivKey::new(
+            ChildNumber(u64::from(path.index)),
+            key.private_key,
+        ))
+    }
+}
+
 #[test]
 fn assert_default_child_number() {
     assert_eq!(ChildNumber::default(), ChildNumber(0));
@@ -238,3 +324,44 @@ fn test_key_derivation() {
         hex::encode(&child_private_1_from_increment.private_key.to_bytes()[..])
     );
 }
+
+#[test]
+fn test_bip39_seed() {
+    // Test vector of the reference BIP-39 implementation, with passphrase "TREZOR".
+    let mnemonic = Mnemonic::from("legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth useful legal will").unwrap();
+    assert_eq!(
+        "f2b94508732bcbacbcc020faefecfc89feafa6649a5491b8c952cede496c214a0c7b3c392d168748f2d4a612bada0753b52a1c7ac53c1e93abd5c6320b9e95dd",
+        hex::encode(&Bip39Seed::new(&mnemonic, "TREZOR").0[..])
+    );
+}
+
+#[test]
+fn test_hd_key_derivation() {
+    let mnemonic = Mnemonic::from("legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth useful legal will").unwrap();
+    let key_factory = HdKeyFactory::new(Bip39Seed::new(&mnemonic, ""));
+    let path = DerivationPath::new(1, 2);
+    assert_eq!(
+        format!("{}", path),
+        format!("m/44/{}/1/0/2", LIBRA_COIN_TYPE)
+    );
+
+    let child = key_factory.private_child(&path).unwrap();
+    assert_eq!(
+        "468f67b18b12ed780134f958a07a25ac8213ca47d2bcd851e10cad8ab9c9f918",
+        hex::encode(&child.private_key.to_bytes()[..])
+    );
+
+    // Check determinism and that keys differ across accounts and indexes.
+    let child_again = key_factory.private_child(&path).unwrap();
+    assert_eq!(
+        child.private_key.to_bytes(),
+        child_again.private_key.to_bytes()
+    );
+    for other_path in &[DerivationPath::new(0, 2), DerivationPath::new(1, 1)] {
+        let other_child = key_factory.private_child(other_path).unwrap();
+        assert_ne!(
+            child.private_key.to_bytes(),
+            other_child.private_key.to_bytes()
+        );
+    }
+}
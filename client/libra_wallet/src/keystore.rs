@@ -0,0 +1,350 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A module to store the wallet recovery data protected by a password.
+//!
+//! The recovery data written by `io_utils::write_recovery` (the mnemonic and the number of
+//! derived child keys) is encrypted with AES-256-GCM under a key derived from the password with
+//! scrypt, a memory-hard KDF. The keystore is a JSON document holding everything but the password
+//! needed to decrypt it: the KDF parameters and salt, the nonce, the ciphertext and the
+//! authentication tag. The version and algorithm fields are authenticated along with the
+//! ciphertext.
+
+use crate::{io_utils, WalletLibrary};
+use crypto::{
+    aead::{AeadDecryptor, AeadEncryptor},
+    aes::KeySize,
+    aes_gcm::AesGcm,
+    scrypt::{scrypt, ScryptParams},
+};
+use failure::prelude::*;
+use rand::{rngs::EntropyRng, Rng};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+const KEYSTORE_VERSION: u32 = 1;
+const KDF_SCRYPT: &str = "scrypt";
+const CIPHER_AES_256_GCM: &str = "aes-256-gcm";
+
+/// scrypt cost parameters used for new keystores: N = 2^15, r = 8 and p = 1, i.e. 32 MiB of
+/// memory per password guess.
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+/// Upper bound of the memory cost accepted when reading a keystore, so that a crafted file can't
+/// exhaust the memory of the client: 128 * r * p * 2^log_n bytes must stay under 1 GiB.
+const MAX_SCRYPT_MEMORY_LOG2: u32 = 30;
+
+const SALT_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+const KEY_LENGTH: usize = 32;
+const TAG_LENGTH: usize = 16;
+
+/// Parameters of the scrypt key derivation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct KdfParams {
+    log_n: u8,
+    r: u32,
+    p: u32,
+    /// Hex encoded salt.
+    salt: String,
+}
+
+/// On-disk representation of an encrypted wallet.
+#[derive(Debug, Serialize, Deserialize)]
+struct Keystore {
+    version: u32,
+    kdf: String,
+    kdf_params: KdfParams,
+    cipher: String,
+    /// Hex encoded nonce of the cipher.
+    nonce: String,
+    /// Hex encoded encrypted recovery data.
+    ciphertext: String,
+    /// Hex encoded authentication tag of the ciphertext.
+    tag: String,
+}
+
+impl Keystore {
+    /// The header fields which are authenticated along with the ciphertext.
+    fn associated_data(&self) -> Vec<u8> {
+        format!(
+            "{}:{}:{}:{}:{}:{}",
+            self.version,
+            self.kdf,
+            self.kdf_params.log_n,
+            self.kdf_params.r,
+            self.kdf_params.p,
+            self.cipher
+        )
+        .into_bytes()
+    }
+}
+
+/// Returns whether the file at path is a keystore, as opposed to plaintext recovery data.
+pub fn is_keystore<P: AsRef<Path>>(path: &P) -> Result<bool> {
+    let content = fs::read_to_string(path)?;
+    Ok(content.trim_start().starts_with('{'))
+}
+
+/// Write the wallet recovery data to path, encrypted with the password.
+pub fn write_keystore<P: AsRef<Path>>(
+    wallet: &WalletLibrary,
+    path: &P,
+    password: &[u8],
+) -> Result<()> {
+    write_keystore_with_params(wallet, path, password, SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)
+}
+
+/// Recover wallet from the keystore at path, decrypting it with the password.
+pub fn recover_keystore<P: AsRef<Path>>(path: &P, password: &[u8]) -> Result<WalletLibrary> {
+    let keystore: Keystore = serde_json::from_str(&fs::read_to_string(path)?)?;
+    ensure!(
+        keystore.version == KEYSTORE_VERSION,
+        "Unsupported keystore version: {}",
+        keystore.version
+    );
+    ensure!(
+        keystore.kdf == KDF_SCRYPT,
+        "Unsupported keystore kdf: {}",
+        keystore.kdf
+    );
+    ensure!(
+        keystore.cipher == CIPHER_AES_256_GCM,
+        "Unsupported keystore cipher: {}",
+        keystore.cipher
+    );
+    let params = &keystore.kdf_params;
+    check_kdf_params(params)?;
+    let salt = hex::decode(&params.salt)?;
+    let nonce = hex::decode(&keystore.nonce)?;
+    let ciphertext = hex::decode(&keystore.ciphertext)?;
+    let tag = hex::decode(&keystore.tag)?;
+    ensure!(
+        nonce.len() == NONCE_LENGTH && tag.len() == TAG_LENGTH,
+        "Invalid keystore nonce or tag length"
+    );
+
+    let key = derive_key(password, &salt, params.log_n, params.r, params.p);
+    let mut plaintext = vec![0u8; ciphertext.len()];
+    let mut cipher = AesGcm::new(
+        KeySize::KeySize256,
+        &key,
+        &nonce,
+        &keystore.associated_data(),
+    );
+    ensure!(
+        cipher.decrypt(&ciphertext, &mut plaintext, &tag),
+        "Unable to decrypt keystore: wrong password or corrupted file"
+    );
+    io_utils::recover_from_str(std::str::from_utf8(&plaintext)?)
+}
+
+/// Encrypt the plaintext recovery data at path in place with the password and return the
+/// recovered wallet. The keystore is written to a temporary file first, so the original data is
+/// left untouched if anything fails.
+pub fn migrate_recovery_file<P: AsRef<Path>>(path: &P, password: &[u8]) -> Result<WalletLibrary> {
+    ensure!(
+        !is_keystore(path)?,
+        "{} is already a keystore",
+        path.as_ref().display()
+    );
+    let wallet = io_utils::recover(path)?;
+    let mut tmp_path = path.as_ref().as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    write_keystore(&wallet, &tmp_path, password)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(wallet)
+}
+
+fn write_keystore_with_params<P: AsRef<Path>>(
+    wallet: &WalletLibrary,
+    path: &P,
+    password: &[u8],
+    log_n: u8,
+    r: u32,
+    p: u32,
+) -> Result<()> {
+    let mut rng = EntropyRng::new();
+    let salt: [u8; SALT_LENGTH] = rng.gen();
+    let nonce: [u8; NONCE_LENGTH] = rng.gen();
+
+    let plaintext = io_utils::recovery_to_string(wallet).into_bytes();
+    let mut keystore = Keystore {
+        version: KEYSTORE_VERSION,
+        kdf: KDF_SCRYPT.to_string(),
+        kdf_params: KdfParams {
+            log_n,
+            r,
+            p,
+            salt: hex::encode(&salt),
+        },
+        cipher: CIPHER_AES_256_GCM.to_string(),
+        nonce: hex::encode(&nonce),
+        ciphertext: String::new(),
+        tag: String::new(),
+    };
+
+    let key = derive_key(password, &salt, log_n, r, p);
+    let mut ciphertext = vec![0u8; plaintext.len()];
+    let mut tag = [0u8; TAG_LENGTH];
+    let mut cipher = AesGcm::new(
+        KeySize::KeySize256,
+        &key,
+        &nonce,
+        &keystore.associated_data(),
+    );
+    cipher.encrypt(&plaintext, &mut ciphertext, &mut tag);
+    keystore.ciphertext = hex::encode(&ciphertext);
+    keystore.tag = hex::encode(&tag);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        // Only the owner may read the keystore.
+        options.mode(0o600);
+    }
+    let mut output = options.open(path)?;
+    output.write_all(serde_json::to_string_pretty(&keystore)?.as_bytes())?;
+    Ok(())
+}
+
+/// Checks the scrypt parameters read from a keystore, since `ScryptParams::new` panics unless
+/// they meet all the requirements of scrypt, and bounds their memory cost.
+fn check_kdf_params(params: &KdfParams) -> Result<()> {
+    let log_n = u32::from(params.log_n);
+    let r = u64::from(params.r);
+    let p = u64::from(params.p);
+    ensure!(
+        log_n > 0 && r > 0 && p > 0,
+        "Invalid keystore kdf parameters, log_n, r and p must be positive: {:?}",
+        params
+    );
+    // N < 2^(128 * r / 8)
+    ensure!(
+        u64::from(log_n) < 16 * r,
+        "Invalid keystore kdf parameters, log_n must be lower than 16 * r: {:?}",
+        params
+    );
+    // p <= (2^32 - 1) * 32 / (128 * r)
+    ensure!(
+        r * p < 1 << 30,
+        "Invalid keystore kdf parameters, r * p must be lower than 2^30: {:?}",
+        params
+    );
+    // This also keeps 128 * r * N and 128 * r * p, which scrypt computes as usize, from
+    // overflowing.
+    ensure!(
+        7 + log_n + log2_ceil(r * p) <= MAX_SCRYPT_MEMORY_LOG2,
+        "Invalid keystore kdf parameters, scrypt would need more than 2^{} bytes: {:?}",
+        MAX_SCRYPT_MEMORY_LOG2,
+        params
+    );
+    Ok(())
+}
+
+fn derive_key(password: &[u8], salt: &[u8], log_n: u8, r: u32, p: u32) -> [u8; KEY_LENGTH] {
+    let mut key = [0u8; KEY_LENGTH];
+    scrypt(password, salt, &ScryptParams::new(log_n, r, p), &mut key);
+    key
+}
+
+fn log2_ceil(value: u64) -> u32 {
+    64 - value.saturating_sub(1).leading_zeros()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    // Cheap scrypt parameters to keep the tests fast.
+    const TEST_LOG_N: u8 = 4;
+
+    fn test_wallet() -> WalletLibrary {
+        let mut wallet = WalletLibrary::new();
+        wallet.generate_addresses(3).unwrap();
+        wallet
+    }
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let wallet = test_wallet();
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        write_keystore_with_params(&wallet, &path, b"password", TEST_LOG_N, 8, 1).unwrap();
+
+        assert!(is_keystore(&path).unwrap());
+        assert!(!fs::read_to_string(&path)
+            .unwrap()
+            .contains(&wallet.mnemonic()));
+        let recovered = recover_keystore(&path, b"password").unwrap();
+        assert_eq!(recovered.mnemonic(), wallet.mnemonic());
+        assert_eq!(recovered.key_leaf(), wallet.key_leaf());
+    }
+
+    #[test]
+    fn test_keystore_wrong_password() {
+        let wallet = test_wallet();
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        write_keystore_with_params(&wallet, &path, b"password", TEST_LOG_N, 8, 1).unwrap();
+
+        assert!(recover_keystore(&path, b"passw0rd").is_err());
+    }
+
+    #[test]
+    fn test_keystore_tampered_params() {
+        let wallet = test_wallet();
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        write_keystore_with_params(&wallet, &path, b"password", TEST_LOG_N, 8, 1).unwrap();
+
+        let mut keystore: Keystore =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        keystore.kdf_params.log_n = 40;
+        fs::write(&path, serde_json::to_string(&keystore).unwrap()).unwrap();
+        assert!(recover_keystore(&path, b"password").is_err());
+    }
+
+    #[test]
+    fn test_check_kdf_params() {
+        let params = |log_n, r, p| KdfParams {
+            log_n,
+            r,
+            p,
+            salt: String::new(),
+        };
+        assert!(check_kdf_params(&params(SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)).is_ok());
+        assert!(check_kdf_params(&params(TEST_LOG_N, 1, 1)).is_ok());
+        // Each of these would make ScryptParams::new panic.
+        assert!(check_kdf_params(&params(0, 8, 1)).is_err());
+        assert!(check_kdf_params(&params(TEST_LOG_N, 0, 1)).is_err());
+        assert!(check_kdf_params(&params(TEST_LOG_N, 8, 0)).is_err());
+        assert!(check_kdf_params(&params(16, 1, 1)).is_err());
+        assert!(check_kdf_params(&params(1, u32::max_value(), u32::max_value())).is_err());
+        // Too much memory.
+        assert!(check_kdf_params(&params(24, 8, 1)).is_err());
+        assert!(check_kdf_params(&params(1, 1 << 24, 1)).is_err());
+    }
+
+    #[test]
+    fn test_migrate_recovery_file() {
+        let wallet = test_wallet();
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        io_utils::write_recovery(&wallet, &path).unwrap();
+        assert!(!is_keystore(&path).unwrap());
+
+        let migrated = migrate_recovery_file(&path, b"password").unwrap();
+        assert_eq!(migrated.mnemonic(), wallet.mnemonic());
+        assert!(is_keystore(&path).unwrap());
+        assert!(migrate_recovery_file(&path, b"password").is_err());
+
+        let recovered = recover_keystore(&path, b"password").unwrap();
+        assert_eq!(recovered.mnemonic(), wallet.mnemonic());
+        assert_eq!(recovered.key_leaf(), wallet.key_leaf());
+    }
+}
@@ -14,11 +14,17 @@ pub mod io_utils;
 /// Utils for key derivation
 pub mod key_factory;
 
+/// Utils for password protected storage of the wallet
+pub mod keystore;
+
 /// Utils for mnemonic seed
 pub mod mnemonic;
 
 /// Utils for wallet library
 pub mod wallet_library;
 
+/// Utils for watch-only wallets
+pub mod watch_only;
+
 /// Default imports
 pub use crate::{mnemonic::Mnemonic, wallet_library::WalletLibrary};
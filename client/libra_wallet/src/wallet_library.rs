@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! The following document is a minimalist version of Libra Wallet. Note that this Wallet does
-//! not promote security as the mnemonic is stored in unencrypted form. In future iterations,
+//! not promote security as the mnemonic is stored in unencrypted form, unless it is written to a
+//! password protected keystore (see `keystore.rs`). In future iterations,
 //! we will be realesing more robust Wallet implementations. It is our intention to present a
 //! foundation that is simple to understand and incrementally improve the LibraWallet
 //! implementation and it's security guarantees throughout testnet. For a more robust wallet
@@ -14,14 +15,23 @@
 use crate::{
     error::*,
     io_utils,
-    key_factory::{ChildNumber, KeyFactory, Seed},
+    key_factory::{
+        Bip39Seed, ChildNumber, DerivationPath, ExtendedPrivKey, HdKeyFactory, KeyFactory, Seed,
+    },
+    keystore,
     mnemonic::Mnemonic,
+    watch_only::WatchOnlyWallet,
 };
+use libra_crypto::ed25519::{Ed25519PublicKey, Ed25519Signature};
 pub use libra_crypto::hash::CryptoHash;
 use proto_conv::{FromProto, IntoProto};
 use protobuf::Message;
 use rand::{rngs::EntropyRng, Rng};
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    path::Path,
+};
 use types::{
     account_address::AccountAddress,
     proto::transaction::SignedTransaction as ProtoSignedTransaction,
@@ -29,12 +39,25 @@ use types::{
     transaction_helpers::TransactionSigner,
 };
 
+/// A named sub-wallet: an account level of the BIP-44 like hierarchy of keys derived from the
+/// mnemonic of the wallet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SubWallet {
+    /// Account level of the derivation paths of the keys of the sub-wallet.
+    pub account: u32,
+    /// Number of keys derived so far in the sub-wallet.
+    pub key_leaf: u32,
+}
+
 /// WalletLibrary contains all the information needed to recreate a particular wallet
 pub struct WalletLibrary {
     mnemonic: Mnemonic,
     key_factory: KeyFactory,
     addr_map: HashMap<AccountAddress, ChildNumber>,
     key_leaf: ChildNumber,
+    hd_key_factory: HdKeyFactory,
+    sub_wallets: BTreeMap<String, SubWallet>,
+    hd_addr_map: HashMap<AccountAddress, DerivationPath>,
 }
 
 impl WalletLibrary {
@@ -51,11 +74,15 @@ impl WalletLibrary {
     /// Constructor that instantiates a new WalletLibrary from Mnemonic
     pub fn new_from_mnemonic(mnemonic: Mnemonic) -> Self {
         let seed = Seed::new(&mnemonic, "LIBRA");
+        let hd_seed = Bip39Seed::new(&mnemonic, "");
         WalletLibrary {
             mnemonic,
             key_factory: KeyFactory::new(&seed).unwrap(),
             addr_map: HashMap::new(),
             key_leaf: ChildNumber(0),
+            hd_key_factory: HdKeyFactory::new(hd_seed),
+            sub_wallets: BTreeMap::new(),
+            hd_addr_map: HashMap::new(),
         }
     }
 
@@ -66,8 +93,8 @@ impl WalletLibrary {
     }
 
     /// Function that writes the wallet Mnemonic to file
-    /// NOTE: This is not secure, and in general the Mnemonic would need to be decrypted before it
-    /// can be written to file; otherwise the encrypted Mnemonic should be written to file
+    /// NOTE: This is not secure, as the Mnemonic is written in plaintext; use `write_keystore` to
+    /// write it encrypted instead
     pub fn write_recovery(&self, output_file_path: &Path) -> Result<()> {
         io_utils::write_recovery(&self, &output_file_path)?;
         Ok(())
@@ -79,6 +106,18 @@ impl WalletLibrary {
         Ok(wallet)
     }
 
+    /// Function that writes the wallet Mnemonic to file, encrypted with the supplied password
+    pub fn write_keystore(&self, output_file_path: &Path, password: &[u8]) -> Result<()> {
+        keystore::write_keystore(&self, &output_file_path, password)?;
+        Ok(())
+    }
+
+    /// Recover wallet from the keystore at input_file_path, decrypting it with the password
+    pub fn recover_keystore(input_file_path: &Path, password: &[u8]) -> Result<WalletLibrary> {
+        let wallet = keystore::recover_keystore(&input_file_path, password)?;
+        Ok(wallet)
+    }
+
     /// Get the current ChildNumber in u64 format
     pub fn key_leaf(&self) -> u64 {
         self.key_leaf.0
@@ -148,29 +187,188 @@ impl WalletLibrary {
         Ok(ret)
     }
 
+    /// Create an empty sub-wallet named name, whose keys are derived at the next unused account
+    /// level, and return that account level. Names only contain letters, digits, '_' and '-'.
+    pub fn new_sub_wallet(&mut self, name: &str) -> Result<u32> {
+        let account = self
+            .sub_wallets
+            .values()
+            .map(|sub_wallet| sub_wallet.account + 1)
+            .max()
+            .unwrap_or(0);
+        self.add_sub_wallet(name, account, 0)?;
+        Ok(account)
+    }
+
+    /// Add the sub-wallet name at an account level, and derive its first depth keys, e.g. when
+    /// recovering the wallet.
+    pub(crate) fn add_sub_wallet(&mut self, name: &str, account: u32, depth: u32) -> Result<()> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "Invalid sub-wallet name {:?}: names only contain letters, digits, '_' and '-'",
+                name
+            )));
+        }
+        if self.sub_wallets.contains_key(name) {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "Sub-wallet {} already exists",
+                name
+            )));
+        }
+        if self
+            .sub_wallets
+            .values()
+            .any(|sub_wallet| sub_wallet.account == account)
+        {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "Account level {} is already used by another sub-wallet",
+                account
+            )));
+        }
+        self.sub_wallets.insert(
+            name.to_string(),
+            SubWallet {
+                account,
+                key_leaf: 0,
+            },
+        );
+        for _ in 0..depth {
+            self.new_sub_wallet_address(name)?;
+        }
+        Ok(())
+    }
+
+    /// All the sub-wallets, ordered by name
+    pub fn sub_wallets(&self) -> &BTreeMap<String, SubWallet> {
+        &self.sub_wallets
+    }
+
+    /// Function that derives the next key of the sub-wallet name and subsequently returns the
+    /// AccountAddress associated to the PrivateKey, along with its DerivationPath
+    pub fn new_sub_wallet_address(
+        &mut self,
+        name: &str,
+    ) -> Result<(AccountAddress, DerivationPath)> {
+        let sub_wallet = self.sub_wallets.get_mut(name).ok_or_else(|| {
+            WalletError::LibraWalletGeneric(format!("No sub-wallet named {}", name))
+        })?;
+        let path = DerivationPath::new(sub_wallet.account, sub_wallet.key_leaf);
+        let address = self.hd_key_factory.private_child(&path)?.get_address()?;
+        sub_wallet.key_leaf += 1;
+        if self.addr_map.contains_key(&address) || self.hd_addr_map.contains_key(&address) {
+            return Err(WalletError::LibraWalletGeneric(
+                "This address is already in your wallet".to_string(),
+            ));
+        }
+        self.hd_addr_map.insert(address, path);
+        Ok((address, path))
+    }
+
+    /// Returns the addresses of the sub-wallet name, ordered by the index of their keys
+    pub fn get_sub_wallet_addresses(&self, name: &str) -> Result<Vec<AccountAddress>> {
+        let sub_wallet = self.sub_wallets.get(name).ok_or_else(|| {
+            WalletError::LibraWalletGeneric(format!("No sub-wallet named {}", name))
+        })?;
+        let mut paths: Vec<_> = self
+            .hd_addr_map
+            .iter()
+            .filter(|(_, path)| path.account == sub_wallet.account)
+            .map(|(address, path)| (path.index, *address))
+            .collect();
+        paths.sort();
+        Ok(paths.into_iter().map(|(_, address)| address).collect())
+    }
+
+    /// Returns the DerivationPath of an address of a sub-wallet, or None if the address is not
+    /// in a sub-wallet
+    pub fn get_derivation_path(&self, address: &AccountAddress) -> Option<DerivationPath> {
+        self.hd_addr_map.get(address).cloned()
+    }
+
+    /// Export the addresses and PublicKeys of all the accounts of the wallet, including the
+    /// sub-wallets, to a WatchOnlyWallet
+    pub fn export_watch_only(&self) -> Result<WatchOnlyWallet> {
+        let mut watch_only = WatchOnlyWallet::new();
+        for address in self.get_addresses()? {
+            watch_only.add_public_key(self.get_public_key(&address)?, None)?;
+        }
+        for (name, sub_wallet) in &self.sub_wallets {
+            for (index, address) in self.get_sub_wallet_addresses(name)?.iter().enumerate() {
+                watch_only.add_public_key(
+                    self.get_public_key(address)?,
+                    Some(format!(
+                        "{} {}",
+                        name,
+                        DerivationPath::new(sub_wallet.account, index as u32)
+                    )),
+                )?;
+            }
+        }
+        Ok(watch_only)
+    }
+
     /// Simple public function that allows to sign a Libra RawTransaction with the PrivateKey
     /// associated to a particular AccountAddress. If the PrivateKey associated to an
-    /// AccountAddress is not contained in the addr_map, then this function will return an Error
+    /// AccountAddress is not contained in the addr_map or in a sub-wallet, then this function
+    /// will return an Error
     pub fn sign_txn(&self, txn: RawTransaction) -> Result<SignedTransaction> {
-        if let Some(child) = self.addr_map.get(&txn.sender()) {
-            let raw_bytes = txn.into_proto().write_to_bytes()?;
-            let txn_hashvalue = RawTransactionBytes(&raw_bytes).hash();
-
-            let child_key = self.key_factory.private_child(child.clone())?;
-            let signature = child_key.sign(txn_hashvalue);
-            let public_key = child_key.get_public();
-
-            let mut signed_txn = ProtoSignedTransaction::new();
-            signed_txn.set_raw_txn_bytes(raw_bytes.to_vec());
-            signed_txn.set_sender_public_key(public_key.to_bytes().to_vec());
-            signed_txn.set_sender_signature(signature.to_bytes().to_vec());
-
-            Ok(SignedTransaction::from_proto(signed_txn)?)
-        } else {
-            Err(WalletError::LibraWalletGeneric(
+        if !self.addr_map.contains_key(&txn.sender())
+            && !self.hd_addr_map.contains_key(&txn.sender())
+        {
+            return Err(WalletError::LibraWalletGeneric(
                 "Well, that address is nowhere to be found... This is awkward".to_string(),
-            ))
+            ));
+        }
+        let child_key = self.private_child_of(&txn.sender())?;
+        let raw_bytes = txn.into_proto().write_to_bytes()?;
+        let txn_hashvalue = RawTransactionBytes(&raw_bytes).hash();
+
+        let signature = child_key.sign(txn_hashvalue);
+        let public_key = child_key.get_public();
+
+        let mut signed_txn = ProtoSignedTransaction::new();
+        signed_txn.set_raw_txn_bytes(raw_bytes.to_vec());
+        signed_txn.set_sender_public_key(public_key.to_bytes().to_vec());
+        signed_txn.set_sender_signature(signature.to_bytes().to_vec());
+
+        Ok(SignedTransaction::from_proto(signed_txn)?)
+    }
+
+    /// Get the PublicKey associated to a particular AccountAddress, e.g. to make it one of the
+    /// keys of a multi-signature account.
+    pub fn get_public_key(&self, address: &AccountAddress) -> Result<Ed25519PublicKey> {
+        let child_key = self.private_child_of(address)?;
+        Ed25519PublicKey::try_from(&child_key.get_public().to_bytes()[..])
+            .map_err(|e| WalletError::LibraWalletGeneric(format!("{}", e)))
+    }
+
+    /// Add the signature of the key of `signer` to a multi-signature transaction. The key of
+    /// `signer` must be one of the keys of the multi-signature of the sender, and the PrivateKey
+    /// associated to `signer` must be contained in the addr_map.
+    pub fn cosign_txn(
+        &self,
+        mut txn: SignedTransaction,
+        signer: &AccountAddress,
+    ) -> Result<SignedTransaction> {
+        let signature = self.private_child_of(signer)?.sign(txn.signing_message());
+        let signature = Ed25519Signature::try_from(&signature.to_bytes()[..])
+            .map_err(|e| WalletError::LibraWalletGeneric(format!("{}", e)))?;
+        txn.add_cosigner_signature(&self.get_public_key(signer)?, signature)?;
+        Ok(txn)
+    }
+
+    fn private_child_of(&self, address: &AccountAddress) -> Result<ExtendedPrivKey> {
+        if let Some(child) = self.addr_map.get(address) {
+            return self.key_factory.private_child(*child);
         }
+        let path = self.hd_addr_map.get(address).ok_or_else(|| {
+            WalletError::LibraWalletGeneric(format!("Address {} is not in the wallet", address))
+        })?;
+        self.hd_key_factory.private_child(path)
     }
 }
 
@@ -180,3 +378,73 @@ impl TransactionSigner for WalletLibrary {
         Ok(self.sign_txn(raw_txn)?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_sub_wallets() {
+        let mut wallet = WalletLibrary::new();
+        let (default_address, _) = wallet.new_address().unwrap();
+        assert_eq!(wallet.new_sub_wallet("savings").unwrap(), 0);
+        assert_eq!(wallet.new_sub_wallet("payroll").unwrap(), 1);
+        assert!(wallet.new_sub_wallet("savings").is_err());
+        assert!(wallet.new_sub_wallet("pay roll").is_err());
+
+        let (address, path) = wallet.new_sub_wallet_address("payroll").unwrap();
+        assert_eq!(path, DerivationPath::new(1, 0));
+        assert_ne!(address, default_address);
+        assert_eq!(wallet.get_derivation_path(&address), Some(path));
+        assert_eq!(wallet.get_derivation_path(&default_address), None);
+        assert_eq!(
+            wallet.get_sub_wallet_addresses("payroll").unwrap(),
+            vec![address]
+        );
+        assert!(wallet.new_sub_wallet_address("unknown").is_err());
+
+        let public_key = wallet.get_public_key(&address).unwrap();
+        assert_eq!(AccountAddress::from_public_key(&public_key), address);
+        let txn = RawTransaction::new_script(
+            address,
+            0,
+            types::transaction::Script::new(vec![], vec![]),
+            0,
+            0,
+            std::time::Duration::from_secs(0),
+        );
+        assert!(wallet.sign_txn(txn).unwrap().check_signature().is_ok());
+    }
+
+    #[test]
+    fn test_sub_wallets_recovery() {
+        let mut wallet = WalletLibrary::new();
+        wallet.generate_addresses(2).unwrap();
+        wallet.new_sub_wallet("savings").unwrap();
+        wallet.new_sub_wallet("payroll").unwrap();
+        for _ in 0..3 {
+            wallet.new_sub_wallet_address("payroll").unwrap();
+        }
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        wallet.write_recovery(&path).unwrap();
+
+        let recovered = WalletLibrary::recover(&path).unwrap();
+        assert_eq!(
+            recovered.get_addresses().unwrap(),
+            wallet.get_addresses().unwrap()
+        );
+        assert_eq!(recovered.sub_wallets(), wallet.sub_wallets());
+        assert_eq!(
+            recovered.get_sub_wallet_addresses("payroll").unwrap(),
+            wallet.get_sub_wallet_addresses("payroll").unwrap()
+        );
+
+        let watch_only = recovered.export_watch_only().unwrap();
+        assert_eq!(watch_only.accounts().len(), 5);
+        assert!(watch_only
+            .accounts()
+            .iter()
+            .all(|account| account.public_key.is_some()));
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A watch-only wallet holds the addresses, and optionally the public keys, of accounts whose
+//! private keys are held elsewhere. It can't sign transactions, but it is enough to monitor the
+//! accounts from a machine which never holds any secret.
+//!
+//! A watch-only wallet is persisted as a JSON list of accounts, each with the hex encoding of
+//! its address and of its public key, if known. `WalletLibrary::export_watch_only` exports the
+//! accounts of a wallet in this format.
+
+use crate::error::{Result, WalletError};
+use libra_crypto::ed25519::Ed25519PublicKey;
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fs, path::Path};
+use types::account_address::AccountAddress;
+
+/// An account watched by a WatchOnlyWallet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchedAccount {
+    /// Address of the account.
+    pub address: AccountAddress,
+    /// Public key of the account, if known. The address is derived from it.
+    pub public_key: Option<Ed25519PublicKey>,
+    /// Free form description of the account.
+    pub label: Option<String>,
+}
+
+/// Serialized form of a WatchedAccount.
+#[derive(Serialize, Deserialize)]
+struct WatchedAccountEntry {
+    address: String,
+    #[serde(default)]
+    public_key: Option<String>,
+    #[serde(default)]
+    label: Option<String>,
+}
+
+/// Accounts watched without their private keys.
+#[derive(Clone, Debug, Default)]
+pub struct WatchOnlyWallet {
+    accounts: Vec<WatchedAccount>,
+}
+
+impl WatchOnlyWallet {
+    /// Constructor for an empty WatchOnlyWallet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The watched accounts, in the order they were added
+    pub fn accounts(&self) -> &[WatchedAccount] {
+        &self.accounts
+    }
+
+    /// Watch the account at address. Its public key is not known.
+    pub fn add_address(&mut self, address: AccountAddress, label: Option<String>) -> Result<()> {
+        self.add(WatchedAccount {
+            address,
+            public_key: None,
+            label,
+        })
+    }
+
+    /// Watch the account of public_key and return its address.
+    pub fn add_public_key(
+        &mut self,
+        public_key: Ed25519PublicKey,
+        label: Option<String>,
+    ) -> Result<AccountAddress> {
+        let address = AccountAddress::from_public_key(&public_key);
+        self.add(WatchedAccount {
+            address,
+            public_key: Some(public_key),
+            label,
+        })?;
+        Ok(address)
+    }
+
+    /// Watch an account. The address of the account must be derived from its public key, if
+    /// any, and must not be watched already.
+    pub fn add(&mut self, account: WatchedAccount) -> Result<()> {
+        if let Some(public_key) = &account.public_key {
+            if AccountAddress::from_public_key(public_key) != account.address {
+                return Err(WalletError::LibraWalletGeneric(format!(
+                    "Address {} does not match its public key",
+                    account.address
+                )));
+            }
+        }
+        if self
+            .accounts
+            .iter()
+            .any(|watched| watched.address == account.address)
+        {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "Address {} is already watched",
+                account.address
+            )));
+        }
+        self.accounts.push(account);
+        Ok(())
+    }
+
+    /// Read a WatchOnlyWallet from input_file_path
+    pub fn read(input_file_path: &Path) -> Result<Self> {
+        let entries: Vec<WatchedAccountEntry> =
+            serde_json::from_str(&fs::read_to_string(input_file_path)?).map_err(|e| {
+                WalletError::LibraWalletGeneric(format!(
+                    "Invalid watch-only wallet {}: {}",
+                    input_file_path.display(),
+                    e
+                ))
+            })?;
+        let mut wallet = Self::new();
+        for entry in entries {
+            let address = AccountAddress::try_from(decode_hex(&entry.address)?)?;
+            let public_key = match entry.public_key {
+                Some(public_key) => Some(
+                    Ed25519PublicKey::try_from(&decode_hex(&public_key)?[..])
+                        .map_err(|e| WalletError::LibraWalletGeneric(format!("{}", e)))?,
+                ),
+                None => None,
+            };
+            wallet.add(WatchedAccount {
+                address,
+                public_key,
+                label: entry.label,
+            })?;
+        }
+        Ok(wallet)
+    }
+
+    /// Write the WatchOnlyWallet to output_file_path
+    pub fn write(&self, output_file_path: &Path) -> Result<()> {
+        let entries: Vec<_> = self
+            .accounts
+            .iter()
+            .map(|account| WatchedAccountEntry {
+                address: hex::encode(account.address),
+                public_key: account
+                    .public_key
+                    .as_ref()
+                    .map(|public_key| hex::encode(public_key.to_bytes())),
+                label: account.label.clone(),
+            })
+            .collect();
+        let json = serde_json::to_string_pretty(&entries)
+            .map_err(|e| WalletError::LibraWalletGeneric(format!("{}", e)))?;
+        fs::write(output_file_path, json)?;
+        Ok(())
+    }
+}
+
+fn decode_hex(data: &str) -> Result<Vec<u8>> {
+    hex::decode(data).map_err(|e| WalletError::LibraWalletGeneric(format!("{}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libra_crypto::ed25519::compat;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_watch_only_persistence() {
+        let (_, public_key) = compat::generate_keypair(None);
+        let mut wallet = WatchOnlyWallet::new();
+        let address = wallet
+            .add_public_key(public_key.clone(), Some("cold".to_string()))
+            .unwrap();
+        assert_eq!(address, AccountAddress::from_public_key(&public_key));
+        wallet.add_address(AccountAddress::random(), None).unwrap();
+        assert!(wallet.add_address(address, None).is_err());
+
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        wallet.write(&path).unwrap();
+        let read_wallet = WatchOnlyWallet::read(&path).unwrap();
+        assert_eq!(read_wallet.accounts(), wallet.accounts());
+    }
+
+    #[test]
+    fn test_watch_only_mismatched_public_key() {
+        let (_, public_key) = compat::generate_keypair(None);
+        let mut wallet = WatchOnlyWallet::new();
+        assert!(wallet
+            .add(WatchedAccount {
+                address: AccountAddress::random(),
+                public_key: Some(public_key),
+                label: None,
+            })
+            .is_err());
+    }
+}
@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{client_proxy::ClientProxy, commands::*};
+use failure::prelude::*;
+use serde_json::{json, Value};
 
 /// Major command for account related operations.
 pub struct AccountCommand {}
@@ -14,18 +16,29 @@ impl Command for AccountCommand {
         "Account operations"
     }
     fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        let commands: Vec<Box<dyn Command>> = vec![
-            Box::new(AccountCommandCreate {}),
-            Box::new(AccountCommandListAccounts {}),
-            Box::new(AccountCommandRecoverWallet {}),
-            Box::new(AccountCommandWriteRecovery {}),
-            Box::new(AccountCommandMint {}),
-        ];
-
-        subcommand_execute(&params[0], commands, client, &params[1..]);
+        subcommand_execute(&params[0], account_subcommands(), client, &params[1..]);
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        subcommand_execute_batch(&params[0], account_subcommands(), client, &params[1..])
     }
 }
 
+fn account_subcommands() -> Vec<Box<dyn Command>> {
+    vec![
+        Box::new(AccountCommandCreate {}),
+        Box::new(AccountCommandListAccounts {}),
+        Box::new(AccountCommandRecoverWallet {}),
+        Box::new(AccountCommandWriteRecovery {}),
+        Box::new(AccountCommandMint {}),
+        Box::new(AccountCommandMultisig {}),
+        Box::new(AccountCommandCreateSubWallet {}),
+        Box::new(AccountCommandListSubWallets {}),
+        Box::new(AccountCommandExportWatchOnly {}),
+        Box::new(AccountCommandWatch {}),
+        Box::new(AccountCommandWatchPublicKey {}),
+    ]
+}
+
 /// Sub command to create a random account. The account will not be saved on chain.
 pub struct AccountCommandCreate {}
 
@@ -33,20 +46,50 @@ impl Command for AccountCommandCreate {
     fn get_aliases(&self) -> Vec<&'static str> {
         vec!["create", "c"]
     }
+    fn get_params_help(&self) -> &'static str {
+        "[sub_wallet_name]"
+    }
     fn get_description(&self) -> &'static str {
-        "Create an account. Returns reference ID to use in other operations"
+        "Create an account, in the given sub-wallet if any. Returns reference ID to use in other \
+         operations"
     }
-    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
         println!(">> Creating/retrieving next account from wallet");
-        match client.create_next_account(true) {
-            Ok(account_data) => println!(
-                "Created/retrieved account #{} address {}",
-                account_data.index,
-                hex::encode(account_data.address)
-            ),
-            Err(e) => report_error("Error creating account", e),
+        match params.get(1) {
+            Some(name) => match client.create_next_sub_wallet_account(name, true) {
+                Ok((account_data, path)) => println!(
+                    "Created/retrieved account #{} address {} of sub-wallet {} at {}",
+                    account_data.index,
+                    hex::encode(account_data.address),
+                    name,
+                    path
+                ),
+                Err(e) => report_error("Error creating account", e),
+            },
+            None => match client.create_next_account(true) {
+                Ok(account_data) => println!(
+                    "Created/retrieved account #{} address {}",
+                    account_data.index,
+                    hex::encode(account_data.address)
+                ),
+                Err(e) => report_error("Error creating account", e),
+            },
         }
     }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let (account_data, path) = match params.get(1) {
+            Some(name) => {
+                let (account_data, path) = client.create_next_sub_wallet_account(name, true)?;
+                (account_data, Some(path))
+            }
+            None => (client.create_next_account(true)?, None),
+        };
+        Ok(json!({
+            "index": account_data.index,
+            "address": hex::encode(account_data.address),
+            "derivation_path": path,
+        }))
+    }
 }
 
 /// Sub command to recover wallet from the file specified.
@@ -77,6 +120,20 @@ impl Command for AccountCommandRecoverWallet {
             Err(e) => report_error("Error recovering Libra wallet", e),
         }
     }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let account_data = client.recover_wallet_accounts(&params)?;
+        Ok(Value::Array(
+            account_data
+                .into_iter()
+                .map(|data| {
+                    json!({
+                        "index": data.index,
+                        "address": hex::encode(data.address),
+                    })
+                })
+                .collect(),
+        ))
+    }
 }
 
 /// Sub command to backup wallet to the file specified.
@@ -99,6 +156,10 @@ impl Command for AccountCommandWriteRecovery {
             Err(e) => report_error("Error writing mnemonic recovery seed to file", e),
         }
     }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        client.write_recovery(&params)?;
+        Ok(json!({ "file": params[1] }))
+    }
 }
 
 /// Sub command to list all accounts information.
@@ -114,6 +175,32 @@ impl Command for AccountCommandListAccounts {
     fn execute(&self, client: &mut ClientProxy, _params: &[&str]) {
         client.print_all_accounts();
     }
+    fn execute_batch(&self, client: &mut ClientProxy, _params: &[&str]) -> Result<Value> {
+        let accounts = client
+            .accounts
+            .iter()
+            .enumerate()
+            .map(|(index, account)| {
+                json!({
+                    "index": index,
+                    "address": hex::encode(&account.address),
+                    "sequence_number": account.sequence_number,
+                    "status": format!("{:?}", account.status),
+                })
+            })
+            .collect::<Vec<_>>();
+        let faucet_account = client.faucet_account.as_ref().map(|faucet_account| {
+            json!({
+                "address": hex::encode(&faucet_account.address),
+                "sequence_number": faucet_account.sequence_number,
+                "status": format!("{:?}", faucet_account.status),
+            })
+        });
+        Ok(json!({
+            "accounts": accounts,
+            "faucet_account": faucet_account,
+        }))
+    }
 }
 
 /// Sub command to mint account.
@@ -148,4 +235,207 @@ impl Command for AccountCommandMint {
             Err(e) => report_error("Error minting coins", e),
         }
     }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let is_blocking = blocking_cmd(params[0]);
+        client.mint_coins(&params, is_blocking)?;
+        Ok(json!({ "blocking": is_blocking }))
+    }
+}
+
+/// Sub command to derive the address of a k-of-n multi-signature account.
+pub struct AccountCommandMultisig {}
+
+impl Command for AccountCommandMultisig {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["multisig", "ms"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<threshold> <account_ref_id>|<public_key_hex> ..."
+    }
+    fn get_description(&self) -> &'static str {
+        "Derive the address of an account requiring <threshold> signatures out of the given keys"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Creating multi-signature account");
+        match client.create_multisig_account(&params) {
+            Ok((address, public_key)) => {
+                println!("Multi-signature account address {}", hex::encode(address));
+                println!("Public key {}", hex::encode(public_key.to_bytes()));
+            }
+            Err(e) => report_error("Error creating multi-signature account", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let (address, public_key) = client.create_multisig_account(&params)?;
+        Ok(json!({
+            "address": hex::encode(address),
+            "public_key": hex::encode(public_key.to_bytes()),
+            "threshold": public_key.threshold(),
+        }))
+    }
+}
+
+/// Sub command to create a named sub-wallet.
+pub struct AccountCommandCreateSubWallet {}
+
+impl Command for AccountCommandCreateSubWallet {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["create_sub_wallet", "csw"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<sub_wallet_name>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Create a sub-wallet, whose accounts are derived from the wallet mnemonic along their own \
+         BIP-44 derivation path. Create its accounts with 'account create <sub_wallet_name>'"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.create_sub_wallet(&params) {
+            Ok(account) => println!(
+                "Created sub-wallet {} at account level {}",
+                params[1], account
+            ),
+            Err(e) => report_error("Error creating sub-wallet", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let account = client.create_sub_wallet(&params)?;
+        Ok(json!({ "name": params[1], "account": account }))
+    }
+}
+
+/// Sub command to list the sub-wallets and their accounts.
+pub struct AccountCommandListSubWallets {}
+
+impl Command for AccountCommandListSubWallets {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["list_sub_wallets", "lsw"]
+    }
+    fn get_description(&self) -> &'static str {
+        "Print all sub-wallets of the wallet with the addresses of their accounts"
+    }
+    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) {
+        match client.get_sub_wallets() {
+            Ok(sub_wallets) => {
+                if sub_wallets.is_empty() {
+                    println!("No sub-wallet");
+                }
+                for (name, account, addresses) in sub_wallets {
+                    println!("Sub-wallet {}, account level {}:", name, account);
+                    for address in addresses {
+                        println!("  {}", hex::encode(address));
+                    }
+                }
+            }
+            Err(e) => report_error("Error listing sub-wallets", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, _params: &[&str]) -> Result<Value> {
+        Ok(Value::Array(
+            client
+                .get_sub_wallets()?
+                .into_iter()
+                .map(|(name, account, addresses)| {
+                    json!({
+                        "name": name,
+                        "account": account,
+                        "addresses": addresses.iter().map(hex::encode).collect::<Vec<_>>(),
+                    })
+                })
+                .collect(),
+        ))
+    }
+}
+
+/// Sub command to export the accounts of the wallet for a watch-only client.
+pub struct AccountCommandExportWatchOnly {}
+
+impl Command for AccountCommandExportWatchOnly {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["export_watch_only", "ewo"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Write the addresses and public keys of all the wallet accounts, but no secret, to a file \
+         usable by a client started with --watch_only"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.export_watch_only(&params) {
+            Ok(num_accounts) => println!(
+                "Wrote {} accounts to watch-only wallet {}",
+                num_accounts, params[1]
+            ),
+            Err(e) => report_error("Error exporting watch-only wallet", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let num_accounts = client.export_watch_only(&params)?;
+        Ok(json!({ "file": params[1], "num_accounts": num_accounts }))
+    }
+}
+
+/// Sub command to watch an account given its address.
+pub struct AccountCommandWatch {}
+
+impl Command for AccountCommandWatch {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["watch", "wa"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_address>|<name> [label]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Watch-only mode: watch an account without holding its key"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.watch_account(&params) {
+            Ok(account_data) => println!(
+                "Watching account #{} address {}",
+                account_data.index,
+                hex::encode(account_data.address)
+            ),
+            Err(e) => report_error("Error watching account", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let account_data = client.watch_account(&params)?;
+        Ok(json!({
+            "index": account_data.index,
+            "address": hex::encode(account_data.address),
+        }))
+    }
+}
+
+/// Sub command to watch an account given its public key.
+pub struct AccountCommandWatchPublicKey {}
+
+impl Command for AccountCommandWatchPublicKey {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["watch_public_key", "wpk"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<public_key_hex> [label]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Watch-only mode: watch the account of a public key without holding its private key"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.watch_public_key(&params) {
+            Ok(account_data) => println!(
+                "Watching account #{} address {}",
+                account_data.index,
+                hex::encode(account_data.address)
+            ),
+            Err(e) => report_error("Error watching account", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let account_data = client.watch_public_key(&params)?;
+        Ok(json!({
+            "index": account_data.index,
+            "address": hex::encode(account_data.address),
+        }))
+    }
 }
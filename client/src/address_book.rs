@@ -0,0 +1,179 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A persistent address book mapping names to account addresses.
+//!
+//! The address book is a JSON object mapping each name to the hex encoding of an address. It is
+//! saved after every change, and can be exported to or merged from another file of the same
+//! format, e.g. to share the addresses used in a demo.
+
+use failure::prelude::*;
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    fs,
+    path::{Path, PathBuf},
+};
+use types::account_address::AccountAddress;
+
+/// Names mapped to account addresses, persisted in a file.
+#[derive(Debug)]
+pub struct AddressBook {
+    path: PathBuf,
+    entries: BTreeMap<String, AccountAddress>,
+}
+
+impl AddressBook {
+    /// Load the address book persisted at `path`. The address book is empty if the file does not
+    /// exist yet: it is created on the first change.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let entries = if path.exists() {
+            read_entries(&path)?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(AddressBook { path, entries })
+    }
+
+    /// The address of `name`, if any.
+    pub fn get(&self, name: &str) -> Option<AccountAddress> {
+        self.entries.get(name).cloned()
+    }
+
+    /// All the entries, ordered by name.
+    pub fn entries(&self) -> &BTreeMap<String, AccountAddress> {
+        &self.entries
+    }
+
+    /// Map `name` to `address`, replacing any previous address of `name`, and save the address
+    /// book. Returns the previous address.
+    pub fn add(&mut self, name: &str, address: AccountAddress) -> Result<Option<AccountAddress>> {
+        check_name(name)?;
+        let previous = self.entries.insert(name.to_string(), address);
+        self.save()?;
+        Ok(previous)
+    }
+
+    /// Remove `name` and save the address book. Returns the address of `name`.
+    pub fn remove(&mut self, name: &str) -> Result<AccountAddress> {
+        let address = self
+            .entries
+            .remove(name)
+            .ok_or_else(|| format_err!("No address book entry named {}", name))?;
+        self.save()?;
+        Ok(address)
+    }
+
+    /// Merge the entries of the address book file at `path`, and save the address book. Entries
+    /// of the file replace existing entries of the same name. Returns the number of entries read.
+    pub fn import<P: AsRef<Path>>(&mut self, path: P) -> Result<usize> {
+        let imported = read_entries(path.as_ref())?;
+        let num_imported = imported.len();
+        self.entries.extend(imported);
+        self.save()?;
+        Ok(num_imported)
+    }
+
+    /// Write all the entries to the address book file at `path`.
+    pub fn export<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_entries(path.as_ref(), &self.entries)
+    }
+
+    fn save(&self) -> Result<()> {
+        write_entries(&self.path, &self.entries)
+    }
+}
+
+/// Names must not be mistaken for the other ways to refer to an account: an account reference id
+/// or a hex encoded address.
+fn check_name(name: &str) -> Result<()> {
+    ensure!(
+        name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+        "Invalid name {:?}: names start with a letter and only contain letters, digits, '_' and \
+         '-'",
+        name
+    );
+    ensure!(
+        hex::decode(name).map_or(true, |bytes| bytes.len() != 32),
+        "Invalid name {:?}: names must not look like an account address",
+        name
+    );
+    Ok(())
+}
+
+fn read_entries(path: &Path) -> Result<BTreeMap<String, AccountAddress>> {
+    let hex_entries: BTreeMap<String, String> = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| format_err!("Invalid address book {}: {}", path.display(), e))?;
+    hex_entries
+        .into_iter()
+        .map(|(name, address)| {
+            check_name(&name)?;
+            let address = AccountAddress::try_from(hex::decode(&address)?)?;
+            Ok((name, address))
+        })
+        .collect()
+}
+
+fn write_entries(path: &Path, entries: &BTreeMap<String, AccountAddress>) -> Result<()> {
+    let hex_entries: BTreeMap<&String, String> = entries
+        .iter()
+        .map(|(name, address)| (name, hex::encode(address)))
+        .collect();
+    fs::write(path, serde_json::to_string_pretty(&hex_entries)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_address_book_persistence() {
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        fs::remove_file(&path).unwrap();
+        let alice = AccountAddress::random();
+        let mut address_book = AddressBook::load(&path).unwrap();
+        assert_eq!(address_book.add("alice", alice).unwrap(), None);
+        address_book.add("bob", AccountAddress::random()).unwrap();
+        address_book.remove("bob").unwrap();
+        assert!(address_book.remove("bob").is_err());
+
+        let address_book = AddressBook::load(&path).unwrap();
+        assert_eq!(address_book.get("alice"), Some(alice));
+        assert_eq!(address_book.get("bob"), None);
+    }
+
+    #[test]
+    fn test_address_book_invalid_names() {
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        fs::remove_file(&path).unwrap();
+        let mut address_book = AddressBook::load(&path).unwrap();
+        let address = AccountAddress::random();
+        assert!(address_book.add("0", address).is_err());
+        assert!(address_book.add("al ice", address).is_err());
+        assert!(address_book.add(&hex::encode(address), address).is_err());
+    }
+
+    #[test]
+    fn test_address_book_import_export() {
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        fs::remove_file(&path).unwrap();
+        let mut address_book = AddressBook::load(&path).unwrap();
+        address_book.add("alice", AccountAddress::random()).unwrap();
+        let export_path = NamedTempFile::new().unwrap().into_temp_path();
+        address_book.export(&export_path).unwrap();
+
+        let other_path = NamedTempFile::new().unwrap().into_temp_path();
+        fs::remove_file(&other_path).unwrap();
+        let mut other = AddressBook::load(&other_path).unwrap();
+        other.add("bob", AccountAddress::random()).unwrap();
+        assert_eq!(other.import(&export_path).unwrap(), 1);
+        assert_eq!(other.entries().len(), 2);
+        assert_eq!(other.get("alice"), address_book.get("alice"));
+    }
+}
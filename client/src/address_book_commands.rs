@@ -0,0 +1,177 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{client_proxy::ClientProxy, commands::*};
+use failure::prelude::*;
+use serde_json::{json, Value};
+
+/// Major command for address book operations.
+pub struct AddressBookCommand {}
+
+impl Command for AddressBookCommand {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["address_book", "ab"]
+    }
+    fn get_description(&self) -> &'static str {
+        "Name accounts, so that names can be used in place of account addresses"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        subcommand_execute(&params[0], address_book_subcommands(), client, &params[1..]);
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        subcommand_execute_batch(&params[0], address_book_subcommands(), client, &params[1..])
+    }
+}
+
+fn address_book_subcommands() -> Vec<Box<dyn Command>> {
+    vec![
+        Box::new(AddressBookCommandAdd {}),
+        Box::new(AddressBookCommandRemove {}),
+        Box::new(AddressBookCommandList {}),
+        Box::new(AddressBookCommandImport {}),
+        Box::new(AddressBookCommandExport {}),
+    ]
+}
+
+/// Sub command to name an account.
+pub struct AddressBookCommandAdd {}
+
+impl Command for AddressBookCommandAdd {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["add", "a"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<name> <account_ref_id>|<account_address>|<name>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Name an account, replacing the previous account of the name if any"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.add_address_book_entry(&params) {
+            Ok((address, previous)) => {
+                println!("Named {} {}", hex::encode(address), params[1]);
+                if let Some(previous) = previous {
+                    println!("{} no longer names {}", params[1], hex::encode(previous));
+                }
+            }
+            Err(e) => report_error("Error adding address book entry", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let (address, previous) = client.add_address_book_entry(&params)?;
+        Ok(json!({
+            "name": params[1],
+            "address": hex::encode(address),
+            "previous_address": previous.map(hex::encode),
+        }))
+    }
+}
+
+/// Sub command to remove a name.
+pub struct AddressBookCommandRemove {}
+
+impl Command for AddressBookCommandRemove {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["remove", "r"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<name>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Remove a name from the address book"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.remove_address_book_entry(&params) {
+            Ok(address) => println!("Removed {} naming {}", params[1], hex::encode(address)),
+            Err(e) => report_error("Error removing address book entry", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let address = client.remove_address_book_entry(&params)?;
+        Ok(json!({
+            "name": params[1],
+            "address": hex::encode(address),
+        }))
+    }
+}
+
+/// Sub command to list the names of the address book.
+pub struct AddressBookCommandList {}
+
+impl Command for AddressBookCommandList {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["list", "la"]
+    }
+    fn get_description(&self) -> &'static str {
+        "Print all the names of the address book"
+    }
+    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) {
+        let entries = client.address_book().entries();
+        if entries.is_empty() {
+            println!("No name in the address book");
+        }
+        for (name, address) in entries {
+            println!("{}: {}", name, hex::encode(address));
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, _params: &[&str]) -> Result<Value> {
+        Ok(Value::Array(
+            client
+                .address_book()
+                .entries()
+                .iter()
+                .map(|(name, address)| json!({"name": name, "address": hex::encode(address)}))
+                .collect(),
+        ))
+    }
+}
+
+/// Sub command to merge an address book file into the address book.
+pub struct AddressBookCommandImport {}
+
+impl Command for AddressBookCommandImport {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["import", "i"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Merge the names of an address book file, replacing the names already in the address book"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.import_address_book(&params) {
+            Ok(num_imported) => println!("Imported {} names from {}", num_imported, params[1]),
+            Err(e) => report_error("Error importing address book", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let num_imported = client.import_address_book(&params)?;
+        Ok(json!({ "file": params[1], "num_imported": num_imported }))
+    }
+}
+
+/// Sub command to write the address book to a file.
+pub struct AddressBookCommandExport {}
+
+impl Command for AddressBookCommandExport {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["export", "e"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Write all the names of the address book to a file"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.export_address_book(&params) {
+            Ok(()) => println!("Address book written to {}", params[1]),
+            Err(e) => report_error("Error exporting address book", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        client.export_address_book(&params)?;
+        Ok(json!({ "file": params[1] }))
+    }
+}
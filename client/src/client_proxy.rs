@@ -1,14 +1,32 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{commands::*, grpc_client::GRPCClient, AccountData, AccountStatus};
-use admission_control_proto::proto::admission_control::SubmitTransactionRequest;
+use crate::{
+    address_book::AddressBook,
+    commands::*,
+    grpc_client::GRPCClient,
+    history::{HistoryEntry, HistoryFormat, PaymentDirection, TransactionHistory},
+    trusted_state::TrustedState,
+    txn_file, AccountData, AccountStatus,
+};
+use admission_control_proto::{
+    proto::admission_control::{SimulateTransactionRequest, SubmitTransactionRequest},
+    SimulateTransactionResponse,
+};
+use canonical_serialization::SimpleDeserializer;
 use config::trusted_peers::TrustedPeersConfig;
-use crypto::{ed25519::*, test_utils::KeyPair};
+use crypto::{
+    ed25519::*,
+    multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature, MAX_NUM_OF_KEYS},
+    test_utils::KeyPair,
+    traits::SigningKey,
+};
 use failure::prelude::*;
 use futures::{future::Future, stream::Stream};
 use hyper;
-use libra_wallet::{io_utils, wallet_library::WalletLibrary};
+use libra_wallet::{
+    io_utils, keystore, wallet_library::WalletLibrary, watch_only::WatchOnlyWallet,
+};
 use logger::prelude::*;
 use num_traits::{
     cast::{FromPrimitive, ToPrimitive},
@@ -18,14 +36,14 @@ use proto_conv::IntoProto;
 use rust_decimal::Decimal;
 use serde_json;
 use std::{
+    cmp::min,
     collections::{BTreeMap, HashMap},
     convert::TryFrom,
     fmt, fs,
     io::{stdout, Seek, SeekFrom, Write},
-    path::{Display, Path},
+    path::{Display, Path, PathBuf},
     process::{Command, Stdio},
     str::{self, FromStr},
-    sync::Arc,
     thread, time,
 };
 use tempfile::{NamedTempFile, TempPath};
@@ -35,21 +53,29 @@ use types::{
     account_address::{AccountAddress, ADDRESS_LENGTH},
     account_config::{
         account_received_event_path, account_sent_event_path, association_address,
-        core_code_address, get_account_resource_or_default, AccountResource,
+        core_code_address, get_account_resource_or_default, AccountEvent, AccountResource,
     },
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
     contract_event::{ContractEvent, EventWithProof},
+    ledger_info::LedgerInfoWithSignatures,
+    ledger_statistics::LedgerStatistics,
     transaction::{
         parse_as_transaction_argument, Program, RawTransaction, SignedTransaction, Version,
     },
     transaction_helpers::{create_signed_txn, create_unsigned_txn, TransactionSigner},
-    validator_verifier::ValidatorVerifier,
 };
 
 const CLIENT_WALLET_MNEMONIC_FILE: &str = "client.mnemonic";
+const CLIENT_ADDRESS_BOOK_FILE: &str = "client.address_book";
 const GAS_UNIT_PRICE: u64 = 0;
 const MAX_GAS_AMOUNT: u64 = 140_000;
 const TX_EXPIRATION: i64 = 100;
+// Transactions signed offline are carried between machines by hand, so they are given more time.
+const OFFLINE_TX_EXPIRATION: i64 = 3600;
+// Number of events fetched per request when exporting the history of an account.
+const HISTORY_PAGE_SIZE: u64 = 100;
+// Maximum number of sets of ledger counters fetched by a query.
+const LEDGER_STATISTICS_LIMIT: u64 = 100;
 
 /// Enum used for error formatting.
 #[derive(Debug)]
@@ -96,12 +122,22 @@ pub struct ClientProxy {
     faucet_server: String,
     /// Account used for mint operations.
     pub faucet_account: Option<AccountData>,
-    /// Wallet library managing user accounts.
-    wallet: WalletLibrary,
+    /// Wallet library managing user accounts. None in watch-only mode, where no key is ever
+    /// loaded.
+    wallet: Option<WalletLibrary>,
+    /// In watch-only mode, the accounts watched and the file they are persisted in.
+    watch_only: Option<(PathBuf, WatchOnlyWallet)>,
+    /// Password protecting the wallet keystore. Without it, wallet recovery data is written in
+    /// plaintext.
+    wallet_password: Option<String>,
     /// Whether to sync with validator on account creation.
     sync_on_wallet_recovery: bool,
     /// temp files (alive for duration of program)
     temp_files: Vec<TempPath>,
+    /// Whether to suppress progress output, e.g. when running in batch mode.
+    quiet: bool,
+    /// Names usable in place of account addresses.
+    address_book: AddressBook,
 }
 
 impl ClientProxy {
@@ -114,8 +150,20 @@ impl ClientProxy {
         sync_on_wallet_recovery: bool,
         faucet_server: Option<String>,
         mnemonic_file: Option<String>,
+        wallet_password: Option<String>,
+        trusted_state_file: Option<String>,
+        waypoint_file: Option<String>,
+        address_book_file: Option<String>,
+        watch_only_file: Option<String>,
     ) -> Result<Self> {
-        let validators_config = TrustedPeersConfig::load_config(Path::new(validator_set_file));
+        let validators_config: TrustedPeersConfig =
+            toml::from_str(&fs::read_to_string(validator_set_file).map_err(|e| {
+                format_err!(
+                    "Unable to read trusted peers config {}: {}",
+                    validator_set_file,
+                    e
+                )
+            })?)?;
         let validators = validators_config.get_trusted_consensus_peers();
         ensure!(
             !validators.is_empty(),
@@ -127,10 +175,51 @@ impl ClientProxy {
             .into_iter()
             .map(|(key, value)| (key, value))
             .collect();
-        let validator_verifier = Arc::new(ValidatorVerifier::new(validator_pubkeys));
-        let client = GRPCClient::new(host, ac_port, validator_verifier)?;
+        let trusted_state = Self::get_trusted_state(
+            validator_pubkeys,
+            trusted_state_file.as_ref(),
+            waypoint_file.as_ref(),
+        )?;
+        let client = GRPCClient::new(
+            host,
+            ac_port,
+            trusted_state,
+            trusted_state_file.map(PathBuf::from),
+        )?;
 
-        let accounts = vec![];
+        // In watch-only mode, the wallet is never loaded, so that no mnemonic file is created or
+        // read: the accounts are the watched ones.
+        let (wallet, watch_only, accounts) = match watch_only_file {
+            Some(path) => {
+                let path = PathBuf::from(path);
+                let watch_only = if path.exists() {
+                    WatchOnlyWallet::read(&path)?
+                } else {
+                    WatchOnlyWallet::new()
+                };
+                let accounts = watch_only
+                    .accounts()
+                    .iter()
+                    .map(|account| {
+                        Self::get_account_data_from_address(
+                            &client,
+                            account.address,
+                            sync_on_wallet_recovery,
+                            None,
+                        )
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                (None, Some((path, watch_only)), accounts)
+            }
+            None => (
+                Some(Self::get_libra_wallet(
+                    mnemonic_file,
+                    wallet_password.as_ref(),
+                )?),
+                None,
+                vec![],
+            ),
+        };
 
         // If we have a faucet account file, then load it to get the keypair
         let faucet_account = if faucet_account_file.is_empty() {
@@ -167,12 +256,22 @@ impl ClientProxy {
             address_to_ref_id,
             faucet_server,
             faucet_account,
-            wallet: Self::get_libra_wallet(mnemonic_file)?,
+            wallet,
+            watch_only,
+            wallet_password,
             sync_on_wallet_recovery,
             temp_files: vec![],
+            quiet: false,
+            address_book: Self::get_address_book(address_book_file)?,
         })
     }
 
+    /// Suppress progress output such as the one printed while waiting for a transaction. Used by
+    /// batch mode so that stdout only carries the structured result of each command.
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
     fn get_account_ref_id(&self, sender_account_address: &AccountAddress) -> Result<usize> {
         Ok(*self
             .address_to_ref_id
@@ -188,7 +287,7 @@ impl ClientProxy {
 
     /// Returns the account index that should be used by user to reference this account
     pub fn create_next_account(&mut self, sync_with_validator: bool) -> Result<AddressAndIndex> {
-        let (address, _) = self.wallet.new_address()?;
+        let (address, _) = self.wallet_mut()?.new_address()?;
 
         let account_data =
             Self::get_account_data_from_address(&self.client, address, sync_with_validator, None)?;
@@ -299,26 +398,42 @@ impl ClientProxy {
         }
     }
 
-    /// Waits for the next transaction for a specific address and prints it
-    pub fn wait_for_transaction(&mut self, account: AccountAddress, sequence_number: u64) {
+    /// Waits for the next transaction for a specific address and prints it. Fails if the
+    /// transaction isn't stored after a while.
+    pub fn wait_for_transaction(
+        &mut self,
+        account: AccountAddress,
+        sequence_number: u64,
+    ) -> Result<()> {
         let mut max_iterations = 5000;
-        print!("waiting ");
+        if !self.quiet {
+            print!("waiting ");
+        }
         loop {
-            stdout().flush().unwrap();
+            stdout().flush()?;
             max_iterations -= 1;
 
             if let Ok(Some((_, Some(events)))) =
                 self.client
                     .get_txn_by_acc_seq(account, sequence_number - 1, true)
             {
-                println!("transaction is stored!");
-                if events.is_empty() {
-                    println!("no events emitted");
+                if !self.quiet {
+                    println!("transaction is stored!");
+                    if events.is_empty() {
+                        println!("no events emitted");
+                    }
                 }
-                break;
+                return Ok(());
             } else if max_iterations == 0 {
-                panic!("wait_for_transaction timeout");
-            } else {
+                if !self.quiet {
+                    println!();
+                }
+                bail!(
+                    "Timed out waiting for transaction {} of account {}",
+                    sequence_number - 1,
+                    account
+                );
+            } else if !self.quiet {
                 print!(".");
             }
             thread::sleep(time::Duration::from_millis(10));
@@ -362,7 +477,7 @@ impl ClientProxy {
         }
 
         if is_blocking {
-            self.wait_for_transaction(sender_address, sender_sequence);
+            self.wait_for_transaction(sender_address, sender_sequence)?;
         }
 
         Ok(IndexAndSequence {
@@ -410,31 +525,8 @@ impl ClientProxy {
 
         let num_coins = Self::convert_to_micro_libras(space_delim_strings[3])?;
 
-        let gas_unit_price = if space_delim_strings.len() > 4 {
-            Some(space_delim_strings[4].parse::<u64>().map_err(|error| {
-                format_parse_data_error(
-                    "gas_unit_price",
-                    InputType::UnsignedInt,
-                    space_delim_strings[4],
-                    error,
-                )
-            })?)
-        } else {
-            None
-        };
-
-        let max_gas_amount = if space_delim_strings.len() > 5 {
-            Some(space_delim_strings[5].parse::<u64>().map_err(|error| {
-                format_parse_data_error(
-                    "max_gas_amount",
-                    InputType::UnsignedInt,
-                    space_delim_strings[5],
-                    error,
-                )
-            })?)
-        } else {
-            None
-        };
+        let gas_unit_price = parse_optional_u64(space_delim_strings, 4, "gas_unit_price")?;
+        let max_gas_amount = parse_optional_u64(space_delim_strings, 5, "max_gas_amount")?;
 
         let sender_account_ref_id = self.get_account_ref_id(&sender_account_address)?;
 
@@ -556,16 +648,193 @@ impl ClientProxy {
             public_key.clone(),
             signature,
         );
+        // blocking by default (until transaction completion)
+        self.submit_crafted_transaction(signed_txn, true /* is_blocking */)
+    }
 
-        let mut req = SubmitTransactionRequest::new();
+    /// Prepare an unsigned transfer transaction and write it to a file, so that it can be signed
+    /// on another machine. The sender does not need to be managed by this client: its sequence
+    /// number is read from the validator.
+    pub fn prepare_transfer_coins_to_file(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<RawTransaction> {
+        ensure!(
+            space_delim_strings.len() >= 5 && space_delim_strings.len() <= 7,
+            "Invalid number of arguments for preparing transaction"
+        );
+        let sender_address = self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let receiver_address = self.get_account_address_from_parameter(space_delim_strings[2])?;
+        let num_coins = Self::convert_to_micro_libras(space_delim_strings[3])?;
+        let output_path = Path::new(space_delim_strings[4]);
+        let gas_unit_price = parse_optional_u64(space_delim_strings, 5, "gas_unit_price")?;
+        let max_gas_amount = parse_optional_u64(space_delim_strings, 6, "max_gas_amount")?;
+
+        let sequence_number = self.client.get_sequence_number(sender_address)?;
+        let program = vm_genesis::encode_transfer_program(&receiver_address, num_coins);
+        let raw_txn = create_unsigned_txn(
+            program,
+            sender_address,
+            sequence_number,
+            max_gas_amount.unwrap_or(MAX_GAS_AMOUNT),
+            gas_unit_price.unwrap_or(GAS_UNIT_PRICE),
+            OFFLINE_TX_EXPIRATION,
+        );
+        txn_file::write_unsigned_txn(output_path, &raw_txn)?;
+        Ok(raw_txn)
+    }
+
+    /// Sign the unsigned transaction read from a file and write the signed transaction to another
+    /// file. The sender must be managed by this client, either by the wallet or through a local
+    /// key pair. No connection to a validator is needed.
+    pub fn sign_transaction_file(&self, space_delim_strings: &[&str]) -> Result<SignedTransaction> {
+        ensure!(
+            space_delim_strings.len() == 3,
+            "Invalid number of arguments for signing transaction"
+        );
+        let raw_txn = txn_file::read_unsigned_txn(Path::new(space_delim_strings[1]))?;
+        let sender_address = raw_txn.sender();
+        let key_pair = match self.address_to_ref_id.get(&sender_address) {
+            Some(ref_id) => self.accounts[*ref_id].key_pair.as_ref(),
+            None => self
+                .faucet_account
+                .as_ref()
+                .filter(|faucet_account| faucet_account.address == sender_address)
+                .and_then(|faucet_account| faucet_account.key_pair.as_ref()),
+        };
+        let signer: Box<&dyn TransactionSigner> = match key_pair {
+            Some(key_pair) => Box::new(key_pair),
+            None => Box::new(self.wallet()?),
+        };
+        let signed_txn = signer.sign_txn(raw_txn)?;
+        txn_file::write_signed_txn(Path::new(space_delim_strings[2]), &signed_txn)?;
+        Ok(signed_txn)
+    }
+
+    /// Derive the address of a k-of-n multi-signature account. Each key is given either as the
+    /// reference id of a local account or as a hex encoded Ed25519 public key.
+    pub fn create_multisig_account(
+        &self,
+        space_delim_strings: &[&str],
+    ) -> Result<(AccountAddress, MultiEd25519PublicKey)> {
+        ensure!(
+            space_delim_strings.len() >= 3,
+            "Invalid number of arguments for creating multi-signature account"
+        );
+        let threshold = space_delim_strings[1].parse::<u8>().map_err(|error| {
+            format_parse_data_error(
+                "threshold",
+                InputType::UnsignedInt,
+                space_delim_strings[1],
+                error,
+            )
+        })?;
+        let public_keys = space_delim_strings[2..]
+            .iter()
+            .map(|key| match key.parse::<usize>() {
+                Ok(ref_id) => {
+                    let account = self.accounts.get(ref_id).ok_or_else(|| {
+                        format_err!(
+                            "Unable to find account by account reference id: {}, to see all \
+                             existing accounts, run: 'account list'",
+                            ref_id
+                        )
+                    })?;
+                    self.get_local_public_key(account)
+                }
+                Err(_) => Ok(Ed25519PublicKey::try_from(&hex::decode(key)?[..])?),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let public_key = MultiEd25519PublicKey::new(public_keys, threshold).map_err(|_| {
+            format_err!(
+                "Invalid threshold {} for {} keys: at least 1 and at most {} keys are required",
+                threshold,
+                space_delim_strings.len() - 2,
+                MAX_NUM_OF_KEYS
+            )
+        })?;
+        let address = AccountAddress::from_authentication_key_preimage(&public_key.to_bytes());
+        Ok((address, public_key))
+    }
+
+    /// Add the signature of a local account to a multi-signature transaction read from a file and
+    /// write the result to another file. The input is either an unsigned transaction, in which
+    /// case the multi-signature public key of the sender must be given, or a transaction already
+    /// signed by other cosigners. No connection to a validator is needed.
+    pub fn cosign_transaction_file(
+        &self,
+        space_delim_strings: &[&str],
+    ) -> Result<SignedTransaction> {
+        ensure!(
+            space_delim_strings.len() == 4 || space_delim_strings.len() == 5,
+            "Invalid number of arguments for cosigning transaction"
+        );
+        let input_path = Path::new(space_delim_strings[1]);
+        let signer_address = self.get_account_address_from_parameter(space_delim_strings[2])?;
+        let mut signed_txn = match space_delim_strings.get(4) {
+            Some(public_key) => SignedTransaction::craft_multi_signed_transaction_for_client(
+                txn_file::read_unsigned_txn(input_path)?,
+                MultiEd25519PublicKey::try_from(&hex::decode(public_key)?[..])?,
+                MultiEd25519Signature::new(vec![])?,
+            ),
+            None => txn_file::read_signed_txn(input_path)?,
+        };
+        let key_pair = self
+            .address_to_ref_id
+            .get(&signer_address)
+            .and_then(|ref_id| self.accounts[*ref_id].key_pair.as_ref());
+        signed_txn = match key_pair {
+            Some(key_pair) => {
+                let signature = key_pair
+                    .private_key
+                    .sign_message(&signed_txn.signing_message());
+                signed_txn.add_cosigner_signature(&key_pair.public_key, signature)?;
+                signed_txn
+            }
+            None => self.wallet()?.cosign_txn(signed_txn, &signer_address)?,
+        };
+        txn_file::write_signed_txn(Path::new(space_delim_strings[3]), &signed_txn)?;
+        Ok(signed_txn)
+    }
+
+    /// Submit the signed transaction read from a file. Its signature is checked before it is sent
+    /// to the validator.
+    pub fn submit_transaction_file(
+        &mut self,
+        space_delim_strings: &[&str],
+        is_blocking: bool,
+    ) -> Result<SignedTransaction> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for submitting transaction"
+        );
+        let signed_txn = txn_file::read_signed_txn(Path::new(space_delim_strings[1]))?
+            .check_signature()?
+            .into_inner();
+        self.submit_crafted_transaction(signed_txn.clone(), is_blocking)?;
+        Ok(signed_txn)
+    }
+
+    /// Submit a transaction signed outside of this client. The local sequence number of the
+    /// sender is bumped if the sender is managed by this client.
+    fn submit_crafted_transaction(
+        &mut self,
+        signed_txn: SignedTransaction,
+        is_blocking: bool,
+    ) -> Result<()> {
         let sender_address = signed_txn.sender();
         let sender_sequence = signed_txn.sequence_number();
 
+        let mut req = SubmitTransactionRequest::new();
         req.set_signed_txn(signed_txn.into_proto());
-        self.client.submit_transaction(None, &req)?;
-        // blocking by default (until transaction completion)
-        self.wait_for_transaction(sender_address, sender_sequence + 1);
-
+        let sender_account = match self.address_to_ref_id.get(&sender_address) {
+            Some(ref_id) => self.accounts.get_mut(*ref_id),
+            None => None,
+        };
+        self.client.submit_transaction(sender_account, &req)?;
+        if is_blocking {
+            self.wait_for_transaction(sender_address, sender_sequence + 1)?;
+        }
         Ok(())
     }
 
@@ -579,9 +848,7 @@ impl ClientProxy {
 
         self.client
             .submit_transaction(self.accounts.get_mut(sender_ref_id), &req)?;
-        self.wait_for_transaction(sender_address, sequence_number + 1);
-
-        Ok(())
+        self.wait_for_transaction(sender_address, sequence_number + 1)
     }
 
     /// Publish move module
@@ -604,6 +871,49 @@ impl ClientProxy {
         )
     }
 
+    /// Execute a transfer or a custom script against the latest state of the validator, without
+    /// submitting it, e.g. to know the gas it uses before picking its max_gas_amount.
+    pub fn simulate_transaction(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<SimulateTransactionResponse> {
+        ensure!(
+            space_delim_strings.len() >= 3,
+            "Invalid number of arguments to simulate transaction"
+        );
+        let sender_address = self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let sender_ref_id = self.get_account_ref_id(&sender_address)?;
+        let program = if space_delim_strings[2] == "transfer" {
+            ensure!(
+                space_delim_strings.len() == 5,
+                "Invalid number of arguments to simulate transfer"
+            );
+            let receiver_address =
+                self.get_account_address_from_parameter(space_delim_strings[3])?;
+            let num_coins = Self::convert_to_micro_libras(space_delim_strings[4])?;
+            vm_genesis::encode_transfer_program(&receiver_address, num_coins)
+        } else {
+            let program: Program = serde_json::from_slice(&fs::read(space_delim_strings[2])?)?;
+            let arguments: Vec<_> = space_delim_strings[3..]
+                .iter()
+                .filter_map(|arg| parse_as_transaction_argument(arg).ok())
+                .collect();
+            let (script, _, modules) = program.into_inner();
+            Program::new(script, modules, arguments)
+        };
+
+        let sender = &self.accounts[sender_ref_id];
+        let mut submit_req = self.create_submit_transaction_req(program, sender, None, None)?;
+        let mut req = SimulateTransactionRequest::new();
+        req.set_signed_txn(submit_req.take_signed_txn());
+        self.client.simulate_transaction(&req)
+    }
+
+    /// Get the latest ledger info verified by the client.
+    pub fn get_trusted_ledger_info(&self) -> LedgerInfoWithSignatures<Ed25519Signature> {
+        self.client.trusted_ledger_info()
+    }
+
     /// Get the latest account state from validator.
     pub fn get_latest_account_state(
         &mut self,
@@ -617,6 +927,21 @@ impl ClientProxy {
         self.get_account_state_and_update(account)
     }
 
+    /// Get the state of an account as of a past version from validator, along with the version
+    /// of the latest ledger info it is proven against.
+    pub fn get_account_state_at_version(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<(Option<AccountStateBlob>, Version)> {
+        ensure!(
+            space_delim_strings.len() == 3,
+            "Invalid number of arguments to get account state at version"
+        );
+        let account = self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let version = parse_version("version", space_delim_strings[2])?;
+        self.client.get_account_blob_at_version(account, version)
+    }
+
     /// Get committed txn by account and sequence number.
     pub fn get_committed_txn_by_acc_seq(
         &mut self,
@@ -687,20 +1012,146 @@ impl ClientProxy {
             .get_txn_by_range(start_version, limit, fetch_events)
     }
 
+    /// Write the reconciled payment history of an account to a file. Every page of the sent and
+    /// received payment events of the account is fetched, along with the transactions which
+    /// emitted them to account for the gas paid by the account.
+    pub fn export_transaction_history(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<TransactionHistory> {
+        ensure!(
+            space_delim_strings.len() >= 3 && space_delim_strings.len() % 2 == 1,
+            "Invalid number of arguments to export transaction history"
+        );
+        let account = self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let output_path = Path::new(space_delim_strings[2]);
+        let mut from = None;
+        let mut to = None;
+        let mut format = HistoryFormat::Csv;
+        for option in space_delim_strings[3..].chunks(2) {
+            match option[0] {
+                "--from" => from = Some(parse_version("from", option[1])?),
+                "--to" => to = Some(parse_version("to", option[1])?),
+                "--format" => format = option[1].parse()?,
+                _ => bail!("Unknown option: {}", option[0]),
+            }
+        }
+
+        // Events committed after the balance was read are left out, so that the running balance
+        // ends on the balance read.
+        let (account_state, ledger_version) = self.client.get_account_blob(account)?;
+        let account_resource = get_account_resource_or_default(&account_state)?;
+        let mut entries = vec![];
+        for (path, direction) in &[
+            (account_sent_event_path(), PaymentDirection::Sent),
+            (account_received_event_path(), PaymentDirection::Received),
+        ] {
+            let access_path = AccessPath::new(account, path.clone());
+            let mut start_seq_number = 0;
+            loop {
+                let (events, _) = self.client.get_events_by_access_path(
+                    access_path.clone(),
+                    start_seq_number,
+                    true, /* ascending */
+                    HISTORY_PAGE_SIZE,
+                )?;
+                let num_events = events.len() as u64;
+                for event_with_proof in events {
+                    start_seq_number = event_with_proof.event.sequence_number() + 1;
+                    if event_with_proof.transaction_version > ledger_version {
+                        continue;
+                    }
+                    let payment = SimpleDeserializer::deserialize::<AccountEvent>(
+                        event_with_proof.event.event_data(),
+                    )?;
+                    entries.push(HistoryEntry::new(
+                        event_with_proof.transaction_version,
+                        event_with_proof.event_index,
+                        *direction,
+                        Some(payment.account()),
+                        payment.amount(),
+                        0,
+                    ));
+                }
+                if num_events < HISTORY_PAGE_SIZE {
+                    break;
+                }
+            }
+        }
+
+        // Every transaction sent by the account paid gas, whether it made payments or not, e.g.
+        // when it aborted. The gas is charged on the first sent payment of the transaction if any,
+        // or on a fee entry otherwise.
+        let mut first_sent_payments = HashMap::new();
+        for (index, entry) in entries.iter().enumerate().rev() {
+            if entry.direction == PaymentDirection::Sent {
+                first_sent_payments.insert(entry.version, index);
+            }
+        }
+        let num_sent_txns = account_resource.sequence_number();
+        let mut start_sequence_number = 0;
+        while start_sequence_number < num_sent_txns {
+            let limit = min(HISTORY_PAGE_SIZE, num_sent_txns - start_sequence_number);
+            for (version, txn, info) in
+                self.client
+                    .get_sent_txns_with_info(account, start_sequence_number, limit)?
+            {
+                ensure!(
+                    version <= ledger_version,
+                    "Transaction {} of {} was committed at version {}, after version {}",
+                    txn.sequence_number(),
+                    account,
+                    version,
+                    ledger_version
+                );
+                let gas_fee = info
+                    .gas_used()
+                    .checked_mul(txn.gas_unit_price())
+                    .ok_or_else(|| format_err!("The gas fee at version {} overflows", version))?;
+                match first_sent_payments.remove(&version) {
+                    Some(index) => entries[index].gas_fee = gas_fee,
+                    None => entries.push(HistoryEntry::new(
+                        version,
+                        0,
+                        PaymentDirection::Fee,
+                        None,
+                        0,
+                        gas_fee,
+                    )),
+                }
+            }
+            start_sequence_number += limit;
+        }
+        if let Some(version) = first_sent_payments.keys().min() {
+            bail!(
+                "Sent payment event at version {} was not emitted by a transaction of {}",
+                version,
+                account
+            );
+        }
+
+        let history = TransactionHistory::reconcile(
+            account,
+            ledger_version,
+            account_resource.balance(),
+            entries,
+            from,
+            to,
+        )?;
+        let mut file = fs::File::create(output_path)?;
+        history.write(&mut file, format)?;
+        Ok(history)
+    }
+
     /// Get account address from parameter. If the parameter is string of address, try to convert
-    /// it to address, otherwise, try to convert to u64 and looking at TestClient::accounts.
+    /// it to address, otherwise, try to convert to u64 and looking at TestClient::accounts, and
+    /// finally look the parameter up in the address book.
     pub fn get_account_address_from_parameter(&self, para: &str) -> Result<AccountAddress> {
-        match is_address(para) {
-            true => ClientProxy::address_from_strings(para),
-            false => {
-                let account_ref_id = para.parse::<usize>().map_err(|error| {
-                    format_parse_data_error(
-                        "account_reference_id/account_address",
-                        InputType::Usize,
-                        para,
-                        error,
-                    )
-                })?;
+        if is_address(para) {
+            return ClientProxy::address_from_strings(para);
+        }
+        match para.parse::<usize>() {
+            Ok(account_ref_id) => {
                 let account_data = self.accounts.get(account_ref_id).ok_or_else(|| {
                     format_err!(
                         "Unable to find account by account reference id: {}, to see all existing \
@@ -710,9 +1161,66 @@ impl ClientProxy {
                 })?;
                 Ok(account_data.address)
             }
+            Err(_) => self.address_book.get(para).ok_or_else(|| {
+                format_err!(
+                    "Unable to parse input for account_reference_id/account_address/name - no \
+                     address book entry is named {}, to see all entries, run: \
+                     'address_book list'",
+                    para
+                )
+            }),
         }
     }
 
+    /// Name an account in the address book. The account is given like any account parameter.
+    pub fn add_address_book_entry(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<(AccountAddress, Option<AccountAddress>)> {
+        ensure!(
+            space_delim_strings.len() == 3,
+            "Invalid number of arguments for adding an address book entry"
+        );
+        let address = self.get_account_address_from_parameter(space_delim_strings[2])?;
+        let previous = self.address_book.add(space_delim_strings[1], address)?;
+        Ok((address, previous))
+    }
+
+    /// Remove an entry of the address book.
+    pub fn remove_address_book_entry(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<AccountAddress> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for removing an address book entry"
+        );
+        self.address_book.remove(space_delim_strings[1])
+    }
+
+    /// Merge the entries of an address book file into the address book.
+    pub fn import_address_book(&mut self, space_delim_strings: &[&str]) -> Result<usize> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for importing an address book"
+        );
+        self.address_book.import(space_delim_strings[1])
+    }
+
+    /// Write the entries of the address book to a file.
+    pub fn export_address_book(&self, space_delim_strings: &[&str]) -> Result<()> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for exporting the address book"
+        );
+        self.address_book.export(space_delim_strings[1])
+    }
+
+    /// The address book.
+    pub fn address_book(&self) -> &AddressBook {
+        &self.address_book
+    }
+
     /// Get events by account and event type with start sequence number and limit.
     pub fn get_events_by_account_and_type(
         &mut self,
@@ -723,14 +1231,7 @@ impl ClientProxy {
             "Invalid number of arguments to get events by access path"
         );
         let account = self.get_account_address_from_parameter(space_delim_strings[1])?;
-        let path = match space_delim_strings[2] {
-            "sent" => account_sent_event_path(),
-            "received" => account_received_event_path(),
-            _ => bail!(
-                "Unknown event type: {:?}, only sent and received are supported",
-                space_delim_strings[2]
-            ),
-        };
+        let path = parse_event_path(space_delim_strings[2])?;
         let access_path = AccessPath::new(account, path);
         let start_seq_number = space_delim_strings[3].parse::<u64>().map_err(|error| {
             format_parse_data_error(
@@ -755,15 +1256,201 @@ impl ClientProxy {
             .get_events_by_access_path(access_path, start_seq_number, ascending, limit)
     }
 
-    /// Write mnemonic recover to the file specified.
+    /// Stream the events of an account and event type as they are committed, from the given
+    /// sequence number on, or from the next event of the account.
+    pub fn watch_events(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<impl Iterator<Item = Result<Vec<EventWithProof>>>> {
+        ensure!(
+            space_delim_strings.len() >= 3 && space_delim_strings.len() <= 4,
+            "Invalid number of arguments to watch events"
+        );
+        let account = self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let path = parse_event_path(space_delim_strings[2])?;
+        let access_path = AccessPath::new(account, path);
+        let start_seq_number = match space_delim_strings.get(3) {
+            Some(start_seq_number) => start_seq_number.parse::<u64>().map_err(|error| {
+                format_parse_data_error(
+                    "start_seq_number",
+                    InputType::UnsignedInt,
+                    start_seq_number,
+                    error,
+                )
+            })?,
+            None => self
+                .get_account_resource_and_update(account)?
+                .get_event_handle_by_query_path(&access_path)?
+                .count(),
+        };
+        self.client.watch_events(access_path, start_seq_number)
+    }
+
+    /// Get the events emitted by the transactions from a start version on, up to a limit number of
+    /// transactions, optionally only the events of an account and event type (sent|received).
+    pub fn get_events_by_version_range(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<Vec<EventWithProof>> {
+        ensure!(
+            space_delim_strings.len() == 3 || space_delim_strings.len() == 5,
+            "Invalid number of arguments to get events by version range"
+        );
+        let start_version = parse_version("start_version", space_delim_strings[1])?;
+        let limit = space_delim_strings[2].parse::<u64>().map_err(|error| {
+            format_parse_data_error(
+                "limit",
+                InputType::UnsignedInt,
+                space_delim_strings[2],
+                error,
+            )
+        })?;
+        let access_path_prefix = if space_delim_strings.len() == 5 {
+            let account = self.get_account_address_from_parameter(space_delim_strings[3])?;
+            let path = parse_event_path(space_delim_strings[4])?;
+            // The events are emitted to the access path of the key of the event handle.
+            Some(
+                self.get_account_resource_and_update(account)?
+                    .get_event_handle_by_query_path(&AccessPath::new(account, path))?
+                    .as_access_path()?,
+            )
+        } else {
+            None
+        };
+        self.client
+            .get_events_by_version_range(start_version, limit, access_path_prefix)
+    }
+
+    /// Get the ledger counters recorded by the validator from the start version to the end version,
+    /// which defaults to the start version. The first set is the latest one recorded at or before
+    /// the start version.
+    pub fn get_ledger_statistics(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<Vec<LedgerStatistics>> {
+        ensure!(
+            space_delim_strings.len() >= 2 && space_delim_strings.len() <= 3,
+            "Invalid number of arguments to get ledger statistics"
+        );
+        let start_version = parse_version("start_version", space_delim_strings[1])?;
+        let end_version =
+            parse_optional_u64(space_delim_strings, 2, "end_version")?.unwrap_or(start_version);
+        self.client
+            .get_ledger_statistics(start_version, end_version, LEDGER_STATISTICS_LIMIT)
+    }
+
+    /// Create an empty sub-wallet, whose keys are derived from the mnemonic of the wallet along
+    /// their own BIP-44 like derivation paths, and return its account level.
+    pub fn create_sub_wallet(&mut self, space_delim_strings: &[&str]) -> Result<u32> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for creating a sub-wallet"
+        );
+        Ok(self.wallet_mut()?.new_sub_wallet(space_delim_strings[1])?)
+    }
+
+    /// Create the next account of a sub-wallet. Returns the account index that should be used by
+    /// user to reference this account, and the derivation path of its key.
+    pub fn create_next_sub_wallet_account(
+        &mut self,
+        name: &str,
+        sync_with_validator: bool,
+    ) -> Result<(AddressAndIndex, String)> {
+        let (address, path) = self.wallet_mut()?.new_sub_wallet_address(name)?;
+
+        let account_data =
+            Self::get_account_data_from_address(&self.client, address, sync_with_validator, None)?;
+
+        Ok((self.insert_account_data(account_data), path.to_string()))
+    }
+
+    /// The sub-wallets of the wallet with their account level and their addresses, ordered by
+    /// name.
+    pub fn get_sub_wallets(&self) -> Result<Vec<(String, u32, Vec<AccountAddress>)>> {
+        let wallet = self.wallet()?;
+        wallet
+            .sub_wallets()
+            .iter()
+            .map(|(name, sub_wallet)| {
+                Ok((
+                    name.clone(),
+                    sub_wallet.account,
+                    wallet.get_sub_wallet_addresses(name)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Write the addresses and public keys of all the accounts of the wallet to a watch-only
+    /// wallet file, to be used by a client started in watch-only mode. Returns the number of
+    /// accounts written.
+    pub fn export_watch_only(&self, space_delim_strings: &[&str]) -> Result<usize> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for exporting a watch-only wallet"
+        );
+        let watch_only = self.wallet()?.export_watch_only()?;
+        watch_only.write(Path::new(space_delim_strings[1]))?;
+        Ok(watch_only.accounts().len())
+    }
+
+    /// Watch an account given its address, in watch-only mode. The watched accounts are saved
+    /// to the watch-only wallet file.
+    pub fn watch_account(&mut self, space_delim_strings: &[&str]) -> Result<AddressAndIndex> {
+        ensure!(
+            space_delim_strings.len() == 2 || space_delim_strings.len() == 3,
+            "Invalid number of arguments for watching an account"
+        );
+        let address = self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let label = space_delim_strings.get(2).map(|label| label.to_string());
+        let (path, watch_only) = self.watch_only_mut()?;
+        watch_only.add_address(address, label)?;
+        watch_only.write(path)?;
+        self.insert_watched_account(address)
+    }
+
+    /// Watch an account given its public key, in watch-only mode. The watched accounts are
+    /// saved to the watch-only wallet file.
+    pub fn watch_public_key(&mut self, space_delim_strings: &[&str]) -> Result<AddressAndIndex> {
+        ensure!(
+            space_delim_strings.len() == 2 || space_delim_strings.len() == 3,
+            "Invalid number of arguments for watching an account"
+        );
+        let public_key = Ed25519PublicKey::try_from(&hex::decode(space_delim_strings[1])?[..])
+            .map_err(|e| format_err!("Invalid public key {}: {}", space_delim_strings[1], e))?;
+        let label = space_delim_strings.get(2).map(|label| label.to_string());
+        let (path, watch_only) = self.watch_only_mut()?;
+        let address = watch_only.add_public_key(public_key, label)?;
+        watch_only.write(path)?;
+        self.insert_watched_account(address)
+    }
+
+    fn watch_only_mut(&mut self) -> Result<(&Path, &mut WatchOnlyWallet)> {
+        let (path, watch_only) = self.watch_only.as_mut().ok_or_else(|| {
+            format_err!("Accounts can only be watched by a client started in watch-only mode")
+        })?;
+        Ok((path.as_path(), watch_only))
+    }
+
+    fn insert_watched_account(&mut self, address: AccountAddress) -> Result<AddressAndIndex> {
+        let account_data = Self::get_account_data_from_address(&self.client, address, true, None)?;
+        Ok(self.insert_account_data(account_data))
+    }
+
+    /// Write mnemonic recover to the file specified. The recovery data is encrypted if the
+    /// client was given a wallet password.
     pub fn write_recovery(&self, space_delim_strings: &[&str]) -> Result<()> {
         ensure!(
             space_delim_strings.len() == 2,
             "Invalid number of arguments for writing recovery"
         );
 
-        self.wallet
-            .write_recovery(&Path::new(space_delim_strings[1]))?;
+        let path = Path::new(space_delim_strings[1]);
+        let wallet = self.wallet()?;
+        match &self.wallet_password {
+            Some(password) => wallet.write_keystore(&path, password.as_bytes())?,
+            None => wallet.write_recovery(&path)?,
+        }
         Ok(())
     }
 
@@ -776,9 +1463,19 @@ impl ClientProxy {
             space_delim_strings.len() == 2,
             "Invalid number of arguments for recovering wallets"
         );
+        ensure!(
+            self.watch_only.is_none(),
+            "The client is in watch-only mode: no wallet can be recovered"
+        );
 
-        let wallet = WalletLibrary::recover(&Path::new(space_delim_strings[1]))?;
-        let wallet_addresses = wallet.get_addresses()?;
+        let wallet = Self::recover_wallet(
+            &Path::new(space_delim_strings[1]),
+            self.wallet_password.as_ref(),
+        )?;
+        let mut wallet_addresses = wallet.get_addresses()?;
+        for name in wallet.sub_wallets().keys() {
+            wallet_addresses.extend(wallet.get_sub_wallet_addresses(name)?);
+        }
         let mut account_data = Vec::new();
         for address in wallet_addresses {
             account_data.push(Self::get_account_data_from_address(
@@ -876,7 +1573,53 @@ impl ClientProxy {
         })
     }
 
-    fn get_libra_wallet(mnemonic_file: Option<String>) -> Result<WalletLibrary> {
+    /// Get the public key of an account managed by this client.
+    fn get_local_public_key(&self, account: &AccountData) -> Result<Ed25519PublicKey> {
+        match &account.key_pair {
+            Some(key_pair) => Ok(key_pair.public_key.clone()),
+            None => Ok(self.wallet()?.get_public_key(&account.address)?),
+        }
+    }
+
+    /// Load the light-client state persisted in `trusted_state_file`. A new client starts from the
+    /// waypoint if any, a trusted state obtained out of band, or from the genesis validators.
+    fn get_trusted_state(
+        validators: HashMap<AccountAddress, Ed25519PublicKey>,
+        trusted_state_file: Option<&String>,
+        waypoint_file: Option<&String>,
+    ) -> Result<TrustedState> {
+        let waypoint = match waypoint_file {
+            Some(path) => Some(TrustedState::load(path)?),
+            None => None,
+        };
+        let trusted_state = match trusted_state_file {
+            Some(path) if Path::new(path).exists() => TrustedState::load(path)?,
+            _ => waypoint
+                .clone()
+                .unwrap_or_else(|| TrustedState::new_genesis(validators)),
+        };
+        if let Some(waypoint) = waypoint {
+            trusted_state.check_waypoint(&waypoint)?;
+        }
+        Ok(trusted_state)
+    }
+
+    fn get_address_book(address_book_file: Option<String>) -> Result<AddressBook> {
+        let address_book_path = match address_book_file {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let mut file_path = std::env::current_dir()?;
+                file_path.push(CLIENT_ADDRESS_BOOK_FILE);
+                file_path
+            }
+        };
+        AddressBook::load(address_book_path)
+    }
+
+    fn get_libra_wallet(
+        mnemonic_file: Option<String>,
+        wallet_password: Option<&String>,
+    ) -> Result<WalletLibrary> {
         let wallet_recovery_file_path = if let Some(input_mnemonic_word) = mnemonic_file {
             Path::new(&input_mnemonic_word).to_path_buf()
         } else {
@@ -885,19 +1628,70 @@ impl ClientProxy {
             file_path
         };
 
+        // Never overwrite an existing keystore: failing to decrypt it must be reported.
+        if wallet_recovery_file_path.exists() && keystore::is_keystore(&wallet_recovery_file_path)?
+        {
+            return Self::recover_wallet(&wallet_recovery_file_path, wallet_password);
+        }
+
         let wallet = if let Ok(recovered_wallet) = io_utils::recover(&wallet_recovery_file_path) {
-            recovered_wallet
+            match wallet_password {
+                Some(password) => {
+                    info!(
+                        "Encrypting plaintext wallet recovery file {:?}",
+                        wallet_recovery_file_path
+                    );
+                    keystore::migrate_recovery_file(
+                        &wallet_recovery_file_path,
+                        password.as_bytes(),
+                    )?
+                }
+                None => recovered_wallet,
+            }
         } else {
             let new_wallet = WalletLibrary::new();
-            new_wallet.write_recovery(&wallet_recovery_file_path)?;
+            match wallet_password {
+                Some(password) => {
+                    new_wallet.write_keystore(&wallet_recovery_file_path, password.as_bytes())?
+                }
+                None => new_wallet.write_recovery(&wallet_recovery_file_path)?,
+            }
             new_wallet
         };
         Ok(wallet)
     }
 
+    /// Recover the wallet from either a keystore or a plaintext recovery file.
+    fn recover_wallet(path: &Path, wallet_password: Option<&String>) -> Result<WalletLibrary> {
+        if keystore::is_keystore(&path)? {
+            let password = wallet_password.ok_or_else(|| {
+                format_err!(
+                    "{} is an encrypted keystore, a wallet password is required",
+                    path.display()
+                )
+            })?;
+            Ok(WalletLibrary::recover_keystore(path, password.as_bytes())?)
+        } else {
+            Ok(WalletLibrary::recover(path)?)
+        }
+    }
+
     /// Set wallet instance used by this client.
     fn set_wallet(&mut self, wallet: WalletLibrary) {
-        self.wallet = wallet;
+        self.wallet = Some(wallet);
+    }
+
+    /// The wallet of this client, unless it runs in watch-only mode.
+    fn wallet(&self) -> Result<&WalletLibrary> {
+        self.wallet.as_ref().ok_or_else(|| {
+            format_err!("The client is in watch-only mode: it holds no key of any account")
+        })
+    }
+
+    fn wallet_mut(&mut self) -> Result<&mut WalletLibrary> {
+        self.wallet.as_mut().ok_or_else(|| {
+            format_err!("The client is in watch-only mode: it holds no key of any account")
+        })
     }
 
     fn load_faucet_account_file(
@@ -948,14 +1742,15 @@ impl ClientProxy {
             None, /* gas_unit_price */
         )?;
         let mut sender_mut = self.faucet_account.as_mut().unwrap();
-        let resp = self.client.submit_transaction(Some(&mut sender_mut), &req);
+        self.client
+            .submit_transaction(Some(&mut sender_mut), &req)?;
         if is_blocking {
             self.wait_for_transaction(
                 sender_address,
                 self.faucet_account.as_ref().unwrap().sequence_number,
-            );
+            )?;
         }
-        resp
+        Ok(())
     }
 
     fn mint_coins_with_faucet_service(
@@ -988,7 +1783,7 @@ impl ClientProxy {
         }
         let sequence_number = raw_data.parse::<u64>()?;
         if is_blocking {
-            self.wait_for_transaction(association_address(), sequence_number);
+            self.wait_for_transaction(association_address(), sequence_number)?;
         }
         Ok(())
     }
@@ -1027,7 +1822,7 @@ impl ClientProxy {
     ) -> Result<SubmitTransactionRequest> {
         let signer: Box<&dyn TransactionSigner> = match &sender_account.key_pair {
             Some(key_pair) => Box::new(key_pair),
-            None => Box::new(&self.wallet),
+            None => Box::new(self.wallet()?),
         };
         let signed_txn = create_signed_txn(
             *signer,
@@ -1084,6 +1879,38 @@ fn format_parse_data_error<T: std::fmt::Debug>(
     )
 }
 
+/// Parse the optional unsigned integer parameter at `index`, if present.
+fn parse_optional_u64(
+    space_delim_strings: &[&str],
+    index: usize,
+    field: &str,
+) -> Result<Option<u64>> {
+    match space_delim_strings.get(index) {
+        Some(value) => Ok(Some(value.parse::<u64>().map_err(|error| {
+            format_parse_data_error(field, InputType::UnsignedInt, value, error)
+        })?)),
+        None => Ok(None),
+    }
+}
+
+/// Parse an event type (sent|received) into the path of its event handle in an account.
+fn parse_event_path(value: &str) -> Result<Vec<u8>> {
+    match value {
+        "sent" => Ok(account_sent_event_path()),
+        "received" => Ok(account_received_event_path()),
+        _ => bail!(
+            "Unknown event type: {:?}, only sent and received are supported",
+            value
+        ),
+    }
+}
+
+fn parse_version(field: &str, value: &str) -> Result<Version> {
+    value
+        .parse::<Version>()
+        .map_err(|error| format_parse_data_error(field, InputType::UnsignedInt, value, error))
+}
+
 fn parse_bool(para: &str) -> Result<bool> {
     Ok(para.to_lowercase().parse::<bool>()?)
 }
@@ -1127,6 +1954,11 @@ mod tests {
             false,
             None,
             Some(mnemonic_path),
+            None,
+            None,
+            None,
+            None,
+            None,
         )
         .unwrap();
         for _ in 0..count {
@@ -1185,11 +2017,34 @@ mod tests {
 
         let file = NamedTempFile::new().unwrap();
         let path = file.into_temp_path();
-        io_utils::write_recovery(&client.wallet, &path).expect("failed to write to file");
+        io_utils::write_recovery(client.wallet().unwrap(), &path).expect("failed to write to file");
 
         let wallet = io_utils::recover(&path).expect("failed to load from file");
 
-        assert_eq!(client.wallet.mnemonic(), wallet.mnemonic());
+        assert_eq!(client.wallet().unwrap().mnemonic(), wallet.mnemonic());
+    }
+
+    #[test]
+    fn test_recover_sub_wallet_accounts() {
+        let (mut client, _) = generate_accounts_from_wallet(2);
+        client.create_sub_wallet(&["csw", "savings"]).unwrap();
+        let (_, path) = client
+            .create_next_sub_wallet_account("savings", false)
+            .unwrap();
+        assert!(path.starts_with("m/44/"));
+        assert!(client
+            .create_next_sub_wallet_account("unknown", false)
+            .is_err());
+
+        let file = NamedTempFile::new().unwrap();
+        let temp_path = file.into_temp_path();
+        let recovery_path = temp_path.to_str().unwrap();
+        client.write_recovery(&["w", recovery_path]).unwrap();
+        let accounts = client
+            .recover_wallet_accounts(&["r", recovery_path])
+            .unwrap();
+        assert_eq!(accounts.len(), 3);
+        assert!(client.watch_account(&["wa", "0"]).is_err());
     }
 
     proptest! {
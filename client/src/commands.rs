@@ -2,12 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    account_commands::AccountCommand, client_proxy::ClientProxy, dev_commands::DevCommand,
+    account_commands::AccountCommand, address_book_commands::AddressBookCommand,
+    client_proxy::ClientProxy, dev_commands::DevCommand, offline_commands::OfflineCommand,
     query_commands::QueryCommand, transfer_commands::TransferCommand,
 };
 
 use failure::prelude::*;
 use metrics::counters::*;
+use serde_json::Value;
 use std::{collections::HashMap, sync::Arc};
 use types::account_address::ADDRESS_LENGTH;
 
@@ -17,7 +19,8 @@ pub fn report_error(msg: &str, e: Error) {
     COUNTER_CLIENT_ERRORS.inc();
 }
 
-fn pretty_format_error(e: Error) -> String {
+/// Format the error for the user, hiding gRPC details for the common connectivity failures.
+pub fn pretty_format_error(e: Error) -> String {
     if let Some(grpc_error) = e.downcast_ref::<grpcio::Error>() {
         if let grpcio::Error::RpcFailure(grpc_rpc_failure) = grpc_error {
             match grpc_rpc_failure.status {
@@ -64,6 +67,8 @@ pub fn get_commands(
         Arc::new(AccountCommand {}),
         Arc::new(QueryCommand {}),
         Arc::new(TransferCommand {}),
+        Arc::new(OfflineCommand {}),
+        Arc::new(AddressBookCommand {}),
     ];
     if include_dev {
         commands.push(Arc::new(DevCommand {}));
@@ -99,14 +104,8 @@ pub fn print_subcommand_help(parent_command: &str, commands: &[Box<dyn Command>]
     println!("\n");
 }
 
-/// Execute sub command.
-// TODO: Convert subcommands arrays to lazy statics
-pub fn subcommand_execute(
-    parent_command_name: &str,
-    commands: Vec<Box<dyn Command>>,
-    client: &mut ClientProxy,
-    params: &[&str],
-) {
+/// Build the reverse index from the aliases of sub commands to their position in `commands`.
+fn subcommand_index(commands: &[Box<dyn Command>]) -> HashMap<&'static str, usize> {
     let mut commands_map = HashMap::new();
     for (i, cmd) in commands.iter().enumerate() {
         for alias in cmd.get_aliases() {
@@ -115,6 +114,18 @@ pub fn subcommand_execute(
             }
         }
     }
+    commands_map
+}
+
+/// Execute sub command.
+// TODO: Convert subcommands arrays to lazy statics
+pub fn subcommand_execute(
+    parent_command_name: &str,
+    commands: Vec<Box<dyn Command>>,
+    client: &mut ClientProxy,
+    params: &[&str],
+) {
+    let commands_map = subcommand_index(&commands);
 
     if params.is_empty() {
         print_subcommand_help(parent_command_name, &commands);
@@ -127,6 +138,30 @@ pub fn subcommand_execute(
     }
 }
 
+/// Execute sub command in batch mode, returning its structured output.
+pub fn subcommand_execute_batch(
+    parent_command_name: &str,
+    commands: Vec<Box<dyn Command>>,
+    client: &mut ClientProxy,
+    params: &[&str],
+) -> Result<Value> {
+    let commands_map = subcommand_index(&commands);
+
+    ensure!(
+        !params.is_empty(),
+        "Missing sub command for {}",
+        parent_command_name
+    );
+    match commands_map.get(&params[0]) {
+        Some(&idx) => commands[idx].execute_batch(client, &params),
+        None => bail!(
+            "Unknown sub command for {}: {:?}",
+            parent_command_name,
+            params[0]
+        ),
+    }
+}
+
 /// Trait to perform client operations.
 pub trait Command {
     /// all commands and aliases this command support.
@@ -139,4 +174,9 @@ pub trait Command {
     fn get_description(&self) -> &'static str;
     /// code to execute.
     fn execute(&self, client: &mut ClientProxy, params: &[&str]);
+    /// code to execute in batch mode. Instead of printing, the command returns its result as a
+    /// JSON value, or the error that should abort the batch.
+    fn execute_batch(&self, _client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        bail!("Command {:?} is not supported in batch mode", params[0])
+    }
 }
@@ -2,6 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{client_proxy::ClientProxy, commands::*};
+use failure::prelude::*;
+use serde_json::{json, Value};
+use types::transaction::TransactionStatus;
 
 /// Major command for account related operations.
 pub struct DevCommand {}
@@ -14,13 +17,20 @@ impl Command for DevCommand {
         "Local move development"
     }
     fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        let commands: Vec<Box<dyn Command>> = vec![
-            Box::new(DevCommandCompile {}),
-            Box::new(DevCommandPublish {}),
-            Box::new(DevCommandExecute {}),
-        ];
-        subcommand_execute(&params[0], commands, client, &params[1..]);
+        subcommand_execute(&params[0], dev_subcommands(), client, &params[1..]);
     }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        subcommand_execute_batch(&params[0], dev_subcommands(), client, &params[1..])
+    }
+}
+
+fn dev_subcommands() -> Vec<Box<dyn Command>> {
+    vec![
+        Box::new(DevCommandCompile {}),
+        Box::new(DevCommandPublish {}),
+        Box::new(DevCommandExecute {}),
+        Box::new(DevCommandSimulate {}),
+    ]
 }
 
 /// Sub command to compile move program
@@ -47,6 +57,14 @@ impl Command for DevCommandCompile {
             Err(e) => println!("{}", e),
         }
     }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        ensure!(
+            params.len() >= 4 && params.len() <= 5,
+            "Invalid number of arguments for compilation"
+        );
+        let path = client.compile_program(params)?;
+        Ok(json!({ "output_path": path }))
+    }
 }
 
 /// Sub command to publish move resource
@@ -75,6 +93,15 @@ impl Command for DevCommandPublish {
             Err(e) => println!("{}", e),
         }
     }
+
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        ensure!(
+            params.len() == 3,
+            "Invalid number of arguments to publish module"
+        );
+        client.publish_module(params)?;
+        Ok(json!({ "published": params[2] }))
+    }
 }
 
 /// Sub command to execute custom move script
@@ -103,4 +130,77 @@ impl Command for DevCommandExecute {
             Err(e) => println!("{}", e),
         }
     }
+
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        ensure!(
+            params.len() >= 3,
+            "Invalid number of arguments to execute script"
+        );
+        client.execute_script(params)?;
+        Ok(json!({ "executed": params[2] }))
+    }
+}
+
+/// Sub command to execute a transaction without submitting it
+pub struct DevCommandSimulate {}
+
+impl Command for DevCommandSimulate {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["simulate", "s"]
+    }
+
+    fn get_params_help(&self) -> &'static str {
+        "<sender_account_address>|<sender_account_ref_id> \
+         <compiled_module_path> [parameters] | transfer <receiver_account_address>|\
+         <receiver_account_ref_id> <number_of_coins>"
+    }
+
+    fn get_description(&self) -> &'static str {
+        "Execute a custom move script or a transfer against the latest state without submitting \
+         it, and print the gas it uses"
+    }
+
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.simulate_transaction(params) {
+            Ok(response) => {
+                let output = &response.output;
+                match output.status() {
+                    TransactionStatus::Keep(vm_status) => {
+                        println!("Transaction would be committed with status {:?}", vm_status)
+                    }
+                    TransactionStatus::Discard(vm_status) => {
+                        println!("Transaction would be discarded with status {:?}", vm_status)
+                    }
+                }
+                println!(
+                    "Gas used: {}, writes: {}, events: {} (simulated at version {})",
+                    output.gas_used(),
+                    output.write_set().len(),
+                    output.events().len(),
+                    response.version
+                );
+                for event in output.events() {
+                    println!("{}", event);
+                }
+            }
+            Err(e) => report_error("Failed to simulate transaction", e),
+        }
+    }
+
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let response = client.simulate_transaction(params)?;
+        let output = &response.output;
+        let (vm_status, discarded) = match output.status() {
+            TransactionStatus::Keep(vm_status) => (vm_status, false),
+            TransactionStatus::Discard(vm_status) => (vm_status, true),
+        };
+        Ok(json!({
+            "version": response.version,
+            "vm_status": format!("{:?}", vm_status),
+            "discarded": discarded,
+            "gas_used": output.gas_used(),
+            "num_writes": output.write_set().len(),
+            "events": output.events().iter().map(|event| event.to_string()).collect::<Vec<_>>(),
+        }))
+    }
 }
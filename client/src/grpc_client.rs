@@ -1,23 +1,28 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::AccountData;
+use crate::{trusted_state::TrustedState, AccountData};
 use admission_control_proto::{
     proto::{
         admission_control::{
-            SubmitTransactionRequest, SubmitTransactionResponse as ProtoSubmitTransactionResponse,
+            GetLedgerStatisticsRequest, SimulateTransactionRequest, SubmitTransactionRequest,
+            SubmitTransactionResponse as ProtoSubmitTransactionResponse, WatchEventsRequest,
         },
         admission_control_grpc::AdmissionControlClient,
     },
-    AdmissionControlStatus, SubmitTransactionResponse,
+    AdmissionControlStatus, SimulateTransactionResponse, SubmitTransactionResponse,
+    WATCH_EVENTS_PAGE_SIZE,
 };
 use crypto::ed25519::*;
 use failure::prelude::*;
-use futures::Future;
+use futures::{Future, Stream};
 use grpcio::{CallOption, ChannelBuilder, EnvBuilder};
 use logger::prelude::*;
 use proto_conv::{FromProto, IntoProto};
-use std::sync::Arc;
+use std::{
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
 use types::{
     access_path::AccessPath,
     account_address::AccountAddress,
@@ -27,8 +32,10 @@ use types::{
     get_with_proof::{
         RequestItem, ResponseItem, UpdateToLatestLedgerRequest, UpdateToLatestLedgerResponse,
     },
-    transaction::{SignedTransaction, Version},
-    validator_verifier::ValidatorVerifier,
+    ledger_info::LedgerInfoWithSignatures,
+    ledger_statistics::LedgerStatistics,
+    proto::get_with_proof::UpdateToLatestLedgerResponse as ProtoUpdateToLatestLedgerResponse,
+    transaction::{SignedTransaction, TransactionInfo, Version},
     vm_error::{VMStatus, VMValidationStatus},
 };
 
@@ -37,15 +44,20 @@ const MAX_GRPC_RETRY_COUNT: u64 = 1;
 /// Struct holding dependencies of client.
 pub struct GRPCClient {
     client: AdmissionControlClient,
-    validator_verifier: Arc<ValidatorVerifier<Ed25519PublicKey>>,
+    /// Latest verified ledger info and validator set, shared with the pending requests.
+    trusted_state: Arc<RwLock<TrustedState>>,
+    /// File the trusted state is persisted to after every change, if any.
+    trusted_state_file: Option<PathBuf>,
 }
 
 impl GRPCClient {
-    /// Construct a new Client instance.
+    /// Construct a new Client instance, verifying the responses of the validator against
+    /// `trusted_state`.
     pub fn new(
         host: &str,
         port: &str,
-        validator_verifier: Arc<ValidatorVerifier<Ed25519PublicKey>>,
+        trusted_state: TrustedState,
+        trusted_state_file: Option<PathBuf>,
     ) -> Result<Self> {
         let conn_addr = format!("{}:{}", host, port);
 
@@ -56,10 +68,20 @@ impl GRPCClient {
 
         Ok(GRPCClient {
             client,
-            validator_verifier,
+            trusted_state: Arc::new(RwLock::new(trusted_state)),
+            trusted_state_file,
         })
     }
 
+    /// The latest ledger info verified by the client.
+    pub fn trusted_ledger_info(&self) -> LedgerInfoWithSignatures<Ed25519Signature> {
+        self.trusted_state
+            .read()
+            .expect("Trusted state lock is poisoned")
+            .ledger_info_with_sigs()
+            .clone()
+    }
+
     /// Submits a transaction and bumps the sequence number for the sender, pass in `None` for
     /// sender_account if sender's address is not managed by the client.
     pub fn submit_transaction(
@@ -126,6 +148,38 @@ impl GRPCClient {
         Ok(resp)
     }
 
+    /// Execute a transaction against the latest state of the validator, without committing it.
+    pub fn simulate_transaction(
+        &self,
+        req: &SimulateTransactionRequest,
+    ) -> Result<SimulateTransactionResponse> {
+        let resp = self
+            .client
+            .simulate_transaction_opt(req, Self::get_default_grpc_call_option())?;
+        SimulateTransactionResponse::from_proto(resp)
+    }
+
+    /// Get up to `limit` sets of ledger counters recorded by the validator from `start_version`
+    /// to `end_version`. They are not part of the ledger, so they can't be verified.
+    pub fn get_ledger_statistics(
+        &self,
+        start_version: Version,
+        end_version: Version,
+        limit: u64,
+    ) -> Result<Vec<LedgerStatistics>> {
+        let mut req = GetLedgerStatisticsRequest::new();
+        req.set_start_version(start_version);
+        req.set_end_version(end_version);
+        req.set_limit(limit);
+        let mut resp = self
+            .client
+            .get_ledger_statistics_opt(&req, Self::get_default_grpc_call_option())?;
+        resp.take_ledger_statistics()
+            .into_iter()
+            .map(LedgerStatistics::from_proto)
+            .collect()
+    }
+
     fn submit_transaction_opt(
         &self,
         resp: &SubmitTransactionRequest,
@@ -141,19 +195,29 @@ impl GRPCClient {
     ) -> Result<
         impl Future<Item = UpdateToLatestLedgerResponse<Ed25519Signature>, Error = failure::Error>,
     > {
-        let req = UpdateToLatestLedgerRequest::new(0, requested_items.clone());
+        let client_known_version = self
+            .trusted_state
+            .read()
+            .expect("Trusted state lock is poisoned")
+            .version();
+        let req = UpdateToLatestLedgerRequest::new(client_known_version, requested_items.clone());
         debug!("get_with_proof with request: {:?}", req);
         let proto_req = req.clone().into_proto();
-        let validator_verifier = Arc::clone(&self.validator_verifier);
+        let trusted_state = Arc::clone(&self.trusted_state);
+        let trusted_state_file = self.trusted_state_file.clone();
         let ret = self
             .client
             .update_to_latest_ledger_async_opt(&proto_req, Self::get_default_grpc_call_option())?
             .then(move |get_with_proof_resp| {
-                // TODO: Cache/persist client_known_version to work with validator set change when
-                // the feature is available.
-
                 let resp = UpdateToLatestLedgerResponse::from_proto(get_with_proof_resp?)?;
-                resp.verify(validator_verifier, &req)?;
+                let mut trusted_state = trusted_state
+                    .write()
+                    .expect("Trusted state lock is poisoned");
+                if trusted_state.verify_and_ratchet(&req, &resp)? {
+                    if let Some(path) = trusted_state_file {
+                        trusted_state.save(&path)?;
+                    }
+                }
                 Ok(resp)
             });
         Ok(ret)
@@ -214,6 +278,27 @@ impl GRPCClient {
         ))
     }
 
+    /// Get the account state blob as of `version` from validator, along with the version of the
+    /// latest ledger info it is proven against.
+    pub(crate) fn get_account_blob_at_version(
+        &self,
+        address: AccountAddress,
+        version: Version,
+    ) -> Result<(Option<AccountStateBlob>, Version)> {
+        let req_item = RequestItem::GetAccountStateAtVersion { address, version };
+
+        let mut response = self.get_with_proof_sync(vec![req_item])?;
+        let account_state_with_proof = response
+            .response_items
+            .remove(0)
+            .into_get_account_state_at_version_response()?;
+
+        Ok((
+            account_state_with_proof.blob,
+            response.ledger_info_with_sigs.ledger_info().version(),
+        ))
+    }
+
     /// Get transaction from validator by account and sequence number.
     pub fn get_txn_by_acc_seq(
         &self,
@@ -268,6 +353,48 @@ impl GRPCClient {
         Ok(res)
     }
 
+    /// Get the transactions sent by `account` with sequence numbers `start_sequence_number` to
+    /// `start_sequence_number + limit - 1`, along with their versions and infos, from validator.
+    /// Fails if any of them is not committed.
+    pub fn get_sent_txns_with_info(
+        &self,
+        account: AccountAddress,
+        start_sequence_number: u64,
+        limit: u64,
+    ) -> Result<Vec<(Version, SignedTransaction, TransactionInfo)>> {
+        let req_items = (start_sequence_number..start_sequence_number + limit)
+            .map(
+                |sequence_number| RequestItem::GetAccountTransactionBySequenceNumber {
+                    account,
+                    sequence_number,
+                    fetch_events: false,
+                },
+            )
+            .collect();
+        let response = self.get_with_proof_sync(req_items)?;
+        response
+            .response_items
+            .into_iter()
+            .zip(start_sequence_number..)
+            .map(|(response_item, sequence_number)| {
+                let (signed_txn_with_proof, _) =
+                    response_item.into_get_account_txn_by_seq_num_response()?;
+                let signed_txn_with_proof = signed_txn_with_proof.ok_or_else(|| {
+                    format_err!(
+                        "Transaction {} of {} is not committed",
+                        sequence_number,
+                        account
+                    )
+                })?;
+                Ok((
+                    signed_txn_with_proof.version,
+                    signed_txn_with_proof.signed_transaction,
+                    signed_txn_with_proof.proof.transaction_info().clone(),
+                ))
+            })
+            .collect()
+    }
+
     /// Get event by access path from validator. AccountStateWithProof will be returned if
     /// 1. No event is available. 2. Ascending and available event number < limit.
     /// 3. Descending and start_seq_num > latest account event sequence number.
@@ -299,9 +426,134 @@ impl GRPCClient {
         }
     }
 
+    /// Get the events emitted by the transactions in range
+    /// (start_version..start_version + limit - 1) from validator, only the ones emitted to access
+    /// paths starting with `access_path_prefix` if given.
+    pub fn get_events_by_version_range(
+        &self,
+        start_version: Version,
+        limit: u64,
+        access_path_prefix: Option<AccessPath>,
+    ) -> Result<Vec<EventWithProof>> {
+        let req_item = RequestItem::GetEventsByVersionRange {
+            start_version,
+            limit,
+            access_path_prefix,
+        };
+
+        let mut response = self.get_with_proof_sync(vec![req_item])?;
+        response
+            .response_items
+            .remove(0)
+            .into_get_events_by_version_range_response()
+    }
+
+    /// Stream the events of `access_path` from sequence number `start_event_seq_num` on, as they
+    /// are committed. Every response of the validator is verified like the response to the
+    /// GetEventsByEventAccessPath request it answers, and ratchets the trusted state. The
+    /// returned iterator blocks until the next batch of events, which may be empty, and ends
+    /// after the first error.
+    pub fn watch_events(
+        &self,
+        access_path: AccessPath,
+        start_event_seq_num: u64,
+    ) -> Result<impl Iterator<Item = Result<Vec<EventWithProof>>>> {
+        let client_known_version = self
+            .trusted_state
+            .read()
+            .expect("Trusted state lock is poisoned")
+            .version();
+        let mut req = WatchEventsRequest::new();
+        req.set_access_path(access_path.clone().into_proto());
+        req.set_start_event_seq_num(start_event_seq_num);
+        req.set_client_known_version(client_known_version);
+        debug!("watch_events with request: {:?}", req);
+        // The stream lives as long as the client watches, so it has no deadline.
+        let receiver = self
+            .client
+            .watch_events_opt(&req, CallOption::default().wait_for_ready(true))?;
+
+        Ok(WatchedEvents {
+            responses: Box::new(receiver.wait()),
+            trusted_state: Arc::clone(&self.trusted_state),
+            trusted_state_file: self.trusted_state_file.clone(),
+            access_path,
+            next_event_seq_num: start_event_seq_num,
+            client_known_version,
+            failed: false,
+        })
+    }
+
     fn get_default_grpc_call_option() -> CallOption {
         CallOption::default()
             .wait_for_ready(true)
             .timeout(std::time::Duration::from_millis(5000))
     }
 }
+
+/// The batches of events of a WatchEvents stream, verified as they are received.
+struct WatchedEvents {
+    responses: Box<dyn Iterator<Item = grpcio::Result<ProtoUpdateToLatestLedgerResponse>>>,
+    trusted_state: Arc<RwLock<TrustedState>>,
+    trusted_state_file: Option<PathBuf>,
+    access_path: AccessPath,
+    /// Sequence number of the first event of the next response, as tracked by the validator.
+    next_event_seq_num: u64,
+    /// Version of the ledger info of the latest response, as tracked by the validator.
+    client_known_version: Version,
+    failed: bool,
+}
+
+impl WatchedEvents {
+    fn verify(
+        &mut self,
+        proto_resp: grpcio::Result<ProtoUpdateToLatestLedgerResponse>,
+    ) -> Result<Vec<EventWithProof>> {
+        let resp = UpdateToLatestLedgerResponse::from_proto(proto_resp?)?;
+        // The request answered by the response.
+        let req = UpdateToLatestLedgerRequest::new(
+            self.client_known_version,
+            vec![RequestItem::GetEventsByEventAccessPath {
+                access_path: self.access_path.clone(),
+                start_event_seq_num: self.next_event_seq_num,
+                ascending: true,
+                limit: WATCH_EVENTS_PAGE_SIZE,
+            }],
+        );
+        let mut trusted_state = self
+            .trusted_state
+            .write()
+            .expect("Trusted state lock is poisoned");
+        if trusted_state.verify_and_ratchet(&req, &resp)? {
+            if let Some(path) = &self.trusted_state_file {
+                trusted_state.save(path)?;
+            }
+        }
+
+        self.client_known_version = resp.ledger_info_with_sigs.ledger_info().version();
+        let events_with_proof = match resp.response_items.into_iter().next() {
+            Some(ResponseItem::GetEventsByEventAccessPath {
+                events_with_proof, ..
+            }) => events_with_proof,
+            response_item => bail!("Incorrect type of response returned: {:?}", response_item),
+        };
+        if let Some(last) = events_with_proof.last() {
+            self.next_event_seq_num = last.event.sequence_number() + 1;
+        }
+        Ok(events_with_proof)
+    }
+}
+
+impl Iterator for WatchedEvents {
+    type Item = Result<Vec<EventWithProof>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let proto_resp = self.responses.next()?;
+        let result = self.verify(proto_resp);
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
@@ -0,0 +1,329 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Transaction history of an account, reconciled from its payment events.
+//!
+//! Every sent and received payment event of the account is turned into a `HistoryEntry`, and so is
+//! the gas paid by every transaction of the account which made no payment. The running balance
+//! starts from zero and must end on the balance of the account at the ledger version the history
+//! was read at: the history of an account whose balance is not fully recorded by payment events,
+//! e.g. the genesis balance of the association account, fails to reconcile.
+
+use failure::prelude::*;
+use serde_json::{json, Value};
+use std::{fmt, io::Write, str::FromStr};
+use types::{account_address::AccountAddress, transaction::Version};
+
+/// Direction of a payment relative to the account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaymentDirection {
+    /// The account paid the counterparty.
+    Sent,
+    /// The account was paid by the counterparty.
+    Received,
+    /// The account paid gas for a transaction which made no payment.
+    Fee,
+}
+
+impl fmt::Display for PaymentDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PaymentDirection::Sent => write!(f, "sent"),
+            PaymentDirection::Received => write!(f, "received"),
+            PaymentDirection::Fee => write!(f, "fee"),
+        }
+    }
+}
+
+/// Output format of a history file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryFormat {
+    /// One line per entry, preceded by a header line.
+    Csv,
+    /// A single JSON document holding the entries and the opening and closing balances.
+    Json,
+}
+
+impl FromStr for HistoryFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "csv" => Ok(HistoryFormat::Csv),
+            "json" => Ok(HistoryFormat::Json),
+            _ => bail!(
+                "Unknown history format: {:?}, only csv and json are supported",
+                s
+            ),
+        }
+    }
+}
+
+/// A payment of the account, or the gas paid by a transaction of the account without payment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// Version of the transaction which emitted the payment event.
+    pub version: Version,
+    /// Index of the payment event in the transaction, 0 for a fee.
+    pub event_index: u64,
+    /// Whether the account sent or received the payment.
+    pub direction: PaymentDirection,
+    /// The receiver of a sent payment or the sender of a received payment, None for a fee.
+    pub counterparty: Option<AccountAddress>,
+    /// Amount of the payment in micro libras.
+    pub amount: u64,
+    /// Gas paid by the account for the transaction of a sent payment or a fee, in micro libras.
+    pub gas_fee: u64,
+    /// Balance of the account after the payment, in micro libras.
+    pub balance: u64,
+}
+
+impl HistoryEntry {
+    /// Build an entry whose balance is not known yet.
+    pub fn new(
+        version: Version,
+        event_index: u64,
+        direction: PaymentDirection,
+        counterparty: Option<AccountAddress>,
+        amount: u64,
+        gas_fee: u64,
+    ) -> Self {
+        HistoryEntry {
+            version,
+            event_index,
+            direction,
+            counterparty,
+            amount,
+            gas_fee,
+            balance: 0,
+        }
+    }
+
+    /// The balance of the account after the entry, given the balance before it. Fails if the
+    /// balance would go below zero or overflow.
+    fn apply(&self, balance: u64) -> Result<u64> {
+        let new_balance = match self.direction {
+            PaymentDirection::Sent | PaymentDirection::Fee => self
+                .amount
+                .checked_add(self.gas_fee)
+                .and_then(|debit| balance.checked_sub(debit)),
+            PaymentDirection::Received => balance.checked_add(self.amount),
+        };
+        new_balance.ok_or_else(|| {
+            format_err!(
+                "The balance of {} paying {} and {} of gas at version {} goes out of range",
+                balance,
+                self.amount,
+                self.gas_fee,
+                self.version
+            )
+        })
+    }
+}
+
+/// The payments of an account within a range of versions, with running balances.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionHistory {
+    /// The account.
+    pub account: AccountAddress,
+    /// Ledger version the history was read at.
+    pub ledger_version: Version,
+    /// Balance before the first entry of the range.
+    pub opening_balance: u64,
+    /// Balance after the last entry of the range.
+    pub closing_balance: u64,
+    /// The entries of the range, ordered by version and event index.
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl TransactionHistory {
+    /// Compute the running balances of all the `entries` of `account` from zero, then keep the
+    /// entries whose version is within `from..=to`. Fails unless the running balance ends on
+    /// `balance`, the balance of the account at `ledger_version`.
+    pub fn reconcile(
+        account: AccountAddress,
+        ledger_version: Version,
+        balance: u64,
+        mut entries: Vec<HistoryEntry>,
+        from: Option<Version>,
+        to: Option<Version>,
+    ) -> Result<Self> {
+        entries.sort_by_key(|entry| (entry.version, entry.event_index));
+        let mut running_balance = 0;
+        let mut opening_balance = running_balance;
+        let mut closing_balance = running_balance;
+        let mut in_range = vec![];
+        for mut entry in entries {
+            running_balance = entry.apply(running_balance)?;
+            entry.balance = running_balance;
+            if from.map_or(false, |from| entry.version < from) {
+                opening_balance = running_balance;
+                closing_balance = running_balance;
+            } else if to.map_or(true, |to| entry.version <= to) {
+                closing_balance = running_balance;
+                in_range.push(entry);
+            }
+        }
+        ensure!(
+            running_balance == balance,
+            "The payments of {} add up to a balance of {}, but its balance at version {} is {}",
+            account,
+            running_balance,
+            ledger_version,
+            balance
+        );
+        Ok(TransactionHistory {
+            account,
+            ledger_version,
+            opening_balance,
+            closing_balance,
+            entries: in_range,
+        })
+    }
+
+    /// Write the history in the given format.
+    pub fn write<W: Write>(&self, output: &mut W, format: HistoryFormat) -> Result<()> {
+        match format {
+            HistoryFormat::Csv => self.write_csv(output),
+            HistoryFormat::Json => {
+                serde_json::to_writer_pretty(&mut *output, &self.to_json())?;
+                Ok(writeln!(output)?)
+            }
+        }
+    }
+
+    /// The history as a JSON document.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "account": hex::encode(self.account),
+            "ledger_version": self.ledger_version,
+            "opening_balance": self.opening_balance,
+            "closing_balance": self.closing_balance,
+            "entries": self.entries.iter().map(|entry| json!({
+                "version": entry.version,
+                "event_index": entry.event_index,
+                "direction": entry.direction.to_string(),
+                "counterparty": entry.counterparty.map(hex::encode),
+                "amount": entry.amount,
+                "gas_fee": entry.gas_fee,
+                "balance": entry.balance,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    fn write_csv<W: Write>(&self, output: &mut W) -> Result<()> {
+        writeln!(
+            output,
+            "version,event_index,direction,counterparty,amount,gas_fee,balance"
+        )?;
+        for entry in &self.entries {
+            writeln!(
+                output,
+                "{},{},{},{},{},{},{}",
+                entry.version,
+                entry.event_index,
+                entry.direction,
+                entry.counterparty.map(hex::encode).unwrap_or_default(),
+                entry.amount,
+                entry.gas_fee,
+                entry.balance
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(counterparty: AccountAddress) -> Vec<HistoryEntry> {
+        vec![
+            HistoryEntry::new(7, 0, PaymentDirection::Sent, Some(counterparty), 30, 2),
+            HistoryEntry::new(3, 0, PaymentDirection::Received, Some(counterparty), 100, 0),
+            HistoryEntry::new(8, 0, PaymentDirection::Fee, None, 0, 1),
+            HistoryEntry::new(9, 1, PaymentDirection::Received, Some(counterparty), 5, 0),
+        ]
+    }
+
+    #[test]
+    fn test_reconcile_full_history() {
+        let account = AccountAddress::random();
+        let history = TransactionHistory::reconcile(
+            account,
+            10,
+            72,
+            entries(AccountAddress::random()),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(history.opening_balance, 0);
+        assert_eq!(history.closing_balance, 72);
+        let versions: Vec<_> = history.entries.iter().map(|entry| entry.version).collect();
+        assert_eq!(versions, vec![3, 7, 8, 9]);
+        let balances: Vec<_> = history.entries.iter().map(|entry| entry.balance).collect();
+        assert_eq!(balances, vec![100, 68, 67, 72]);
+    }
+
+    #[test]
+    fn test_reconcile_range() {
+        let account = AccountAddress::random();
+        let history = TransactionHistory::reconcile(
+            account,
+            10,
+            72,
+            entries(AccountAddress::random()),
+            Some(4),
+            Some(8),
+        )
+        .unwrap();
+        assert_eq!(history.opening_balance, 100);
+        assert_eq!(history.closing_balance, 67);
+        let versions: Vec<_> = history.entries.iter().map(|entry| entry.version).collect();
+        assert_eq!(versions, vec![7, 8]);
+    }
+
+    #[test]
+    fn test_reconcile_mismatch() {
+        let account = AccountAddress::random();
+        let counterparty = AccountAddress::random();
+        // The balance isn't fully recorded by the entries.
+        assert!(TransactionHistory::reconcile(
+            account,
+            10,
+            1072,
+            entries(counterparty),
+            None,
+            None
+        )
+        .is_err());
+        // The balance would go below zero.
+        let mut overdrawn = entries(counterparty);
+        overdrawn[0].amount = 200;
+        assert!(TransactionHistory::reconcile(account, 10, 0, overdrawn, None, None).is_err());
+        // The balance would overflow.
+        let mut overflowing = entries(counterparty);
+        overflowing[3].amount = u64::max_value();
+        assert!(TransactionHistory::reconcile(account, 10, 0, overflowing, None, None).is_err());
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let account = AccountAddress::random();
+        let counterparty = AccountAddress::random();
+        let history =
+            TransactionHistory::reconcile(account, 10, 72, entries(counterparty), None, None)
+                .unwrap();
+        let mut output = vec![];
+        history.write(&mut output, HistoryFormat::Csv).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[2],
+            format!("7,0,sent,{},30,2,68", hex::encode(counterparty))
+        );
+        assert_eq!(lines[3], "8,0,fee,,0,1,67");
+    }
+}
@@ -19,6 +19,9 @@ pub use types::{
 pub use vm_genesis;
 
 pub(crate) mod account_commands;
+/// Persistent address book naming accounts.
+pub mod address_book;
+pub(crate) mod address_book_commands;
 /// Main instance of client holding corresponding information, e.g. account address.
 pub mod client_proxy;
 /// Command struct to interact with client.
@@ -26,8 +29,15 @@ pub mod commands;
 pub(crate) mod dev_commands;
 /// gRPC client wrapper to connect to validator.
 pub(crate) mod grpc_client;
+/// Reconciled payment history of an account.
+pub mod history;
+pub(crate) mod offline_commands;
 pub(crate) mod query_commands;
 pub(crate) mod transfer_commands;
+/// Light-client state tracking the latest verified ledger info.
+pub mod trusted_state;
+/// On-disk format of transactions signed offline.
+pub mod txn_file;
 
 /// Struct used to store data for each created account.  We track the sequence number
 /// so we can create new transactions easily
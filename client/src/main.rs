@@ -2,8 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use client::{client_proxy::ClientProxy, commands::*};
+use failure::prelude::*;
 use logger::set_default_global_logger;
 use rustyline::{config::CompletionType, error::ReadlineError, Config, Editor};
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    sync::Arc,
+};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -35,6 +43,11 @@ struct Args {
     /// directory.
     #[structopt(short = "n", long = "mnemonic_file")]
     pub mnemonic_file: Option<String>,
+    /// File containing the password of the wallet keystore. If passed, the wallet recovery data
+    /// is kept encrypted: an existing plaintext mnemonic file is encrypted in place, and an
+    /// encrypted one is decrypted with this password.
+    #[structopt(short = "w", long = "wallet_password_file")]
+    pub wallet_password_file: Option<String>,
     /// File location from which to load config of trusted validators. It is used to verify
     /// validator signatures in validator query response. The file should at least include public
     /// key of all validators trusted by the client - which should typically be all validators on
@@ -47,6 +60,30 @@ struct Args {
     /// If set, client will sync with validator during wallet recovery.
     #[structopt(short = "r", long = "sync")]
     pub sync: bool,
+    /// Run non-interactively: read commands line by line from this file ('-' for stdin) and
+    /// print one JSON object per command. Stops at the first failing command and exits with a
+    /// non-zero code. Empty lines and lines starting with '#' are skipped.
+    #[structopt(short = "b", long = "batch")]
+    pub batch_file: Option<String>,
+    /// File in which the client persists the latest ledger info it verified, along with the
+    /// validator set of its epoch. Responses older than this ledger info, or contradicting it,
+    /// are rejected, across runs of the client.
+    #[structopt(short = "t", long = "trusted_state_file")]
+    pub trusted_state_file: Option<String>,
+    /// Trusted state file obtained out of band from a trusted source, e.g. another client. A new
+    /// client starts from this waypoint instead of the genesis validators, and an existing trusted
+    /// state must not be older than it.
+    #[structopt(long = "waypoint")]
+    pub waypoint_file: Option<String>,
+    /// File location of the address book naming accounts. Names can be used anywhere an account
+    /// address is accepted. If not passed, the address book is kept in the current directory.
+    #[structopt(long = "address_book")]
+    pub address_book_file: Option<String>,
+    /// Run in watch-only mode: no mnemonic file is read or created, and the accounts are the
+    /// ones watched in this file, which holds their addresses and possibly their public keys.
+    /// Such a file is written by `account export_watch_only` on a machine holding the wallet.
+    #[structopt(long = "watch_only")]
+    pub watch_only_file: Option<String>,
 }
 
 fn main() -> std::io::Result<()> {
@@ -56,22 +93,54 @@ fn main() -> std::io::Result<()> {
 
     let (commands, alias_to_cmd) = get_commands(args.faucet_account_file.is_some());
 
-    let faucet_account_file = args.faucet_account_file.unwrap_or_else(|| "".to_string());
-
-    let mut client_proxy = ClientProxy::new(
-        &args.host,
-        &args.port,
-        &args.validator_set_file,
-        &faucet_account_file,
-        args.sync,
-        args.faucet_server,
-        args.mnemonic_file,
-    )
-    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, &format!("{}", e)[..]))?;
+    let mut client_proxy = match create_client_proxy(&args) {
+        Ok(client_proxy) => client_proxy,
+        Err(e) => {
+            if args.batch_file.is_some() {
+                write_batch_error(&mut io::stdout(), "", &pretty_format_error(e))?;
+                std::process::exit(1);
+            }
+            return Err(io::Error::new(io::ErrorKind::Other, &format!("{}", e)[..]));
+        }
+    };
 
     // Test connection to validator
     let test_ret = client_proxy.test_validator_connection();
 
+    if let Some(batch_file) = args.batch_file {
+        if let Err(e) = test_ret {
+            write_batch_error(
+                &mut io::stdout(),
+                "",
+                &format!(
+                    "Not able to connect to validator at {}:{}, error {:?}",
+                    args.host, args.port, e
+                ),
+            )?;
+            std::process::exit(1);
+        }
+        client_proxy.set_quiet(true);
+        let reader: Box<dyn BufRead> = if batch_file == "-" {
+            Box::new(BufReader::new(io::stdin()))
+        } else {
+            match File::open(&batch_file) {
+                Ok(file) => Box::new(BufReader::new(file)),
+                Err(e) => {
+                    write_batch_error(
+                        &mut io::stdout(),
+                        "",
+                        &format!("Unable to open batch file {}: {}", batch_file, e),
+                    )?;
+                    std::process::exit(1);
+                }
+            }
+        };
+        if !run_batch(&mut client_proxy, &alias_to_cmd, reader, &mut io::stdout())? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     if let Err(e) = test_ret {
         println!(
             "Not able to connect to validator at {}:{}, error {:?}",
@@ -125,6 +194,93 @@ fn main() -> std::io::Result<()> {
     Ok(())
 }
 
+/// Create the client proxy from the command line arguments.
+fn create_client_proxy(args: &Args) -> Result<ClientProxy> {
+    let faucet_account_file = args
+        .faucet_account_file
+        .clone()
+        .unwrap_or_else(|| "".to_string());
+    let wallet_password = match &args.wallet_password_file {
+        Some(path) => Some(
+            std::fs::read_to_string(path)?
+                .lines()
+                .next()
+                .unwrap_or("")
+                .to_string(),
+        ),
+        None => None,
+    };
+
+    ClientProxy::new(
+        &args.host,
+        &args.port,
+        &args.validator_set_file,
+        &faucet_account_file,
+        args.sync,
+        args.faucet_server.clone(),
+        args.mnemonic_file.clone(),
+        wallet_password,
+        args.trusted_state_file.clone(),
+        args.waypoint_file.clone(),
+        args.address_book_file.clone(),
+        args.watch_only_file.clone(),
+    )
+}
+
+/// Execute the commands read from `reader` in order, writing one JSON object per command to
+/// `out`. Returns `Ok(false)` as soon as one command fails, after writing its error.
+fn run_batch<R: BufRead, W: Write>(
+    client_proxy: &mut ClientProxy,
+    alias_to_cmd: &HashMap<&'static str, Arc<dyn Command>>,
+    reader: R,
+    out: &mut W,
+) -> io::Result<bool> {
+    for line in reader.lines() {
+        let line = line?;
+        let params = parse_cmd(&line);
+        if params.is_empty() || params[0].starts_with('#') {
+            continue;
+        }
+        let result = match alias_to_cmd.get(&params[0]) {
+            Some(cmd) => cmd.execute_batch(client_proxy, &params),
+            None => match params[0] {
+                "quit" | "q!" => break,
+                x => Err(format_err!("Unknown command: {:?}", x)),
+            },
+        };
+        match result {
+            Ok(value) => writeln!(
+                out,
+                "{}",
+                json!({
+                    "command": line.trim(),
+                    "status": "ok",
+                    "result": value,
+                })
+            )?,
+            Err(e) => {
+                write_batch_error(out, line.trim(), &pretty_format_error(e))?;
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Write the JSON object reporting a failed command in batch mode. The errors that occur before
+/// any command is run are reported with an empty command.
+fn write_batch_error<W: Write>(out: &mut W, command: &str, error: &str) -> io::Result<()> {
+    writeln!(
+        out,
+        "{}",
+        json!({
+            "command": command,
+            "status": "error",
+            "error": error,
+        })
+    )
+}
+
 /// Print the help message for the client and underlying command.
 fn print_help(client_info: &str, commands: &[std::sync::Arc<dyn Command>]) {
     println!("{}", client_info);
@@ -142,3 +298,95 @@ fn print_help(client_info: &str, commands: &[std::sync::Arc<dyn Command>]) {
     println!("quit | q! \n\tExit this client");
     println!("\n");
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{create_client_proxy, run_batch, Args};
+    use client::commands::get_commands;
+    use config::trusted_peers::TrustedPeersConfigHelpers;
+    use serde_json::Value;
+    use structopt::StructOpt;
+    use tempfile::{NamedTempFile, TempDir};
+
+    #[test]
+    fn test_batch_output() {
+        let dir = TempDir::new().unwrap();
+        let (_, trusted_peers_config) = TrustedPeersConfigHelpers::get_test_config(1, None);
+        let validator_set_file = dir.path().join("trusted_peers.config.toml");
+        trusted_peers_config.save_config(&validator_set_file);
+        let mnemonic_file = dir.path().join("client.mnemonic");
+        let address_book_file = dir.path().join("address_book.toml");
+        // The client is not connected to any validator: only the local commands are run.
+        let args = Args::from_iter(&[
+            "client",
+            "--host",
+            "localhost",
+            "--validator_set_file",
+            validator_set_file.to_str().unwrap(),
+            "--mnemonic_file",
+            mnemonic_file.to_str().unwrap(),
+            "--address_book",
+            address_book_file.to_str().unwrap(),
+            "--batch",
+            "-",
+        ]);
+        let mut client_proxy = create_client_proxy(&args).unwrap();
+        let (_, alias_to_cmd) = get_commands(false);
+
+        let address = "ab".repeat(32);
+        let batch = [
+            "# comment",
+            "",
+            format!("address_book add alice {}", address).as_str(),
+            "  address_book list  ",
+            "foo",
+            "address_book remove alice",
+        ]
+        .join("\n");
+        let mut out = vec![];
+        assert!(!run_batch(&mut client_proxy, &alias_to_cmd, batch.as_bytes(), &mut out).unwrap());
+
+        // One JSON object per command, up to the first failing one.
+        let lines: Vec<Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0]["command"],
+            format!("address_book add alice {}", address)
+        );
+        assert_eq!(lines[0]["status"], "ok");
+        assert_eq!(lines[0]["result"]["address"], address);
+        assert_eq!(lines[1]["command"], "address_book list");
+        assert_eq!(lines[1]["status"], "ok");
+        assert_eq!(lines[1]["result"][0]["name"], "alice");
+        assert_eq!(lines[2]["command"], "foo");
+        assert_eq!(lines[2]["status"], "error");
+        assert!(lines[2]["error"]
+            .as_str()
+            .unwrap()
+            .contains("Unknown command"));
+        assert!(lines[2].get("result").is_none());
+    }
+
+    #[test]
+    fn test_create_client_proxy_error() {
+        // A missing trusted peers config is reported as an error instead of a panic, so that
+        // batch mode can print it as JSON.
+        let validator_set_file = NamedTempFile::new().unwrap().into_temp_path();
+        let validator_set_path = validator_set_file.to_str().unwrap().to_string();
+        validator_set_file.close().unwrap();
+        let args = Args::from_iter(&[
+            "client",
+            "--host",
+            "localhost",
+            "--validator_set_file",
+            &validator_set_path,
+            "--batch",
+            "-",
+        ]);
+        assert!(create_client_proxy(&args).is_err());
+    }
+}
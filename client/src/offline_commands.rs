@@ -0,0 +1,186 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{client_proxy::ClientProxy, commands::*};
+use failure::prelude::*;
+use serde_json::{json, Value};
+use vm_genesis::get_transaction_name;
+
+/// Major command for the offline signing workflow: a transaction is prepared on an online
+/// machine, signed on a machine holding the keys and submitted from any online machine.
+pub struct OfflineCommand {}
+
+impl Command for OfflineCommand {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["offline", "o"]
+    }
+    fn get_description(&self) -> &'static str {
+        "Prepare, sign and submit transactions through files"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        subcommand_execute(&params[0], offline_subcommands(), client, &params[1..]);
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        subcommand_execute_batch(&params[0], offline_subcommands(), client, &params[1..])
+    }
+}
+
+fn offline_subcommands() -> Vec<Box<dyn Command>> {
+    vec![
+        Box::new(OfflineCommandPrepare {}),
+        Box::new(OfflineCommandSign {}),
+        Box::new(OfflineCommandCosign {}),
+        Box::new(OfflineCommandSubmit {}),
+    ]
+}
+
+/// Sub command to write an unsigned transfer transaction to a file.
+pub struct OfflineCommandPrepare {}
+
+impl Command for OfflineCommandPrepare {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["prepare", "p"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "\n\t<sender_account_address>|<sender_account_ref_id> \
+         <receiver_account_address>|<receiver_account_ref_id> <number_of_coins> \
+         <output_file_path> [gas_unit_price_in_micro_libras (default=0)] \
+         [max_gas_amount_in_micro_libras (default 140000)]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Write an unsigned transfer transaction to a file, to be signed offline"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Preparing transaction");
+        match client.prepare_transfer_coins_to_file(&params) {
+            Ok(raw_txn) => println!(
+                "Unsigned transaction written to {}: {}",
+                params[4],
+                raw_txn.format_for_client(get_transaction_name)
+            ),
+            Err(e) => report_error("Failed to prepare transaction", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let raw_txn = client.prepare_transfer_coins_to_file(&params)?;
+        Ok(json!({
+            "file": params[4],
+            "sender": hex::encode(raw_txn.sender()),
+            "transaction": raw_txn.format_for_client(get_transaction_name),
+        }))
+    }
+}
+
+/// Sub command to sign the unsigned transaction read from a file.
+pub struct OfflineCommandSign {}
+
+impl Command for OfflineCommandSign {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["sign", "s"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<unsigned_txn_file_path> <output_file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Sign an unsigned transaction with a local account and write it to a file"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Signing transaction");
+        match client.sign_transaction_file(&params) {
+            Ok(signed_txn) => println!(
+                "Signed transaction written to {}: {}",
+                params[2],
+                signed_txn.format_for_client(get_transaction_name)
+            ),
+            Err(e) => report_error("Failed to sign transaction", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let signed_txn = client.sign_transaction_file(&params)?;
+        Ok(json!({
+            "file": params[2],
+            "sender": hex::encode(signed_txn.sender()),
+            "sequence_number": signed_txn.sequence_number(),
+        }))
+    }
+}
+
+/// Sub command to add the signature of a local account to a multi-signature transaction.
+pub struct OfflineCommandCosign {}
+
+impl Command for OfflineCommandCosign {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["cosign", "cs"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<txn_file_path> <signer_account_ref_id>|<signer_account_address> <output_file_path> \
+         [multisig_public_key_hex (required if the transaction is unsigned)]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Add the signature of a local account to a multi-signature transaction and write it to a \
+         file"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Cosigning transaction");
+        match client.cosign_transaction_file(&params) {
+            Ok(signed_txn) => println!(
+                "Cosigned transaction written to {}: {}",
+                params[3],
+                signed_txn.format_for_client(get_transaction_name)
+            ),
+            Err(e) => report_error("Failed to cosign transaction", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let signed_txn = client.cosign_transaction_file(&params)?;
+        Ok(json!({
+            "file": params[3],
+            "sender": hex::encode(signed_txn.sender()),
+            "sequence_number": signed_txn.sequence_number(),
+        }))
+    }
+}
+
+/// Sub command to submit the signed transaction read from a file.
+pub struct OfflineCommandSubmit {}
+
+impl Command for OfflineCommandSubmit {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["submit", "submitb"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<signed_txn_file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Submit a signed transaction read from a file. Suffix 'b' is for blocking"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Submitting transaction");
+        let is_blocking = blocking_cmd(params[0]);
+        match client.submit_transaction_file(&params, is_blocking) {
+            Ok(signed_txn) => {
+                if is_blocking {
+                    println!("Finished transaction!");
+                } else {
+                    println!("Transaction submitted to validator");
+                }
+                println!(
+                    "To query for transaction status, run: query txn_acc_seq {} {} \
+                     <fetch_events=true|false>",
+                    signed_txn.sender(),
+                    signed_txn.sequence_number()
+                );
+            }
+            Err(e) => report_error("Failed to submit transaction", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let is_blocking = blocking_cmd(params[0]);
+        let signed_txn = client.submit_transaction_file(&params, is_blocking)?;
+        Ok(json!({
+            "sender": hex::encode(signed_txn.sender()),
+            "sequence_number": signed_txn.sequence_number(),
+            "blocking": is_blocking,
+        }))
+    }
+}
@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{client_proxy::ClientProxy, commands::*};
-use types::account_config::get_account_resource_or_default;
+use failure::prelude::*;
+use serde_json::{json, Value};
+use types::{account_config::get_account_resource_or_default, contract_event::ContractEvent};
 use vm_genesis::get_transaction_name;
 
 /// Major command for query operations.
@@ -16,16 +18,40 @@ impl Command for QueryCommand {
         "Query operations"
     }
     fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        let commands: Vec<Box<dyn Command>> = vec![
-            Box::new(QueryCommandGetBalance {}),
-            Box::new(QueryCommandGetSeqNum {}),
-            Box::new(QueryCommandGetLatestAccountState {}),
-            Box::new(QueryCommandGetTxnByAccountSeq {}),
-            Box::new(QueryCommandGetTxnByRange {}),
-            Box::new(QueryCommandGetEvent {}),
-        ];
+        subcommand_execute(&params[0], query_subcommands(), client, &params[1..]);
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        subcommand_execute_batch(&params[0], query_subcommands(), client, &params[1..])
+    }
+}
+
+fn query_subcommands() -> Vec<Box<dyn Command>> {
+    vec![
+        Box::new(QueryCommandGetBalance {}),
+        Box::new(QueryCommandGetSeqNum {}),
+        Box::new(QueryCommandGetLatestAccountState {}),
+        Box::new(QueryCommandGetAccountStateAtVersion {}),
+        Box::new(QueryCommandGetTxnByAccountSeq {}),
+        Box::new(QueryCommandGetTxnByRange {}),
+        Box::new(QueryCommandGetEvent {}),
+        Box::new(QueryCommandGetEventsByVersionRange {}),
+        Box::new(QueryCommandWatchEvents {}),
+        Box::new(QueryCommandGetTrustedLedgerInfo {}),
+        Box::new(QueryCommandGetLedgerStatistics {}),
+        Box::new(QueryCommandExportHistory {}),
+    ]
+}
 
-        subcommand_execute(&params[0], commands, client, &params[1..]);
+/// Format an optional list of events as JSON strings, as they would be printed interactively.
+fn events_to_json(events: Option<Vec<ContractEvent>>) -> Value {
+    match events {
+        Some(events) => Value::Array(
+            events
+                .iter()
+                .map(|event| Value::String(event.to_string()))
+                .collect(),
+        ),
+        None => Value::Null,
     }
 }
 
@@ -52,6 +78,10 @@ impl Command for QueryCommandGetBalance {
             Err(e) => report_error("Failed to get balance", e),
         }
     }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let balance = client.get_balance(&params)?;
+        Ok(json!({ "balance": balance }))
+    }
 }
 
 /// Sub command to get the latest sequence number from validator for the account specified.
@@ -75,6 +105,10 @@ impl Command for QueryCommandGetSeqNum {
             Err(e) => report_error("Error getting sequence number", e),
         }
     }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let sequence_number = client.get_sequence_number(&params)?;
+        Ok(json!({ "sequence_number": sequence_number }))
+    }
 }
 
 /// Command to query latest account state from validator.
@@ -110,6 +144,67 @@ impl Command for QueryCommandGetLatestAccountState {
             Err(e) => report_error("Error getting latest account state", e),
         }
     }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let (acc, version) = client.get_latest_account_state(&params)?;
+        let address = client.get_account_address_from_parameter(params[1])?;
+        let resource = get_account_resource_or_default(&acc)?;
+        Ok(json!({
+            "address": hex::encode(address),
+            "exists": acc.is_some(),
+            "balance": resource.balance(),
+            "sequence_number": resource.sequence_number(),
+            "version": version,
+        }))
+    }
+}
+
+/// Command to query the state of an account as of a past version from validator.
+pub struct QueryCommandGetAccountStateAtVersion {}
+
+impl Command for QueryCommandGetAccountStateAtVersion {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["account_state_at_version", "asv"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address> <version>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Get the state of an account as of a past version, proven against the latest ledger info"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Getting account state at version");
+        match client.get_account_state_at_version(&params) {
+            Ok((acc, ledger_version)) => match get_account_resource_or_default(&acc) {
+                Ok(_) => println!(
+                    "Account state at version {} is: \n \
+                     Account: {:#?}\n \
+                     State: {:#?}\n \
+                     Blockchain Version: {}\n",
+                    params[2],
+                    client
+                        .get_account_address_from_parameter(params[1])
+                        .expect("Unable to parse account parameter"),
+                    acc,
+                    ledger_version,
+                ),
+                Err(e) => report_error("Error converting account blob to account resource", e),
+            },
+            Err(e) => report_error("Error getting account state at version", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let (acc, ledger_version) = client.get_account_state_at_version(&params)?;
+        let address = client.get_account_address_from_parameter(params[1])?;
+        let resource = get_account_resource_or_default(&acc)?;
+        Ok(json!({
+            "address": hex::encode(address),
+            "version": params[2].parse::<u64>()?,
+            "exists": acc.is_some(),
+            "balance": resource.balance(),
+            "sequence_number": resource.sequence_number(),
+            "ledger_version": ledger_version,
+        }))
+    }
 }
 
 /// Sub command  to get transaction by account and sequence number from validator.
@@ -152,6 +247,15 @@ impl Command for QueryCommandGetTxnByAccountSeq {
             ),
         }
     }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        Ok(match client.get_committed_txn_by_acc_seq(&params)? {
+            Some((comm_txn, events)) => json!({
+                "transaction": comm_txn.format_for_client(get_transaction_name),
+                "events": events_to_json(events),
+            }),
+            None => Value::Null,
+        })
+    }
 }
 
 /// Sub command to query transactions by range from validator.
@@ -197,6 +301,23 @@ impl Command for QueryCommandGetTxnByRange {
             Err(e) => report_error("Error getting committed transactions by range", e),
         }
     }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let comm_txns_and_events = client.get_committed_txn_by_range(&params)?;
+        let start_version = params[1].parse::<u64>()?;
+        Ok(Value::Array(
+            comm_txns_and_events
+                .into_iter()
+                .enumerate()
+                .map(|(i, (txn, events))| {
+                    json!({
+                        "version": start_version + i as u64,
+                        "transaction": txn.format_for_client(get_transaction_name),
+                        "events": events_to_json(events),
+                    })
+                })
+                .collect(),
+        ))
+    }
 }
 
 /// Sub command to query events from validator.
@@ -228,4 +349,239 @@ impl Command for QueryCommandGetEvent {
             Err(e) => report_error("Error getting events by access path", e),
         }
     }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let (events, last_event_state) = client.get_events_by_account_and_type(&params)?;
+        Ok(json!({
+            "events": events.iter().map(|event| event.to_string()).collect::<Vec<_>>(),
+            "last_event_state_version": last_event_state.version,
+        }))
+    }
+}
+
+/// Sub command to query the events emitted in a range of versions from validator.
+pub struct QueryCommandGetEventsByVersionRange {}
+
+impl Command for QueryCommandGetEventsByVersionRange {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["event_range", "er"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<start_version> <limit> [<account_ref_id>|<account_address> <sent|received>]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Get the events emitted by up to limit transactions from start_version, \
+         optionally only the ones of an account and event type (sent|received)."
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Getting events by version range.");
+        match client.get_events_by_version_range(&params) {
+            Ok(events) => {
+                if events.is_empty() {
+                    println!("No events returned");
+                } else {
+                    for event in events {
+                        println!("{}", event);
+                    }
+                }
+            }
+            Err(e) => report_error("Error getting events by version range", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let events = client.get_events_by_version_range(&params)?;
+        Ok(json!({
+            "events": events.iter().map(|event| event.to_string()).collect::<Vec<_>>(),
+        }))
+    }
+}
+
+/// Sub command to stream events from validator as they are committed.
+pub struct QueryCommandWatchEvents {}
+
+impl Command for QueryCommandWatchEvents {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["watch", "w"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address> <sent|received> [start_sequence_number]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Print the events of an account and event type (sent|received) as they are committed, \
+         from the next event of the account by default. In batch mode, wait for the next events \
+         only."
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Watching events by account and event type.");
+        let events = match client.watch_events(&params) {
+            Ok(events) => events,
+            Err(e) => return report_error("Error watching events", e),
+        };
+        for batch in events {
+            match batch {
+                Ok(batch) => {
+                    for event in batch {
+                        println!("{}", event);
+                    }
+                }
+                Err(e) => return report_error("Error watching events", e),
+            }
+        }
+        println!("Event stream closed by the validator");
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        for batch in client.watch_events(&params)? {
+            let batch = batch?;
+            if !batch.is_empty() {
+                return Ok(json!({
+                    "events": batch.iter().map(|event| event.to_string()).collect::<Vec<_>>(),
+                }));
+            }
+        }
+        bail!("Event stream closed by the validator")
+    }
+}
+
+/// Sub command to show the latest ledger info verified by the client.
+pub struct QueryCommandGetTrustedLedgerInfo {}
+
+impl Command for QueryCommandGetTrustedLedgerInfo {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["ledger_info", "li"]
+    }
+    fn get_description(&self) -> &'static str {
+        "Show the latest ledger info verified by the client and the validators who signed it"
+    }
+    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) {
+        let ledger_info_with_sigs = client.get_trusted_ledger_info();
+        println!("{}", ledger_info_with_sigs.ledger_info());
+        println!(
+            "Transaction accumulator hash: {:x}",
+            ledger_info_with_sigs
+                .ledger_info()
+                .transaction_accumulator_hash()
+        );
+        for signer in ledger_info_with_sigs.signatures().keys() {
+            println!("Signed by validator {}", signer);
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, _params: &[&str]) -> Result<Value> {
+        let ledger_info_with_sigs = client.get_trusted_ledger_info();
+        let ledger_info = ledger_info_with_sigs.ledger_info();
+        Ok(json!({
+            "version": ledger_info.version(),
+            "epoch_num": ledger_info.epoch_num(),
+            "timestamp_usecs": ledger_info.timestamp_usecs(),
+            "transaction_accumulator_hash":
+                format!("{:x}", ledger_info.transaction_accumulator_hash()),
+            "signers": ledger_info_with_sigs
+                .signatures()
+                .keys()
+                .map(hex::encode)
+                .collect::<Vec<_>>(),
+        }))
+    }
+}
+
+/// Sub command to query the ledger counters of the validator over a range of versions.
+pub struct QueryCommandGetLedgerStatistics {}
+
+impl Command for QueryCommandGetLedgerStatistics {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["ledger_stats", "ls"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<start_version> [end_version]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Get how many events, accounts and state tree nodes the ledger holds, as recorded by the \
+         validator at the end of each batch of committed transactions from the latest batch \
+         at or before start_version to end_version (default=start_version). \
+         These are not verified."
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Getting ledger statistics");
+        match client.get_ledger_statistics(&params) {
+            Ok(ledger_statistics) => {
+                if ledger_statistics.is_empty() {
+                    println!("No ledger statistics returned");
+                }
+                for statistics in ledger_statistics {
+                    println!(
+                        "Version {}: {} events, {} accounts, {} state tree nodes \
+                         ({} leaves and {} nodes created, {} leaves and {} nodes stale)",
+                        statistics.version,
+                        statistics.events_created,
+                        statistics.num_accounts(),
+                        statistics.live_state_nodes(),
+                        statistics.new_state_leaves,
+                        statistics.new_state_nodes,
+                        statistics.stale_state_leaves,
+                        statistics.stale_state_nodes,
+                    );
+                }
+            }
+            Err(e) => report_error("Error getting ledger statistics", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        Ok(Value::Array(
+            client
+                .get_ledger_statistics(&params)?
+                .into_iter()
+                .map(|statistics| {
+                    json!({
+                        "version": statistics.version,
+                        "events_created": statistics.events_created,
+                        "new_state_leaves": statistics.new_state_leaves,
+                        "stale_state_leaves": statistics.stale_state_leaves,
+                        "new_state_nodes": statistics.new_state_nodes,
+                        "stale_state_nodes": statistics.stale_state_nodes,
+                        "num_accounts": statistics.num_accounts(),
+                        "live_state_nodes": statistics.live_state_nodes(),
+                    })
+                })
+                .collect(),
+        ))
+    }
+}
+
+/// Sub command to export the payment history of an account to a file.
+pub struct QueryCommandExportHistory {}
+
+impl Command for QueryCommandExportHistory {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["history", "h"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address> <output_file_path> [--from <version>] \
+         [--to <version>] [--format csv|json (default=csv)]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Write every payment and gas fee of an account to a file, with running balances"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Exporting transaction history");
+        match client.export_transaction_history(&params) {
+            Ok(history) => println!(
+                "{} entries written to {}, opening balance {}, closing balance {} (micro libras) \
+                 at version {}",
+                history.entries.len(),
+                params[2],
+                history.opening_balance,
+                history.closing_balance,
+                history.ledger_version
+            ),
+            Err(e) => report_error("Error exporting transaction history", e),
+        }
+    }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let history = client.export_transaction_history(&params)?;
+        Ok(json!({
+            "file": params[2],
+            "num_entries": history.entries.len(),
+            "ledger_version": history.ledger_version,
+            "opening_balance": history.opening_balance,
+            "closing_balance": history.closing_balance,
+        }))
+    }
 }
@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{client_proxy::ClientProxy, commands::*};
+use failure::prelude::*;
+use serde_json::{json, Value};
 
 /// Command to transfer coins between two accounts.
 pub struct TransferCommand {}
@@ -48,4 +50,13 @@ impl Command for TransferCommand {
             Err(e) => report_error("Failed to perform transaction", e),
         }
     }
+    fn execute_batch(&self, client: &mut ClientProxy, params: &[&str]) -> Result<Value> {
+        let is_blocking = blocking_cmd(&params[0]);
+        let index_and_seq = client.transfer_coins(&params, is_blocking)?;
+        Ok(json!({
+            "sender": index_and_seq.account_index.to_string(),
+            "sequence_number": index_and_seq.sequence_number,
+            "blocking": is_blocking,
+        }))
+    }
 }
@@ -0,0 +1,509 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Light-client state of the client.
+//!
+//! The trusted state is the latest ledger info verified by the client together with the validator
+//! set of its epoch. Every response to `UpdateToLatestLedger` is verified against it and then
+//! ratchets it forward:
+//!   1. The validator changes carried by the response are followed in order. Each one is the
+//! ledger info ending an epoch, signed by the validators of that epoch and carrying the validators
+//! of the next one.
+//!   2. The ledger info of the response must be signed by the validators of the current epoch and
+//! must not go back in time: a response older than the trusted ledger info comes from a stale
+//! node, a response at the trusted version with a different transaction accumulator comes from a
+//! forked one.
+//!
+//! The state can be persisted, so that the guarantees hold across runs of the client. A persisted
+//! state obtained from a trusted source can also serve as a waypoint to bootstrap a new client.
+
+use crypto::{ed25519::*, HashValue};
+use failure::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::Path, sync::Arc};
+use types::{
+    account_address::AccountAddress,
+    get_with_proof::{UpdateToLatestLedgerRequest, UpdateToLatestLedgerResponse},
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    transaction::Version,
+    validator_set::ValidatorSet,
+    validator_verifier::ValidatorVerifier,
+};
+
+/// The latest ledger info verified by the client and the validators signing the ledger infos of
+/// the current epoch.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrustedState {
+    ledger_info_with_sigs: LedgerInfoWithSignatures<Ed25519Signature>,
+    /// Epoch of the validators, one past the epoch of the ledger info if it ended an epoch.
+    epoch_num: u64,
+    validators: Vec<(AccountAddress, Ed25519PublicKey)>,
+}
+
+impl TrustedState {
+    /// Trusted state of a client which only knows the genesis validators, e.g. from the trusted
+    /// peers config.
+    pub fn new_genesis(validators: HashMap<AccountAddress, Ed25519PublicKey>) -> Self {
+        let genesis_ledger_info = LedgerInfo::new(
+            0,
+            HashValue::zero(),
+            HashValue::zero(),
+            HashValue::zero(),
+            0,
+            0,
+            None,
+        );
+        Self::new(
+            LedgerInfoWithSignatures::new(genesis_ledger_info, HashMap::new()),
+            0,
+            validators,
+        )
+    }
+
+    fn new(
+        ledger_info_with_sigs: LedgerInfoWithSignatures<Ed25519Signature>,
+        epoch_num: u64,
+        validators: HashMap<AccountAddress, Ed25519PublicKey>,
+    ) -> Self {
+        TrustedState {
+            ledger_info_with_sigs,
+            epoch_num,
+            validators: Self::sorted_validators(validators),
+        }
+    }
+
+    fn sorted_validators(
+        validators: HashMap<AccountAddress, Ed25519PublicKey>,
+    ) -> Vec<(AccountAddress, Ed25519PublicKey)> {
+        let mut validators: Vec<_> = validators.into_iter().collect();
+        validators.sort_by_key(|(address, _)| *address);
+        validators
+    }
+
+    fn validators_of(validator_set: &ValidatorSet) -> HashMap<AccountAddress, Ed25519PublicKey> {
+        validator_set
+            .payload()
+            .iter()
+            .map(|keys| (*keys.account_address(), keys.consensus_public_key().clone()))
+            .collect()
+    }
+
+    /// Load a trusted state persisted by `save`. Unless the ledger info ended an epoch, it is
+    /// checked to be signed by the validators stored along with it. Otherwise the stored
+    /// validators must be the ones of the next epoch carried by the ledger info.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let trusted_state: TrustedState = bincode::deserialize(&fs::read(path.as_ref())?)?;
+        let ledger_info = trusted_state.ledger_info_with_sigs.ledger_info();
+        if ledger_info.epoch_num() == trusted_state.epoch_num {
+            trusted_state
+                .ledger_info_with_sigs
+                .verify(&trusted_state.validator_verifier())
+                .map_err(|e| {
+                    format_err!(
+                        "Invalid trusted state in {}: {}",
+                        path.as_ref().display(),
+                        e
+                    )
+                })?;
+        } else {
+            let next_validators = ledger_info
+                .next_validator_set()
+                .map(|validator_set| Self::sorted_validators(Self::validators_of(validator_set)));
+            ensure!(
+                ledger_info.epoch_num() + 1 == trusted_state.epoch_num
+                    && next_validators.as_ref() == Some(&trusted_state.validators),
+                "Invalid trusted state in {}: the validators of epoch {} are not the ones \
+                 carried by the ledger info ending epoch {}",
+                path.as_ref().display(),
+                trusted_state.epoch_num,
+                ledger_info.epoch_num()
+            );
+        }
+        Ok(trusted_state)
+    }
+
+    /// Persist the trusted state to path. The state is written to a temporary file first so that
+    /// a crash can't leave a truncated state behind.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut tmp_path = path.as_ref().as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, bincode::serialize(self)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// The latest verified ledger info.
+    pub fn ledger_info_with_sigs(&self) -> &LedgerInfoWithSignatures<Ed25519Signature> {
+        &self.ledger_info_with_sigs
+    }
+
+    /// Version of the latest verified ledger info, to be sent as `client_known_version`.
+    pub fn version(&self) -> Version {
+        self.ledger_info_with_sigs.ledger_info().version()
+    }
+
+    /// Current epoch, whose ledger infos are signed by the trusted validators.
+    pub fn epoch_num(&self) -> u64 {
+        self.epoch_num
+    }
+
+    /// Verifier of the signatures of the validators of the current epoch.
+    pub fn validator_verifier(&self) -> Arc<ValidatorVerifier<Ed25519PublicKey>> {
+        Arc::new(ValidatorVerifier::new(
+            self.validators.iter().cloned().collect(),
+        ))
+    }
+
+    /// Ensure that a waypoint, i.e. a trusted state obtained from a trusted source, is not ahead of
+    /// this state and does not contradict it.
+    pub fn check_waypoint(&self, waypoint: &TrustedState) -> Result<()> {
+        ensure!(
+            self.version() >= waypoint.version(),
+            "Trusted state at version {} is older than the waypoint at version {}",
+            self.version(),
+            waypoint.version()
+        );
+        if self.version() == waypoint.version() {
+            self.ensure_same_ledger(waypoint.ledger_info_with_sigs.ledger_info())?;
+        }
+        Ok(())
+    }
+
+    /// Verify the response to `request`, and move the trusted state forward to the ledger info of
+    /// the response. Returns whether the trusted state changed. The state is left untouched if the
+    /// verification fails.
+    pub fn verify_and_ratchet(
+        &mut self,
+        request: &UpdateToLatestLedgerRequest,
+        response: &UpdateToLatestLedgerResponse<Ed25519Signature>,
+    ) -> Result<bool> {
+        let mut new_state = self.clone();
+        for change_event in &response.validator_change_events {
+            let ledger_info = change_event.ledger_info_with_sigs().ledger_info();
+            if ledger_info.epoch_num() < new_state.epoch_num() {
+                // Change already known to the client.
+                continue;
+            }
+            ensure!(
+                ledger_info.epoch_num() == new_state.epoch_num(),
+                "Validator change of epoch {} skips the changes since epoch {}",
+                ledger_info.epoch_num(),
+                new_state.epoch_num()
+            );
+            let validator_set = change_event.verify(&new_state.validator_verifier())?;
+            new_state.ensure_not_stale(ledger_info)?;
+            let validators = Self::validators_of(&validator_set);
+            // The ledger info ending the epoch is the last one signed by its validators, the
+            // next ones belong to the new epoch.
+            new_state = Self::new(
+                change_event.ledger_info_with_sigs().clone(),
+                ledger_info.epoch_num() + 1,
+                validators,
+            );
+        }
+
+        let ledger_info = response.ledger_info_with_sigs.ledger_info();
+        ensure!(
+            ledger_info.epoch_num() == new_state.epoch_num(),
+            "Got ledger_info of epoch {}, known epoch: {}",
+            ledger_info.epoch_num(),
+            new_state.epoch_num()
+        );
+        response.verify(new_state.validator_verifier(), request)?;
+        new_state.ensure_not_stale(ledger_info)?;
+        if ledger_info.version() > new_state.version() {
+            new_state.ledger_info_with_sigs = response.ledger_info_with_sigs.clone();
+        }
+
+        let changed = new_state.ledger_info_with_sigs != self.ledger_info_with_sigs
+            || new_state.epoch_num != self.epoch_num;
+        *self = new_state;
+        Ok(changed)
+    }
+
+    /// Ensure that `ledger_info` is not older than the trusted one, and is consistent with it when
+    /// both are at the same version.
+    fn ensure_not_stale(&self, ledger_info: &LedgerInfo) -> Result<()> {
+        ensure!(
+            ledger_info.version() >= self.version(),
+            "Got stale ledger_info with version {}, trusted version: {}",
+            ledger_info.version(),
+            self.version()
+        );
+        if ledger_info.version() == self.version() {
+            self.ensure_same_ledger(ledger_info)?;
+        }
+        Ok(())
+    }
+
+    fn ensure_same_ledger(&self, ledger_info: &LedgerInfo) -> Result<()> {
+        let trusted_ledger_info = self.ledger_info_with_sigs.ledger_info();
+        // The genesis state doesn't know the accumulator at version 0.
+        ensure!(
+            trusted_ledger_info.version() == 0
+                || ledger_info.transaction_accumulator_hash()
+                    == trusted_ledger_info.transaction_accumulator_hash(),
+            "Got ledger_info forking from the trusted one at version {}: {:x} != {:x}",
+            ledger_info.version(),
+            ledger_info.transaction_accumulator_hash(),
+            trusted_ledger_info.transaction_accumulator_hash()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::{hash::CryptoHash, x25519};
+    use tempfile::NamedTempFile;
+    use types::{
+        validator_change::ValidatorChangeEventWithProof,
+        validator_public_keys::ValidatorPublicKeys, validator_signer::ValidatorSigner,
+    };
+
+    fn sign_ledger_info(
+        signer: &ValidatorSigner<Ed25519PrivateKey>,
+        ledger_info: LedgerInfo,
+    ) -> LedgerInfoWithSignatures<Ed25519Signature> {
+        let mut signatures = HashMap::new();
+        signatures.insert(
+            signer.author(),
+            signer.sign_message(ledger_info.hash()).unwrap(),
+        );
+        LedgerInfoWithSignatures::new(ledger_info, signatures)
+    }
+
+    fn signed_ledger_info(
+        signer: &ValidatorSigner<Ed25519PrivateKey>,
+        version: Version,
+        accumulator_hash: HashValue,
+    ) -> LedgerInfoWithSignatures<Ed25519Signature> {
+        sign_ledger_info(
+            signer,
+            LedgerInfo::new(
+                version,
+                accumulator_hash,
+                HashValue::zero(),
+                HashValue::zero(),
+                0,
+                version,
+                None,
+            ),
+        )
+    }
+
+    /// Ledger info at `version` ending epoch 0, signed by `signer`, which hands over to
+    /// `next_signer`.
+    fn epoch_change(
+        signer: &ValidatorSigner<Ed25519PrivateKey>,
+        next_signer: &ValidatorSigner<Ed25519PrivateKey>,
+        version: Version,
+    ) -> ValidatorChangeEventWithProof<Ed25519Signature> {
+        let (_, network_identity_public_key) = x25519::compat::generate_keypair(None);
+        let next_validator_set = ValidatorSet::new(vec![ValidatorPublicKeys::new(
+            next_signer.author(),
+            next_signer.public_key(),
+            next_signer.public_key(),
+            network_identity_public_key,
+        )]);
+        ValidatorChangeEventWithProof::new(sign_ledger_info(
+            signer,
+            LedgerInfo::new(
+                version,
+                HashValue::random(),
+                HashValue::zero(),
+                HashValue::zero(),
+                0,
+                version,
+                Some(next_validator_set),
+            ),
+        ))
+    }
+
+    fn update(
+        trusted_state: &mut TrustedState,
+        ledger_info_with_sigs: LedgerInfoWithSignatures<Ed25519Signature>,
+    ) -> Result<bool> {
+        update_with_changes(trusted_state, vec![], ledger_info_with_sigs)
+    }
+
+    fn update_with_changes(
+        trusted_state: &mut TrustedState,
+        validator_change_events: Vec<ValidatorChangeEventWithProof<Ed25519Signature>>,
+        ledger_info_with_sigs: LedgerInfoWithSignatures<Ed25519Signature>,
+    ) -> Result<bool> {
+        let request = UpdateToLatestLedgerRequest::new(trusted_state.version(), vec![]);
+        let response = UpdateToLatestLedgerResponse::new(
+            vec![],
+            ledger_info_with_sigs,
+            validator_change_events,
+        );
+        trusted_state.verify_and_ratchet(&request, &response)
+    }
+
+    fn genesis_state(signer: &ValidatorSigner<Ed25519PrivateKey>) -> TrustedState {
+        let mut validators = HashMap::new();
+        validators.insert(signer.author(), signer.public_key());
+        TrustedState::new_genesis(validators)
+    }
+
+    #[test]
+    fn test_ratchet() {
+        let signer = ValidatorSigner::<Ed25519PrivateKey>::random(None);
+        let mut trusted_state = genesis_state(&signer);
+        let hash = HashValue::random();
+
+        assert!(update(&mut trusted_state, signed_ledger_info(&signer, 10, hash)).unwrap());
+        assert_eq!(trusted_state.version(), 10);
+        // Same ledger, nothing new.
+        assert!(!update(&mut trusted_state, signed_ledger_info(&signer, 10, hash)).unwrap());
+        assert!(update(
+            &mut trusted_state,
+            signed_ledger_info(&signer, 12, HashValue::random())
+        )
+        .unwrap());
+        assert_eq!(trusted_state.version(), 12);
+    }
+
+    #[test]
+    fn test_reject_stale_and_forked_ledger_info() {
+        let signer = ValidatorSigner::<Ed25519PrivateKey>::random(None);
+        let mut trusted_state = genesis_state(&signer);
+        update(
+            &mut trusted_state,
+            signed_ledger_info(&signer, 10, HashValue::random()),
+        )
+        .unwrap();
+
+        // Stale.
+        assert!(update(
+            &mut trusted_state,
+            signed_ledger_info(&signer, 9, HashValue::random())
+        )
+        .is_err());
+        // Forked.
+        assert!(update(
+            &mut trusted_state,
+            signed_ledger_info(&signer, 10, HashValue::random())
+        )
+        .is_err());
+        // Not signed by the trusted validators.
+        let other_signer = ValidatorSigner::<Ed25519PrivateKey>::random([1u8; 32]);
+        assert!(update(
+            &mut trusted_state,
+            signed_ledger_info(&other_signer, 11, HashValue::random())
+        )
+        .is_err());
+        assert_eq!(trusted_state.version(), 10);
+    }
+
+    #[test]
+    fn test_ratchet_across_epochs() {
+        let signer = ValidatorSigner::<Ed25519PrivateKey>::random(None);
+        let next_signer = ValidatorSigner::<Ed25519PrivateKey>::random([1u8; 32]);
+        let mut trusted_state = genesis_state(&signer);
+        update(
+            &mut trusted_state,
+            signed_ledger_info(&signer, 5, HashValue::random()),
+        )
+        .unwrap();
+        let change = epoch_change(&signer, &next_signer, 10);
+        let ledger_info_of_next_epoch = sign_ledger_info(
+            &next_signer,
+            LedgerInfo::new(
+                15,
+                HashValue::random(),
+                HashValue::zero(),
+                HashValue::zero(),
+                1,
+                15,
+                None,
+            ),
+        );
+
+        // The ledger infos of the next epoch can't be verified without the change.
+        assert!(update(&mut trusted_state, ledger_info_of_next_epoch.clone()).is_err());
+        // The change must be signed by the trusted validators.
+        let forged_change = epoch_change(&next_signer, &next_signer, 10);
+        assert!(update_with_changes(
+            &mut trusted_state,
+            vec![forged_change],
+            ledger_info_of_next_epoch.clone()
+        )
+        .is_err());
+        assert_eq!(trusted_state.epoch_num(), 0);
+
+        assert!(update_with_changes(
+            &mut trusted_state,
+            vec![change.clone()],
+            ledger_info_of_next_epoch.clone()
+        )
+        .unwrap());
+        assert_eq!(trusted_state.epoch_num(), 1);
+        assert_eq!(trusted_state.version(), 15);
+        // The change is already known, and the previous validators are no longer trusted.
+        assert!(
+            !update_with_changes(&mut trusted_state, vec![change], ledger_info_of_next_epoch)
+                .unwrap()
+        );
+        let ledger_info_of_old_validators = sign_ledger_info(
+            &signer,
+            LedgerInfo::new(
+                20,
+                HashValue::random(),
+                HashValue::zero(),
+                HashValue::zero(),
+                1,
+                20,
+                None,
+            ),
+        );
+        assert!(update(&mut trusted_state, ledger_info_of_old_validators).is_err());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let signer = ValidatorSigner::<Ed25519PrivateKey>::random(None);
+        let mut trusted_state = genesis_state(&signer);
+        let ledger_info_with_sigs = signed_ledger_info(&signer, 10, HashValue::random());
+        update(&mut trusted_state, ledger_info_with_sigs.clone()).unwrap();
+
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        trusted_state.save(&path).unwrap();
+        let loaded = TrustedState::load(&path).unwrap();
+        assert_eq!(loaded.ledger_info_with_sigs(), &ledger_info_with_sigs);
+        assert!(loaded.check_waypoint(&trusted_state).is_ok());
+        assert!(genesis_state(&signer).check_waypoint(&loaded).is_err());
+    }
+
+    #[test]
+    fn test_load_state_ending_epoch() {
+        let signer = ValidatorSigner::<Ed25519PrivateKey>::random(None);
+        let next_signer = ValidatorSigner::<Ed25519PrivateKey>::random([1u8; 32]);
+        // The state of a client which followed the change ending epoch 0.
+        let change = epoch_change(&signer, &next_signer, 10);
+        let mut next_validators = HashMap::new();
+        next_validators.insert(next_signer.author(), next_signer.public_key());
+        let trusted_state =
+            TrustedState::new(change.ledger_info_with_sigs().clone(), 1, next_validators);
+
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        trusted_state.save(&path).unwrap();
+        let loaded = TrustedState::load(&path).unwrap();
+        assert_eq!(loaded.validators, trusted_state.validators);
+
+        // The validators of the next epoch are not the ones carried by the ledger info.
+        let mut tampered = trusted_state.clone();
+        let mut validators = HashMap::new();
+        validators.insert(signer.author(), signer.public_key());
+        tampered.validators = TrustedState::sorted_validators(validators);
+        tampered.save(&path).unwrap();
+        assert!(TrustedState::load(&path).is_err());
+        // The epoch doesn't follow the epoch of the ledger info.
+        let mut tampered = trusted_state.clone();
+        tampered.epoch_num = 2;
+        tampered.save(&path).unwrap();
+        assert!(TrustedState::load(&path).is_err());
+    }
+}
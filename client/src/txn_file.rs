@@ -0,0 +1,117 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! On-disk format of the transactions exchanged by the offline signing workflow.
+//!
+//! A transaction file is a text file of two lines: a header naming the kind of transaction and
+//! the version of the format, followed by the hex encoding of the canonical serialization of the
+//! transaction:
+//!
+//! ```text
+//! libra_unsigned_transaction_v1
+//! <hex of the canonical serialization of a RawTransaction>
+//! ```
+//!
+//! Signed transactions use the `libra_signed_transaction_v1` header and the canonical
+//! serialization of `SignedTransaction`, which carries the exact raw transaction bytes covered by
+//! the signature.
+
+use canonical_serialization::{SimpleDeserializer, SimpleSerializer};
+use failure::prelude::*;
+use std::{fs, path::Path};
+use types::transaction::{RawTransaction, SignedTransaction};
+
+const UNSIGNED_TXN_HEADER: &str = "libra_unsigned_transaction_v1";
+const SIGNED_TXN_HEADER: &str = "libra_signed_transaction_v1";
+
+/// Write an unsigned transaction to `path`.
+pub fn write_unsigned_txn(path: &Path, raw_txn: &RawTransaction) -> Result<()> {
+    let bytes = SimpleSerializer::<Vec<u8>>::serialize(raw_txn)?;
+    write_txn_file(path, UNSIGNED_TXN_HEADER, &bytes)
+}
+
+/// Read an unsigned transaction from `path`.
+pub fn read_unsigned_txn(path: &Path) -> Result<RawTransaction> {
+    let bytes = read_txn_file(path, UNSIGNED_TXN_HEADER)?;
+    SimpleDeserializer::deserialize(&bytes)
+}
+
+/// Write a signed transaction to `path`.
+pub fn write_signed_txn(path: &Path, signed_txn: &SignedTransaction) -> Result<()> {
+    let bytes = SimpleSerializer::<Vec<u8>>::serialize(signed_txn)?;
+    write_txn_file(path, SIGNED_TXN_HEADER, &bytes)
+}
+
+/// Read a signed transaction from `path`. The signature is not checked.
+pub fn read_signed_txn(path: &Path) -> Result<SignedTransaction> {
+    let bytes = read_txn_file(path, SIGNED_TXN_HEADER)?;
+    SimpleDeserializer::deserialize(&bytes)
+}
+
+fn write_txn_file(path: &Path, header: &str, bytes: &[u8]) -> Result<()> {
+    fs::write(path, format!("{}\n{}\n", header, hex::encode(bytes)))?;
+    Ok(())
+}
+
+fn read_txn_file(path: &Path, header: &str) -> Result<Vec<u8>> {
+    let content = fs::read_to_string(path)?;
+    let mut lines = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    let file_header = lines.next().unwrap_or("");
+    ensure!(
+        file_header == header,
+        "Unexpected header in transaction file {}: expected {}, found {:?}",
+        path.display(),
+        header,
+        file_header
+    );
+    let data = lines
+        .next()
+        .ok_or_else(|| format_err!("Missing transaction in file {}", path.display()))?;
+    ensure!(
+        lines.next().is_none(),
+        "Unexpected trailing data in transaction file {}",
+        path.display()
+    );
+    Ok(hex::decode(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::ed25519::compat;
+    use tempfile::NamedTempFile;
+    use types::{account_address::AccountAddress, transaction_helpers::create_unsigned_txn};
+
+    fn test_raw_txn() -> RawTransaction {
+        let program = vm_genesis::encode_transfer_program(&AccountAddress::random(), 10);
+        create_unsigned_txn(program, AccountAddress::random(), 7, 100_000, 0, 100)
+    }
+
+    #[test]
+    fn test_unsigned_txn_round_trip() {
+        let raw_txn = test_raw_txn();
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        write_unsigned_txn(&path, &raw_txn).unwrap();
+        assert_eq!(read_unsigned_txn(&path).unwrap(), raw_txn);
+        // An unsigned transaction file can't be mistaken for a signed one.
+        assert!(read_signed_txn(&path).is_err());
+    }
+
+    #[test]
+    fn test_signed_txn_round_trip() {
+        let (private_key, public_key) = compat::generate_keypair(None);
+        let signed_txn = test_raw_txn()
+            .sign(&private_key, public_key)
+            .unwrap()
+            .into_inner();
+        let path = NamedTempFile::new().unwrap().into_temp_path();
+        write_signed_txn(&path, &signed_txn).unwrap();
+        let read_txn = read_signed_txn(&path).unwrap();
+        assert_eq!(read_txn, signed_txn);
+        assert!(read_txn.check_signature().is_ok());
+        assert!(read_unsigned_txn(&path).is_err());
+    }
+}
@@ -27,7 +27,9 @@ use proto_conv::FromProtoBytes;
 use types::transaction::{SignedTransaction, SCRIPT_HASH_LENGTH};
 
 use crate::{
-    config::ConsensusProposerType::{FixedProposer, RotatingProposer},
+    config::ConsensusProposerType::{
+        FixedProposer, LeaderReputation, RotatingProposer, VrfProposer,
+    },
     seed_peers::{SeedPeersConfig, SeedPeersConfigHelpers},
     trusted_peers::{
         deserialize_key, deserialize_opt_key, serialize_key, serialize_opt_key,
@@ -156,6 +158,13 @@ pub struct KeyPairs {
     #[serde(serialize_with = "serialize_key")]
     #[serde(deserialize_with = "deserialize_key")]
     consensus_public_key: Ed25519PublicKey,
+
+    // The VRF key used by the VRF proposer election, which every validator must have.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_opt_key")]
+    #[serde(deserialize_with = "deserialize_opt_key")]
+    vrf_private_key: Option<Ed25519PrivateKey>,
 }
 
 // required for serialization
@@ -165,6 +174,7 @@ impl Default for KeyPairs {
         let (net_private_sig, net_public_sig) = compat::generate_keypair(&mut rng);
         let (consensus_private_sig, consensus_public_sig) = compat::generate_keypair(&mut rng);
         let (private_kex, public_kex) = x25519::compat::generate_keypair(&mut rng);
+        let (vrf_private_key, _) = compat::generate_keypair(&mut rng);
         Self {
             network_signing_private_key: Some(net_private_sig),
             network_signing_public_key: net_public_sig,
@@ -172,6 +182,7 @@ impl Default for KeyPairs {
             network_identity_public_key: public_kex,
             consensus_private_key: Some(consensus_private_sig),
             consensus_public_key: consensus_public_sig,
+            vrf_private_key: Some(vrf_private_key),
         }
     }
 }
@@ -202,8 +213,12 @@ impl KeyPairs {
 
     // used in testing to fill the structure with test keypairs
     pub fn load(private_keys: TrustedPeerPrivateKeys) -> Self {
-        let (network_signing_private_key, network_identity_private_key, consensus_private_key) =
-            private_keys.get_key_triplet();
+        let (
+            network_signing_private_key,
+            network_identity_private_key,
+            consensus_private_key,
+            vrf_private_key,
+        ) = private_keys.get_keys();
         let network_signing_public_key = (&network_signing_private_key).into();
         let network_identity_public_key = (&network_identity_private_key).into();
         let consensus_public_key = (&consensus_private_key).into();
@@ -214,6 +229,7 @@ impl KeyPairs {
             network_identity_public_key,
             consensus_private_key: Some(consensus_private_key),
             consensus_public_key,
+            vrf_private_key: Some(vrf_private_key),
         }
     }
     // getters for private keys
@@ -235,6 +251,11 @@ impl KeyPairs {
     pub fn take_consensus_private(&mut self) -> Option<Ed25519PrivateKey> {
         std::mem::replace(&mut self.consensus_private_key, None)
     }
+
+    /// Beware, this destroys the private key from this NodeConfig
+    pub fn take_vrf_private(&mut self) -> Option<Ed25519PrivateKey> {
+        std::mem::replace(&mut self.vrf_private_key, None)
+    }
     // getters for public keys
     pub fn get_network_signing_public(&self) -> &Ed25519PublicKey {
         &self.network_signing_public_key
@@ -429,6 +450,16 @@ pub struct AdmissionControlConfig {
     pub address: String,
     pub admission_control_service_port: u16,
     pub need_to_check_mempool_before_validation: bool,
+    // Interval at which the streams of WatchEvents check storage for new events.
+    pub event_watch_poll_interval_ms: u64,
+    // A stream without new event sends an empty response after this interval.
+    pub event_watch_heartbeat_interval_ms: u64,
+    // Maximum number of concurrent WatchEvents streams.
+    pub max_event_watchers: usize,
+    // Maximum number of concurrent SimulateTransaction requests. Each of them executes a
+    // transaction through the VM, so their number is kept small. The API is disabled when it is
+    // set to 0.
+    pub max_concurrent_simulations: usize,
 }
 
 impl Default for AdmissionControlConfig {
@@ -437,6 +468,10 @@ impl Default for AdmissionControlConfig {
             address: "0.0.0.0".to_string(),
             admission_control_service_port: 8000,
             need_to_check_mempool_before_validation: false,
+            event_watch_poll_interval_ms: 100,
+            event_watch_heartbeat_interval_ms: 10_000,
+            max_event_watchers: 100,
+            max_concurrent_simulations: 4,
         }
     }
 }
@@ -471,6 +506,7 @@ pub struct StorageConfig {
     pub port: u16,
     pub dir: PathBuf,
     pub grpc_max_receive_len: Option<i32>,
+    pub pruning: StoragePruningConfig,
 }
 
 impl StorageConfig {
@@ -486,6 +522,32 @@ impl Default for StorageConfig {
             port: 6184,
             dir: PathBuf::from("libradb"),
             grpc_max_receive_len: Some(100_000_000),
+            pruning: StoragePruningConfig::default(),
+        }
+    }
+}
+
+/// How much history the storage keeps. It can be changed at runtime through the storage admin
+/// APIs, until the node restarts.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StoragePruningConfig {
+    /// Keep the whole history, ignoring the windows below.
+    pub archive: bool,
+    /// Other than the latest version, how many historical versions of the state to keep.
+    pub state_window: u64,
+    /// Other than the latest version, how many historical versions of the transactions and
+    /// events to keep. They are never pruned if not set. It must not be smaller than
+    /// `state_window`.
+    pub ledger_window: Option<u64>,
+}
+
+impl Default for StoragePruningConfig {
+    fn default() -> StoragePruningConfig {
+        StoragePruningConfig {
+            archive: false,
+            state_window: 1_000_000,
+            ledger_window: None,
         }
     }
 }
@@ -528,6 +590,10 @@ pub struct ConsensusConfig {
     contiguous_rounds: u32,
     max_pruned_blocks_in_mem: Option<u64>,
     pacemaker_initial_timeout_ms: Option<u64>,
+    vrf_proposal_wait_ms: Option<u64>,
+    safety_rules_type: String,
+    safety_rules_address: String,
+    safety_rules_port: u16,
 }
 
 impl Default for ConsensusConfig {
@@ -538,16 +604,35 @@ impl Default for ConsensusConfig {
             contiguous_rounds: 2,
             max_pruned_blocks_in_mem: None,
             pacemaker_initial_timeout_ms: None,
+            vrf_proposal_wait_ms: None,
+            safety_rules_type: "local".to_string(),
+            safety_rules_address: "localhost".to_string(),
+            safety_rules_port: 6186,
         }
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ConsensusProposerType {
     // Choose the smallest PeerId as the proposer
     FixedProposer,
     // Round robin rotation of proposers
     RotatingProposer,
+    // Proposers drawn according to their participation in the recent committed blocks
+    LeaderReputation,
+    // All validators propose, the proposal with the lowest VRF output wins. The VRF is evaluated
+    // on the round and the hash of the quorum certificate the proposal extends, so a proposer
+    // knowing several certificates may pick the one giving its lowest output
+    VrfProposer,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SafetyRulesType {
+    // The safety rules run in the consensus process, which holds the consensus key
+    Local,
+    // The safety rules run in their own process, which owns the consensus key through the
+    // secret service and serves the consensus process at safety_rules_address:safety_rules_port
+    Process,
 }
 
 impl ConsensusConfig {
@@ -555,10 +640,28 @@ impl ConsensusConfig {
         match self.proposer_type.as_str() {
             "fixed_proposer" => FixedProposer,
             "rotating_proposer" => RotatingProposer,
+            "leader_reputation" => LeaderReputation,
+            "vrf_proposer" => VrfProposer,
             &_ => unimplemented!("Invalid proposer type: {}", self.proposer_type),
         }
     }
 
+    pub fn get_safety_rules_type(&self) -> SafetyRulesType {
+        match self.safety_rules_type.as_str() {
+            "local" => SafetyRulesType::Local,
+            "process" => SafetyRulesType::Process,
+            &_ => unimplemented!("Invalid safety rules type: {}", self.safety_rules_type),
+        }
+    }
+
+    pub fn safety_rules_address(&self) -> &String {
+        &self.safety_rules_address
+    }
+
+    pub fn safety_rules_port(&self) -> u16 {
+        self.safety_rules_port
+    }
+
     pub fn contiguous_rounds(&self) -> u32 {
         self.contiguous_rounds
     }
@@ -574,6 +677,10 @@ impl ConsensusConfig {
     pub fn pacemaker_initial_timeout_ms(&self) -> &Option<u64> {
         &self.pacemaker_initial_timeout_ms
     }
+
+    pub fn vrf_proposal_wait_ms(&self) -> &Option<u64> {
+        &self.vrf_proposal_wait_ms
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -818,6 +925,7 @@ impl NodeConfigHelpers {
 
     pub fn randomize_config_ports(config: &mut NodeConfig) {
         config.admission_control.admission_control_service_port = get_available_port();
+        config.consensus.safety_rules_port = get_available_port();
         config.debug_interface.admission_control_node_debug_port = get_available_port();
         config.debug_interface.metrics_server_port = get_available_port();
         config.debug_interface.secret_service_node_debug_port = get_available_port();
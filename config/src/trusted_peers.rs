@@ -35,20 +35,38 @@ pub struct TrustedPeer {
     #[serde(deserialize_with = "deserialize_key")]
     #[serde(rename = "c")]
     consensus_pubkey: Ed25519PublicKey,
+    // The VRF public key of the validator, required by the VRF proposer election. It is distinct
+    // from the consensus key.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_opt_key")]
+    #[serde(deserialize_with = "deserialize_opt_key")]
+    #[serde(rename = "v")]
+    vrf_pubkey: Option<Ed25519PublicKey>,
 }
 
 pub struct TrustedPeerPrivateKeys {
     network_signing_private_key: Ed25519PrivateKey,
     network_identity_private_key: X25519StaticPrivateKey,
     consensus_private_key: Ed25519PrivateKey,
+    vrf_private_key: Ed25519PrivateKey,
 }
 
 impl TrustedPeerPrivateKeys {
-    pub fn get_key_triplet(self) -> (Ed25519PrivateKey, X25519StaticPrivateKey, Ed25519PrivateKey) {
+    /// Returns the network signing, network identity, consensus and VRF private keys.
+    pub fn get_keys(
+        self,
+    ) -> (
+        Ed25519PrivateKey,
+        X25519StaticPrivateKey,
+        Ed25519PrivateKey,
+        Ed25519PrivateKey,
+    ) {
         (
             self.network_signing_private_key,
             self.network_identity_private_key,
             self.consensus_private_key,
+            self.vrf_private_key,
         )
     }
 }
@@ -63,6 +81,9 @@ impl TrustedPeer {
     pub fn get_consensus_public(&self) -> &Ed25519PublicKey {
         &self.consensus_pubkey
     }
+    pub fn get_vrf_public(&self) -> Option<&Ed25519PublicKey> {
+        self.vrf_pubkey.as_ref()
+    }
 }
 
 pub fn serialize_key<S, K>(key: &K, serializer: S) -> Result<S::Ok, S::Error>
@@ -169,6 +190,22 @@ impl TrustedPeersConfig {
         res
     }
 
+    /// Returns a map of AccountAddress to its VRF PublicKey, for the peers which have one.
+    pub fn get_trusted_vrf_peers(&self) -> HashMap<AccountAddress, Ed25519PublicKey> {
+        self.peers
+            .iter()
+            .filter_map(|(account, keys)| {
+                keys.vrf_pubkey.as_ref().map(|vrf_pubkey| {
+                    (
+                        AccountAddress::try_from(account.clone())
+                            .expect("Failed to parse account addr"),
+                        vrf_pubkey.clone(),
+                    )
+                })
+            })
+            .collect()
+    }
+
     /// Returns a map of AccountAddress to a pair of PublicKeys for network peering. The first
     /// PublicKey is the one used for signing, whereas the second is to determine eligible members
     /// of the network.
@@ -226,11 +263,13 @@ impl TrustedPeersConfigHelpers {
             let (private0, public0) = compat::generate_keypair(&mut fast_rng);
             let (private1, public1) = x25519::compat::generate_keypair(&mut fast_rng);
             let (private2, public2) = compat::generate_keypair(&mut fast_rng);
+            let (private3, public3) = compat::generate_keypair(&mut fast_rng);
             // save the public_key in peers hashmap
             let peer = TrustedPeer {
                 network_signing_pubkey: public0,
                 network_identity_pubkey: public1,
                 consensus_pubkey: public2,
+                vrf_pubkey: Some(public3),
             };
             let peer_id = AccountAddress::from_public_key(&peer.consensus_pubkey);
             peers.insert(peer_id.to_string(), peer);
@@ -239,6 +278,7 @@ impl TrustedPeersConfigHelpers {
                 network_signing_private_key: private0,
                 network_identity_private_key: private1,
                 consensus_private_key: private2,
+                vrf_private_key: private3,
             };
             peers_private_keys.insert(peer_id.to_string(), private_keys);
         }
@@ -1,9 +1,32 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use super::TrustedPeersConfigHelpers;
+use super::{TrustedPeersConfig, TrustedPeersConfigHelpers};
 
 #[test]
 fn generate_test_config() {
     let (_, _) = TrustedPeersConfigHelpers::get_test_config(10, None);
 }
+
+#[test]
+fn test_vrf_keys() {
+    let (_, config) = TrustedPeersConfigHelpers::get_test_config(2, None);
+    assert_eq!(config.get_trusted_vrf_peers().len(), 2);
+    let contents = toml::to_string(&config).unwrap();
+    let parsed = TrustedPeersConfig::parse(&contents);
+    assert_eq!(
+        parsed.get_trusted_vrf_peers(),
+        config.get_trusted_vrf_peers()
+    );
+
+    // The peers of an older config have no VRF key.
+    let mut config = parsed;
+    for peer in config.peers.values_mut() {
+        peer.vrf_pubkey = None;
+    }
+    let contents = toml::to_string(&config).unwrap();
+    assert!(!contents.contains("v ="));
+    let parsed = TrustedPeersConfig::parse(&contents);
+    assert!(parsed.get_trusted_vrf_peers().is_empty());
+    assert_eq!(parsed.get_trusted_consensus_peers().len(), 2);
+}
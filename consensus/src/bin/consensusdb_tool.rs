@@ -0,0 +1,154 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use consensus::ConsensusDBInspector;
+use crypto::HashValue;
+use failure::prelude::*;
+use libradb::inspector::LibraDBInspector;
+use std::collections::{HashMap, HashSet};
+use structopt::StructOpt;
+use types::transaction::SignedTransaction;
+
+type Inspector = ConsensusDBInspector<Vec<SignedTransaction>>;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "consensusdb-tool",
+    author = "Libra",
+    about = "Look into the ConsensusDB of a validator, read-only unless asked to repair it"
+)]
+struct Args {
+    /// Storage directory of the node, holding its ConsensusDB and LibraDB
+    #[structopt(short = "d", long = "db_dir")]
+    pub db_dir: String,
+    #[structopt(subcommand)]
+    pub cmd: Command,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Print the block tree, with the round and the parent of every block
+    #[structopt(name = "tree")]
+    Tree,
+    /// Print the consensus state and the highest timeout certificates
+    #[structopt(name = "state")]
+    State,
+    /// Check that the root the validator starts from is the block committed by the latest ledger
+    /// info of LibraDB
+    #[structopt(name = "check-root")]
+    CheckRoot,
+    /// Drop all the blocks but the committed root, keeping the consensus state. The node must be
+    /// stopped.
+    #[structopt(name = "reset-to-committed-root")]
+    ResetToCommittedRoot,
+}
+
+/// Returns the id of the block committed by the latest ledger info of LibraDB.
+fn root_from_storage(db_dir: &str) -> Result<HashValue> {
+    let ledger_info_with_sigs = LibraDBInspector::open(db_dir)?
+        .get_latest_ledger_info()?
+        .ok_or_else(|| format_err!("No ledger info in LibraDB."))?;
+    Ok(ledger_info_with_sigs.ledger_info().consensus_block_id())
+}
+
+fn print_tree(inspector: &Inspector) -> Result<()> {
+    let blocks = inspector.get_blocks()?;
+    let quorum_certs = inspector.get_quorum_certs()?;
+    let certified: HashSet<_> = quorum_certs
+        .iter()
+        .map(|qc| qc.certified_block_id())
+        .collect();
+    let committed: HashSet<_> = quorum_certs
+        .iter()
+        .filter_map(|qc| qc.committed_block_id())
+        .collect();
+    // The blocks are ordered by round, so the parent of a block is printed before it.
+    let mut depths = HashMap::new();
+    for block in &blocks {
+        let depth = depths.get(&block.parent_id()).map_or(0, |depth| depth + 1);
+        depths.insert(block.id(), depth);
+        println!(
+            "{}{}{}{}",
+            "  ".repeat(depth),
+            block,
+            if certified.contains(&block.id()) {
+                " certified"
+            } else {
+                ""
+            },
+            if committed.contains(&block.id()) {
+                " committed"
+            } else {
+                ""
+            },
+        );
+    }
+    println!(
+        "{} blocks, {} quorum certificates.",
+        blocks.len(),
+        quorum_certs.len()
+    );
+    Ok(())
+}
+
+fn run(args: Args) -> Result<()> {
+    match args.cmd {
+        Command::Tree => print_tree(&Inspector::open(&args.db_dir)?)?,
+        Command::State => {
+            let inspector = Inspector::open(&args.db_dir)?;
+            match inspector.get_consensus_state()? {
+                Some(state) => println!("{}", state),
+                None => println!("No consensus state."),
+            }
+            match inspector.get_highest_timeout_certificates()? {
+                Some(certificates) => {
+                    match certificates.highest_local_timeout_certificate() {
+                        Some(tc) => println!("Highest local timeout certificate: {}", tc),
+                        None => println!("No local timeout certificate."),
+                    }
+                    match certificates.highest_received_timeout_certificate() {
+                        Some(tc) => println!("Highest received timeout certificate: {}", tc),
+                        None => println!("No received timeout certificate."),
+                    }
+                }
+                None => println!("No timeout certificates."),
+            }
+        }
+        Command::CheckRoot => {
+            let root_from_storage = root_from_storage(&args.db_dir)?;
+            let root_check = Inspector::open(&args.db_dir)?.check_root(root_from_storage)?;
+            println!(
+                "Root {} at round {}, {} blocks to prune.",
+                root_check.root_id,
+                root_check.root_round,
+                root_check.blocks_to_prune.len()
+            );
+            ensure!(
+                root_check.matches_storage,
+                "The root doesn't match the block {} committed in LibraDB: the validator will \
+                 synchronize its state up to the root on start.",
+                root_from_storage
+            );
+        }
+        Command::ResetToCommittedRoot => {
+            let root_from_storage = root_from_storage(&args.db_dir)?;
+            let inspector = Inspector::open_for_repair(&args.db_dir)?;
+            let num_blocks = inspector.get_blocks()?.len();
+            let root_check = inspector.reset_to_committed_root(root_from_storage)?;
+            println!(
+                "Reset to the root {} at round {}, dropped {} blocks.",
+                root_check.root_id,
+                root_check.root_round,
+                num_blocks - 1
+            );
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(Args::from_args()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,19 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use consensus::SafetyRulesNode;
+use executable_helpers::helpers::{
+    setup_executable, ARG_CONFIG_PATH, ARG_DISABLE_LOGGING, ARG_PEER_ID,
+};
+
+/// Run the safety rules of a validator in their own process.
+fn main() {
+    let (config, _logger, _args) = setup_executable(
+        "Libra Safety Rules".to_string(),
+        vec![ARG_PEER_ID, ARG_CONFIG_PATH, ARG_DISABLE_LOGGING],
+    );
+
+    let mut safety_rules_node = SafetyRulesNode::new(config);
+
+    safety_rules_node.run().expect("Unable to run SafetyRules");
+}
@@ -54,7 +54,9 @@ pub enum NeedFetchResult {
 ///             | -------------> D3
 pub struct BlockStore<T> {
     inner: Arc<RwLock<BlockTree<T>>>,
-    validator_signer: ValidatorSigner<Ed25519PrivateKey>,
+    validator_signer: Arc<ValidatorSigner<Ed25519PrivateKey>>,
+    /// The epoch of the blocks of the tree, which all its votes and certificates belong to.
+    epoch: u64,
     state_computer: Arc<dyn StateComputer<Payload = T>>,
     enforce_increasing_timestamps: bool,
     /// The persistent storage backing up the in-memory data structure, every write should go
@@ -66,12 +68,13 @@ impl<T: Payload> BlockStore<T> {
     pub async fn new(
         storage: Arc<dyn PersistentStorage<T>>,
         initial_data: RecoveryData<T>,
-        validator_signer: ValidatorSigner<Ed25519PrivateKey>,
+        validator_signer: Arc<ValidatorSigner<Ed25519PrivateKey>>,
         state_computer: Arc<dyn StateComputer<Payload = T>>,
         enforce_increasing_timestamps: bool,
         max_pruned_blocks_in_mem: usize,
     ) -> Self {
         let (root, blocks, quorum_certs) = initial_data.take();
+        let epoch = root.1.certified_block_epoch();
         let inner = Arc::new(RwLock::new(
             Self::build_block_tree(
                 root,
@@ -85,6 +88,7 @@ impl<T: Payload> BlockStore<T> {
         BlockStore {
             inner,
             validator_signer,
+            epoch,
             state_computer,
             enforce_increasing_timestamps,
             storage,
@@ -162,6 +166,10 @@ impl<T: Payload> BlockStore<T> {
         &self.validator_signer
     }
 
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
     /// Execute and insert a block if it passes all validation tests.
     /// Returns the Arc to the block kept in the block store after persisting it to storage
     ///
@@ -326,44 +334,61 @@ impl<T: Payload> BlockStore<T> {
 
     /// If block id information is found, returns the ledger info placeholder, otherwise, return
     /// a placeholder with info of the genesis block.
+    ///
+    /// The epoch ends with the first block that changes the validator set: if the block to commit
+    /// descends from such a block, the placeholder commits this block instead, and carries the
+    /// validator set of the next epoch.
     pub fn ledger_info_placeholder(&self, id: Option<HashValue>) -> LedgerInfo {
         let block_id = match id {
-            None => return Self::zero_ledger_info_placeholder(),
+            None => return self.zero_ledger_info_placeholder(),
             Some(id) => id,
         };
         let block = match self.get_block(block_id) {
             Some(b) => b,
             None => {
-                return Self::zero_ledger_info_placeholder();
+                return self.zero_ledger_info_placeholder();
             }
         };
-        let (state_id, version) = match self.get_state_for_block(block_id) {
+        let (block, next_validator_set) = self
+            .path_from_root(Arc::clone(&block))
+            .unwrap_or_else(Vec::new)
+            .into_iter()
+            .rev()
+            .find_map(|b| {
+                self.get_compute_result(b.id())
+                    .and_then(|compute_result| compute_result.validators.clone())
+                    .map(|validators| (b, Some(validators)))
+            })
+            .unwrap_or((block, None));
+        let (state_id, version) = match self.get_state_for_block(block.id()) {
             Some(state) => (state.state_id, state.version),
             None => {
-                return Self::zero_ledger_info_placeholder();
+                return self.zero_ledger_info_placeholder();
             }
         };
         LedgerInfo::new(
             version,
             state_id,
             HashValue::zero(),
-            block_id,
-            0, // TODO [Reconfiguration] use the real epoch number.
+            block.id(),
+            self.epoch,
             block.timestamp_usecs(),
+            next_validator_set,
         )
     }
 
     /// Used in case we're using a ledger info just as a placeholder for signing the votes / QCs
     /// and there is no real block committed.
     /// It's all pretty much zeroes.
-    fn zero_ledger_info_placeholder() -> LedgerInfo {
+    fn zero_ledger_info_placeholder(&self) -> LedgerInfo {
         LedgerInfo::new(
             0,
             HashValue::zero(),
             HashValue::zero(),
             HashValue::zero(),
+            self.epoch,
             0,
-            0,
+            None,
         )
     }
 
@@ -8,17 +8,27 @@ use crate::chained_bft::{
         block::{block_test, Block},
         quorum_cert::QuorumCert,
     },
+    persistent_storage::PersistentStorage,
     safety::vote_msg::VoteMsg,
     test_utils::{
         build_empty_tree, build_empty_tree_with_custom_signing, placeholder_certificate_for_block,
-        placeholder_ledger_info, TreeInserter,
+        placeholder_ledger_info, EmptyStorage, MockStateComputer, TreeInserter,
     },
 };
 use crypto::{ed25519::*, HashValue, *};
-use futures::executor::block_on;
+use futures::{channel::mpsc, executor::block_on};
 use proptest::prelude::*;
-use std::{cmp::min, collections::HashSet, sync::Arc};
-use types::{account_address::AccountAddress, validator_signer::ValidatorSigner};
+use std::{
+    cmp::min,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use types::{
+    account_address::AccountAddress,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_set::ValidatorSet,
+    validator_signer::ValidatorSigner,
+};
 
 fn build_simple_tree() -> (Vec<Arc<Block<Vec<usize>>>>, Arc<BlockStore<Vec<usize>>>) {
     let block_store = build_empty_tree();
@@ -518,3 +528,54 @@ fn test_need_sync_for_qc() {
         false
     );
 }
+
+#[test]
+fn test_epoch_genesis() {
+    // The ledger info committing the last block of epoch 3.
+    let ledger_info = LedgerInfoWithSignatures::new(
+        LedgerInfo::new(
+            10,
+            HashValue::random(),
+            HashValue::random(),
+            HashValue::random(),
+            3,
+            100,
+            Some(ValidatorSet::new(vec![])),
+        ),
+        HashMap::new(),
+    );
+    let genesis = Block::<Vec<usize>>::make_epoch_genesis_block(ledger_info.clone());
+    assert!(genesis.is_genesis_block());
+    assert_eq!(genesis.id(), ledger_info.ledger_info().consensus_block_id());
+    let genesis_qc = genesis.quorum_cert().clone();
+    assert!(genesis_qc.is_epoch_genesis());
+    assert_eq!(genesis_qc.certified_block_epoch(), 4);
+
+    let (storage, _) = EmptyStorage::start_for_testing::<Vec<usize>>();
+    let initial_data = storage
+        .reset_to_epoch_genesis(genesis.clone(), genesis_qc)
+        .unwrap();
+    let (commit_cb_sender, _commit_cb_receiver) =
+        mpsc::unbounded::<LedgerInfoWithSignatures<Ed25519Signature>>();
+    let block_store = Arc::new(block_on(BlockStore::new(
+        storage,
+        initial_data,
+        Arc::new(ValidatorSigner::random(None)),
+        Arc::new(MockStateComputer::new(commit_cb_sender)),
+        true,
+        10, // max pruned blocks in mem
+    )));
+    assert_eq!(block_store.epoch(), 4);
+    assert_eq!(block_store.root().id(), genesis.id());
+    assert_eq!(block_store.ledger_info_placeholder(None).epoch_num(), 4);
+
+    let mut inserter = TreeInserter::new(block_store.clone());
+    let a1 = inserter.insert_block(&genesis, 1);
+    assert_eq!(a1.quorum_cert().certified_block_id(), genesis.id());
+    assert_eq!(
+        block_store
+            .ledger_info_placeholder(Some(a1.id()))
+            .epoch_num(),
+        4
+    );
+}
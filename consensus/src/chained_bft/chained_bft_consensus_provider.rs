@@ -3,17 +3,24 @@
 
 use crate::{
     chained_bft::{
-        chained_bft_smr::ChainedBftSMR, network::ConsensusNetworkImpl,
-        persistent_storage::PersistentStorage,
+        chained_bft_smr::ChainedBftSMR, liveness::vrf_proposer_election::VrfKeys,
+        network::ConsensusNetworkImpl, persistent_storage::PersistentStorage,
     },
-    consensus_provider::ConsensusProvider,
+    consensus_provider::{create_storage_read_client, ConsensusProvider},
     counters,
     state_computer::ExecutionProxy,
     state_replication::StateMachineReplication,
     txn_manager::MempoolProxy,
 };
-use crypto::ed25519::*;
-use network::validator_network::{ConsensusNetworkEvents, ConsensusNetworkSender};
+use canonical_serialization::SimpleDeserializer;
+use crypto::{
+    ed25519::*,
+    vrf::ecvrf::{VRFPrivateKey, VRFPublicKey},
+};
+use network::{
+    validator_network::{ConsensusNetworkEvents, ConsensusNetworkSender},
+    NetworkPublicKeys,
+};
 
 use crate::chained_bft::{
     chained_bft_smr::ChainedBftSMRConfig, common::Author, persistent_storage::StorageWriteProxy,
@@ -24,16 +31,27 @@ use failure::prelude::*;
 use logger::prelude::*;
 use mempool::proto::mempool_grpc::MempoolClient;
 use state_synchronizer::StateSyncClient;
-use std::{convert::TryFrom, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    sync::{Arc, RwLock},
+};
+use storage_client::StorageRead;
 use tokio::runtime;
 use types::{
-    account_address::AccountAddress, transaction::SignedTransaction,
-    validator_signer::ValidatorSigner, validator_verifier::ValidatorVerifier,
+    access_path::VALIDATOR_SET_ACCESS_PATH,
+    account_address::AccountAddress,
+    get_with_proof::{RequestItem, ResponseItem},
+    transaction::SignedTransaction,
+    validator_set::ValidatorSet,
+    validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
 };
 
 struct InitialSetup {
     author: Author,
     signer: ValidatorSigner<Ed25519PrivateKey>,
+    vrf_keys: VrfKeys,
     quorum_size: usize,
     peers: Arc<Vec<Author>>,
     validator: Arc<ValidatorVerifier<Ed25519PublicKey>>,
@@ -52,6 +70,7 @@ impl ChainedBftProvider {
         node_config: &mut NodeConfig,
         network_sender: ConsensusNetworkSender,
         network_events: ConsensusNetworkEvents,
+        trusted_network_peers: Arc<RwLock<HashMap<AccountAddress, NetworkPublicKeys>>>,
         mempool_client: Arc<MempoolClient>,
         execution_client: Arc<ExecutionClient>,
         synchronizer_client: Arc<StateSyncClient>,
@@ -89,8 +108,10 @@ impl ChainedBftProvider {
             initial_setup.author,
             initial_setup.quorum_size,
             initial_setup.signer,
+            initial_setup.vrf_keys,
             proposer,
             network,
+            trusted_network_peers,
             runtime,
             config,
             storage,
@@ -121,27 +142,76 @@ impl ChainedBftProvider {
         );
 
         let signer = ValidatorSigner::new(author, private_key);
-        let peers_with_public_keys = node_config.base.trusted_peers.get_trusted_consensus_peers();
-        let peers = Arc::new(
-            peers_with_public_keys
-                .keys()
-                .map(AccountAddress::clone)
-                .collect(),
-        );
-        let validator = Arc::new(ValidatorVerifier::new(peers_with_public_keys));
-        counters::EPOCH_NUM.set(0); // No reconfiguration yet, so it is always zero
+        // The VRF keys are only used by the VRF proposer election.
+        let vrf_keys = VrfKeys {
+            private_key: node_config
+                .base
+                .peer_keypairs
+                .take_vrf_private()
+                .map(|private_key| Arc::new(VRFPrivateKey::from(&private_key))),
+            public_keys: Arc::new(
+                node_config
+                    .base
+                    .trusted_peers
+                    .get_trusted_vrf_peers()
+                    .iter()
+                    .map(|(author, public_key)| {
+                        let public_key = VRFPublicKey::try_from(public_key)
+                            .unwrap_or_else(|e| panic!("Invalid VRF key of {}: {:?}", author, e));
+                        (*author, public_key)
+                    })
+                    .collect(),
+            ),
+        };
+        // Once the validator set is reconfigured, the validators of the current epoch are the
+        // ones on chain.
+        let validator = match Self::read_validator_set(node_config) {
+            Ok(Some(validator_set)) => ValidatorVerifier::from(&validator_set),
+            Ok(None) => {
+                ValidatorVerifier::new(node_config.base.trusted_peers.get_trusted_consensus_peers())
+            }
+            Err(e) => panic!("Failed to read the validator set from storage: {:?}", e),
+        };
+        let peers = Arc::new(validator.get_ordered_account_addresses());
+        let validator = Arc::new(validator);
         counters::CURRENT_EPOCH_NUM_VALIDATORS.set(validator.len() as i64);
         counters::CURRENT_EPOCH_QUORUM_SIZE.set(validator.quorum_size() as i64);
         debug!("[Consensus]: quorum_size = {:?}", validator.quorum_size());
         InitialSetup {
             author,
             signer,
+            vrf_keys,
             quorum_size: validator.quorum_size(),
             peers,
             validator,
         }
     }
 
+    /// Reads the validator set of the latest committed state, which is absent until the first
+    /// reconfiguration.
+    fn read_validator_set(node_config: &NodeConfig) -> Result<Option<ValidatorSet>> {
+        let (mut response_items, _, _) = create_storage_read_client(node_config)
+            .update_to_latest_ledger(
+                0,
+                vec![RequestItem::GetAccountState {
+                    address: VALIDATOR_SET_ACCESS_PATH.address,
+                }],
+            )?;
+        let blob = match response_items.pop() {
+            Some(ResponseItem::GetAccountState {
+                account_state_with_proof,
+            }) => account_state_with_proof.blob,
+            _ => bail!("Unexpected response to the account state request"),
+        };
+        match blob {
+            Some(blob) => BTreeMap::<Vec<u8>, Vec<u8>>::try_from(&blob)?
+                .get(&VALIDATOR_SET_ACCESS_PATH.path)
+                .map(|bytes| SimpleDeserializer::deserialize(bytes))
+                .transpose(),
+            None => Ok(None),
+        }
+    }
+
     /// Choose a proposer that is going to be the single leader (relevant for a mock fixed proposer
     /// election only).
     fn choose_leader(initial_setup: &InitialSetup) -> Author {
@@ -4,24 +4,18 @@
 use crate::{
     chained_bft::{
         block_storage::BlockStore,
-        common::{Payload, Round},
+        common::{Author, Payload, Round},
+        epoch_manager::EpochManager,
         event_processor::EventProcessor,
-        liveness::{
-            pacemaker::{ExponentialTimeInterval, Pacemaker},
-            pacemaker_timeout_manager::HighestTimeoutCertificates,
-            proposal_generator::ProposalGenerator,
-            proposer_election::ProposerElection,
-            rotating_proposer_election::RotatingProposer,
-        },
+        liveness::vrf_proposer_election::VrfKeys,
         network::{ConsensusNetworkImpl, NetworkReceivers},
-        persistent_storage::{PersistentLivenessStorage, PersistentStorage, RecoveryData},
-        safety::safety_rules::SafetyRules,
+        persistent_storage::{PersistentStorage, RecoveryData},
     },
-    counters,
     state_replication::{StateComputer, StateMachineReplication, TxnManager},
-    util::time_service::{ClockTimeService, TimeService},
+    util::time_service::ClockTimeService,
 };
 use channel;
+use config::config::{ConsensusConfig, ConsensusProposerType, SafetyRulesType};
 use crypto::ed25519::*;
 use failure::prelude::*;
 use futures::{
@@ -31,13 +25,15 @@ use futures::{
     select,
     stream::StreamExt,
 };
-use types::validator_signer::ValidatorSigner;
-
-use crate::chained_bft::common::Author;
-use config::config::ConsensusConfig;
 use logger::prelude::*;
-use std::{sync::Arc, time::Duration};
+use network::NetworkPublicKeys;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 use tokio::runtime::{Runtime, TaskExecutor};
+use types::{account_address::AccountAddress, validator_signer::ValidatorSigner};
 
 /// Consensus configuration derived from ConsensusConfig
 pub struct ChainedBftSMRConfig {
@@ -49,16 +45,33 @@ pub struct ChainedBftSMRConfig {
     pub contiguous_rounds: u32,
     /// Max block size (number of transactions) that consensus pulls from mempool
     pub max_block_size: u64,
+    /// How the proposers of a new epoch are chosen among its validators
+    pub proposer_type: ConsensusProposerType,
+    /// How long the voters wait for proposals after the first one of a round (VRF proposer only)
+    pub vrf_proposal_wait: Duration,
+    /// Whether the safety rules run in the consensus process or in their own process
+    pub safety_rules_type: SafetyRulesType,
+    /// The address of the safety rules process (process safety rules only)
+    pub safety_rules_address: String,
 }
 
 impl ChainedBftSMRConfig {
     pub fn from_node_config(cfg: &ConsensusConfig) -> ChainedBftSMRConfig {
         let pacemaker_initial_timeout_ms = cfg.pacemaker_initial_timeout_ms().unwrap_or(1000);
+        let vrf_proposal_wait_ms = cfg.vrf_proposal_wait_ms().unwrap_or(100);
         ChainedBftSMRConfig {
             max_pruned_blocks_in_mem: cfg.max_pruned_blocks_in_mem().unwrap_or(10000) as usize,
             pacemaker_initial_timeout: Duration::from_millis(pacemaker_initial_timeout_ms),
             contiguous_rounds: cfg.contiguous_rounds(),
             max_block_size: cfg.max_block_size(),
+            proposer_type: cfg.get_proposer_type(),
+            vrf_proposal_wait: Duration::from_millis(vrf_proposal_wait_ms),
+            safety_rules_type: cfg.get_safety_rules_type(),
+            safety_rules_address: format!(
+                "{}:{}",
+                cfg.safety_rules_address(),
+                cfg.safety_rules_port()
+            ),
         }
     }
 }
@@ -68,14 +81,16 @@ impl ChainedBftSMRConfig {
 /// ConsensusProvider for the e2e flow.
 pub struct ChainedBftSMR<T> {
     author: Author,
-    // TODO [Reconfiguration] quorum size is just a function of current validator set.
+    // The quorum size of the first epoch the SMR starts with.
     quorum_size: usize,
     signer: Option<ValidatorSigner<Ed25519PrivateKey>>,
+    vrf_keys: VrfKeys,
     proposers: Vec<Author>,
     runtime: Option<Runtime>,
     block_store: Option<Arc<BlockStore<T>>>,
     network: ConsensusNetworkImpl,
-    config: ChainedBftSMRConfig,
+    trusted_network_peers: Arc<RwLock<HashMap<AccountAddress, NetworkPublicKeys>>>,
+    config: Option<ChainedBftSMRConfig>,
     storage: Arc<dyn PersistentStorage<T>>,
     initial_data: Option<RecoveryData<T>>,
 }
@@ -85,8 +100,10 @@ impl<T: Payload> ChainedBftSMR<T> {
         author: Author,
         quorum_size: usize,
         signer: ValidatorSigner<Ed25519PrivateKey>,
+        vrf_keys: VrfKeys,
         proposers: Vec<Author>,
         network: ConsensusNetworkImpl,
+        trusted_network_peers: Arc<RwLock<HashMap<AccountAddress, NetworkPublicKeys>>>,
         runtime: Runtime,
         config: ChainedBftSMRConfig,
         storage: Arc<dyn PersistentStorage<T>>,
@@ -96,11 +113,13 @@ impl<T: Payload> ChainedBftSMR<T> {
             author,
             quorum_size,
             signer: Some(signer),
+            vrf_keys,
             proposers,
             runtime: Some(runtime),
             block_store: None,
             network,
-            config,
+            trusted_network_peers,
+            config: Some(config),
             storage,
             initial_data: Some(initial_data),
         }
@@ -111,50 +130,18 @@ impl<T: Payload> ChainedBftSMR<T> {
         self.block_store.clone()
     }
 
-    fn create_pacemaker(
-        &self,
-        persistent_liveness_storage: Box<dyn PersistentLivenessStorage>,
-        time_service: Arc<dyn TimeService>,
-        timeout_sender: channel::Sender<Round>,
-        highest_timeout_certificate: HighestTimeoutCertificates,
-    ) -> Pacemaker {
-        // 1.5^6 ~= 11
-        // Timeout goes from initial_timeout to initial_timeout*11 in 6 steps
-        let time_interval = Box::new(ExponentialTimeInterval::new(
-            self.config.pacemaker_initial_timeout,
-            1.5,
-            6,
-        ));
-        Pacemaker::new(
-            persistent_liveness_storage,
-            time_interval,
-            time_service,
-            timeout_sender,
-            self.quorum_size,
-            highest_timeout_certificate,
-        )
-    }
-
-    /// Create a proposer election handler based on proposers
-    fn create_proposer_election(&self) -> Arc<dyn ProposerElection<T> + Send + Sync> {
-        assert!(!self.proposers.is_empty());
-        Arc::new(RotatingProposer::new(
-            self.proposers.clone(),
-            self.config.contiguous_rounds,
-        ))
-    }
-
     fn start_event_processing(
-        &self,
         executor: TaskExecutor,
+        mut epoch_manager: EpochManager<T>,
         mut event_processor: EventProcessor<T>,
         mut network_receivers: NetworkReceivers<T>,
         mut pacemaker_timeout_sender_rx: channel::Receiver<Round>,
+        mut proposal_wait_rx: channel::Receiver<Round>,
     ) {
-        let quorum_size = self.quorum_size;
         let fut = async move {
             event_processor.start().await;
             loop {
+                let quorum_size = epoch_manager.quorum_size();
                 select! {
                     proposal_msg = network_receivers.proposals.select_next_some() => {
                         event_processor.process_proposal_msg(proposal_msg).await;
@@ -171,6 +158,9 @@ impl<T: Payload> ChainedBftSMR<T> {
                     local_timeout_round = pacemaker_timeout_sender_rx.select_next_some() => {
                         event_processor.process_local_timeout(local_timeout_round).await;
                     }
+                    proposal_wait_round = proposal_wait_rx.select_next_some() => {
+                        event_processor.process_proposal_wait_end(proposal_wait_round).await;
+                    }
                     sync_info_msg = network_receivers.sync_info_msgs.select_next_some() => {
                         event_processor.process_sync_info_msg(sync_info_msg.0, sync_info_msg.1).await;
                     }
@@ -178,6 +168,17 @@ impl<T: Payload> ChainedBftSMR<T> {
                         break;
                     }
                 }
+                if let Some(ledger_info) = event_processor.epoch_ending_ledger_info().cloned() {
+                    let (
+                        new_event_processor,
+                        new_pacemaker_timeout_sender_rx,
+                        new_proposal_wait_rx,
+                    ) = epoch_manager.start_new_epoch(ledger_info).await;
+                    event_processor = new_event_processor;
+                    pacemaker_timeout_sender_rx = new_pacemaker_timeout_sender_rx;
+                    proposal_wait_rx = new_proposal_wait_rx;
+                    event_processor.start().await;
+                }
             }
         };
         executor.spawn(fut.boxed().unit_error().compat());
@@ -207,8 +208,6 @@ impl<T: Payload> StateMachineReplication for ChainedBftSMR<T> {
             .initial_data
             .take()
             .expect("already started, initial data is None");
-        let consensus_state = initial_data.state();
-        let highest_timeout_certificates = initial_data.highest_timeout_certificates().clone();
         if initial_data.need_sync() {
             loop {
                 // make sure we sync to the root state in case we're not
@@ -233,59 +232,43 @@ impl<T: Payload> StateMachineReplication for ChainedBftSMR<T> {
         // the signer is only stored in the SMR to be provided here
         let opt_signer = std::mem::replace(&mut self.signer, None);
         if let Some(signer) = opt_signer {
-            let block_store = Arc::new(block_on(BlockStore::new(
-                Arc::clone(&self.storage),
-                initial_data,
-                signer,
-                Arc::clone(&state_computer),
-                true,
-                self.config.max_pruned_blocks_in_mem,
-            )));
-
-            self.block_store = Some(Arc::clone(&block_store));
-
-            // txn manager is required both by proposal generator (to pull the proposers)
-            // and by event processor (to update their status).
-            let proposal_generator = ProposalGenerator::new(
-                block_store.clone(),
-                Arc::clone(&txn_manager),
-                time_service.clone(),
-                self.config.max_block_size,
-                true,
-            );
-
-            let safety_rules = SafetyRules::new(consensus_state);
-
-            let (timeout_sender, timeout_receiver) =
-                channel::new(1_024, &counters::PENDING_PACEMAKER_TIMEOUTS);
-            let pacemaker = self.create_pacemaker(
-                self.storage.persistent_liveness_storage(),
-                time_service.clone(),
-                timeout_sender,
-                highest_timeout_certificates,
-            );
-
-            let proposer_election = self.create_proposer_election();
-            let event_processor = EventProcessor::new(
+            let mut epoch_manager = EpochManager::new(
                 self.author,
-                Arc::clone(&block_store),
-                pacemaker,
-                Arc::clone(&proposer_election),
-                proposal_generator,
-                safety_rules,
-                state_computer,
-                txn_manager,
+                self.config.take().expect("already started, config is None"),
+                time_service,
+                Arc::new(signer),
+                self.vrf_keys.clone(),
                 self.network.clone(),
+                Arc::clone(&self.trusted_network_peers),
+                txn_manager,
+                state_computer,
                 Arc::clone(&self.storage),
-                time_service.clone(),
-                true,
+                self.quorum_size,
             );
 
-            self.start_event_processing(
+            // The node may have stopped right after committing the block ending an epoch.
+            let root_ledger_info = initial_data.root_ledger_info();
+            let (event_processor, timeout_receiver, proposal_wait_receiver) = if root_ledger_info
+                .ledger_info()
+                .ledger_info()
+                .next_validator_set()
+                .is_some()
+                && !root_ledger_info.is_epoch_genesis()
+            {
+                block_on(epoch_manager.start_new_epoch(root_ledger_info.ledger_info().clone()))
+            } else {
+                block_on(epoch_manager.start_epoch(initial_data, self.proposers.clone()))
+            };
+
+            self.block_store = Some(event_processor.block_store());
+
+            Self::start_event_processing(
                 executor,
+                epoch_manager,
                 event_processor,
                 network_receivers,
                 timeout_receiver,
+                proposal_wait_receiver,
             );
         } else {
             panic!("start called twice on the same Chained BFT SMR!");
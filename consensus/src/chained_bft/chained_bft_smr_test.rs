@@ -7,6 +7,7 @@ use crate::{
         chained_bft_smr::{ChainedBftSMR, ChainedBftSMRConfig},
         common::Author,
         consensus_types::proposal_msg::ProposalMsg,
+        liveness::vrf_proposer_election::VrfKeys,
         network::ConsensusNetworkImpl,
         network_tests::NetworkPlayground,
         safety::vote_msg::VoteMsg,
@@ -15,18 +16,27 @@ use crate::{
     state_replication::StateMachineReplication,
 };
 use channel;
-use crypto::{ed25519::*, hash::CryptoHash};
+use crypto::{
+    ed25519::*,
+    hash::CryptoHash,
+    vrf::ecvrf::{VRFPrivateKey, VRFPublicKey},
+    Uniform,
+};
 use futures::{channel::mpsc, executor::block_on, prelude::*};
 use network::validator_network::{ConsensusNetworkEvents, ConsensusNetworkSender};
 use proto_conv::FromProto;
-use std::sync::Arc;
+use rand::{rngs::StdRng, SeedableRng};
+use std::sync::{Arc, RwLock};
 use types::{validator_signer::ValidatorSigner, validator_verifier::ValidatorVerifier};
 
 use crate::chained_bft::{
     persistent_storage::RecoveryData,
     test_utils::{consensus_runtime, with_smr_id},
 };
-use config::config::ConsensusProposerType::{self, FixedProposer, RotatingProposer};
+use config::config::{
+    ConsensusProposerType::{self, FixedProposer, LeaderReputation, RotatingProposer, VrfProposer},
+    SafetyRulesType,
+};
 use std::{collections::HashMap, time::Duration};
 use tokio::runtime;
 use types::ledger_info::LedgerInfoWithSignatures;
@@ -35,9 +45,11 @@ use types::ledger_info::LedgerInfoWithSignatures;
 struct SMRNode {
     author: Author,
     signer: ValidatorSigner<Ed25519PrivateKey>,
+    vrf_keys: VrfKeys,
     validator: Arc<ValidatorVerifier<Ed25519PublicKey>>,
     peers: Arc<Vec<Author>>,
     proposer: Vec<Author>,
+    proposer_type: ConsensusProposerType,
     smr_id: usize,
     smr: ChainedBftSMR<TestPayload>,
     commit_cb_receiver: mpsc::UnboundedReceiver<LedgerInfoWithSignatures<Ed25519Signature>>,
@@ -51,9 +63,11 @@ impl SMRNode {
         quorum_size: usize,
         playground: &mut NetworkPlayground,
         signer: ValidatorSigner<Ed25519PrivateKey>,
+        vrf_keys: VrfKeys,
         validator: Arc<ValidatorVerifier<Ed25519PublicKey>>,
         peers: Arc<Vec<Author>>,
         proposer: Vec<Author>,
+        proposer_type: ConsensusProposerType,
         smr_id: usize,
         storage: Arc<MockStorage<TestPayload>>,
         initial_data: RecoveryData<TestPayload>,
@@ -83,13 +97,19 @@ impl SMRNode {
             pacemaker_initial_timeout: Duration::from_secs(3),
             contiguous_rounds: 2,
             max_block_size: 50,
+            proposer_type,
+            vrf_proposal_wait: Duration::from_millis(500),
+            safety_rules_type: SafetyRulesType::Local,
+            safety_rules_address: String::new(),
         };
         let mut smr = ChainedBftSMR::new(
             author,
             quorum_size,
             signer.clone(),
+            vrf_keys.clone(),
             proposer.clone(),
             network,
+            Arc::new(RwLock::new(HashMap::new())),
             runtime,
             config,
             storage.clone(),
@@ -108,9 +128,11 @@ impl SMRNode {
         Self {
             author,
             signer,
+            vrf_keys,
             validator,
             peers,
             proposer,
+            proposer_type,
             smr_id,
             smr,
             commit_cb_receiver,
@@ -130,9 +152,11 @@ impl SMRNode {
             quorum_size,
             playground,
             self.signer,
+            self.vrf_keys,
             self.validator,
             self.peers,
             self.proposer,
+            self.proposer_type,
             self.smr_id + 10,
             self.storage,
             recover_data,
@@ -147,6 +171,8 @@ impl SMRNode {
     ) -> Vec<Self> {
         let mut signers = vec![];
         let mut author_to_public_keys = HashMap::new();
+        let mut vrf_private_keys = vec![];
+        let mut author_to_vrf_public_keys = HashMap::new();
         for smr_id in 0..num_nodes {
             // 0 -> [0000], 1 -> [1000] in the logs
             let random_validator_signer = ValidatorSigner::from_int(smr_id as u8);
@@ -154,8 +180,16 @@ impl SMRNode {
                 random_validator_signer.author(),
                 random_validator_signer.public_key(),
             );
+            let vrf_private_key =
+                VRFPrivateKey::generate_for_testing(&mut StdRng::from_seed([smr_id as u8; 32]));
+            author_to_vrf_public_keys.insert(
+                random_validator_signer.author(),
+                VRFPublicKey::from(&vrf_private_key),
+            );
+            vrf_private_keys.push(Arc::new(vrf_private_key));
             signers.push(random_validator_signer);
         }
+        let vrf_public_keys = Arc::new(author_to_vrf_public_keys);
         let validator_verifier = Arc::new(
             ValidatorVerifier::new_with_quorum_size(author_to_public_keys, quorum_size)
                 .expect("Invalid quorum_size."),
@@ -165,7 +199,9 @@ impl SMRNode {
         let proposer = {
             match proposer_type {
                 FixedProposer => vec![peers[0]],
-                RotatingProposer => validator_verifier.get_ordered_account_addresses(),
+                RotatingProposer | LeaderReputation | VrfProposer => {
+                    validator_verifier.get_ordered_account_addresses()
+                }
             }
         };
         let mut nodes = vec![];
@@ -175,9 +211,14 @@ impl SMRNode {
                 quorum_size,
                 playground,
                 signers.remove(0),
+                VrfKeys {
+                    private_key: Some(vrf_private_keys.remove(0)),
+                    public_keys: Arc::clone(&vrf_public_keys),
+                },
                 Arc::clone(&validator_verifier),
                 Arc::clone(&peers),
                 proposer.clone(),
+                proposer_type,
                 smr_id,
                 storage,
                 initial_data,
@@ -288,6 +329,63 @@ fn basic_full_round() {
     });
 }
 
+#[test]
+/// Same as basic_full_round with the proposers elected according to their reputation.
+fn leader_reputation_full_round() {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.executor());
+    let _nodes = SMRNode::start_num_nodes(2, 2, &mut playground, LeaderReputation);
+
+    block_on(async move {
+        let _broadcast_proposals_1 = playground
+            .wait_for_messages(1, NetworkPlayground::proposals_only)
+            .await;
+        let _votes_1 = playground
+            .wait_for_messages(1, NetworkPlayground::votes_only)
+            .await;
+        let mut broadcast_proposals_2 = playground
+            .wait_for_messages(1, NetworkPlayground::proposals_only)
+            .await;
+        let next_proposal =
+            ProposalMsg::<Vec<u64>>::from_proto(broadcast_proposals_2[0].1.take_proposal())
+                .unwrap();
+        assert_eq!(next_proposal.proposal.round(), 2);
+        assert_eq!(next_proposal.proposal.height(), 2);
+    });
+}
+
+#[test]
+/// With the VRF proposer, all the participants propose and then vote for the same proposal, which
+/// is certified and extended by the next proposals.
+fn vrf_proposer_full_round() {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.executor());
+    let _nodes = SMRNode::start_num_nodes(2, 2, &mut playground, VrfProposer);
+
+    block_on(async move {
+        let _broadcast_proposals_1 = playground
+            .wait_for_messages(2, NetworkPlayground::proposals_only)
+            .await;
+        let votes: Vec<VoteMsg> = playground
+            .wait_for_messages(2, NetworkPlayground::votes_only)
+            .await
+            .into_iter()
+            .map(|(_, mut msg)| VoteMsg::from_proto(msg.take_vote()).unwrap())
+            .collect();
+        let chosen_block_id = votes[0].proposed_block_id();
+        assert_eq!(votes[1].proposed_block_id(), chosen_block_id);
+        let broadcast_proposals_2 = playground
+            .wait_for_messages(2, NetworkPlayground::proposals_only)
+            .await;
+        for (_, mut msg) in broadcast_proposals_2 {
+            let next_proposal = ProposalMsg::<Vec<u64>>::from_proto(msg.take_proposal()).unwrap();
+            assert!(next_proposal.vrf_proof.is_some());
+            assert_eq!(next_proposal.proposal.round(), 2);
+            assert_eq!(next_proposal.proposal.parent_id(), chosen_block_id);
+        }
+    });
+}
+
 /// Verify the basic e2e flow: blocks are committed, txn manager is notified, block tree is
 /// pruned, restart the node and we can still continue.
 #[test]
@@ -145,6 +145,7 @@ where
                     HashValue::zero(),
                     0,
                     0,
+                    None,
                 ),
                 HashMap::new(),
             ),
@@ -173,6 +174,34 @@ where
         }
     }
 
+    /// Makes the genesis block of a new epoch from the ledger info ending the previous one: it
+    /// is the block committed by this ledger info, which is the root the new epoch builds on.
+    pub fn make_epoch_genesis_block(
+        ledger_info: LedgerInfoWithSignatures<Ed25519Signature>,
+    ) -> Self {
+        let genesis_validator_signer = ValidatorSigner::<Ed25519PrivateKey>::genesis();
+        let timestamp_usecs = ledger_info.ledger_info().timestamp_usecs();
+        let quorum_cert = QuorumCert::certificate_for_epoch_genesis(ledger_info);
+        let genesis_id = quorum_cert.certified_block_id();
+        let signature = genesis_validator_signer
+            .sign_message(genesis_id)
+            .expect("Failed to sign genesis id.");
+
+        Block {
+            id: genesis_id,
+            payload: T::default(),
+            parent_id: HashValue::zero(),
+            round: 0,
+            height: 0,
+            timestamp_usecs,
+            quorum_cert,
+            block_source: BlockSource::Proposal {
+                author: genesis_validator_signer.author(),
+                signature,
+            },
+        }
+    }
+
     // Create a block directly.  Most users should prefer make_block() as it ensures correct block
     // chaining.  This functionality should typically only be used for testing.
     pub fn new_internal(
@@ -184,6 +213,34 @@ where
         quorum_cert: QuorumCert,
         validator_signer: &ValidatorSigner<Ed25519PrivateKey>,
     ) -> Self {
+        Self::new_with_signer(
+            payload,
+            parent_id,
+            round,
+            height,
+            timestamp_usecs,
+            quorum_cert,
+            validator_signer.author(),
+            |hash| validator_signer.sign_message(hash),
+        )
+        .expect("Failed to sign message")
+    }
+
+    /// Same as `new_internal`, with the block signed for the given author by the given function
+    /// (e.g., by the safety rules, which own the consensus key).
+    pub fn new_with_signer<F>(
+        payload: T,
+        parent_id: HashValue,
+        round: Round,
+        height: Height,
+        timestamp_usecs: u64,
+        quorum_cert: QuorumCert,
+        author: Author,
+        sign: F,
+    ) -> Result<Self>
+    where
+        F: FnOnce(HashValue) -> Result<Ed25519Signature>,
+    {
         let block_internal = BlockSerializer {
             parent_id,
             payload: &payload,
@@ -191,15 +248,13 @@ where
             height,
             timestamp_usecs,
             quorum_cert: &quorum_cert,
-            author: Some(validator_signer.author()),
+            author: Some(author),
         };
 
         let id = block_internal.hash();
-        let signature = validator_signer
-            .sign_message(id)
-            .expect("Failed to sign message");
+        let signature = sign(id)?;
 
-        Block {
+        Ok(Block {
             id,
             payload,
             parent_id,
@@ -207,11 +262,26 @@ where
             height,
             timestamp_usecs,
             quorum_cert,
-            block_source: BlockSource::Proposal {
-                author: validator_signer.author(),
-                signature,
-            },
-        }
+            block_source: BlockSource::Proposal { author, signature },
+        })
+    }
+
+    /// Returns the proposal of the given author with the content of this block, signed by the
+    /// given function.
+    pub fn sign_proposal<F>(self, author: Author, sign: F) -> Result<Self>
+    where
+        F: FnOnce(HashValue) -> Result<Ed25519Signature>,
+    {
+        Self::new_with_signer(
+            self.payload,
+            self.parent_id,
+            self.round,
+            self.height,
+            self.timestamp_usecs,
+            self.quorum_cert,
+            author,
+            sign,
+        )
     }
 
     pub fn make_block(
@@ -291,8 +361,16 @@ where
     pub fn verify(
         &self,
         validator: &ValidatorVerifier<Ed25519PublicKey>,
+        epoch_genesis: Option<&LedgerInfo>,
     ) -> ::std::result::Result<(), BlockVerificationError> {
         if self.is_genesis_block() {
+            // The genesis of an epoch is only valid if its QuorumCert is.
+            if self.quorum_cert.is_epoch_genesis() {
+                return self
+                    .quorum_cert
+                    .verify(validator, epoch_genesis)
+                    .map_err(BlockVerificationError::QCVerificationError);
+            }
             return Ok(());
         }
         if self.id() != self.hash() {
@@ -315,7 +393,7 @@ where
             return Err(BlockVerificationError::NilBlockWithPayload);
         }
         self.quorum_cert
-            .verify(validator)
+            .verify(validator, epoch_genesis)
             .map_err(BlockVerificationError::QCVerificationError)
     }
 
@@ -372,12 +450,14 @@ where
     }
 
     pub fn is_genesis_block(&self) -> bool {
-        self.id() == *GENESIS_BLOCK_ID
+        let is_first_genesis = self.id() == *GENESIS_BLOCK_ID && self.timestamp_usecs == 0;
+        let is_epoch_genesis = self.quorum_cert.is_epoch_genesis()
+            && self.quorum_cert.certified_block_id() == self.id();
+        (is_first_genesis || is_epoch_genesis)
             && self.payload == T::default()
             && self.parent_id == HashValue::zero()
             && self.round == 0
             && self.height == 0
-            && self.timestamp_usecs == 0
     }
 
     pub fn is_nil_block(&self) -> bool {
@@ -12,6 +12,7 @@ use crate::chained_bft::{
 
 use crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    hash::CryptoHash,
     HashValue,
 };
 use proptest::{prelude::*, std_facade::hash_map::HashMap};
@@ -22,7 +23,12 @@ use std::{
 };
 #[cfg(test)]
 use types::validator_signer::proptests;
-use types::{validator_signer::ValidatorSigner, validator_verifier::ValidatorVerifier};
+use types::{
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_set::ValidatorSet,
+    validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
+};
 
 type LinearizedBlockForest<T> = Vec<Block<T>>;
 
@@ -198,6 +204,61 @@ fn test_genesis() {
     assert!(genesis_block.is_genesis_block());
 }
 
+#[test]
+fn test_epoch_genesis_verification() {
+    let signer = ValidatorSigner::random(None);
+    let verifier = ValidatorVerifier::new_single(signer.author(), signer.public_key());
+    let make_ledger_info = || {
+        LedgerInfo::new(
+            10,
+            HashValue::random(),
+            HashValue::random(),
+            HashValue::random(),
+            3,
+            100,
+            Some(ValidatorSet::new(vec![])),
+        )
+    };
+
+    // The genesis of the current epoch is signed by the validators of the previous one.
+    let epoch_genesis = make_ledger_info();
+    let genesis_block = Block::<i64>::make_epoch_genesis_block(LedgerInfoWithSignatures::new(
+        epoch_genesis.clone(),
+        HashMap::new(),
+    ));
+    assert!(genesis_block
+        .verify(&verifier, Some(&epoch_genesis))
+        .is_ok());
+    assert!(genesis_block.verify(&verifier, None).is_err());
+    let forged_block = Block::<i64>::make_epoch_genesis_block(LedgerInfoWithSignatures::new(
+        make_ledger_info(),
+        HashMap::new(),
+    ));
+    assert!(forged_block
+        .verify(&verifier, Some(&epoch_genesis))
+        .is_err());
+    assert!(forged_block
+        .quorum_cert()
+        .verify(&verifier, Some(&epoch_genesis))
+        .is_err());
+
+    // The genesis of the next epoch is signed by the validators of the current one.
+    let next_epoch_genesis = make_ledger_info();
+    let mut signatures = HashMap::new();
+    signatures.insert(
+        signer.author(),
+        signer.sign_message(next_epoch_genesis.hash()).unwrap(),
+    );
+    let next_genesis_block = Block::<i64>::make_epoch_genesis_block(LedgerInfoWithSignatures::new(
+        next_epoch_genesis,
+        signatures,
+    ));
+    assert!(next_genesis_block
+        .quorum_cert()
+        .verify(&verifier, Some(&epoch_genesis))
+        .is_ok());
+}
+
 #[test]
 fn test_nil_block() {
     let genesis_block = Block::make_genesis_block();
@@ -217,7 +278,7 @@ fn test_nil_block() {
     assert!(nil_block.author().is_none());
 
     let dummy_verifier = Arc::new(ValidatorVerifier::<Ed25519PublicKey>::new(HashMap::new()));
-    assert!(nil_block.verify(dummy_verifier.as_ref()).is_ok());
+    assert!(nil_block.verify(dummy_verifier.as_ref(), None).is_ok());
 
     let signer = ValidatorSigner::random(None);
     let payload = 101;
@@ -5,12 +5,12 @@ use crate::chained_bft::{
     common::{Author, Payload},
     consensus_types::{block::Block, sync_info::SyncInfo},
 };
-use crypto::ed25519::*;
+use crypto::{ed25519::*, vrf::ecvrf::Proof};
 use failure::prelude::*;
 use network::proto::Proposal as ProtoProposal;
 use proto_conv::{FromProto, IntoProto};
-use std::fmt;
-use types::validator_verifier::ValidatorVerifier;
+use std::{convert::TryFrom, fmt};
+use types::{ledger_info::LedgerInfo, validator_verifier::ValidatorVerifier};
 
 /// ProposalMsg contains the required information for the proposer election protocol to make its
 /// choice (typically depends on round and proposer info).
@@ -18,15 +18,22 @@ use types::validator_verifier::ValidatorVerifier;
 pub struct ProposalMsg<T> {
     pub proposal: Block<T>,
     pub sync_info: SyncInfo,
+    /// The VRF proof that the author may propose the block, for the proposer elections based on
+    /// a VRF (see `VrfProposer`).
+    pub vrf_proof: Option<Proof>,
 }
 
 impl<T: Payload> ProposalMsg<T> {
-    pub fn verify(&self, validator: &ValidatorVerifier<Ed25519PublicKey>) -> Result<()> {
+    pub fn verify(
+        &self,
+        validator: &ValidatorVerifier<Ed25519PublicKey>,
+        epoch_genesis: Option<&LedgerInfo>,
+    ) -> Result<()> {
         if self.proposal.is_nil_block() {
             return Err(format_err!("Proposal {} for a NIL block", self.proposal));
         }
         self.proposal
-            .verify(validator)
+            .verify(validator, epoch_genesis)
             .map_err(|e| format_err!("{:?}", e))?;
         ensure!(
             self.proposal.round() > 0,
@@ -70,7 +77,7 @@ impl<T: Payload> ProposalMsg<T> {
         }
         self.sync_info
             .highest_ledger_info()
-            .verify(validator)
+            .verify(validator, epoch_genesis)
             .map_err(|e| format_err!("{:?}", e))?;
 
         Ok(())
@@ -100,6 +107,9 @@ impl<T: Payload> IntoProto for ProposalMsg<T> {
         let mut proto = Self::ProtoType::new();
         proto.set_proposed_block(self.proposal.into_proto());
         proto.set_sync_info(self.sync_info.into_proto());
+        if let Some(vrf_proof) = self.vrf_proof {
+            proto.set_vrf_proof(vrf_proof.to_bytes().to_vec());
+        }
         proto
     }
 }
@@ -110,9 +120,15 @@ impl<T: Payload> FromProto for ProposalMsg<T> {
     fn from_proto(mut object: Self::ProtoType) -> Result<Self> {
         let proposal = Block::<T>::from_proto(object.take_proposed_block())?;
         let sync_info = SyncInfo::from_proto(object.take_sync_info())?;
+        let vrf_proof = if object.get_vrf_proof().is_empty() {
+            None
+        } else {
+            Some(Proof::try_from(object.get_vrf_proof())?)
+        };
         Ok(ProposalMsg {
             proposal,
             sync_info,
+            vrf_proof,
         })
     }
 }
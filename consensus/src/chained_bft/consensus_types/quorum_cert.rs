@@ -144,6 +144,7 @@ impl QuorumCert {
             *GENESIS_BLOCK_ID,
             0,
             0,
+            None,
         );
         let signature = signer
             .sign_message(li.hash())
@@ -162,10 +163,68 @@ impl QuorumCert {
         )
     }
 
+    /// QuorumCert for the genesis block of a new epoch, which is the block committed by the
+    /// ledger info ending the previous epoch:
+    /// - the certified block, its parent and grandparent are the committed block at round 0.
+    /// - the executed state is the one of the ledger info.
+    /// - the ledger info is the one ending the previous epoch, signed by its validators.
+    pub fn certificate_for_epoch_genesis(
+        ledger_info: LedgerInfoWithSignatures<Ed25519Signature>,
+    ) -> QuorumCert {
+        let block_id = ledger_info.ledger_info().consensus_block_id();
+        let state = ExecutedState {
+            state_id: ledger_info.ledger_info().transaction_accumulator_hash(),
+            version: ledger_info.ledger_info().version(),
+        };
+        QuorumCert::new(block_id, state, 0, ledger_info, block_id, 0, block_id, 0)
+    }
+
+    /// Whether this is the QuorumCert for the genesis block of an epoch other than the first one
+    /// (see `certificate_for_epoch_genesis`).
+    pub fn is_epoch_genesis(&self) -> bool {
+        let ledger_info = self.ledger_info().ledger_info();
+        self.certified_block_round == 0
+            && ledger_info.next_validator_set().is_some()
+            && ledger_info.consensus_block_id() == self.certified_block_id
+            && ledger_info.transaction_accumulator_hash() == self.certified_state.state_id
+            && ledger_info.version() == self.certified_state.version
+    }
+
+    /// The epoch of the certified block: the genesis block of an epoch is certified by the
+    /// ledger info ending the previous one.
+    pub fn certified_block_epoch(&self) -> u64 {
+        let epoch_num = self.ledger_info().ledger_info().epoch_num();
+        if self.is_epoch_genesis() {
+            epoch_num + 1
+        } else {
+            epoch_num
+        }
+    }
+
+    /// Verifies the QuorumCert with the validators of the current epoch. `epoch_genesis` is the
+    /// ledger info ending the previous epoch, if this validator started the current epoch from it.
     pub fn verify(
         &self,
         validator: &ValidatorVerifier<Ed25519PublicKey>,
+        epoch_genesis: Option<&LedgerInfo>,
     ) -> ::std::result::Result<(), VoteMsgVerificationError> {
+        // The genesis of an epoch is agreed upon by the validators of the previous epoch: its
+        // ledger info is signed by them and doesn't carry the digest of a vote for this block.
+        if self.is_epoch_genesis() {
+            if *self != QuorumCert::certificate_for_epoch_genesis(self.ledger_info().clone()) {
+                return Err(VoteMsgVerificationError::ConsensusDataMismatch);
+            }
+            // The genesis of the current epoch was verified when this validator started from it.
+            if epoch_genesis == Some(self.ledger_info().ledger_info()) {
+                return Ok(());
+            }
+            // Otherwise, only the genesis of the next epoch can be verified: its ledger info
+            // ends the current epoch.
+            return self
+                .ledger_info()
+                .verify(validator)
+                .map_err(VoteMsgVerificationError::SigVerifyError);
+        }
         let vote_hash = VoteMsg::vote_digest(
             self.certified_block_id,
             self.certified_state,
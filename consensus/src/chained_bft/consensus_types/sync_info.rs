@@ -11,7 +11,7 @@ use crate::chained_bft::{
 use proto_conv::{FromProto, IntoProto};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
-use types::validator_verifier::ValidatorVerifier;
+use types::{ledger_info::LedgerInfo, validator_verifier::ValidatorVerifier};
 
 #[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
 /// This struct describes basic synchronization metadata.
@@ -109,9 +109,10 @@ impl SyncInfo {
     pub fn verify(
         &self,
         validator: &ValidatorVerifier<Ed25519PublicKey>,
+        epoch_genesis: Option<&LedgerInfo>,
     ) -> Result<(), SyncInfoVerificationError> {
-        self.highest_quorum_cert.verify(validator)?;
-        self.highest_ledger_info.verify(validator)?;
+        self.highest_quorum_cert.verify(validator, epoch_genesis)?;
+        self.highest_ledger_info.verify(validator, epoch_genesis)?;
         if let Some(tc) = &self.highest_timeout_cert {
             tc.verify(validator)?;
         }
@@ -81,17 +81,30 @@ impl PacemakerTimeout {
         validator_signer: &ValidatorSigner<Ed25519PrivateKey>,
         vote: Option<VoteMsg>,
     ) -> Self {
-        let author = validator_signer.author();
-        let digest = PacemakerTimeoutSerializer { round, author }.hash();
-        let signature = validator_signer
-            .sign_message(digest)
-            .expect("Failed to sign PacemakerTimeout");
-        PacemakerTimeout {
+        Self::new_with_signer(round, validator_signer.author(), vote, |hash| {
+            validator_signer.sign_message(hash)
+        })
+        .expect("Failed to sign PacemakerTimeout")
+    }
+
+    /// Same as `new`, with the timeout signed for the given author by the given function (e.g.,
+    /// by the safety rules, which own the consensus key).
+    pub fn new_with_signer<F>(
+        round: Round,
+        author: Author,
+        vote: Option<VoteMsg>,
+        sign: F,
+    ) -> failure::Result<Self>
+    where
+        F: FnOnce(HashValue) -> failure::Result<Ed25519Signature>,
+    {
+        let signature = sign(Self::pacemaker_timeout_digest(author, round))?;
+        Ok(PacemakerTimeout {
             round,
             author,
             signature,
             vote,
-        }
+        })
     }
 
     fn pacemaker_timeout_digest(author: AccountAddress, round: Round) -> HashValue {
@@ -220,15 +233,28 @@ impl TimeoutMsg {
         pacemaker_timeout: PacemakerTimeout,
         validator_signer: &ValidatorSigner<Ed25519PrivateKey>,
     ) -> TimeoutMsg {
-        let digest = Self::new_round_digest(pacemaker_timeout.digest());
-        let signature = validator_signer
-            .sign_message(digest)
-            .expect("Failed to sign PacemakerTimeoutMsg");
-        TimeoutMsg {
+        Self::new_with_signer(sync_info, pacemaker_timeout, |hash| {
+            validator_signer.sign_message(hash)
+        })
+        .expect("Failed to sign PacemakerTimeoutMsg")
+    }
+
+    /// Same as `new`, with the message signed by the given function (e.g., by the safety rules,
+    /// which own the consensus key).
+    pub fn new_with_signer<F>(
+        sync_info: SyncInfo,
+        pacemaker_timeout: PacemakerTimeout,
+        sign: F,
+    ) -> failure::Result<TimeoutMsg>
+    where
+        F: FnOnce(HashValue) -> failure::Result<Ed25519Signature>,
+    {
+        let signature = sign(Self::new_round_digest(pacemaker_timeout.digest()))?;
+        Ok(TimeoutMsg {
             sync_info,
             pacemaker_timeout,
             signature,
-        }
+        })
     }
 
     fn new_round_digest(pacemaker_timeout_digest: HashValue) -> HashValue {
@@ -3,6 +3,10 @@
 
 use super::*;
 use tempfile::tempdir;
+use types::{
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_set::ValidatorSet,
+};
 
 #[test]
 fn test_put_get() {
@@ -53,3 +57,49 @@ fn test_delete_block_and_qc() {
     assert_eq!(db.get_blocks::<i64>().unwrap().len(), 0);
     assert_eq!(db.get_quorum_certificates().unwrap().len(), 0);
 }
+
+#[test]
+fn test_reset() {
+    let tmp_dir = tempdir().unwrap();
+    let db = ConsensusDB::new(&tmp_dir);
+
+    let block = Block::<i64>::make_genesis_block();
+    let qc = QuorumCert::certificate_for_genesis();
+    db.save_blocks_and_quorum_certificates(vec![block], vec![qc])
+        .unwrap();
+    db.save_state(vec![0x01, 0x02, 0x03]).unwrap();
+
+    let epoch_ending_ledger_info = LedgerInfoWithSignatures::new(
+        LedgerInfo::new(
+            1,
+            HashValue::random(),
+            HashValue::random(),
+            HashValue::random(),
+            0,
+            1,
+            Some(ValidatorSet::new(vec![])),
+        ),
+        HashMap::new(),
+    );
+    let new_block = Block::<i64>::make_epoch_genesis_block(epoch_ending_ledger_info);
+    let new_qc = new_block.quorum_cert().clone();
+    db.reset(
+        vec![new_block.clone()],
+        vec![new_qc.clone()],
+        vec![0x04],
+        vec![0x05],
+    )
+    .unwrap();
+
+    let blocks = db.get_blocks::<i64>().unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks.get(&new_block.id()), Some(&new_block));
+    let qcs = db.get_quorum_certificates().unwrap();
+    assert_eq!(qcs.len(), 1);
+    assert_eq!(qcs.get(&new_qc.certified_block_id()), Some(&new_qc));
+    assert_eq!(db.get_state().unwrap(), Some(vec![0x04]));
+    assert_eq!(
+        db.get_highest_timeout_certificates().unwrap(),
+        Some(vec![0x05])
+    );
+}
@@ -0,0 +1,150 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides [`ConsensusDBInspector`], which reads the data of a ConsensusDB in order to
+//! look into it when a validator is wedged, and resets it to its committed root as a last resort.
+//! It backs the `consensusdb-tool` binary.
+
+use crate::chained_bft::{
+    common::{Payload, Round},
+    consensus_types::{block::Block, quorum_cert::QuorumCert},
+    consensusdb::{ConsensusDB, CONSENSUSDB_DIR_NAME},
+    liveness::pacemaker_timeout_manager::HighestTimeoutCertificates,
+    persistent_storage::RecoveryData,
+    safety::safety_rules::ConsensusState,
+};
+use crypto::HashValue;
+use failure::prelude::*;
+use rmp_serde::{from_slice, to_vec_named};
+use std::{marker::PhantomData, path::Path};
+
+#[cfg(test)]
+#[path = "inspector_test.rs"]
+mod inspector_test;
+
+/// The root a validator starts its consensus from, as found by
+/// [`ConsensusDBInspector::check_root`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RootCheck {
+    /// The id of the root block.
+    pub root_id: HashValue,
+    /// The round of the root block.
+    pub root_round: Round,
+    /// Whether the root is the block committed by the latest ledger info of LibraDB. If not, the
+    /// validator synchronizes its state up to the root on start.
+    pub matches_storage: bool,
+    /// The blocks that don't descend from the root, which the validator prunes on start.
+    pub blocks_to_prune: Vec<HashValue>,
+}
+
+/// Reads the blocks, the quorum certificates and the single entries of a ConsensusDB with the
+/// given payload type.
+pub struct ConsensusDBInspector<T> {
+    db: ConsensusDB,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Payload> ConsensusDBInspector<T> {
+    /// Opens the ConsensusDB under `db_root_path`, which must exist, read-only. It can be in use by
+    /// a running node, in which case it is read as of the time it is opened.
+    pub fn open<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        Ok(Self::new(ConsensusDB::open_readonly(db_root_path)?))
+    }
+
+    /// Opens the ConsensusDB under `db_root_path` for writes, as required by
+    /// [`reset_to_committed_root`](ConsensusDBInspector::reset_to_committed_root). It must exist,
+    /// and it can't be in use by a running node.
+    pub fn open_for_repair<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        let path = db_root_path.as_ref().join(CONSENSUSDB_DIR_NAME);
+        ensure!(path.is_dir(), "No ConsensusDB at {:?}.", path);
+        Ok(Self::new(ConsensusDB::open(db_root_path)?))
+    }
+
+    fn new(db: ConsensusDB) -> Self {
+        Self {
+            db,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns all the blocks, ordered by round: the parent of a block comes before it.
+    pub fn get_blocks(&self) -> Result<Vec<Block<T>>> {
+        let mut blocks: Vec<_> = self.db.get_blocks()?.into_iter().map(|(_, b)| b).collect();
+        blocks.sort_by_key(Block::round);
+        Ok(blocks)
+    }
+
+    /// Returns all the quorum certificates, ordered by the round of the block they certify.
+    pub fn get_quorum_certs(&self) -> Result<Vec<QuorumCert>> {
+        let mut quorum_certs: Vec<_> = self
+            .db
+            .get_quorum_certificates()?
+            .into_iter()
+            .map(|(_, qc)| qc)
+            .collect();
+        quorum_certs.sort_by_key(QuorumCert::certified_block_round);
+        Ok(quorum_certs)
+    }
+
+    /// Returns the persisted consensus state, if any.
+    pub fn get_consensus_state(&self) -> Result<Option<ConsensusState>> {
+        match self.db.get_state()? {
+            Some(state) => Ok(Some(from_slice(&state)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the persisted highest timeout certificates, if any.
+    pub fn get_highest_timeout_certificates(&self) -> Result<Option<HighestTimeoutCertificates>> {
+        match self.db.get_highest_timeout_certificates()? {
+            Some(certificates) => Ok(Some(from_slice(&certificates)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Finds the root the validator would start from given the block committed by the latest
+    /// ledger info of LibraDB, the same way it does on start. Fails if the data is inconsistent,
+    /// e.g., if no quorum certificate commits a block of the tree.
+    pub fn check_root(&self, root_from_storage: HashValue) -> Result<RootCheck> {
+        Ok(self.recover(root_from_storage)?.0)
+    }
+
+    /// Replaces the blocks and the quorum certificates by the root found by
+    /// [`check_root`](ConsensusDBInspector::check_root), along with its quorum certificate and
+    /// the one committing it, and clears the highest timeout certificates. The consensus state is
+    /// kept, so that the validator never votes again at a round it voted at. The validator
+    /// retrieves the blocks above the root from its peers once it restarts.
+    pub fn reset_to_committed_root(&self, root_from_storage: HashValue) -> Result<RootCheck> {
+        let (root_check, recovery_data) = self.recover(root_from_storage)?;
+        let state = recovery_data.state();
+        let ((root_block, root_quorum_cert, root_ledger_info), _, _) = recovery_data.take();
+        self.db.reset(
+            vec![root_block],
+            vec![root_quorum_cert, root_ledger_info],
+            to_vec_named(&state)?,
+            to_vec_named(&HighestTimeoutCertificates::default())?,
+        )?;
+        Ok(root_check)
+    }
+
+    fn recover(&self, root_from_storage: HashValue) -> Result<(RootCheck, RecoveryData<T>)> {
+        let blocks = self.get_blocks()?;
+        ensure!(!blocks.is_empty(), "The ConsensusDB holds no block.");
+        let mut recovery_data = RecoveryData::new(
+            self.get_consensus_state()?.unwrap_or_default(),
+            blocks,
+            self.get_quorum_certs()?,
+            root_from_storage,
+            self.get_highest_timeout_certificates()?.unwrap_or_default(),
+        )?;
+        let blocks_to_prune = recovery_data.take_blocks_to_prune();
+        let root = recovery_data.root_block();
+        let root_check = RootCheck {
+            root_id: root.id(),
+            root_round: root.round(),
+            matches_storage: !recovery_data.need_sync(),
+            blocks_to_prune,
+        };
+        Ok((root_check, recovery_data))
+    }
+}
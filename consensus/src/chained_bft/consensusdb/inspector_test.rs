@@ -0,0 +1,97 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::chained_bft::{
+    block_storage::BlockReader,
+    test_utils::{build_empty_tree, TestPayload, TreeInserter},
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_inspect_and_reset() {
+    let tmp_dir = tempdir().unwrap();
+    let block_tree = build_empty_tree();
+    let mut inserter = TreeInserter::new(block_tree.clone());
+
+    // build a tree of the following form:
+    // genesis---a1---a2
+    //         \
+    //          ---------b3
+    let genesis = block_tree.root();
+    let a1 = inserter.insert_block(genesis.as_ref(), 1);
+    let a2 = inserter.insert_block(a1.as_ref(), 2);
+    let b3 = inserter.insert_block(genesis.as_ref(), 3);
+    let genesis_qc = QuorumCert::certificate_for_genesis();
+    let state = ConsensusState::new(3, 0);
+
+    let db = ConsensusDB::new(&tmp_dir);
+    db.save_blocks_and_quorum_certificates(
+        vec![
+            genesis.as_ref().clone(),
+            a1.as_ref().clone(),
+            a2.as_ref().clone(),
+            b3.as_ref().clone(),
+        ],
+        vec![genesis_qc.clone(), a2.quorum_cert().clone()],
+    )
+    .unwrap();
+    db.save_state(to_vec_named(&state).unwrap()).unwrap();
+
+    // The ConsensusDB can be inspected while it is in use.
+    let inspector = ConsensusDBInspector::<TestPayload>::open(&tmp_dir).unwrap();
+    let rounds: Vec<_> = inspector
+        .get_blocks()
+        .unwrap()
+        .iter()
+        .map(Block::round)
+        .collect();
+    assert_eq!(rounds, vec![0, 1, 2, 3]);
+    assert_eq!(
+        inspector.get_quorum_certs().unwrap(),
+        vec![genesis_qc.clone(), a2.quorum_cert().clone()]
+    );
+    assert_eq!(
+        inspector.get_consensus_state().unwrap(),
+        Some(state.clone())
+    );
+    assert!(inspector
+        .get_highest_timeout_certificates()
+        .unwrap()
+        .is_none());
+
+    // Only the genesis block is committed.
+    let root_check = RootCheck {
+        root_id: genesis.id(),
+        root_round: 0,
+        matches_storage: true,
+        blocks_to_prune: vec![],
+    };
+    assert_eq!(inspector.check_root(genesis.id()).unwrap(), root_check);
+    assert_eq!(
+        inspector.check_root(a1.id()).unwrap(),
+        RootCheck {
+            matches_storage: false,
+            ..root_check.clone()
+        }
+    );
+    assert!(inspector.reset_to_committed_root(genesis.id()).is_err());
+
+    drop(inspector);
+    drop(db);
+    let inspector = ConsensusDBInspector::<TestPayload>::open_for_repair(&tmp_dir).unwrap();
+    assert_eq!(
+        inspector.reset_to_committed_root(genesis.id()).unwrap(),
+        root_check
+    );
+    assert_eq!(
+        inspector.get_blocks().unwrap(),
+        vec![genesis.as_ref().clone()]
+    );
+    assert_eq!(inspector.get_quorum_certs().unwrap(), vec![genesis_qc]);
+    assert_eq!(inspector.get_consensus_state().unwrap(), Some(state));
+    assert!(inspector
+        .get_highest_timeout_certificates()
+        .unwrap()
+        .is_some());
+}
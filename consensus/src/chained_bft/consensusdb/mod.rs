@@ -3,6 +3,7 @@
 
 #[cfg(test)]
 mod consensusdb_test;
+pub mod inspector;
 mod schema;
 
 use crate::chained_bft::{
@@ -26,30 +27,40 @@ use std::{collections::HashMap, iter::Iterator, path::Path, time::Instant};
 type HighestTimeoutCertificates = Vec<u8>;
 type ConsensusStateData = Vec<u8>;
 
+/// The name of the ConsensusDB directory, under the storage directory of the node.
+const CONSENSUSDB_DIR_NAME: &str = "consensusdb";
+
+fn column_families() -> ColumnFamilyOptionsMap {
+    [
+        (
+            /* UNUSED CF = */ DEFAULT_CF_NAME,
+            ColumnFamilyOptions::default(),
+        ),
+        (BLOCK_CF_NAME, ColumnFamilyOptions::default()),
+        (QC_CF_NAME, ColumnFamilyOptions::default()),
+        (SINGLE_ENTRY_CF_NAME, ColumnFamilyOptions::default()),
+    ]
+    .iter()
+    .cloned()
+    .collect()
+}
+
 pub struct ConsensusDB {
     db: DB,
 }
 
 impl ConsensusDB {
     pub fn new<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
-        let cf_opts_map: ColumnFamilyOptionsMap = [
-            (
-                /* UNUSED CF = */ DEFAULT_CF_NAME,
-                ColumnFamilyOptions::default(),
-            ),
-            (BLOCK_CF_NAME, ColumnFamilyOptions::default()),
-            (QC_CF_NAME, ColumnFamilyOptions::default()),
-            (SINGLE_ENTRY_CF_NAME, ColumnFamilyOptions::default()),
-        ]
-        .iter()
-        .cloned()
-        .collect();
-
-        let path = db_root_path.as_ref().join("consensusdb");
-        let instant = Instant::now();
-        let db = DB::open(path.clone(), cf_opts_map).unwrap_or_else(|e| {
+        Self::open(db_root_path).unwrap_or_else(|e| {
             panic!("ConsensusDB open failed due to {:?}, unable to continue", e)
-        });
+        })
+    }
+
+    /// Opens the ConsensusDB under `db_root_path`, creating it if needed.
+    pub fn open<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        let path = db_root_path.as_ref().join(CONSENSUSDB_DIR_NAME);
+        let instant = Instant::now();
+        let db = DB::open(path.clone(), column_families())?;
 
         info!(
             "Opened ConsensusDB at {:?} in {} ms",
@@ -57,7 +68,16 @@ impl ConsensusDB {
             instant.elapsed().as_millis()
         );
 
-        Self { db }
+        Ok(Self { db })
+    }
+
+    /// Opens the existing ConsensusDB under `db_root_path` for reads only: any write fails. It can
+    /// be in use by a running node, in which case it is read as of the time it is opened.
+    pub fn open_readonly<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        let path = db_root_path.as_ref().join(CONSENSUSDB_DIR_NAME);
+        let db = DB::open_readonly(path.clone(), column_families())?;
+        info!("Opened ConsensusDB read-only at {:?}", path);
+        Ok(Self { db })
     }
 
     pub fn get_data<T: Payload>(
@@ -145,6 +165,40 @@ impl ConsensusDB {
         self.commit(batch)
     }
 
+    /// Replaces all the blocks and quorum certificates by the given ones, and the consensus state
+    /// and highest timeout certificates by the given ones, atomically.
+    pub fn reset<T: Payload>(
+        &self,
+        block_data: Vec<Block<T>>,
+        qc_data: Vec<QuorumCert>,
+        state: ConsensusStateData,
+        highest_timeout_certificates: HighestTimeoutCertificates,
+    ) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        self.get_blocks::<T>()?
+            .keys()
+            .map(|hash| batch.delete::<BlockSchema<T>>(hash))
+            .collect::<Result<()>>()?;
+        self.get_quorum_certificates()?
+            .keys()
+            .map(|hash| batch.delete::<QCSchema>(hash))
+            .collect::<Result<()>>()?;
+        block_data
+            .iter()
+            .map(|block| batch.put::<BlockSchema<T>>(&block.id(), block))
+            .collect::<Result<()>>()?;
+        qc_data
+            .iter()
+            .map(|qc| batch.put::<QCSchema>(&qc.certified_block_id(), qc))
+            .collect::<Result<()>>()?;
+        batch.put::<SingleEntrySchema>(&SingleEntryKey::ConsensusState, &state)?;
+        batch.put::<SingleEntrySchema>(
+            &SingleEntryKey::HighestTimeoutCertificates,
+            &highest_timeout_certificates,
+        )?;
+        self.commit(batch)
+    }
+
     /// Write the whole schema batch including all data necessary to mutate the ledger
     /// state of some transaction by leveraging rocksdb atomicity support.
     fn commit(&self, batch: SchemaBatch) -> Result<()> {
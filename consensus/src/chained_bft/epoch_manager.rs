@@ -0,0 +1,324 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    chained_bft::{
+        block_storage::BlockStore,
+        chained_bft_smr::ChainedBftSMRConfig,
+        common::{Author, Payload, Round},
+        consensus_types::block::Block,
+        event_processor::EventProcessor,
+        liveness::{
+            leader_reputation::LeaderReputation,
+            pacemaker::{ExponentialTimeInterval, Pacemaker},
+            pacemaker_timeout_manager::HighestTimeoutCertificates,
+            proposal_generator::ProposalGenerator,
+            proposer_election::ProposerElection,
+            rotating_proposer_election::RotatingProposer,
+            vrf_proposer_election::{VrfKeys, VrfProposer},
+        },
+        network::ConsensusNetworkImpl,
+        persistent_storage::{PersistentStorage, RecoveryData},
+        safety::{
+            consensus_safety_rules::ConsensusSafetyRules, local_safety_rules::LocalSafetyRules,
+            process_safety_rules::ProcessSafetyRules, proto::safety_rules_grpc::SafetyRulesClient,
+            safety_rules::ConsensusState,
+        },
+    },
+    counters,
+    state_replication::{StateComputer, TxnManager},
+    util::time_service::TimeService,
+};
+use channel;
+use config::config::{ConsensusProposerType, SafetyRulesType};
+use crypto::ed25519::*;
+use grpcio::{ChannelBuilder, EnvBuilder};
+use logger::prelude::*;
+use network::NetworkPublicKeys;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+use types::{
+    account_address::AccountAddress, ledger_info::LedgerInfoWithSignatures,
+    validator_signer::ValidatorSigner, validator_verifier::ValidatorVerifier,
+};
+
+/// Starts the components running consensus during an epoch: BlockStore, SafetyRules, Pacemaker,
+/// proposer election and the EventProcessor driving them.
+/// Once the block ending an epoch is committed, the components of the next epoch start from this
+/// block with the validators carried by its ledger info, and the network switches to them.
+pub struct EpochManager<T> {
+    author: Author,
+    config: ChainedBftSMRConfig,
+    time_service: Arc<dyn TimeService>,
+    signer: Arc<ValidatorSigner<Ed25519PrivateKey>>,
+    vrf_keys: VrfKeys,
+    network: ConsensusNetworkImpl,
+    // The peers the network layer accepts connections with, which are the validators.
+    trusted_network_peers: Arc<RwLock<HashMap<AccountAddress, NetworkPublicKeys>>>,
+    txn_manager: Arc<dyn TxnManager<Payload = T>>,
+    state_computer: Arc<dyn StateComputer<Payload = T>>,
+    storage: Arc<dyn PersistentStorage<T>>,
+    quorum_size: usize,
+    // The client of the safety rules process, if the safety rules don't run in this process.
+    safety_rules_client: Option<Arc<SafetyRulesClient>>,
+}
+
+impl<T: Payload> EpochManager<T> {
+    pub fn new(
+        author: Author,
+        config: ChainedBftSMRConfig,
+        time_service: Arc<dyn TimeService>,
+        signer: Arc<ValidatorSigner<Ed25519PrivateKey>>,
+        vrf_keys: VrfKeys,
+        network: ConsensusNetworkImpl,
+        trusted_network_peers: Arc<RwLock<HashMap<AccountAddress, NetworkPublicKeys>>>,
+        txn_manager: Arc<dyn TxnManager<Payload = T>>,
+        state_computer: Arc<dyn StateComputer<Payload = T>>,
+        storage: Arc<dyn PersistentStorage<T>>,
+        quorum_size: usize,
+    ) -> Self {
+        let safety_rules_client = match config.safety_rules_type {
+            SafetyRulesType::Local => None,
+            SafetyRulesType::Process => {
+                let env = Arc::new(EnvBuilder::new().name_prefix("grpc-con-saf-").build());
+                Some(Arc::new(SafetyRulesClient::new(
+                    ChannelBuilder::new(env).connect(&config.safety_rules_address),
+                )))
+            }
+        };
+        Self {
+            author,
+            config,
+            time_service,
+            signer,
+            vrf_keys,
+            network,
+            trusted_network_peers,
+            txn_manager,
+            state_computer,
+            storage,
+            quorum_size,
+            safety_rules_client,
+        }
+    }
+
+    /// The quorum size of the current epoch.
+    pub fn quorum_size(&self) -> usize {
+        self.quorum_size
+    }
+
+    /// Starts the epoch recovered in the given data with the given proposers. Returns its event
+    /// processor, the receiver of its local timeouts and the receiver of the ends of its waits for
+    /// proposals.
+    pub async fn start_epoch(
+        &self,
+        initial_data: RecoveryData<T>,
+        proposers: Vec<Author>,
+    ) -> (
+        EventProcessor<T>,
+        channel::Receiver<Round>,
+        channel::Receiver<Round>,
+    ) {
+        assert!(!proposers.is_empty());
+        let consensus_state = initial_data.state();
+        let highest_timeout_certificates = initial_data.highest_timeout_certificates().clone();
+        // The genesis of the epoch is only known if the epoch starts from it.
+        let root_quorum_cert = initial_data.root_block().quorum_cert();
+        let epoch_genesis = if root_quorum_cert.is_epoch_genesis() {
+            Some(root_quorum_cert.ledger_info().clone())
+        } else {
+            None
+        };
+        self.network.update_epoch_genesis(
+            epoch_genesis
+                .as_ref()
+                .map(|ledger_info| ledger_info.ledger_info().clone()),
+        );
+        let block_store = Arc::new(
+            BlockStore::new(
+                Arc::clone(&self.storage),
+                initial_data,
+                Arc::clone(&self.signer),
+                Arc::clone(&self.state_computer),
+                true,
+                self.config.max_pruned_blocks_in_mem,
+            )
+            .await,
+        );
+        counters::EPOCH_NUM.set(block_store.epoch() as i64);
+
+        // txn manager is required both by proposal generator (to pull the proposers)
+        // and by event processor (to update their status).
+        let proposal_generator = ProposalGenerator::new(
+            block_store.clone(),
+            Arc::clone(&self.txn_manager),
+            self.time_service.clone(),
+            self.config.max_block_size,
+            true,
+        );
+
+        let safety_rules =
+            self.create_safety_rules(consensus_state, block_store.epoch(), epoch_genesis);
+
+        let (timeout_sender, timeout_receiver) =
+            channel::new(1_024, &counters::PENDING_PACEMAKER_TIMEOUTS);
+        let pacemaker = self.create_pacemaker(timeout_sender, highest_timeout_certificates);
+
+        let (proposal_wait_sender, proposal_wait_receiver) =
+            channel::new(1_024, &counters::PENDING_PROPOSAL_WAITS);
+        let proposer_election =
+            self.create_proposer_election(proposers, block_store.clone(), proposal_wait_sender);
+        let event_processor = EventProcessor::new(
+            self.author,
+            block_store,
+            pacemaker,
+            proposer_election,
+            proposal_generator,
+            safety_rules,
+            Arc::clone(&self.state_computer),
+            Arc::clone(&self.txn_manager),
+            self.network.clone(),
+            Arc::clone(&self.storage),
+            self.time_service.clone(),
+            true,
+        );
+        (event_processor, timeout_receiver, proposal_wait_receiver)
+    }
+
+    /// Starts the epoch following the one ended by the given ledger info, with the validators it
+    /// carries. The genesis block of the new epoch is the block committed by the ledger info.
+    pub async fn start_new_epoch(
+        &mut self,
+        ledger_info: LedgerInfoWithSignatures<Ed25519Signature>,
+    ) -> (
+        EventProcessor<T>,
+        channel::Receiver<Round>,
+        channel::Receiver<Round>,
+    ) {
+        let validator_set = ledger_info
+            .ledger_info()
+            .next_validator_set()
+            .expect("The ledger info doesn't end an epoch")
+            .clone();
+        let validator = Arc::new(ValidatorVerifier::from(&validator_set));
+        let genesis_block = Block::make_epoch_genesis_block(ledger_info);
+        let genesis_quorum_cert = genesis_block.quorum_cert().clone();
+        let initial_data = self
+            .storage
+            .reset_to_epoch_genesis(genesis_block, genesis_quorum_cert)
+            .expect("Failed to persist the genesis block of the new epoch");
+
+        let peers = validator.get_ordered_account_addresses();
+        let proposers = match self.config.proposer_type {
+            // As for the first epoch, pick the max address to be the single proposer.
+            ConsensusProposerType::FixedProposer => {
+                vec![*peers.iter().max().expect("No validators in the new epoch")]
+            }
+            ConsensusProposerType::RotatingProposer
+            | ConsensusProposerType::LeaderReputation
+            | ConsensusProposerType::VrfProposer => peers.clone(),
+        };
+        self.network
+            .update_validators(Arc::new(peers), Arc::clone(&validator));
+        *self.trusted_network_peers.write().unwrap() = validator_set
+            .payload()
+            .iter()
+            .map(|keys| {
+                (
+                    *keys.account_address(),
+                    NetworkPublicKeys {
+                        signing_public_key: keys.network_signing_public_key().clone(),
+                        identity_public_key: keys.network_identity_public_key().clone(),
+                    },
+                )
+            })
+            .collect();
+        self.quorum_size = validator.quorum_size();
+        counters::CURRENT_EPOCH_NUM_VALIDATORS.set(validator.len() as i64);
+        counters::CURRENT_EPOCH_QUORUM_SIZE.set(validator.quorum_size() as i64);
+        info!(
+            "Starting a new epoch with {} validators, quorum_size = {}",
+            validator.len(),
+            validator.quorum_size()
+        );
+
+        self.start_epoch(initial_data, proposers).await
+    }
+
+    fn create_proposer_election(
+        &self,
+        proposers: Vec<Author>,
+        block_store: Arc<BlockStore<T>>,
+        proposal_wait_sender: channel::Sender<Round>,
+    ) -> Arc<dyn ProposerElection<T> + Send + Sync> {
+        match self.config.proposer_type {
+            ConsensusProposerType::FixedProposer | ConsensusProposerType::RotatingProposer => {
+                Arc::new(RotatingProposer::new(
+                    proposers,
+                    self.config.contiguous_rounds,
+                ))
+            }
+            ConsensusProposerType::LeaderReputation => {
+                Arc::new(LeaderReputation::new(proposers, block_store))
+            }
+            // The VRF keys come from the trusted peers config: a validator joining in a later
+            // epoch without one stops the validator instead of being left out of the proposers.
+            ConsensusProposerType::VrfProposer => Arc::new(
+                VrfProposer::new(
+                    proposers,
+                    self.vrf_keys.clone(),
+                    block_store.epoch(),
+                    self.time_service.clone(),
+                    self.config.vrf_proposal_wait,
+                    proposal_wait_sender,
+                )
+                .expect("Failed to start the VRF proposer election"),
+            ),
+        }
+    }
+
+    fn create_safety_rules(
+        &self,
+        consensus_state: ConsensusState,
+        epoch: u64,
+        epoch_genesis: Option<LedgerInfoWithSignatures<Ed25519Signature>>,
+    ) -> Box<dyn ConsensusSafetyRules<T>> {
+        match &self.safety_rules_client {
+            None => Box::new(LocalSafetyRules::new(
+                consensus_state,
+                Arc::clone(&self.signer),
+            )),
+            // The safety rules process keeps its own consensus state, and starts the epoch from its
+            // genesis.
+            Some(client) => Box::new(ProcessSafetyRules::new(
+                Arc::clone(client),
+                epoch,
+                epoch_genesis,
+            )),
+        }
+    }
+
+    fn create_pacemaker(
+        &self,
+        timeout_sender: channel::Sender<Round>,
+        highest_timeout_certificate: HighestTimeoutCertificates,
+    ) -> Pacemaker {
+        // 1.5^6 ~= 11
+        // Timeout goes from initial_timeout to initial_timeout*11 in 6 steps
+        let time_interval = Box::new(ExponentialTimeInterval::new(
+            self.config.pacemaker_initial_timeout,
+            1.5,
+            6,
+        ));
+        Pacemaker::new(
+            self.storage.persistent_liveness_storage(),
+            time_interval,
+            self.time_service.clone(),
+            timeout_sender,
+            self.quorum_size,
+            highest_timeout_certificate,
+        )
+    }
+}
@@ -14,7 +14,7 @@ use crate::{
             proposal_msg::ProposalMsg,
             quorum_cert::QuorumCert,
             sync_info::SyncInfo,
-            timeout_msg::{PacemakerTimeout, PacemakerTimeoutCertificate, TimeoutMsg},
+            timeout_msg::{PacemakerTimeoutCertificate, TimeoutMsg},
         },
         liveness::{
             pacemaker::{NewRoundEvent, NewRoundReason, Pacemaker},
@@ -23,7 +23,10 @@ use crate::{
         },
         network::{BlockRetrievalRequest, BlockRetrievalResponse, ConsensusNetworkImpl},
         persistent_storage::PersistentStorage,
-        safety::{safety_rules::SafetyRules, vote_msg::VoteMsg},
+        safety::{
+            consensus_safety_rules::ConsensusSafetyRules, safety_rules::SafetyRules,
+            vote_msg::VoteMsg,
+        },
         sync_manager::{SyncManager, SyncMgrContext},
     },
     counters,
@@ -54,7 +57,7 @@ pub struct EventProcessor<T> {
     pacemaker: Pacemaker,
     proposer_election: Arc<dyn ProposerElection<T> + Send + Sync>,
     proposal_generator: ProposalGenerator<T>,
-    safety_rules: SafetyRules,
+    safety_rules: Box<dyn ConsensusSafetyRules<T>>,
     state_computer: Arc<dyn StateComputer<Payload = T>>,
     txn_manager: Arc<dyn TxnManager<Payload = T>>,
     network: ConsensusNetworkImpl,
@@ -64,6 +67,12 @@ pub struct EventProcessor<T> {
     enforce_increasing_timestamps: bool,
     // Cache of the last sent vote message.
     last_vote_sent: Option<(VoteMsg, Round)>,
+    // The ledger info ending the epoch, once committed: the event processor of the next epoch
+    // takes over from there on.
+    epoch_ending_ledger_info: Option<LedgerInfoWithSignatures<Ed25519Signature>>,
+    // The QuorumCert of the genesis of the epoch, if the epoch was started from it: it proves
+    // the end of the previous epoch to the validators lagging behind.
+    epoch_genesis: Option<QuorumCert>,
 }
 
 impl<T: Payload> EventProcessor<T> {
@@ -73,7 +82,7 @@ impl<T: Payload> EventProcessor<T> {
         pacemaker: Pacemaker,
         proposer_election: Arc<dyn ProposerElection<T> + Send + Sync>,
         proposal_generator: ProposalGenerator<T>,
-        safety_rules: SafetyRules,
+        safety_rules: Box<dyn ConsensusSafetyRules<T>>,
         state_computer: Arc<dyn StateComputer<Payload = T>>,
         txn_manager: Arc<dyn TxnManager<Payload = T>>,
         network: ConsensusNetworkImpl,
@@ -81,6 +90,12 @@ impl<T: Payload> EventProcessor<T> {
         time_service: Arc<dyn TimeService>,
         enforce_increasing_timestamps: bool,
     ) -> Self {
+        let root_quorum_cert = block_store.root().quorum_cert().clone();
+        let epoch_genesis = if root_quorum_cert.is_epoch_genesis() {
+            Some(root_quorum_cert)
+        } else {
+            None
+        };
         let sync_manager = SyncManager::new(
             Arc::clone(&block_store),
             Arc::clone(&storage),
@@ -102,9 +117,21 @@ impl<T: Payload> EventProcessor<T> {
             time_service,
             enforce_increasing_timestamps,
             last_vote_sent: None,
+            epoch_ending_ledger_info: None,
+            epoch_genesis,
         }
     }
 
+    pub fn block_store(&self) -> Arc<BlockStore<T>> {
+        Arc::clone(&self.block_store)
+    }
+
+    /// Returns the ledger info ending the epoch of this event processor once it is committed.
+    /// It carries the validators of the next epoch.
+    pub fn epoch_ending_ledger_info(&self) -> Option<&LedgerInfoWithSignatures<Ed25519Signature>> {
+        self.epoch_ending_ledger_info.as_ref()
+    }
+
     /// Leader:
     ///
     /// This event is triggered by a new quorum certificate at the previous round or a
@@ -118,6 +145,9 @@ impl<T: Payload> EventProcessor<T> {
     ///
     /// Do nothing
     async fn process_new_round_event(&self, new_round_event: NewRoundEvent) {
+        if self.epoch_ending_ledger_info.is_some() {
+            return;
+        }
         debug!("Processing {}", new_round_event);
         counters::CURRENT_ROUND.set(new_round_event.round as i64);
         counters::ROUND_TIMEOUT_MS.set(new_round_event.timeout.as_millis() as i64);
@@ -152,6 +182,14 @@ impl<T: Payload> EventProcessor<T> {
             }
             Ok(proposal) => proposal,
         };
+        // The proposal is signed by the safety rules, which own the consensus key.
+        let proposal = match self.safety_rules.sign_proposal(proposal) {
+            Err(e) => {
+                error!("Failed to sign the proposal: {:?}", e);
+                return;
+            }
+            Ok(proposal) => proposal,
+        };
         let mut network = self.network.clone();
         debug!("Propose {}", proposal);
         let timeout_certificate = match &new_round_event.reason {
@@ -167,10 +205,12 @@ impl<T: Payload> EventProcessor<T> {
             (*self.block_store.highest_ledger_info()).clone(),
             timeout_certificate,
         );
+        let vrf_proof = self.proposer_election.prove_proposer(&proposal);
         network
             .broadcast_proposal(ProposalMsg {
                 proposal,
                 sync_info,
+                vrf_proof,
             })
             .await;
         counters::PROPOSALS_COUNT.inc();
@@ -205,6 +245,14 @@ impl<T: Payload> EventProcessor<T> {
             );
             return None;
         }
+        let epoch = self.block_store.epoch();
+        if proposal_msg.proposal.quorum_cert().certified_block_epoch() != epoch {
+            warn!(
+                "Proposal {} is ignored because it is not from the current epoch {}",
+                proposal_msg, epoch
+            );
+            return None;
+        }
         if self
             .proposer_election
             .is_valid_proposer(proposal_msg.proposer(), proposal_msg.proposal.round())
@@ -240,8 +288,26 @@ impl<T: Payload> EventProcessor<T> {
             return None;
         }
 
-        self.proposer_election
-            .process_proposal(proposal_msg.proposal)
+        self.proposer_election.process_proposal_msg(proposal_msg)
+    }
+
+    /// The wait for the proposals of the given round is over (for the proposer elections choosing
+    /// among the proposals received within a bounded wait): process the chosen proposal if the
+    /// round is still the current one.
+    pub async fn process_proposal_wait_end(&mut self, round: Round) {
+        let proposal = match self.proposer_election.choose_proposal(round) {
+            Some(proposal) => proposal,
+            None => return,
+        };
+        let current_round = self.pacemaker.current_round();
+        if round != current_round {
+            warn!(
+                "Proposal {} is ignored because its round {} != current round {}",
+                proposal, round, current_round
+            );
+            return;
+        }
+        self.process_proposed_block(proposal).await
     }
 
     /// Upon receiving TimeoutMsg, ensure that any branches with higher quorum certificates are
@@ -317,6 +383,48 @@ impl<T: Payload> EventProcessor<T> {
         }
     }
 
+    /// In case some peer is still at the previous epoch, send it a SyncInfo carrying the genesis
+    /// of the current epoch, which proves the end of the previous one.
+    async fn help_remote_to_current_epoch(&self, peer: Author) {
+        if self.author == peer {
+            return;
+        }
+        if let Some(epoch_genesis) = &self.epoch_genesis {
+            let sync_info = SyncInfo::new(epoch_genesis.clone(), epoch_genesis.clone(), None);
+            debug!(
+                "Peer {} is at the previous epoch, sending it {}",
+                peer.short_str(),
+                sync_info
+            );
+            counters::SYNC_INFO_MSGS_SENT_COUNT.inc();
+            self.network.send_sync_info(sync_info, peer).await;
+        }
+    }
+
+    /// Synchronizes the state up to the ledger info ending the current epoch, carried by the
+    /// genesis of the next epoch, and ends the current epoch: this validator lagged behind and
+    /// missed the commit of the block ending it.
+    async fn sync_to_next_epoch(&mut self, epoch_genesis: &QuorumCert) -> failure::Result<()> {
+        if self.epoch_ending_ledger_info.is_some() {
+            return Ok(());
+        }
+        let ledger_info = epoch_genesis.ledger_info().clone();
+        info!(
+            "Epoch {} ended with the commit of block {}, synchronizing to {}",
+            self.block_store.epoch(),
+            ledger_info.ledger_info().consensus_block_id(),
+            ledger_info
+        );
+        if !self.state_computer.sync_to(epoch_genesis.clone()).await? {
+            return Err(format_err!(
+                "Failed to synchronize to the end of epoch {}",
+                self.block_store.epoch()
+            ));
+        }
+        self.epoch_ending_ledger_info = Some(ledger_info);
+        Ok(())
+    }
+
     /// The function makes sure that it brings the missing dependencies from the QC and LedgerInfo
     /// of the given sync info and update the pacemaker with the certificates if succeed.
     /// Returns Error in case sync mgr failed to bring the missing dependencies.
@@ -327,6 +435,28 @@ impl<T: Payload> EventProcessor<T> {
         author: Author,
         help_remote: bool,
     ) -> failure::Result<()> {
+        // The certificates of other epochs are signed by other validators: they can't be
+        // trusted, even if they verify with the validators of the current epoch.
+        let epoch = self.block_store.epoch();
+        let remote_epoch = sync_info.highest_ledger_info().certified_block_epoch();
+        if sync_info.highest_quorum_cert().certified_block_epoch() != epoch || remote_epoch != epoch
+        {
+            // Only the genesis of the next epoch can be trusted, being certified by the ledger
+            // info ending the current epoch.
+            if remote_epoch == epoch + 1 && sync_info.highest_ledger_info().is_epoch_genesis() {
+                return self
+                    .sync_to_next_epoch(sync_info.highest_ledger_info())
+                    .await;
+            }
+            if help_remote && remote_epoch + 1 == epoch {
+                self.help_remote_to_current_epoch(author).await;
+            }
+            return Err(format_err!(
+                "{} is not from the current epoch {}",
+                sync_info,
+                epoch
+            ));
+        }
         if help_remote {
             self.help_remote_if_stale(author, sync_info.highest_round(), sync_info.hqc_round())
                 .await;
@@ -385,11 +515,15 @@ impl<T: Payload> EventProcessor<T> {
         if !self.pacemaker.process_local_timeout(round) {
             return;
         }
-        let last_vote_round = self.safety_rules.consensus_state().last_vote_round();
+        let last_vote_round = self
+            .safety_rules
+            .consensus_state()
+            .ok()
+            .map(|state| state.last_vote_round());
         warn!(
             "Round {} timed out and {}, expected round proposer was {:?}, broadcasting new round to all replicas",
             round,
-            if last_vote_round == round { "already executed and voted at this round" } else { "will vote for NIL at this round" },
+            if last_vote_round == Some(round) { "already executed and voted at this round" } else { "will vote for NIL at this round" },
             self.proposer_election.get_valid_proposers(round),
         );
 
@@ -412,7 +546,21 @@ impl<T: Payload> EventProcessor<T> {
 
         // Stop voting at this round, persist the consensus state to support restarting from
         // a recent round (i.e. > the last vote round)  and then send the SyncInfo
-        let consensus_state = self.safety_rules.increase_last_vote_round(round);
+        let sync_info = SyncInfo::new(
+            self.block_store.highest_quorum_cert().as_ref().clone(),
+            self.block_store.highest_ledger_info().as_ref().clone(),
+            self.pacemaker.highest_timeout_certificate(),
+        );
+        let signed_timeout = self
+            .safety_rules
+            .sign_timeout(round, vote_msg_to_attach, sync_info);
+        let (timeout_msg, consensus_state) = match signed_timeout {
+            Ok(signed_timeout) => signed_timeout,
+            Err(e) => {
+                error!("Failed to sign the timeout due to {:?}", e);
+                return;
+            }
+        };
 
         if let Some(consensus_state) = consensus_state {
             if let Err(e) = self.storage.save_consensus_state(consensus_state) {
@@ -421,17 +569,7 @@ impl<T: Payload> EventProcessor<T> {
             }
         }
 
-        self.network
-            .broadcast_timeout_msg(TimeoutMsg::new(
-                SyncInfo::new(
-                    self.block_store.highest_quorum_cert().as_ref().clone(),
-                    self.block_store.highest_ledger_info().as_ref().clone(),
-                    self.pacemaker.highest_timeout_certificate(),
-                ),
-                PacemakerTimeout::new(round, self.block_store.signer(), vote_msg_to_attach),
-                self.block_store.signer(),
-            ))
-            .await;
+        self.network.broadcast_timeout_msg(timeout_msg).await;
     }
 
     async fn gen_nil_vote(&mut self, round: Round) -> failure::Result<VoteMsg> {
@@ -444,7 +582,9 @@ impl<T: Payload> EventProcessor<T> {
         qc: &QuorumCert,
         tc: Option<&PacemakerTimeoutCertificate>,
     ) {
-        self.safety_rules.update(qc);
+        if let Err(e) = self.safety_rules.update(qc) {
+            error!("Failed to update the safety rules with {}: {:?}", qc, e);
+        }
 
         let mut highest_committed_proposal_round = None;
         if let Some(new_commit) = qc.committed_block_id() {
@@ -456,6 +596,24 @@ impl<T: Payload> EventProcessor<T> {
                 }
                 self.process_commit(block, finality_proof).await;
             }
+            // The epoch ends once the block changing the validator set is committed: its ledger
+            // info is the last one signed by the validators of this epoch.
+            if qc
+                .ledger_info()
+                .ledger_info()
+                .next_validator_set()
+                .is_some()
+                && !qc.is_epoch_genesis()
+                && self.block_store.root().id() == new_commit
+            {
+                info!(
+                    "Epoch {} ended with the commit of block {}",
+                    self.block_store.epoch(),
+                    new_commit
+                );
+                self.epoch_ending_ledger_info = Some(qc.ledger_info().clone());
+                return;
+            }
         }
         if let Some(new_round_event) = self.pacemaker.process_certificates(
             qc.certified_block_round(),
@@ -472,6 +630,9 @@ impl<T: Payload> EventProcessor<T> {
     /// 3. In case a validator chooses to vote, send the vote to the representatives at the next
     /// position.
     async fn process_proposed_block(&mut self, proposal: Block<T>) {
+        if self.epoch_ending_ledger_info.is_some() {
+            return;
+        }
         if let Some(time_to_receival) =
             duration_since_epoch().checked_sub(Duration::from_micros(proposal.timestamp_usecs()))
         {
@@ -584,41 +745,30 @@ impl<T: Payload> EventProcessor<T> {
         self.wait_before_vote_if_needed(block.timestamp_usecs())
             .await?;
 
-        let vote_info = self
+        let executed_state = self
+            .block_store
+            .get_state_for_block(block.id())
+            .expect("Block proposed_block: no execution state found for inserted block.");
+        let potential_commit_id =
+            SafetyRules::commit_rule_for_certified_block(block.quorum_cert(), block.round());
+        let ledger_info_placeholder = self
+            .block_store
+            .ledger_info_placeholder(potential_commit_id);
+
+        let (vote, consensus_state) = self
             .safety_rules
-            .voting_rule(Arc::clone(&block))
+            .construct_and_sign_vote(Arc::clone(&block), executed_state, ledger_info_placeholder)
             .map_err(|e| {
                 debug!("{}Rejected{} {}: {:?}", Fg(Red), Fg(Reset), block, e);
                 e
             })?;
         self.storage
-            .save_consensus_state(vote_info.consensus_state().clone())
+            .save_consensus_state(consensus_state)
             .map_err(|e| {
                 debug!("Fail to persist consensus state: {:?}", e);
                 e
             })?;
-
-        let proposal_id = vote_info.proposal_id();
-        let executed_state = self
-            .block_store
-            .get_state_for_block(proposal_id)
-            .expect("Block proposed_block: no execution state found for inserted block.");
-
-        let ledger_info_placeholder = self
-            .block_store
-            .ledger_info_placeholder(vote_info.potential_commit_id());
-        Ok(VoteMsg::new(
-            proposal_id,
-            executed_state,
-            block.round(),
-            vote_info.parent_block_id(),
-            vote_info.parent_block_round(),
-            vote_info.grandparent_block_id(),
-            vote_info.grandparent_block_round(),
-            self.author,
-            ledger_info_placeholder,
-            self.block_store.signer(),
-        ))
+        Ok(vote)
     }
 
     /// Upon new vote:
@@ -660,7 +810,16 @@ impl<T: Payload> EventProcessor<T> {
     async fn add_vote(&mut self, vote: VoteMsg, quorum_size: usize) -> Option<Arc<QuorumCert>> {
         let deadline = self.pacemaker.current_round_deadline();
         let preferred_peer = vote.author();
-        // TODO [Reconfiguration] Verify epoch of the vote message.
+        let epoch = self.block_store.epoch();
+        if vote.ledger_info().epoch_num() != epoch {
+            debug!(
+                "Received {} from epoch {}, but the current epoch is {}, ignore.",
+                vote,
+                vote.ledger_info().epoch_num(),
+                epoch
+            );
+            return None;
+        }
         // Add the vote and check whether it completes a new QC.
         if let VoteReceptionResult::NewQuorumCertificate(qc) =
             self.block_store.insert_vote(vote, quorum_size)
@@ -805,6 +964,8 @@ impl<T: Payload> EventProcessor<T> {
     /// Inspect the current consensus state.
     #[cfg(test)]
     pub fn consensus_state(&self) -> ConsensusState {
-        self.safety_rules.consensus_state()
+        self.safety_rules
+            .consensus_state()
+            .expect("Failed to get the consensus state")
     }
 }
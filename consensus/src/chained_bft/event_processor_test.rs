@@ -24,8 +24,7 @@ use crate::{
         network_tests::NetworkPlayground,
         persistent_storage::{PersistentStorage, RecoveryData},
         safety::{
-            safety_rules::{ConsensusState, SafetyRules},
-            vote_msg::VoteMsg,
+            local_safety_rules::LocalSafetyRules, safety_rules::ConsensusState, vote_msg::VoteMsg,
         },
         test_utils::{
             consensus_runtime, placeholder_certificate_for_block, placeholder_ledger_info,
@@ -35,7 +34,7 @@ use crate::{
     util::time_service::{ClockTimeService, TimeService},
 };
 use channel;
-use crypto::{ed25519::*, HashValue};
+use crypto::{ed25519::*, hash::CryptoHash, HashValue};
 use futures::{
     channel::{mpsc, oneshot},
     compat::Future01CompatExt,
@@ -46,10 +45,12 @@ use network::{
     validator_network::{ConsensusNetworkEvents, ConsensusNetworkSender},
 };
 use proto_conv::FromProto;
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use tokio::runtime::TaskExecutor;
 use types::{
-    ledger_info::LedgerInfoWithSignatures, validator_signer::ValidatorSigner,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_set::ValidatorSet,
+    validator_signer::ValidatorSigner,
     validator_verifier::ValidatorVerifier,
 };
 
@@ -76,7 +77,7 @@ impl NodeSetup {
         Arc::new(block_on(BlockStore::new(
             storage,
             initial_data,
-            signer,
+            Arc::new(signer),
             Arc::new(MockStateComputer::new(commit_cb_sender)),
             true,
             10, // max pruned blocks in mem
@@ -171,7 +172,10 @@ impl NodeSetup {
             1,
             true,
         );
-        let safety_rules = SafetyRules::new(consensus_state);
+        let safety_rules = Box::new(LocalSafetyRules::new(
+            consensus_state,
+            Arc::new(signer.clone()),
+        ));
 
         let pacemaker = Self::create_pacemaker(time_service.clone());
 
@@ -427,6 +431,7 @@ fn process_round_mismatch_test() {
         let bad_proposal = ProposalMsg::<TestPayload> {
             proposal: block_skip_round,
             sync_info: SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), None),
+            vrf_proof: None,
         };
         assert_eq!(
             node.event_processor
@@ -437,6 +442,7 @@ fn process_round_mismatch_test() {
         let good_proposal = ProposalMsg::<TestPayload> {
             proposal: correct_block.clone(),
             sync_info: SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), None),
+            vrf_proof: None,
         };
         assert_eq!(
             node.event_processor
@@ -559,6 +565,7 @@ fn process_proposer_mismatch_test() {
         let bad_proposal = ProposalMsg::<TestPayload> {
             proposal: block_incorrect_proposer,
             sync_info: SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), None),
+            vrf_proof: None,
         };
         assert_eq!(
             node.event_processor
@@ -569,6 +576,7 @@ fn process_proposer_mismatch_test() {
         let good_proposal = ProposalMsg::<TestPayload> {
             proposal: correct_block.clone(),
             sync_info: SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), None),
+            vrf_proof: None,
         };
 
         assert_eq!(
@@ -614,6 +622,7 @@ fn process_timeout_certificate_test() {
         let skip_round_proposal = ProposalMsg::<TestPayload> {
             proposal: block_skip_round,
             sync_info: SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), Some(tc)),
+            vrf_proof: None,
         };
         assert_eq!(
             node.event_processor
@@ -624,6 +633,7 @@ fn process_timeout_certificate_test() {
         let old_good_proposal = ProposalMsg::<TestPayload> {
             proposal: correct_block.clone(),
             sync_info: SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), None),
+            vrf_proof: None,
         };
         assert_eq!(
             node.event_processor
@@ -827,3 +837,58 @@ fn nil_vote_on_timeout() {
         assert_eq!(vote_msg.parent_block_id(), genesis_id);
     });
 }
+
+#[test]
+/// A node that missed the commit of the block ending its epoch ends it once a peer sends it the
+/// genesis of the next epoch.
+fn sync_to_next_epoch_test() {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.executor());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.executor(), 2);
+    let peer = nodes[1].author;
+    let node = &mut nodes[0];
+    let epoch = node.block_store.epoch();
+    let make_epoch_genesis = |epoch_num| {
+        let ledger_info = LedgerInfo::new(
+            0,
+            HashValue::random(),
+            HashValue::random(),
+            HashValue::random(),
+            epoch_num,
+            0,
+            Some(ValidatorSet::new(vec![])),
+        );
+        let mut signatures = HashMap::new();
+        signatures.insert(
+            node.signer.author(),
+            node.signer.sign_message(ledger_info.hash()).unwrap(),
+        );
+        QuorumCert::certificate_for_epoch_genesis(LedgerInfoWithSignatures::new(
+            ledger_info,
+            signatures,
+        ))
+    };
+    let later_epoch_genesis = make_epoch_genesis(epoch + 1);
+    let next_epoch_genesis = make_epoch_genesis(epoch);
+    block_on(async move {
+        // Only the genesis of the next epoch proves the end of the current one.
+        node.event_processor
+            .process_sync_info_msg(
+                SyncInfo::new(later_epoch_genesis.clone(), later_epoch_genesis, None),
+                peer,
+            )
+            .await;
+        assert!(node.event_processor.epoch_ending_ledger_info().is_none());
+
+        node.event_processor
+            .process_sync_info_msg(
+                SyncInfo::new(next_epoch_genesis.clone(), next_epoch_genesis.clone(), None),
+                peer,
+            )
+            .await;
+        assert_eq!(
+            node.event_processor.epoch_ending_ledger_info(),
+            Some(next_epoch_genesis.ledger_info())
+        );
+    });
+}
@@ -0,0 +1,210 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::chained_bft::{
+    block_storage::BlockReader,
+    common::{Author, Payload, Round},
+    consensus_types::block::Block,
+    liveness::proposer_election::ProposerElection,
+};
+use byteorder::{ByteOrder, LittleEndian};
+use crypto::{
+    hash::{CryptoHasher, ProposerElectionHasher},
+    HashValue,
+};
+use std::{collections::HashSet, sync::Arc};
+
+/// The history used for choosing the proposer of round r is the chain of the blocks up to round
+/// r - ROUND_GAP which the proposal of round r extends: leaving out the latest rounds, the history
+/// is the same whichever recent quorum certificate of the chain a validator knows of.
+const ROUND_GAP: Round = 4;
+/// The number of blocks looked at for the reputation of the proposers, which is also the number of
+/// failed rounds looked at.
+const WINDOW_SIZE: usize = 10;
+/// The weight of a proposer that has proposed or voted for a block of the window.
+const ACTIVE_WEIGHT: u64 = 100;
+/// The weight of a proposer that has proposed or voted for a block of the window, but was elected
+/// for a round that failed: it most likely can't get its proposals certified.
+const FAILING_WEIGHT: u64 = 10;
+/// The weight of a proposer that hasn't: it is most likely down, but keeps a chance to be elected
+/// so that it can make its way back into the window.
+const INACTIVE_WEIGHT: u64 = 1;
+
+/// The leader reputation maps a round to an author drawn with a deterministic hash of the round,
+/// weighted by the reputation of the authors in the recent history: the authors of the ancestors
+/// of the block certified by the quorum certificate of the proposal and the signers of their
+/// quorum certificates are active, the others are inactive. The active authors that were elected
+/// for a round without a block in the history, i.e., whose proposal didn't get a quorum
+/// certificate, are down-weighted. A crashed validator thus loses its rounds shortly after it
+/// stops participating, whereas the rotating proposer keeps electing it (and timing out) in 1/n of
+/// the rounds, and so does a validator that votes but fails to propose. The history doesn't depend
+/// on what a validator committed: a proposal is checked against the history of the certificate it
+/// carries, and the proposer of a round is otherwise expected to extend the highest certificate
+/// known. Validators that don't know of a recent certificate of the chain (e.g., right after a
+/// restart) may still disagree on the proposer of a round, which costs at most a timeout of this
+/// round.
+pub struct LeaderReputation<T> {
+    // Ordering of the candidate proposers (all honest replicas must agree on this)
+    proposers: Vec<Author>,
+    block_store: Arc<dyn BlockReader<Payload = T> + Send + Sync>,
+}
+
+impl<T: Payload> LeaderReputation<T> {
+    pub fn new(
+        proposers: Vec<Author>,
+        block_store: Arc<dyn BlockReader<Payload = T> + Send + Sync>,
+    ) -> Self {
+        assert!(!proposers.is_empty());
+        Self {
+            proposers,
+            block_store,
+        }
+    }
+
+    /// Returns the (at most WINDOW_SIZE) blocks with a round not greater than round - ROUND_GAP
+    /// among the given block and its ancestors, from the highest to the lowest round. The history
+    /// stops at the genesis block of the epoch, as well as at the blocks that are not kept in
+    /// memory anymore.
+    fn history(&self, round: Round, block_id: HashValue) -> Vec<Arc<Block<T>>> {
+        let target_round = round.saturating_sub(ROUND_GAP);
+        let mut history = vec![];
+        let mut current = self.block_store.get_block(block_id);
+        while let Some(block) = current {
+            if history.len() == WINDOW_SIZE || block.is_genesis_block() {
+                break;
+            }
+            current = self.block_store.get_block(block.parent_id());
+            if block.round() <= target_round {
+                history.push(block);
+            }
+        }
+        history
+    }
+
+    /// Returns the (at most WINDOW_SIZE) failed rounds of the history, i.e., the rounds between
+    /// the round of each block and the round of its parent, along with the parent, from the
+    /// highest to the lowest round.
+    fn failed_rounds(history: &[Arc<Block<T>>]) -> Vec<(Round, HashValue)> {
+        history
+            .iter()
+            .flat_map(|block| {
+                let parent_id = block.parent_id();
+                let parent_round = block.quorum_cert().certified_block_round();
+                ((parent_round + 1)..block.round())
+                    .rev()
+                    .map(move |round| (round, parent_id))
+            })
+            .take(WINDOW_SIZE)
+            .collect()
+    }
+
+    /// Returns the weight of every proposer given its participation in the history. Note that
+    /// the quorum certificate of a block carries the votes for its parent.
+    fn get_participation_weights(&self, history: &[Arc<Block<T>>]) -> Vec<u64> {
+        let mut active = HashSet::new();
+        for block in history {
+            active.extend(block.author());
+            active.extend(
+                block
+                    .quorum_cert()
+                    .ledger_info()
+                    .signatures()
+                    .keys()
+                    .cloned(),
+            );
+        }
+        self.proposers
+            .iter()
+            .map(|author| {
+                if active.contains(author) {
+                    ACTIVE_WEIGHT
+                } else {
+                    INACTIVE_WEIGHT
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the weight of every proposer given the history. The author elected for a failed
+    /// round is the one elected by the participation alone in the history of this round, so that
+    /// the weights don't depend on older failures recursively.
+    fn get_weights(&self, history: &[Arc<Block<T>>]) -> Vec<u64> {
+        let failing: HashSet<_> = Self::failed_rounds(history)
+            .into_iter()
+            .map(|(round, parent_id)| {
+                let weights = self.get_participation_weights(&self.history(round, parent_id));
+                self.choose_proposer(round, &weights)
+            })
+            .collect();
+        self.get_participation_weights(history)
+            .into_iter()
+            .zip(self.proposers.iter())
+            .map(|(weight, author)| {
+                if weight == ACTIVE_WEIGHT && failing.contains(author) {
+                    FAILING_WEIGHT
+                } else {
+                    weight
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the proposer drawn for the given round with the given weights.
+    fn choose_proposer(&self, round: Round, weights: &[u64]) -> Author {
+        let total_weight: u64 = weights.iter().sum();
+
+        let mut hasher = ProposerElectionHasher::default();
+        hasher.write(&round.to_le_bytes());
+        let mut chosen_weight = LittleEndian::read_u64(&hasher.finish().to_vec()) % total_weight;
+        for (author, weight) in self.proposers.iter().zip(weights) {
+            if chosen_weight < *weight {
+                return *author;
+            }
+            chosen_weight -= *weight;
+        }
+        unreachable!("The chosen weight is lower than the total weight")
+    }
+
+    /// Returns the proposer of the given round for a proposal extending the quorum certificate of
+    /// the given block.
+    fn get_proposer(&self, round: Round, certified_block_id: HashValue) -> Author {
+        let weights = self.get_weights(&self.history(round, certified_block_id));
+        self.choose_proposer(round, &weights)
+    }
+
+    /// Returns the proposer of the given round as far as the local validator knows: its proposal
+    /// is expected to extend the highest quorum certificate.
+    fn get_local_proposer(&self, round: Round) -> Author {
+        let certified_block_id = self.block_store.highest_quorum_cert().certified_block_id();
+        self.get_proposer(round, certified_block_id)
+    }
+}
+
+impl<T: Payload> ProposerElection<T> for LeaderReputation<T> {
+    fn is_valid_proposer(&self, author: Author, round: Round) -> Option<Author> {
+        if self.get_local_proposer(round) == author {
+            Some(author)
+        } else {
+            None
+        }
+    }
+
+    fn get_valid_proposers(&self, round: Round) -> Vec<Author> {
+        vec![self.get_local_proposer(round)]
+    }
+
+    fn process_proposal(&self, proposal: Block<T>) -> Option<Block<T>> {
+        // Same as for the rotating proposer, the proposal is processed in the context of the
+        // caller task: the history of the quorum certificate of the proposal is read from the
+        // block store, which holds the certified block once the proposal is synced up.
+        let round_author = self.get_proposer(
+            proposal.round(),
+            proposal.quorum_cert().certified_block_id(),
+        );
+        if Some(round_author) != proposal.author() {
+            None
+        } else {
+            Some(proposal)
+        }
+    }
+}
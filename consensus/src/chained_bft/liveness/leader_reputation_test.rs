@@ -0,0 +1,186 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::chained_bft::{
+    block_storage::BlockReader,
+    consensus_types::block::Block,
+    liveness::{leader_reputation::LeaderReputation, proposer_election::ProposerElection},
+    test_utils::{
+        build_empty_tree, build_empty_tree_with_custom_signing, placeholder_certificate_for_block,
+        TestPayload, TreeInserter,
+    },
+};
+use crypto::ed25519::*;
+use futures::executor::block_on;
+use std::sync::Arc;
+use types::validator_signer::ValidatorSigner;
+
+/// Inserts a block of the given round extending parent, whose quorum certificate is signed by
+/// signers.
+fn insert_block_certified_by(
+    inserter: &mut TreeInserter,
+    parent: &Block<TestPayload>,
+    round: u64,
+    signers: Vec<&ValidatorSigner<Ed25519PrivateKey>>,
+) -> Arc<Block<TestPayload>> {
+    let parent_qc = placeholder_certificate_for_block(
+        signers,
+        parent.id(),
+        parent.round(),
+        parent.quorum_cert().certified_block_id(),
+        parent.quorum_cert().certified_block_round(),
+        parent.quorum_cert().certified_parent_block_id(),
+        parent.quorum_cert().certified_parent_block_round(),
+    );
+    inserter.insert_block_with_qc(parent_qc, parent, round)
+}
+
+#[test]
+fn test_leader_reputation_deterministic() {
+    let block_store = build_empty_tree();
+    let proposers: Vec<_> = (0..4)
+        .map(|i| ValidatorSigner::<Ed25519PrivateKey>::random([i; 32]).author())
+        .collect();
+    let pe1: LeaderReputation<TestPayload> =
+        LeaderReputation::new(proposers.clone(), block_store.clone());
+    let pe2: LeaderReputation<TestPayload> =
+        LeaderReputation::new(proposers.clone(), block_store.clone());
+
+    for round in 1..50 {
+        let valid_proposers = pe1.get_valid_proposers(round);
+        assert_eq!(valid_proposers.len(), 1);
+        assert_eq!(pe2.get_valid_proposers(round), valid_proposers);
+        for author in &proposers {
+            assert_eq!(
+                pe1.is_valid_proposer(*author, round).is_some(),
+                *author == valid_proposers[0]
+            );
+        }
+    }
+}
+
+#[test]
+fn test_leader_reputation_inactive_proposer() {
+    let block_store = build_empty_tree();
+    let active_author = block_store.signer().author();
+    let inactive_author = ValidatorSigner::<Ed25519PrivateKey>::random([1u8; 32]).author();
+
+    // All the committed blocks are proposed and certified by the signer of the block store.
+    let mut inserter = TreeInserter::new(block_store.clone());
+    let mut block = block_store.root();
+    for round in 1..=10 {
+        block = inserter.insert_block(block.as_ref(), round);
+    }
+    block_store.prune_tree(block.id());
+
+    let pe = LeaderReputation::new(vec![inactive_author, active_author], block_store.clone());
+    let inactive_rounds = (5..100)
+        .filter(|round| pe.get_valid_proposers(*round) == vec![inactive_author])
+        .count();
+    assert!(inactive_rounds < 10);
+
+    // The proposals of the elected author are accepted.
+    let round = (11..100)
+        .find(|round| pe.get_valid_proposers(*round) == vec![active_author])
+        .unwrap();
+    let proposal = inserter.insert_block(block.as_ref(), round);
+    assert_eq!(
+        pe.process_proposal(proposal.as_ref().clone()),
+        Some(proposal.as_ref().clone())
+    );
+}
+
+#[test]
+fn test_leader_reputation_different_commit_heights() {
+    let block_store = build_empty_tree();
+    let lagging_block_store = build_empty_tree();
+    let active_author = block_store.signer().author();
+    let inactive_author = ValidatorSigner::<Ed25519PrivateKey>::random([1u8; 32]).author();
+
+    // Both validators know of the same blocks, but only the first one committed them.
+    let mut inserter = TreeInserter::new(block_store.clone());
+    let mut block = block_store.root();
+    for round in 1..=10 {
+        block = inserter.insert_block(block.as_ref(), round);
+        block_on(lagging_block_store.insert_block_with_qc(block.as_ref().clone())).unwrap();
+    }
+    block_store.prune_tree(block.id());
+    assert_ne!(block_store.root().id(), lagging_block_store.root().id());
+
+    let proposers = vec![inactive_author, active_author];
+    let pe = LeaderReputation::new(proposers.clone(), block_store.clone());
+    let lagging_pe = LeaderReputation::new(proposers, lagging_block_store.clone());
+    for round in 1..100 {
+        assert_eq!(
+            pe.get_valid_proposers(round),
+            lagging_pe.get_valid_proposers(round)
+        );
+    }
+
+    // The proposals are checked against the history of their quorum certificate.
+    for round in 11..100 {
+        let proposal = inserter.insert_block(block.as_ref(), round);
+        let expected = if pe.get_valid_proposers(round) == vec![active_author] {
+            Some(proposal.as_ref().clone())
+        } else {
+            None
+        };
+        assert_eq!(pe.process_proposal(proposal.as_ref().clone()), expected);
+        assert_eq!(
+            lagging_pe.process_proposal(proposal.as_ref().clone()),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_leader_reputation_failing_proposer() {
+    // The failing author votes for all the blocks, but its rounds never get a block.
+    let block_store = build_empty_tree();
+    let active_signer = block_store.signer().clone();
+    let reference_block_store = build_empty_tree_with_custom_signing(active_signer.clone());
+    let failing_signer = ValidatorSigner::<Ed25519PrivateKey>::random([1u8; 32]);
+    let proposers = vec![active_signer.author(), failing_signer.author()];
+    let signers = vec![&active_signer, &failing_signer];
+
+    // Without any failure, both authors are elected in about half the rounds.
+    let mut reference_inserter = TreeInserter::new(reference_block_store.clone());
+    let mut block = reference_block_store.root();
+    for round in 1..=40 {
+        block = insert_block_certified_by(
+            &mut reference_inserter,
+            block.as_ref(),
+            round,
+            signers.clone(),
+        );
+    }
+    let reference_pe = LeaderReputation::new(proposers.clone(), reference_block_store.clone());
+    let failing_rounds: Vec<_> = (5..=40)
+        .filter(|round| reference_pe.get_valid_proposers(*round) == vec![failing_signer.author()])
+        .collect();
+
+    let mut inserter = TreeInserter::new(block_store.clone());
+    let mut block = block_store.root();
+    let mut num_blocks = 0;
+    for round in 1..=40 {
+        if !failing_rounds.contains(&round) {
+            block =
+                insert_block_certified_by(&mut inserter, block.as_ref(), round, signers.clone());
+            num_blocks += 1;
+        }
+    }
+    assert!(!failing_rounds.is_empty());
+    assert_eq!(num_blocks + failing_rounds.len(), 40);
+
+    let pe = LeaderReputation::new(proposers, block_store.clone());
+    let rounds = 45..245;
+    let reference_failing_rounds = rounds
+        .clone()
+        .filter(|round| reference_pe.get_valid_proposers(*round) == vec![failing_signer.author()])
+        .count();
+    let failing_rounds = rounds
+        .filter(|round| pe.get_valid_proposers(*round) == vec![failing_signer.author()])
+        .count();
+    assert!(reference_failing_rounds > 60);
+    assert!(failing_rounds < 40);
+}
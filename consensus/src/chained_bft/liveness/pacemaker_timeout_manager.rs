@@ -57,6 +57,16 @@ impl HighestTimeoutCertificates {
             self.highest_local_timeout_certificate.as_ref()
         }
     }
+
+    /// Return the highest timeout certificate gathered locally, if any
+    pub fn highest_local_timeout_certificate(&self) -> Option<&PacemakerTimeoutCertificate> {
+        self.highest_local_timeout_certificate.as_ref()
+    }
+
+    /// Return the highest timeout certificate received from another replica, if any
+    pub fn highest_received_timeout_certificate(&self) -> Option<&PacemakerTimeoutCertificate> {
+        self.highest_received_timeout_certificate.as_ref()
+    }
 }
 
 /// Manages the PacemakerTimeout structs received from replicas.
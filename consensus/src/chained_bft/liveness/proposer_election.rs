@@ -3,8 +3,9 @@
 
 use crate::chained_bft::{
     common::{Author, Round},
-    consensus_types::block::Block,
+    consensus_types::{block::Block, proposal_msg::ProposalMsg},
 };
+use crypto::vrf::ecvrf::Proof;
 
 /// ProposerElection incorporates the logic of choosing a leader among multiple candidates.
 /// We are open to a possibility for having multiple proposers per round, the ultimate choice
@@ -23,4 +24,23 @@ pub trait ProposerElection<T> {
     /// proposer election is going to notify the client about the chosen proposal via a dedicated
     /// channel (to be passed in constructor).
     fn process_proposal(&self, proposal: Block<T>) -> Option<Block<T>>;
+
+    /// Returns the proof that the local author may propose the given block, for the elections
+    /// requiring one (e.g., the VRF proposer). It is sent along with the block in the ProposalMsg.
+    fn prove_proposer(&self, _proposal: &Block<T>) -> Option<Proof> {
+        None
+    }
+
+    /// Notify proposer election about a new proposal msg: the elections requiring a proof of the
+    /// proposer verify the one carried by the msg, the others process its block.
+    fn process_proposal_msg(&self, proposal_msg: ProposalMsg<T>) -> Option<Block<T>> {
+        self.process_proposal(proposal_msg.proposal)
+    }
+
+    /// Notify proposer election that the wait for the proposals of the given round is over, for
+    /// the elections choosing among the proposals received within a bounded wait (e.g., the VRF
+    /// proposer). Returns the chosen proposal, if any.
+    fn choose_proposal(&self, _round: Round) -> Option<Block<T>> {
+        None
+    }
 }
@@ -0,0 +1,200 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    chained_bft::{
+        common::{Author, Payload, Round},
+        consensus_types::{block::Block, proposal_msg::ProposalMsg, quorum_cert::QuorumCert},
+        liveness::proposer_election::ProposerElection,
+    },
+    util::time_service::{SendTask, TimeService},
+};
+use channel;
+use crypto::{
+    hash::CryptoHash,
+    vrf::ecvrf::{Output, Proof, VRFPrivateKey, VRFPublicKey},
+};
+use failure::prelude::*;
+use logger::prelude::*;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// The VRF keys of the validators, which are distinct from their consensus keys.
+#[derive(Clone, Default)]
+pub struct VrfKeys {
+    /// The VRF private key of the local validator, if it runs for proposer.
+    pub private_key: Option<Arc<VRFPrivateKey>>,
+    /// The VRF public keys of the validators.
+    pub public_keys: Arc<HashMap<Author, VRFPublicKey>>,
+}
+
+/// The proposals received for the latest round.
+struct RoundProposals<T> {
+    round: Round,
+    // Whether the proposal of the round has been chosen already.
+    chosen: bool,
+    // The proposal with the lowest VRF output so far, along with its output.
+    best: Option<(Vec<u8>, Block<T>)>,
+}
+
+/// The VRF proposer lets all the proposers propose at every round, along with the proof of a VRF
+/// evaluated on the round and the hash of the quorum certificate their block extends. The voters
+/// wait for a bounded time after the first valid proposal of a round, then choose the proposal
+/// with the lowest VRF output. Unlike with the rotating proposer, the winner of a round is not
+/// known before the proposals are out, so it can't be targeted beforehand (e.g., with DoS). Note
+/// that a proposer knowing several quorum certificates it may extend gets one output per
+/// certificate, and can propose the block with the lowest one. Every proposer must have a VRF key.
+pub struct VrfProposer<T> {
+    // The validators allowed to propose (all honest replicas must agree on this)
+    proposers: Vec<Author>,
+    vrf_keys: VrfKeys,
+    time_service: Arc<dyn TimeService>,
+    // How long to wait for proposals after the first valid proposal of a round
+    proposal_wait: Duration,
+    // To send the rounds whose wait for proposals is over
+    proposal_wait_sender: channel::Sender<Round>,
+    round_proposals: Mutex<RoundProposals<T>>,
+}
+
+impl<T: Payload> VrfProposer<T> {
+    /// Fails if there is no proposer or if a proposer has no VRF key, since its proposals could
+    /// never be chosen.
+    pub fn new(
+        proposers: Vec<Author>,
+        vrf_keys: VrfKeys,
+        epoch: u64,
+        time_service: Arc<dyn TimeService>,
+        proposal_wait: Duration,
+        proposal_wait_sender: channel::Sender<Round>,
+    ) -> Result<Self> {
+        ensure!(!proposers.is_empty(), "No proposer in epoch {}", epoch);
+        if let Some(proposer) = proposers
+            .iter()
+            .find(|proposer| !vrf_keys.public_keys.contains_key(proposer))
+        {
+            bail!("No VRF key for proposer {} of epoch {}", proposer, epoch);
+        }
+        Ok(Self {
+            proposers,
+            vrf_keys,
+            time_service,
+            proposal_wait,
+            proposal_wait_sender,
+            round_proposals: Mutex::new(RoundProposals {
+                round: 0,
+                chosen: true,
+                best: None,
+            }),
+        })
+    }
+
+    /// The input of the VRF for a proposal of the given round extending the given quorum
+    /// certificate.
+    fn vrf_input(round: Round, quorum_cert: &QuorumCert) -> Vec<u8> {
+        let mut input = quorum_cert.ledger_info().ledger_info().hash().to_vec();
+        input.extend_from_slice(&round.to_le_bytes());
+        input
+    }
+
+    /// Verifies the VRF proof of the given proposal and returns its output.
+    fn verify_proof(&self, proposal_msg: &ProposalMsg<T>) -> Result<Vec<u8>> {
+        let proof = proposal_msg
+            .vrf_proof
+            .as_ref()
+            .ok_or_else(|| format_err!("{} does not carry a VRF proof", proposal_msg))?;
+        let public_key = self
+            .vrf_keys
+            .public_keys
+            .get(&proposal_msg.proposer())
+            .ok_or_else(|| format_err!("No VRF key for the proposer of {}", proposal_msg))?;
+        public_key.verify(
+            proof,
+            &Self::vrf_input(
+                proposal_msg.proposal.round(),
+                proposal_msg.proposal.quorum_cert(),
+            ),
+        )?;
+        Ok(Output::from(proof).to_bytes().to_vec())
+    }
+}
+
+impl<T: Payload> ProposerElection<T> for VrfProposer<T> {
+    fn is_valid_proposer(&self, author: Author, _round: Round) -> Option<Author> {
+        if self.proposers.contains(&author) {
+            Some(author)
+        } else {
+            None
+        }
+    }
+
+    fn get_valid_proposers(&self, _round: Round) -> Vec<Author> {
+        self.proposers.clone()
+    }
+
+    fn prove_proposer(&self, proposal: &Block<T>) -> Option<Proof> {
+        self.vrf_keys.private_key.as_ref().map(|private_key| {
+            private_key.prove(&Self::vrf_input(proposal.round(), proposal.quorum_cert()))
+        })
+    }
+
+    fn process_proposal(&self, _proposal: Block<T>) -> Option<Block<T>> {
+        // A proposal can't be chosen without its VRF proof.
+        None
+    }
+
+    fn process_proposal_msg(&self, proposal_msg: ProposalMsg<T>) -> Option<Block<T>> {
+        if self
+            .is_valid_proposer(proposal_msg.proposer(), proposal_msg.proposal.round())
+            .is_none()
+        {
+            return None;
+        }
+        let output = match self.verify_proof(&proposal_msg) {
+            Ok(output) => output,
+            Err(e) => {
+                warn!("Invalid VRF proof for {}: {:?}", proposal_msg, e);
+                return None;
+            }
+        };
+        let round = proposal_msg.proposal.round();
+        let mut round_proposals = self.round_proposals.lock().unwrap();
+        if round < round_proposals.round
+            || (round == round_proposals.round && round_proposals.chosen)
+        {
+            return None;
+        }
+        if round > round_proposals.round {
+            *round_proposals = RoundProposals {
+                round,
+                chosen: false,
+                best: None,
+            };
+            self.time_service.run_after(
+                self.proposal_wait,
+                SendTask::make(self.proposal_wait_sender.clone(), round),
+            );
+        }
+        // The ties (e.g., between the proposals of a proposer extending the same quorum
+        // certificate) are broken with the block ids, for all the voters to choose the same.
+        match &round_proposals.best {
+            Some((best_output, best_proposal))
+                if (&best_output[..], best_proposal.id())
+                    <= (&output[..], proposal_msg.proposal.id()) => {}
+            _ => round_proposals.best = Some((output, proposal_msg.proposal)),
+        }
+        // The proposal is chosen once the wait is over.
+        None
+    }
+
+    fn choose_proposal(&self, round: Round) -> Option<Block<T>> {
+        let mut round_proposals = self.round_proposals.lock().unwrap();
+        if round_proposals.round != round || round_proposals.chosen {
+            return None;
+        }
+        round_proposals.chosen = true;
+        round_proposals.best.take().map(|(_, proposal)| proposal)
+    }
+}
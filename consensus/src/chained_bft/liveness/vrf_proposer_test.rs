@@ -0,0 +1,169 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    chained_bft::{
+        consensus_types::{
+            block::Block, proposal_msg::ProposalMsg, quorum_cert::QuorumCert, sync_info::SyncInfo,
+        },
+        liveness::{
+            proposer_election::ProposerElection,
+            vrf_proposer_election::{VrfKeys, VrfProposer},
+        },
+        test_utils::TestPayload,
+    },
+    util::mock_time_service::SimulatedTimeService,
+};
+use channel;
+use crypto::{
+    ed25519::*,
+    vrf::ecvrf::{Output, VRFPrivateKey, VRFPublicKey},
+    Uniform,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use types::validator_signer::ValidatorSigner;
+
+#[test]
+fn test_vrf_proposer() {
+    // The last validator has no VRF key.
+    let consensus_private_keys: Vec<_> = (0..4)
+        .map(|i| compat::generate_keypair(&mut StdRng::from_seed([i; 32])).0)
+        .collect();
+    let signers: Vec<_> = consensus_private_keys
+        .iter()
+        .map(|private_key| ValidatorSigner::new(None, private_key.clone()))
+        .collect();
+    let vrf_private_keys: Vec<_> = (0..3)
+        .map(|i| {
+            Arc::new(VRFPrivateKey::generate_for_testing(&mut StdRng::from_seed(
+                [i + 10; 32],
+            )))
+        })
+        .collect();
+    let vrf_public_keys = Arc::new(
+        signers
+            .iter()
+            .zip(vrf_private_keys.iter())
+            .map(|(signer, private_key)| {
+                (signer.author(), VRFPublicKey::from(private_key.as_ref()))
+            })
+            .collect::<HashMap<_, _>>(),
+    );
+    let validators: Vec<_> = signers.iter().map(|signer| signer.author()).collect();
+    let proposers = validators[..3].to_vec();
+    let new_election = |private_key: Arc<VRFPrivateKey>| {
+        let (proposal_wait_sender, _proposal_wait_receiver) = channel::new_test(8);
+        VrfProposer::<TestPayload>::new(
+            proposers.clone(),
+            VrfKeys {
+                private_key: Some(private_key),
+                public_keys: Arc::clone(&vrf_public_keys),
+            },
+            1,
+            Arc::new(SimulatedTimeService::new()),
+            Duration::from_millis(100),
+            proposal_wait_sender,
+        )
+        .unwrap()
+    };
+    let elections: Vec<_> = vrf_private_keys.iter().cloned().map(new_election).collect();
+    let pe = &elections[0];
+
+    // All the proposers must have a VRF key.
+    for proposers in vec![validators.clone(), vec![]] {
+        let (proposal_wait_sender, _proposal_wait_receiver) = channel::new_test(8);
+        assert!(VrfProposer::<TestPayload>::new(
+            proposers,
+            VrfKeys {
+                private_key: None,
+                public_keys: Arc::clone(&vrf_public_keys),
+            },
+            1,
+            Arc::new(SimulatedTimeService::new()),
+            Duration::from_millis(100),
+            proposal_wait_sender,
+        )
+        .is_err());
+    }
+
+    // All the proposers may propose at any round.
+    for author in &proposers {
+        assert_eq!(pe.is_valid_proposer(*author, 1), Some(*author));
+    }
+    assert_eq!(pe.is_valid_proposer(validators[3], 1), None);
+    assert_eq!(pe.get_valid_proposers(1), proposers);
+
+    let genesis_block = Block::make_genesis_block();
+    let quorum_cert = QuorumCert::certificate_for_genesis();
+    let proposal_msgs: Vec<_> = signers
+        .iter()
+        .zip(elections.iter())
+        .map(|(signer, election)| {
+            let proposal =
+                Block::make_block(&genesis_block, vec![], 1, 1, quorum_cert.clone(), signer);
+            let vrf_proof = election.prove_proposer(&proposal);
+            ProposalMsg {
+                proposal,
+                sync_info: SyncInfo::new(quorum_cert.clone(), quorum_cert.clone(), None),
+                vrf_proof,
+            }
+        })
+        .collect();
+
+    // The output of a proposer only depends on the round and the quorum certificate its block
+    // extends.
+    let other_proposal = Block::make_block(
+        &genesis_block,
+        vec![42],
+        1,
+        1,
+        quorum_cert.clone(),
+        &signers[0],
+    );
+    assert_eq!(
+        elections[0].prove_proposer(&other_proposal),
+        proposal_msgs[0].vrf_proof
+    );
+
+    // The proposals without a valid proof are rejected.
+    let no_proof = ProposalMsg {
+        vrf_proof: None,
+        ..proposal_msgs[1].clone()
+    };
+    assert_eq!(pe.process_proposal_msg(no_proof), None);
+    let wrong_proof = ProposalMsg {
+        vrf_proof: proposal_msgs[2].vrf_proof.clone(),
+        ..proposal_msgs[1].clone()
+    };
+    assert_eq!(pe.process_proposal_msg(wrong_proof), None);
+    // A proof with the consensus key of the proposer is rejected.
+    let consensus_key_proof = ProposalMsg {
+        vrf_proof: new_election(Arc::new(VRFPrivateKey::from(&consensus_private_keys[1])))
+            .prove_proposer(&proposal_msgs[1].proposal),
+        ..proposal_msgs[1].clone()
+    };
+    assert_eq!(pe.process_proposal_msg(consensus_key_proof), None);
+    assert_eq!(pe.choose_proposal(1), None);
+
+    // The proposal with the lowest VRF output is chosen once the wait is over.
+    for proposal_msg in &proposal_msgs {
+        assert_eq!(pe.process_proposal_msg(proposal_msg.clone()), None);
+    }
+    let best_proposal = proposal_msgs
+        .iter()
+        .min_by_key(|proposal_msg| {
+            Output::from(proposal_msg.vrf_proof.as_ref().unwrap())
+                .to_bytes()
+                .to_vec()
+        })
+        .unwrap()
+        .proposal
+        .clone();
+    assert_eq!(pe.choose_proposal(2), None);
+    assert_eq!(pe.choose_proposal(1), Some(best_proposal));
+
+    // The round is over.
+    assert_eq!(pe.process_proposal_msg(proposal_msgs[0].clone()), None);
+    assert_eq!(pe.choose_proposal(1), None);
+}
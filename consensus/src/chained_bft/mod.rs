@@ -10,7 +10,10 @@ mod safety;
 mod block_storage;
 pub mod chained_bft_consensus_provider;
 pub use consensus_types::quorum_cert::QuorumCert;
+pub use consensusdb::inspector::{ConsensusDBInspector, RootCheck};
+pub use safety::safety_rules_node::SafetyRulesNode;
 mod chained_bft_smr;
+mod epoch_manager;
 mod event_processor;
 mod network;
 
@@ -28,11 +28,13 @@ use network::{
 use proto_conv::{FromProto, IntoProto};
 use protobuf::Message;
 use std::{
-    sync::Arc,
+    sync::{Arc, RwLock},
     time::{Duration, Instant},
 };
 use tokio::runtime::TaskExecutor;
-use types::{account_address::AccountAddress, validator_verifier::ValidatorVerifier};
+use types::{
+    account_address::AccountAddress, ledger_info::LedgerInfo, validator_verifier::ValidatorVerifier,
+};
 
 /// The response sent back from EventProcessor for the BlockRetrievalRequest.
 #[derive(Debug)]
@@ -94,8 +96,12 @@ pub struct ConsensusNetworkImpl {
     // Note that we do not support self rpc requests as it might cause infinite recursive calls.
     self_sender: channel::Sender<Result<Event<ConsensusMsg>, failure::Error>>,
     self_receiver: Option<channel::Receiver<Result<Event<ConsensusMsg>, failure::Error>>>,
-    peers: Arc<Vec<Author>>,
-    validator: Arc<ValidatorVerifier<Ed25519PublicKey>>,
+    // The validators of the current epoch, shared by all the clones and the network task.
+    peers: Arc<RwLock<Arc<Vec<Author>>>>,
+    validator: Arc<RwLock<Arc<ValidatorVerifier<Ed25519PublicKey>>>>,
+    // The ledger info ending the previous epoch, if the current epoch was started from it: the
+    // only epoch genesis accepted in the messages.
+    epoch_genesis: Arc<RwLock<Option<Arc<LedgerInfo>>>>,
 }
 
 impl Clone for ConsensusNetworkImpl {
@@ -106,8 +112,9 @@ impl Clone for ConsensusNetworkImpl {
             network_events: None,
             self_sender: self.self_sender.clone(),
             self_receiver: None,
-            peers: self.peers.clone(),
+            peers: Arc::clone(&self.peers),
             validator: Arc::clone(&self.validator),
+            epoch_genesis: Arc::clone(&self.epoch_genesis),
         }
     }
 }
@@ -127,11 +134,43 @@ impl ConsensusNetworkImpl {
             network_events: Some(network_events),
             self_sender,
             self_receiver: Some(self_receiver),
-            peers,
-            validator,
+            peers: Arc::new(RwLock::new(peers)),
+            validator: Arc::new(RwLock::new(validator)),
+            epoch_genesis: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Switches to the validators of a new epoch: from now on, the messages are broadcast to
+    /// these peers and verified with this validator by all the clones of this network.
+    pub fn update_validators(
+        &self,
+        peers: Arc<Vec<Author>>,
+        validator: Arc<ValidatorVerifier<Ed25519PublicKey>>,
+    ) {
+        *self.peers.write().unwrap() = peers;
+        *self.validator.write().unwrap() = validator;
+    }
+
+    fn peers(&self) -> Arc<Vec<Author>> {
+        Arc::clone(&self.peers.read().unwrap())
+    }
+
+    /// The validator of the current epoch.
+    pub fn validator(&self) -> Arc<ValidatorVerifier<Ed25519PublicKey>> {
+        Arc::clone(&self.validator.read().unwrap())
+    }
+
+    /// Sets the ledger info ending the previous epoch that the current epoch was started from, if
+    /// any: the messages carrying the genesis of the current epoch are only accepted if it is
+    /// certified by this ledger info.
+    pub fn update_epoch_genesis(&self, epoch_genesis: Option<LedgerInfo>) {
+        *self.epoch_genesis.write().unwrap() = epoch_genesis.map(Arc::new);
+    }
+
+    fn epoch_genesis(&self) -> Option<Arc<LedgerInfo>> {
+        self.epoch_genesis.read().unwrap().clone()
+    }
+
     /// Establishes the initial connections with the peers and returns the receivers.
     pub fn start<T: Payload>(&mut self, executor: &TaskExecutor) -> NetworkReceivers<T> {
         let (proposal_tx, proposal_rx) = channel::new(1_024, &counters::PENDING_PROPOSAL);
@@ -152,6 +191,7 @@ impl ConsensusNetworkImpl {
             .expect("[consensus]: self receiver is already taken");
         let all_events = select(network_events, own_msgs);
         let validator = Arc::clone(&self.validator);
+        let epoch_genesis = Arc::clone(&self.epoch_genesis);
         executor.spawn(
             NetworkTask {
                 proposal_tx,
@@ -161,6 +201,7 @@ impl ConsensusNetworkImpl {
                 sync_info_tx,
                 all_events,
                 validator,
+                epoch_genesis,
             }
             .run()
             .boxed()
@@ -200,9 +241,17 @@ impl ConsensusNetworkImpl {
             .request_block(from, req_msg, timeout)
             .await?;
         let mut blocks = vec![];
+        let validator = self.validator();
+        let epoch_genesis = self.epoch_genesis();
         for block in res_block.take_blocks().into_iter() {
             if let Ok(block) = Block::from_proto(block) {
-                if block.verify(self.validator.as_ref()).is_err() {
+                if block
+                    .verify(
+                        validator.as_ref(),
+                        epoch_genesis.as_ref().map(AsRef::as_ref),
+                    )
+                    .is_err()
+                {
                     return Err(BlockRetrievalFailure::InvalidSignature);
                 }
                 blocks.push(block);
@@ -237,7 +286,7 @@ impl ConsensusNetworkImpl {
     }
 
     async fn broadcast(&mut self, msg: ConsensusMsg) {
-        for peer in self.peers.iter() {
+        for peer in self.peers().iter() {
             if self.author == *peer {
                 let self_msg = Event::Message((self.author, msg.clone()));
                 if let Err(err) = self.self_sender.send(Ok(self_msg)).await {
@@ -315,7 +364,8 @@ struct NetworkTask<T, S> {
     timeout_msg_tx: channel::Sender<TimeoutMsg>,
     sync_info_tx: channel::Sender<(SyncInfo, AccountAddress)>,
     all_events: S,
-    validator: Arc<ValidatorVerifier<Ed25519PublicKey>>,
+    validator: Arc<RwLock<Arc<ValidatorVerifier<Ed25519PublicKey>>>>,
+    epoch_genesis: Arc<RwLock<Option<Arc<LedgerInfo>>>>,
 }
 
 impl<T, S> NetworkTask<T, S>
@@ -323,6 +373,14 @@ where
     S: Stream<Item = Result<Event<ConsensusMsg>, failure::Error>> + Unpin,
     T: Payload,
 {
+    fn validator(&self) -> Arc<ValidatorVerifier<Ed25519PublicKey>> {
+        Arc::clone(&self.validator.read().unwrap())
+    }
+
+    fn epoch_genesis(&self) -> Option<Arc<LedgerInfo>> {
+        self.epoch_genesis.read().unwrap().clone()
+    }
+
     pub async fn run(mut self) {
         while let Some(Ok(message)) = self.all_events.next().await {
             match message {
@@ -366,13 +424,19 @@ where
 
     async fn process_proposal<'a>(&'a mut self, msg: &'a mut ConsensusMsg) -> failure::Result<()> {
         let proposal = ProposalMsg::<T>::from_proto(msg.take_proposal())?;
-        proposal.verify(self.validator.as_ref()).map_err(|e| {
-            security_log(SecurityEvent::InvalidConsensusProposal)
-                .error(&e)
-                .data(&proposal)
-                .log();
-            e
-        })?;
+        let epoch_genesis = self.epoch_genesis();
+        proposal
+            .verify(
+                self.validator().as_ref(),
+                epoch_genesis.as_ref().map(AsRef::as_ref),
+            )
+            .map_err(|e| {
+                security_log(SecurityEvent::InvalidConsensusProposal)
+                    .error(&e)
+                    .data(&proposal)
+                    .log();
+                e
+            })?;
         debug!("Received proposal {}", proposal);
         self.proposal_tx.send(proposal).await?;
         Ok(())
@@ -381,7 +445,7 @@ where
     async fn process_vote<'a>(&'a mut self, msg: &'a mut ConsensusMsg) -> failure::Result<()> {
         let vote = VoteMsg::from_proto(msg.take_vote())?;
         debug!("Received {}", vote);
-        vote.verify(self.validator.as_ref()).map_err(|e| {
+        vote.verify(self.validator().as_ref()).map_err(|e| {
             security_log(SecurityEvent::InvalidConsensusVote)
                 .error(&e)
                 .data(&vote)
@@ -397,7 +461,7 @@ where
         msg: &'a mut ConsensusMsg,
     ) -> failure::Result<()> {
         let timeout_msg = TimeoutMsg::from_proto(msg.take_timeout_msg())?;
-        timeout_msg.verify(self.validator.as_ref()).map_err(|e| {
+        timeout_msg.verify(self.validator().as_ref()).map_err(|e| {
             security_log(SecurityEvent::InvalidConsensusRound)
                 .error(&e)
                 .data(&timeout_msg)
@@ -414,13 +478,19 @@ where
         peer: AccountAddress,
     ) -> failure::Result<()> {
         let sync_info = SyncInfo::from_proto(msg.take_sync_info())?;
-        sync_info.verify(self.validator.as_ref()).map_err(|e| {
-            security_log(SecurityEvent::InvalidSyncInfoMsg)
-                .error(&e)
-                .data(&sync_info)
-                .log();
-            e
-        })?;
+        let epoch_genesis = self.epoch_genesis();
+        sync_info
+            .verify(
+                self.validator().as_ref(),
+                epoch_genesis.as_ref().map(AsRef::as_ref),
+            )
+            .map_err(|e| {
+                security_log(SecurityEvent::InvalidSyncInfoMsg)
+                    .error(&e)
+                    .data(&sync_info)
+                    .log();
+                e
+            })?;
         self.sync_info_tx.send((sync_info, peer)).await?;
         Ok(())
     }
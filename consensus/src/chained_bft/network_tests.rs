@@ -368,6 +368,7 @@ fn test_network_api() {
     let proposal = ProposalMsg {
         proposal: Block::make_block(&previous_block, 0, 1, 0, previous_qc.clone(), &signers[0]),
         sync_info: SyncInfo::new(previous_qc.clone(), previous_qc.clone(), None),
+        vrf_proof: None,
     };
     block_on(async move {
         nodes[0].send_vote(vote.clone(), peers[2..5].to_vec()).await;
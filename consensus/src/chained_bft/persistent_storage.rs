@@ -51,6 +51,15 @@ pub trait PersistentStorage<T>: PersistentLivenessStorage + Send + Sync {
     /// Persist the consensus state.
     fn save_consensus_state(&self, state: ConsensusState) -> Result<()>;
 
+    /// Replaces all the blocks and quorum certs by the genesis block of a new epoch and its quorum
+    /// cert, and resets the consensus state and the highest timeout certificates atomically.
+    /// Returns the recovery data to start the new epoch with.
+    fn reset_to_epoch_genesis(
+        &self,
+        genesis_block: Block<T>,
+        genesis_quorum_cert: QuorumCert,
+    ) -> Result<RecoveryData<T>>;
+
     /// When the node restart, construct the instance and returned the data read from db.
     /// This could guarantee we only read once during start, and we would panic if the
     /// read fails.
@@ -148,6 +157,10 @@ impl<T: Payload> RecoveryData<T> {
         &self.highest_timeout_certificates
     }
 
+    pub fn root_block(&self) -> &Block<T> {
+        &self.root.0
+    }
+
     pub fn root_ledger_info(&self) -> QuorumCert {
         self.root.2.clone()
     }
@@ -307,6 +320,29 @@ impl<T: Payload> PersistentStorage<T> for StorageWriteProxy {
         self.db.save_state(to_vec_named(&state)?)
     }
 
+    fn reset_to_epoch_genesis(
+        &self,
+        genesis_block: Block<T>,
+        genesis_quorum_cert: QuorumCert,
+    ) -> Result<RecoveryData<T>> {
+        let state = ConsensusState::default();
+        let highest_timeout_certificates = HighestTimeoutCertificates::default();
+        self.db.reset(
+            vec![genesis_block.clone()],
+            vec![genesis_quorum_cert.clone()],
+            to_vec_named(&state)?,
+            to_vec_named(&highest_timeout_certificates)?,
+        )?;
+        let root_id = genesis_block.id();
+        RecoveryData::new(
+            state,
+            vec![genesis_block],
+            vec![genesis_quorum_cert],
+            root_id,
+            highest_timeout_certificates,
+        )
+    }
+
     fn start(config: &NodeConfig) -> (Arc<Self>, RecoveryData<T>) {
         info!("Start consensus recovery.");
         let read_client = create_storage_read_client(config);
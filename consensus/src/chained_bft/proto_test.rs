@@ -11,8 +11,9 @@ use crate::{
     },
     state_replication::ExecutedState,
 };
-use crypto::{ed25519::Ed25519PrivateKey, HashValue};
+use crypto::{ed25519::Ed25519PrivateKey, vrf::ecvrf::VRFPrivateKey, HashValue, Uniform};
 use proto_conv::test_helper::assert_protobuf_encode_decode;
+use rand::{rngs::StdRng, SeedableRng};
 use types::validator_signer::ValidatorSigner;
 
 #[test]
@@ -27,6 +28,14 @@ fn test_proto_convert_proposal() {
     let proposal = ProposalMsg {
         proposal: Block::<u64>::make_genesis_block(),
         sync_info: SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), None),
+        vrf_proof: None,
+    };
+    assert_protobuf_encode_decode(&proposal);
+
+    let vrf_private_key = VRFPrivateKey::generate_for_testing(&mut StdRng::from_seed([0; 32]));
+    let proposal = ProposalMsg {
+        vrf_proof: Some(vrf_private_key.prove(b"alpha")),
+        ..proposal
     };
     assert_protobuf_encode_decode(&proposal);
 }
@@ -0,0 +1,55 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    chained_bft::{
+        common::Round,
+        consensus_types::{
+            block::Block, quorum_cert::QuorumCert, sync_info::SyncInfo, timeout_msg::TimeoutMsg,
+        },
+        safety::{safety_rules::ConsensusState, vote_msg::VoteMsg},
+    },
+    state_replication::ExecutedState,
+};
+use failure::Result;
+use std::sync::Arc;
+use types::ledger_info::LedgerInfo;
+
+/// ConsensusSafetyRules is the interface of the event processor to the safety rules (see
+/// `SafetyRules`): they keep the consensus state of the current epoch and sign all the messages of
+/// the validator, i.e., its proposals, its timeouts and only the votes that respect the voting
+/// rules. The implementations may run the rules in the consensus process or in a separate process
+/// owning the consensus key, which keeps the validator from signing against the rules even if the
+/// consensus process is compromised.
+/// All the functions may fail if the safety rules can't be reached.
+pub trait ConsensusSafetyRules<T>: Send + Sync {
+    /// Learn about a new quorum certificate, which may update the preferred block round.
+    fn update(&mut self, qc: &QuorumCert) -> Result<()>;
+
+    /// Sign the given proposal of the validator, unless it already voted at its round.
+    fn sign_proposal(&self, proposal: Block<T>) -> Result<Block<T>>;
+
+    /// Stop voting at the given round and sign the timeout of the validator at this round, which
+    /// carries the given vote and sync info. The new state is returned along with it if the last
+    /// vote round increased, to be persisted before the timeout is sent.
+    fn sign_timeout(
+        &mut self,
+        round: Round,
+        vote: Option<VoteMsg>,
+        sync_info: SyncInfo,
+    ) -> Result<(TimeoutMsg, Option<ConsensusState>)>;
+
+    /// Return the up-to-date consensus state.
+    fn consensus_state(&self) -> Result<ConsensusState>;
+
+    /// Check the voting rules for the given proposal and sign a vote for it if they hold, with the
+    /// given ledger info computed for the block of the commit rule (see
+    /// `BlockStore::ledger_info_placeholder`). The consensus state after the vote is returned along
+    /// with it, to be persisted before the vote is sent.
+    fn construct_and_sign_vote(
+        &mut self,
+        proposed_block: Arc<Block<T>>,
+        executed_state: ExecutedState,
+        ledger_info_placeholder: LedgerInfo,
+    ) -> Result<(VoteMsg, ConsensusState)>;
+}
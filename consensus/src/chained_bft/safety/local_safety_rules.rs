@@ -0,0 +1,89 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    chained_bft::{
+        common::{Payload, Round},
+        consensus_types::{
+            block::Block,
+            quorum_cert::QuorumCert,
+            sync_info::SyncInfo,
+            timeout_msg::{PacemakerTimeout, TimeoutMsg},
+        },
+        safety::{
+            consensus_safety_rules::ConsensusSafetyRules,
+            safety_rules::{ConsensusState, SafetyRules},
+            vote_msg::VoteMsg,
+        },
+    },
+    state_replication::ExecutedState,
+};
+use crypto::ed25519::*;
+use failure::Result;
+use std::sync::Arc;
+use types::{ledger_info::LedgerInfo, validator_signer::ValidatorSigner};
+
+/// The safety rules running in the consensus process: the messages are signed with the consensus
+/// key held by the process, and the consensus state is persisted by the caller.
+pub struct LocalSafetyRules {
+    safety_rules: SafetyRules,
+    signer: Arc<ValidatorSigner<Ed25519PrivateKey>>,
+}
+
+impl LocalSafetyRules {
+    pub fn new(state: ConsensusState, signer: Arc<ValidatorSigner<Ed25519PrivateKey>>) -> Self {
+        Self {
+            safety_rules: SafetyRules::new(state),
+            signer,
+        }
+    }
+}
+
+impl<T: Payload> ConsensusSafetyRules<T> for LocalSafetyRules {
+    fn update(&mut self, qc: &QuorumCert) -> Result<()> {
+        self.safety_rules.update(qc);
+        Ok(())
+    }
+
+    fn sign_proposal(&self, proposal: Block<T>) -> Result<Block<T>> {
+        self.safety_rules.proposal_rule(proposal.round())?;
+        proposal.sign_proposal(self.signer.author(), |hash| self.signer.sign_message(hash))
+    }
+
+    fn sign_timeout(
+        &mut self,
+        round: Round,
+        vote: Option<VoteMsg>,
+        sync_info: SyncInfo,
+    ) -> Result<(TimeoutMsg, Option<ConsensusState>)> {
+        let consensus_state = self.safety_rules.increase_last_vote_round(round);
+        let pacemaker_timeout =
+            PacemakerTimeout::new_with_signer(round, self.signer.author(), vote, |hash| {
+                self.signer.sign_message(hash)
+            })?;
+        let timeout_msg = TimeoutMsg::new_with_signer(sync_info, pacemaker_timeout, |hash| {
+            self.signer.sign_message(hash)
+        })?;
+        Ok((timeout_msg, consensus_state))
+    }
+
+    fn consensus_state(&self) -> Result<ConsensusState> {
+        Ok(self.safety_rules.consensus_state())
+    }
+
+    fn construct_and_sign_vote(
+        &mut self,
+        proposed_block: Arc<Block<T>>,
+        executed_state: ExecutedState,
+        ledger_info_placeholder: LedgerInfo,
+    ) -> Result<(VoteMsg, ConsensusState)> {
+        let vote_info = self.safety_rules.voting_rule(proposed_block)?;
+        let vote = vote_info.sign_vote(
+            self.signer.author(),
+            executed_state,
+            ledger_info_placeholder,
+            |hash| self.signer.sign_message(hash),
+        )?;
+        Ok((vote, vote_info.consensus_state().clone()))
+    }
+}
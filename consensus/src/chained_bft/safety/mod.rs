@@ -1,5 +1,11 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub(crate) mod consensus_safety_rules;
+pub(crate) mod local_safety_rules;
+pub(crate) mod process_safety_rules;
+pub(crate) mod proto;
 pub(crate) mod safety_rules;
+pub(crate) mod safety_rules_node;
+pub(crate) mod safety_rules_service;
 pub(crate) mod vote_msg;
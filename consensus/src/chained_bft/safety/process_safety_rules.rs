@@ -0,0 +1,150 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    chained_bft::{
+        common::{Payload, Round},
+        consensus_types::{
+            block::Block, quorum_cert::QuorumCert, sync_info::SyncInfo, timeout_msg::TimeoutMsg,
+        },
+        safety::{
+            consensus_safety_rules::ConsensusSafetyRules,
+            proto::{
+                safety_rules::{
+                    ConstructAndSignVoteRequest, GetConsensusStateRequest, SignProposalRequest,
+                    SignTimeoutRequest, StartNewEpochRequest, UpdateRequest,
+                },
+                safety_rules_grpc::SafetyRulesClient,
+            },
+            safety_rules::ConsensusState,
+            vote_msg::VoteMsg,
+        },
+    },
+    state_replication::ExecutedState,
+};
+use crypto::ed25519::*;
+use failure::prelude::*;
+use proto_conv::{FromProto, FromProtoBytes, IntoProtoBytes};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use types::ledger_info::{LedgerInfo, LedgerInfoWithSignatures};
+
+/// The safety rules running in a separate process (see `SafetyRulesService`), which owns the
+/// consensus key and persists the consensus state: the consensus process only gets the messages
+/// that respect the rules, and never signs any. The requests are made on behalf of the given
+/// epoch.
+pub struct ProcessSafetyRules {
+    client: Arc<SafetyRulesClient>,
+    epoch: u64,
+    // The ledger info ending the previous epoch if the epoch starts from it, which the safety
+    // rules need to start the epoch.
+    epoch_genesis: Option<LedgerInfoWithSignatures<Ed25519Signature>>,
+    epoch_started: AtomicBool,
+}
+
+impl ProcessSafetyRules {
+    pub fn new(
+        client: Arc<SafetyRulesClient>,
+        epoch: u64,
+        epoch_genesis: Option<LedgerInfoWithSignatures<Ed25519Signature>>,
+    ) -> Self {
+        let epoch_started = AtomicBool::new(epoch_genesis.is_none());
+        Self {
+            client,
+            epoch,
+            epoch_genesis,
+            epoch_started,
+        }
+    }
+
+    /// Starts the epoch in the safety rules before the first request, if it starts from the
+    /// ledger info ending the previous one.
+    fn start_epoch(&self) -> Result<()> {
+        if self.epoch_started.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        if let Some(ledger_info) = &self.epoch_genesis {
+            let mut req = StartNewEpochRequest::new();
+            req.set_ledger_info(ledger_info.clone().into_proto_bytes()?);
+            self.client.start_new_epoch(&req)?;
+        }
+        self.epoch_started.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+impl<T: Payload> ConsensusSafetyRules<T> for ProcessSafetyRules {
+    fn update(&mut self, qc: &QuorumCert) -> Result<()> {
+        self.start_epoch()?;
+        let mut req = UpdateRequest::new();
+        req.set_epoch(self.epoch);
+        req.set_quorum_cert(qc.clone().into_proto_bytes()?);
+        self.client.update(&req)?;
+        Ok(())
+    }
+
+    fn sign_proposal(&self, proposal: Block<T>) -> Result<Block<T>> {
+        self.start_epoch()?;
+        let mut req = SignProposalRequest::new();
+        req.set_epoch(self.epoch);
+        req.set_proposal(proposal.into_proto_bytes()?);
+        let response = self.client.sign_proposal(&req)?;
+        Block::from_proto_bytes(response.get_proposal())
+    }
+
+    fn sign_timeout(
+        &mut self,
+        round: Round,
+        vote: Option<VoteMsg>,
+        sync_info: SyncInfo,
+    ) -> Result<(TimeoutMsg, Option<ConsensusState>)> {
+        self.start_epoch()?;
+        let mut req = SignTimeoutRequest::new();
+        req.set_epoch(self.epoch);
+        req.set_round(round);
+        if let Some(vote) = vote {
+            req.set_vote(vote.into_proto_bytes()?);
+        }
+        req.set_sync_info(sync_info.into_proto_bytes()?);
+        let mut response = self.client.sign_timeout(&req)?;
+        let consensus_state = if response.has_consensus_state() {
+            Some(ConsensusState::from_proto(response.take_consensus_state())?)
+        } else {
+            None
+        };
+        Ok((
+            TimeoutMsg::from_proto_bytes(response.get_timeout_msg())?,
+            consensus_state,
+        ))
+    }
+
+    fn consensus_state(&self) -> Result<ConsensusState> {
+        self.start_epoch()?;
+        let mut req = GetConsensusStateRequest::new();
+        req.set_epoch(self.epoch);
+        let mut response = self.client.get_consensus_state(&req)?;
+        ConsensusState::from_proto(response.take_consensus_state())
+    }
+
+    fn construct_and_sign_vote(
+        &mut self,
+        proposed_block: Arc<Block<T>>,
+        executed_state: ExecutedState,
+        ledger_info_placeholder: LedgerInfo,
+    ) -> Result<(VoteMsg, ConsensusState)> {
+        self.start_epoch()?;
+        let mut req = ConstructAndSignVoteRequest::new();
+        req.set_epoch(self.epoch);
+        req.set_proposed_block(proposed_block.as_ref().clone().into_proto_bytes()?);
+        req.set_executed_state_id(executed_state.state_id.to_vec());
+        req.set_executed_version(executed_state.version);
+        req.set_ledger_info_placeholder(ledger_info_placeholder.into_proto_bytes()?);
+        let mut response = self.client.construct_and_sign_vote(&req)?;
+        Ok((
+            VoteMsg::from_proto_bytes(response.get_vote())?,
+            ConsensusState::from_proto(response.take_consensus_state())?,
+        ))
+    }
+}
@@ -3,18 +3,22 @@
 
 use crate::{
     chained_bft::{
-        common::{Payload, Round},
+        common::{Author, Payload, Round},
         consensus_types::{block::Block, quorum_cert::QuorumCert},
+        safety::{proto::safety_rules::ConsensusState as ProtoConsensusState, vote_msg::VoteMsg},
     },
     counters,
+    state_replication::ExecutedState,
 };
 
-use crypto::HashValue;
+use crypto::{ed25519::*, HashValue};
+use proto_conv::{FromProto, IntoProto};
 use serde::{Deserialize, Serialize};
 use std::{
     fmt::{Display, Formatter},
     sync::Arc,
 };
+use types::ledger_info::LedgerInfo;
 
 #[cfg(test)]
 #[path = "safety_rules_test.rs"]
@@ -48,32 +52,39 @@ pub struct VoteInfo {
 }
 
 impl VoteInfo {
-    pub fn proposal_id(&self) -> HashValue {
-        self.proposal_id
-    }
-
     pub fn consensus_state(&self) -> &ConsensusState {
         &self.consensus_state
     }
 
+    #[cfg(test)]
     pub fn potential_commit_id(&self) -> Option<HashValue> {
         self.potential_commit_id
     }
 
-    pub fn parent_block_id(&self) -> HashValue {
-        self.parent_block_id
-    }
-
-    pub fn parent_block_round(&self) -> Round {
-        self.parent_block_round
-    }
-
-    pub fn grandparent_block_id(&self) -> HashValue {
-        self.grandparent_block_id
-    }
-
-    pub fn grandparent_block_round(&self) -> Round {
-        self.grandparent_block_round
+    /// Returns the vote of the given author for the proposal, with the LedgerInfo signed by the
+    /// given function.
+    pub fn sign_vote<F>(
+        &self,
+        author: Author,
+        executed_state: ExecutedState,
+        ledger_info_placeholder: LedgerInfo,
+        sign: F,
+    ) -> failure::Result<VoteMsg>
+    where
+        F: FnOnce(HashValue) -> failure::Result<Ed25519Signature>,
+    {
+        VoteMsg::new_with_signer(
+            self.proposal_id,
+            executed_state,
+            self.proposal_round,
+            self.parent_block_id,
+            self.parent_block_round,
+            self.grandparent_block_id,
+            self.grandparent_block_round,
+            author,
+            ledger_info_placeholder,
+            sign,
+        )
     }
 }
 
@@ -131,6 +142,28 @@ impl Display for ConsensusState {
     }
 }
 
+impl IntoProto for ConsensusState {
+    type ProtoType = ProtoConsensusState;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        proto.set_last_vote_round(self.last_vote_round);
+        proto.set_preferred_block_round(self.preferred_block_round);
+        proto
+    }
+}
+
+impl FromProto for ConsensusState {
+    type ProtoType = ProtoConsensusState;
+
+    fn from_proto(object: Self::ProtoType) -> failure::Result<Self> {
+        Ok(Self {
+            last_vote_round: object.get_last_vote_round(),
+            preferred_block_round: object.get_preferred_block_round(),
+        })
+    }
+}
+
 impl ConsensusState {
     #[cfg(test)]
     pub fn new(last_vote_round: Round, preferred_block_round: Round) -> Self {
@@ -202,8 +235,7 @@ impl SafetyRules {
 
     /// Check if a one-chain at round r+2 causes a commit at round r and return the committed
     /// block id at round r if possible
-    fn commit_rule_for_certified_block(
-        &self,
+    pub fn commit_rule_for_certified_block(
         certified_block_qc: &QuorumCert,
         certified_block_round: u64,
     ) -> Option<HashValue> {
@@ -229,6 +261,18 @@ impl SafetyRules {
         self.state.set_last_vote_round(round)
     }
 
+    /// Checks that the validator may propose at the given round: it never proposes at a round it
+    /// already voted at, so that it doesn't sign another proposal once it voted for one.
+    pub fn proposal_rule(&self, proposal_round: Round) -> Result<(), ProposalReject> {
+        if proposal_round <= self.state.last_vote_round() {
+            return Err(ProposalReject::OldProposal {
+                proposal_round,
+                last_vote_round: self.state.last_vote_round(),
+            });
+        }
+        Ok(())
+    }
+
     /// Clones the up-to-date state of consensus (for monitoring / debugging purposes)
     pub fn consensus_state(&self) -> ConsensusState {
         self.state.clone()
@@ -244,37 +288,48 @@ impl SafetyRules {
         &mut self,
         proposed_block: Arc<Block<T>>,
     ) -> Result<VoteInfo, ProposalReject> {
-        if proposed_block.round() <= self.state.last_vote_round() {
+        self.voting_rule_for_proposal(
+            proposed_block.id(),
+            proposed_block.round(),
+            proposed_block.quorum_cert(),
+        )
+    }
+
+    /// Same as `voting_rule`, given the id, the round and the quorum certificate of the proposed
+    /// block.
+    pub fn voting_rule_for_proposal(
+        &mut self,
+        proposal_id: HashValue,
+        proposal_round: Round,
+        quorum_cert: &QuorumCert,
+    ) -> Result<VoteInfo, ProposalReject> {
+        if proposal_round <= self.state.last_vote_round() {
             return Err(ProposalReject::OldProposal {
-                proposal_round: proposed_block.round(),
+                proposal_round,
                 last_vote_round: self.state.last_vote_round(),
             });
         }
 
-        let respects_preferred_block = proposed_block.quorum_cert().certified_block_round()
-            >= self.state.preferred_block_round();
+        let respects_preferred_block =
+            quorum_cert.certified_block_round() >= self.state.preferred_block_round();
         if respects_preferred_block {
-            self.state.set_last_vote_round(proposed_block.round());
+            self.state.set_last_vote_round(proposal_round);
 
             // If the vote for the given proposal is gathered into QC, then this QC might eventually
             // commit another block following the rules defined in
             // `commit_rule_for_certified_block()` function.
-            let potential_commit_id = self.commit_rule_for_certified_block(
-                proposed_block.quorum_cert(),
-                proposed_block.round(),
-            );
+            let potential_commit_id =
+                Self::commit_rule_for_certified_block(quorum_cert, proposal_round);
 
             Ok(VoteInfo {
-                proposal_id: proposed_block.id(),
-                proposal_round: proposed_block.round(),
+                proposal_id,
+                proposal_round,
                 consensus_state: self.state.clone(),
                 potential_commit_id,
-                parent_block_id: proposed_block.quorum_cert().certified_block_id(),
-                parent_block_round: proposed_block.quorum_cert().certified_block_round(),
-                grandparent_block_id: proposed_block.quorum_cert().certified_parent_block_id(),
-                grandparent_block_round: proposed_block
-                    .quorum_cert()
-                    .certified_parent_block_round(),
+                parent_block_id: quorum_cert.certified_block_id(),
+                parent_block_round: quorum_cert.certified_block_round(),
+                grandparent_block_id: quorum_cert.certified_parent_block_id(),
+                grandparent_block_round: quorum_cert.certified_parent_block_round(),
             })
         } else {
             Err(ProposalReject::ProposalRoundLowerThenPreferredBlock {
@@ -0,0 +1,95 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::chained_bft::safety::{
+    proto::safety_rules_grpc, safety_rules_service::SafetyRulesService,
+};
+use config::config::NodeConfig;
+use failure::prelude::*;
+use grpc_helpers::spawn_service_thread;
+use grpcio::{ChannelBuilder, EnvBuilder};
+use logger::prelude::*;
+use secret_service::{
+    proto::secret_service_grpc::SecretServiceClient, secret_service_client::ConsensusKeyManager,
+};
+use std::{convert::TryFrom, sync::Arc, thread};
+use types::{
+    account_address::AccountAddress, transaction::SignedTransaction,
+    validator_verifier::ValidatorVerifier,
+};
+
+/// The safety rules node runs the safety rules of a validator in their own process, serving the
+/// consensus process on the safety rules port of the consensus config. The consensus key of the
+/// config is imported into the secret service of the config, which signs all the messages, and the
+/// state of the rules is persisted in the storage directory. The validators of the first epoch are
+/// the trusted peers of the config, and the node fails to start if the consensus key isn't the key
+/// of the validator among the validators of the current epoch.
+pub struct SafetyRulesNode {
+    node_config: NodeConfig,
+}
+
+impl SafetyRulesNode {
+    /// Instantiates the node with a config file.
+    pub fn new(node_config: NodeConfig) -> Self {
+        Self { node_config }
+    }
+
+    /// Starts the safety rules service.
+    pub fn run(&mut self) -> Result<()> {
+        info!("Starting safety rules node");
+
+        let author = AccountAddress::try_from(self.node_config.base.peer_id.clone())?;
+        let env = Arc::new(EnvBuilder::new().name_prefix("grpc-saf-sec-").build());
+        let secret_service = SecretServiceClient::new(ChannelBuilder::new(env).connect(&format!(
+            "{}:{}",
+            self.node_config.secret_service.address,
+            self.node_config.secret_service.secret_service_port
+        )));
+        let consensus_private_key = self
+            .node_config
+            .base
+            .peer_keypairs
+            .take_consensus_private()
+            .ok_or_else(|| {
+                format_err!(
+                    "Failed to move a Consensus private key from a NodeConfig, key absent or \
+                     already read"
+                )
+            })?;
+        let key_manager =
+            ConsensusKeyManager::import(Arc::new(secret_service), consensus_private_key)?;
+        info!(
+            "Consensus public key of the validator: {:?}",
+            key_manager.get_consensus_public_key()?
+        );
+
+        let initial_validator = ValidatorVerifier::new(
+            self.node_config
+                .base
+                .trusted_peers
+                .get_trusted_consensus_peers(),
+        );
+        let handle = SafetyRulesService::<Vec<SignedTransaction>>::new(
+            author,
+            key_manager,
+            self.node_config.storage.get_dir(),
+            initial_validator,
+        )?;
+        let service = safety_rules_grpc::create_safety_rules(handle);
+        let _safety_rules_handle = spawn_service_thread(
+            service,
+            self.node_config.consensus.safety_rules_address().clone(),
+            self.node_config.consensus.safety_rules_port(),
+            "safety_rules",
+        );
+
+        info!(
+            "Started safety rules node on port {}",
+            self.node_config.consensus.safety_rules_port()
+        );
+
+        loop {
+            thread::park();
+        }
+    }
+}
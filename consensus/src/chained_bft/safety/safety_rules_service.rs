@@ -0,0 +1,521 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    chained_bft::{
+        common::{Author, Payload, Round},
+        consensus_types::{
+            block::Block,
+            quorum_cert::QuorumCert,
+            sync_info::SyncInfo,
+            timeout_msg::{PacemakerTimeout, TimeoutMsg},
+        },
+        safety::{
+            proto::{
+                safety_rules::{
+                    ConstructAndSignVoteRequest, ConstructAndSignVoteResponse,
+                    GetConsensusStateRequest, GetConsensusStateResponse, SignProposalRequest,
+                    SignProposalResponse, SignTimeoutRequest, SignTimeoutResponse,
+                    StartNewEpochRequest, StartNewEpochResponse, UpdateRequest, UpdateResponse,
+                },
+                safety_rules_grpc,
+            },
+            safety_rules::{ConsensusState, SafetyRules},
+            vote_msg::VoteMsg,
+        },
+    },
+    state_replication::ExecutedState,
+};
+use crypto::{ed25519::*, HashValue};
+use failure::prelude::*;
+use grpc_helpers::provide_grpc_response;
+use logger::prelude::*;
+use proto_conv::{FromProtoBytes, IntoProto, IntoProtoBytes};
+use rmp_serde::{from_slice, to_vec_named};
+use secret_service::{
+    crypto_wrappers::{GenericPublicKey, GenericSignature},
+    secret_service_client::ConsensusKeyManager,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::Write,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use types::{
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_verifier::ValidatorVerifier,
+};
+
+#[cfg(test)]
+#[path = "safety_rules_service_test.rs"]
+mod safety_rules_service_test;
+
+/// The file the state of the safety rules is persisted to, in the directory of the service.
+const SAFETY_RULES_STATE_FILE: &str = "safety_rules_state";
+
+/// The persisted state of the safety rules: the consensus state of the current epoch.
+#[derive(Default, Deserialize, Serialize)]
+struct SafetyRulesState {
+    epoch: u64,
+    // The ledger info ending the previous epoch, which carries the validators of the current one,
+    // or None for the first epoch.
+    epoch_genesis: Option<LedgerInfo>,
+    consensus_state: ConsensusState,
+}
+
+/// The safety rules of the current epoch, along with its validators.
+struct EpochSafetyRules {
+    epoch: u64,
+    epoch_genesis: Option<LedgerInfo>,
+    validator: ValidatorVerifier<Ed25519PublicKey>,
+    safety_rules: SafetyRules,
+}
+
+impl EpochSafetyRules {
+    fn new(
+        state: SafetyRulesState,
+        initial_validator: &ValidatorVerifier<Ed25519PublicKey>,
+    ) -> Result<Self> {
+        let validator = match &state.epoch_genesis {
+            Some(ledger_info) => {
+                ValidatorVerifier::from(ledger_info.next_validator_set().ok_or_else(|| {
+                    format_err!("The ledger info {} doesn't end an epoch", ledger_info)
+                })?)
+            }
+            None => initial_validator.clone(),
+        };
+        Ok(Self {
+            epoch: state.epoch,
+            epoch_genesis: state.epoch_genesis,
+            validator,
+            safety_rules: SafetyRules::new(state.consensus_state),
+        })
+    }
+
+    /// Checks that the quorum certificate is signed by the validators of the epoch.
+    fn verify_quorum_cert(&self, qc: &QuorumCert) -> Result<()> {
+        ensure!(
+            qc.certified_block_epoch() == self.epoch,
+            "{} is not from the current epoch {}",
+            qc,
+            self.epoch
+        );
+        qc.verify(&self.validator, self.epoch_genesis.as_ref())?;
+        Ok(())
+    }
+
+    /// Returns the ledger info to sign in a vote for the given proposal of the epoch: it commits
+    /// the block of the commit rule, if any, with the state and the next validators that
+    /// consensus computed when executing the block (see `BlockStore::ledger_info_placeholder`),
+    /// which the safety rules don't execute.
+    fn ledger_info_placeholder<T: Payload>(
+        &self,
+        proposal: &Block<T>,
+        executed_ledger_info: &LedgerInfo,
+    ) -> Result<LedgerInfo> {
+        let commit_id = match SafetyRules::commit_rule_for_certified_block(
+            proposal.quorum_cert(),
+            proposal.round(),
+        ) {
+            Some(id) => id,
+            None => {
+                return Ok(LedgerInfo::new(
+                    0,
+                    HashValue::zero(),
+                    HashValue::zero(),
+                    HashValue::zero(),
+                    self.epoch,
+                    0,
+                    None,
+                ))
+            }
+        };
+        // The ledger info ending the epoch commits the first block that changes the validator set
+        // instead, when the block of the commit rule descends from it.
+        ensure!(
+            executed_ledger_info.consensus_block_id() == commit_id
+                || executed_ledger_info.next_validator_set().is_some(),
+            "The ledger info {} doesn't commit the block {} of the commit rule for {}",
+            executed_ledger_info,
+            commit_id,
+            proposal
+        );
+        Ok(LedgerInfo::new(
+            executed_ledger_info.version(),
+            executed_ledger_info.transaction_accumulator_hash(),
+            HashValue::zero(),
+            executed_ledger_info.consensus_block_id(),
+            self.epoch,
+            executed_ledger_info.timestamp_usecs(),
+            executed_ledger_info.next_validator_set().cloned(),
+        ))
+    }
+
+    fn state(&self) -> SafetyRulesState {
+        SafetyRulesState {
+            epoch: self.epoch,
+            epoch_genesis: self.epoch_genesis.clone(),
+            consensus_state: self.safety_rules.consensus_state(),
+        }
+    }
+}
+
+/// SafetyRulesService runs the safety rules of a validator in their own process (see
+/// `ProcessSafetyRules` for the client used by consensus), for blocks with the payload T. The
+/// proposals, votes and timeouts are signed by the secret service with the consensus key of the
+/// validator, which must be its key among the validators of the current epoch when the service
+/// starts, and the state of the rules is persisted before any message is
+/// returned, so that the validator never votes twice at a round or against its preferred block
+/// round, even across restarts.
+/// The quorum certificates sent by consensus are checked against the validators of the current
+/// epoch, and the next epoch only starts afresh with the ledger info ending the current one, signed
+/// by these validators: the requests of other epochs are rejected.
+#[derive(Clone)]
+pub struct SafetyRulesService<T> {
+    author: Author,
+    key_manager: Arc<ConsensusKeyManager>,
+    state_path: PathBuf,
+    // The validators of the first epoch.
+    initial_validator: Arc<ValidatorVerifier<Ed25519PublicKey>>,
+    // Mutex since every request may update the state, which is persisted under the lock.
+    rules: Arc<Mutex<EpochSafetyRules>>,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Payload> SafetyRulesService<T> {
+    /// Starts the safety rules of the given author from the state persisted in the given
+    /// directory, if any, or else from the first epoch with the given validators. Fails if the
+    /// consensus key of the key manager isn't the key of the author among the validators of the
+    /// current epoch.
+    pub fn new(
+        author: Author,
+        key_manager: ConsensusKeyManager,
+        dir: &Path,
+        initial_validator: ValidatorVerifier<Ed25519PublicKey>,
+    ) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        let state_path = dir.join(SAFETY_RULES_STATE_FILE);
+        let state = if state_path.exists() {
+            from_slice(&fs::read(&state_path)?)?
+        } else {
+            SafetyRulesState::default()
+        };
+        info!(
+            "Starting the safety rules at epoch {} with {}",
+            state.epoch, state.consensus_state
+        );
+        let rules = EpochSafetyRules::new(state, &initial_validator)?;
+        let public_key = match key_manager.get_consensus_public_key()? {
+            GenericPublicKey::Ed(public_key) => public_key,
+            GenericPublicKey::BLS(_) => bail!("The consensus key is not an Ed25519 key"),
+        };
+        ensure!(
+            rules.validator.get_public_key(author) == Some(public_key),
+            "The consensus key of the secret service is not the key of {} among the validators of \
+             epoch {}",
+            author,
+            rules.epoch
+        );
+        Ok(Self {
+            author,
+            key_manager: Arc::new(key_manager),
+            state_path,
+            initial_validator: Arc::new(initial_validator),
+            rules: Arc::new(Mutex::new(rules)),
+            phantom: PhantomData,
+        })
+    }
+
+    /// Starts the next epoch afresh, given the ledger info ending the current one. Starting the
+    /// current epoch again from the ledger info it started from has no effect.
+    pub fn start_new_epoch_inner(
+        &self,
+        ledger_info: &LedgerInfoWithSignatures<Ed25519Signature>,
+    ) -> Result<()> {
+        let mut rules = self.rules.lock().unwrap();
+        if rules.epoch_genesis.as_ref() == Some(ledger_info.ledger_info()) {
+            return Ok(());
+        }
+        ensure!(
+            ledger_info.ledger_info().epoch_num() == rules.epoch,
+            "The ledger info ends epoch {}, but the current epoch is {}",
+            ledger_info.ledger_info().epoch_num(),
+            rules.epoch
+        );
+        ledger_info.verify(&rules.validator)?;
+        let new_rules = EpochSafetyRules::new(
+            SafetyRulesState {
+                epoch: rules.epoch + 1,
+                epoch_genesis: Some(ledger_info.ledger_info().clone()),
+                consensus_state: ConsensusState::default(),
+            },
+            &self.initial_validator,
+        )?;
+        self.persist(new_rules.state())?;
+        info!("Starting the safety rules of epoch {}", new_rules.epoch);
+        *rules = new_rules;
+        Ok(())
+    }
+
+    /// Learns about a new quorum certificate of the given epoch.
+    pub fn update_inner(&self, epoch: u64, qc: &QuorumCert) -> Result<()> {
+        self.with_safety_rules(epoch, |rules| {
+            rules.verify_quorum_cert(qc)?;
+            rules.safety_rules.update(qc);
+            Ok(())
+        })
+    }
+
+    /// Returns the given proposal of the given epoch signed by the secret service, if its quorum
+    /// certificate is valid and no vote was signed at its round.
+    pub fn sign_proposal_inner(&self, epoch: u64, proposal: Block<T>) -> Result<Block<T>> {
+        self.with_safety_rules(epoch, |rules| {
+            rules.verify_quorum_cert(proposal.quorum_cert())?;
+            ensure!(
+                proposal.quorum_cert().certified_block_id() == proposal.parent_id(),
+                "The proposal {} doesn't carry the quorum certificate of its parent",
+                proposal
+            );
+            Ok(rules.safety_rules.proposal_rule(proposal.round())?)
+        })?;
+        proposal.sign_proposal(self.author, |hash| self.sign(hash))
+    }
+
+    /// Stops voting at the given round of the given epoch and returns the timeout at this round
+    /// signed by the secret service. The new state is returned along with it if the last vote
+    /// round increased.
+    pub fn sign_timeout_inner(
+        &self,
+        epoch: u64,
+        round: Round,
+        vote: Option<VoteMsg>,
+        sync_info: SyncInfo,
+    ) -> Result<(TimeoutMsg, Option<ConsensusState>)> {
+        // The state is persisted by the time the timeout gets signed.
+        let consensus_state = self.with_safety_rules(epoch, |rules| {
+            Ok(rules.safety_rules.increase_last_vote_round(round))
+        })?;
+        let pacemaker_timeout =
+            PacemakerTimeout::new_with_signer(round, self.author, vote, |hash| self.sign(hash))?;
+        let timeout_msg =
+            TimeoutMsg::new_with_signer(sync_info, pacemaker_timeout, |hash| self.sign(hash))?;
+        Ok((timeout_msg, consensus_state))
+    }
+
+    /// Returns the consensus state of the given epoch.
+    pub fn consensus_state_inner(&self, epoch: u64) -> Result<ConsensusState> {
+        self.with_safety_rules(epoch, |rules| Ok(rules.safety_rules.consensus_state()))
+    }
+
+    /// Returns the vote for the given proposal of the given epoch, signed by the secret service,
+    /// if the proposal is valid and respects the voting rules. The ledger info of the vote is built
+    /// from the commit rule, given the ledger info consensus computed for the block to commit.
+    /// The consensus state after the vote is returned along with it.
+    pub fn construct_and_sign_vote_inner(
+        &self,
+        epoch: u64,
+        proposed_block: &Block<T>,
+        executed_state: ExecutedState,
+        executed_ledger_info: &LedgerInfo,
+    ) -> Result<(VoteMsg, ConsensusState)> {
+        // The state is persisted by the time the vote gets signed.
+        let (vote_info, ledger_info_placeholder) = self.with_safety_rules(epoch, |rules| {
+            rules.verify_quorum_cert(proposed_block.quorum_cert())?;
+            proposed_block
+                .verify(&rules.validator, rules.epoch_genesis.as_ref())
+                .map_err(|e| format_err!("{:?}", e))?;
+            let ledger_info_placeholder =
+                rules.ledger_info_placeholder(proposed_block, executed_ledger_info)?;
+            let vote_info = rules.safety_rules.voting_rule_for_proposal(
+                proposed_block.id(),
+                proposed_block.round(),
+                proposed_block.quorum_cert(),
+            )?;
+            Ok((vote_info, ledger_info_placeholder))
+        })?;
+        let vote = vote_info.sign_vote(
+            self.author,
+            executed_state,
+            ledger_info_placeholder,
+            |hash| self.sign(hash),
+        )?;
+        Ok((vote, vote_info.consensus_state().clone()))
+    }
+
+    /// Signs the given hash with the consensus key of the secret service.
+    fn sign(&self, hash: HashValue) -> Result<Ed25519Signature> {
+        match self.key_manager.sign_consensus_message(&hash)? {
+            GenericSignature::Ed(signature) => Ok(signature),
+            GenericSignature::BLS(_) => bail!("The consensus key is not an Ed25519 key"),
+        }
+    }
+
+    /// Runs the given function on the safety rules of the given epoch, which must be the current
+    /// one, and persists their state if it changed.
+    fn with_safety_rules<F, R>(&self, epoch: u64, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut EpochSafetyRules) -> Result<R>,
+    {
+        let mut rules = self.rules.lock().unwrap();
+        ensure!(
+            epoch == rules.epoch,
+            "Request of epoch {}, but the current epoch is {}",
+            epoch,
+            rules.epoch
+        );
+        let previous_state = rules.safety_rules.consensus_state();
+        let result = f(&mut rules);
+        if rules.safety_rules.consensus_state() != previous_state {
+            self.persist(rules.state())?;
+        }
+        result
+    }
+
+    fn persist(&self, state: SafetyRulesState) -> Result<()> {
+        // Write the new state aside and rename it, so that a crash never leaves a partial state.
+        let tmp_path = self.state_path.with_extension("tmp");
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(&to_vec_named(&state)?)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &self.state_path)?;
+        Ok(())
+    }
+
+    fn start_new_epoch_grpc_inner(
+        &self,
+        req: StartNewEpochRequest,
+    ) -> Result<StartNewEpochResponse> {
+        let ledger_info = LedgerInfoWithSignatures::from_proto_bytes(req.get_ledger_info())?;
+        self.start_new_epoch_inner(&ledger_info)?;
+        Ok(StartNewEpochResponse::new())
+    }
+
+    fn update_grpc_inner(&self, req: UpdateRequest) -> Result<UpdateResponse> {
+        let qc = QuorumCert::from_proto_bytes(req.get_quorum_cert())?;
+        self.update_inner(req.get_epoch(), &qc)?;
+        Ok(UpdateResponse::new())
+    }
+
+    fn sign_proposal_grpc_inner(&self, req: SignProposalRequest) -> Result<SignProposalResponse> {
+        let proposal = Block::from_proto_bytes(req.get_proposal())?;
+        let proposal = self.sign_proposal_inner(req.get_epoch(), proposal)?;
+        let mut response = SignProposalResponse::new();
+        response.set_proposal(proposal.into_proto_bytes()?);
+        Ok(response)
+    }
+
+    fn sign_timeout_grpc_inner(&self, req: SignTimeoutRequest) -> Result<SignTimeoutResponse> {
+        let vote = if req.get_vote().is_empty() {
+            None
+        } else {
+            Some(VoteMsg::from_proto_bytes(req.get_vote())?)
+        };
+        let (timeout_msg, consensus_state) = self.sign_timeout_inner(
+            req.get_epoch(),
+            req.get_round(),
+            vote,
+            SyncInfo::from_proto_bytes(req.get_sync_info())?,
+        )?;
+        let mut response = SignTimeoutResponse::new();
+        response.set_timeout_msg(timeout_msg.into_proto_bytes()?);
+        if let Some(consensus_state) = consensus_state {
+            response.set_consensus_state(consensus_state.into_proto());
+        }
+        Ok(response)
+    }
+
+    fn get_consensus_state_grpc_inner(
+        &self,
+        req: GetConsensusStateRequest,
+    ) -> Result<GetConsensusStateResponse> {
+        let mut response = GetConsensusStateResponse::new();
+        response.set_consensus_state(self.consensus_state_inner(req.get_epoch())?.into_proto());
+        Ok(response)
+    }
+
+    fn construct_and_sign_vote_grpc_inner(
+        &self,
+        req: ConstructAndSignVoteRequest,
+    ) -> Result<ConstructAndSignVoteResponse> {
+        let (vote, consensus_state) = self.construct_and_sign_vote_inner(
+            req.get_epoch(),
+            &Block::from_proto_bytes(req.get_proposed_block())?,
+            ExecutedState {
+                state_id: HashValue::from_slice(req.get_executed_state_id())?,
+                version: req.get_executed_version(),
+            },
+            &LedgerInfo::from_proto_bytes(req.get_ledger_info_placeholder())?,
+        )?;
+        let mut response = ConstructAndSignVoteResponse::new();
+        response.set_vote(vote.into_proto_bytes()?);
+        response.set_consensus_state(consensus_state.into_proto());
+        Ok(response)
+    }
+}
+
+/// SafetyRulesService implements the proto trait safety_rules_grpc::SafetyRules: the methods below
+/// wrap around the inner methods and operate on the grpc requests/responses.
+impl<T: Payload> safety_rules_grpc::SafetyRules for SafetyRulesService<T> {
+    fn start_new_epoch(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        req: StartNewEpochRequest,
+        sink: ::grpcio::UnarySink<StartNewEpochResponse>,
+    ) {
+        let resp = self.start_new_epoch_grpc_inner(req);
+        provide_grpc_response(resp, ctx, sink);
+    }
+
+    fn update(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        req: UpdateRequest,
+        sink: ::grpcio::UnarySink<UpdateResponse>,
+    ) {
+        let resp = self.update_grpc_inner(req);
+        provide_grpc_response(resp, ctx, sink);
+    }
+
+    fn sign_proposal(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        req: SignProposalRequest,
+        sink: ::grpcio::UnarySink<SignProposalResponse>,
+    ) {
+        let resp = self.sign_proposal_grpc_inner(req);
+        provide_grpc_response(resp, ctx, sink);
+    }
+
+    fn sign_timeout(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        req: SignTimeoutRequest,
+        sink: ::grpcio::UnarySink<SignTimeoutResponse>,
+    ) {
+        let resp = self.sign_timeout_grpc_inner(req);
+        provide_grpc_response(resp, ctx, sink);
+    }
+
+    fn get_consensus_state(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        req: GetConsensusStateRequest,
+        sink: ::grpcio::UnarySink<GetConsensusStateResponse>,
+    ) {
+        let resp = self.get_consensus_state_grpc_inner(req);
+        provide_grpc_response(resp, ctx, sink);
+    }
+
+    fn construct_and_sign_vote(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        req: ConstructAndSignVoteRequest,
+        sink: ::grpcio::UnarySink<ConstructAndSignVoteResponse>,
+    ) {
+        let resp = self.construct_and_sign_vote_grpc_inner(req);
+        provide_grpc_response(resp, ctx, sink);
+    }
+}
@@ -0,0 +1,290 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    chained_bft::{
+        block_storage::{BlockReader, BlockStore},
+        consensus_types::{block::Block, sync_info::SyncInfo},
+        safety::{
+            safety_rules::{ConsensusState, SafetyRules},
+            safety_rules_service::SafetyRulesService,
+            vote_msg::VoteMsg,
+        },
+        test_utils::{
+            build_empty_tree_with_custom_signing, placeholder_certificate_for_block, TestPayload,
+            TreeInserter,
+        },
+    },
+    state_replication::ExecutedState,
+};
+use config::utils::get_available_port;
+use crypto::{ed25519::compat, hash::CryptoHash, x25519, HashValue};
+use failure::Result;
+use grpc_helpers::{spawn_service_thread, ServerHandle};
+use grpcio::{ChannelBuilder, EnvBuilder};
+use proto_conv::{FromProto, IntoProto};
+use rand::{rngs::StdRng, SeedableRng};
+use secret_service::{
+    proto::secret_service_grpc::{create_secret_service, SecretServiceClient},
+    secret_service_client::ConsensusKeyManager,
+    secret_service_server::SecretServiceServer,
+};
+use std::{collections::HashMap, sync::Arc};
+use tempfile::TempDir;
+use types::{
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_public_keys::ValidatorPublicKeys,
+    validator_set::ValidatorSet,
+    validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
+};
+
+fn start_secret_service() -> (ServerHandle, Arc<SecretServiceClient>) {
+    let port = get_available_port();
+    let handle = spawn_service_thread(
+        create_secret_service(SecretServiceServer::new()),
+        "localhost".to_string(),
+        port,
+        "secret_service",
+    );
+    let env = Arc::new(EnvBuilder::new().build());
+    let client =
+        SecretServiceClient::new(ChannelBuilder::new(env).connect(&format!("localhost:{}", port)));
+    (handle, Arc::new(client))
+}
+
+/// Asks the service for a vote for the given block, with the ledger info computed by the tree for
+/// the block of the commit rule, as consensus does.
+fn vote(
+    service: &SafetyRulesService<TestPayload>,
+    block_tree: &BlockStore<TestPayload>,
+    block: &Block<TestPayload>,
+) -> Result<(VoteMsg, ConsensusState)> {
+    let potential_commit_id =
+        SafetyRules::commit_rule_for_certified_block(block.quorum_cert(), block.round());
+    service.construct_and_sign_vote_inner(
+        block.quorum_cert().certified_block_epoch(),
+        block,
+        ExecutedState::state_for_genesis(),
+        &block_tree.ledger_info_placeholder(potential_commit_id),
+    )
+}
+
+#[test]
+fn test_safety_rules_service() {
+    let (_secret_service, client) = start_secret_service();
+    // The validator signs the quorum certificates of the tree with its consensus key.
+    let (private_key, public_key) = compat::generate_keypair(&mut StdRng::from_seed([1; 32]));
+    let block_tree =
+        build_empty_tree_with_custom_signing(ValidatorSigner::new(None, private_key.clone()));
+    let mut inserter = TreeInserter::new(block_tree.clone());
+    let author = block_tree.signer().author();
+    let validator = ValidatorVerifier::new_single(author, public_key.clone());
+    let epoch = block_tree.epoch();
+    let dir = TempDir::new().unwrap();
+
+    // build a tree of the following form:
+    // genesis---a1---a2---a3
+    //         \
+    //          ------------------b5
+    let genesis = block_tree.root();
+    let a1 = inserter.insert_block(genesis.as_ref(), 1);
+    let a2 = inserter.insert_block(a1.as_ref(), 2);
+    let a3 = inserter.insert_block(a2.as_ref(), 3);
+    let b5 = inserter.insert_block(genesis.as_ref(), 5);
+
+    // The service only starts with the consensus key of the validator.
+    let key_manager = ConsensusKeyManager::new(Arc::clone(&client)).unwrap();
+    assert!(SafetyRulesService::<TestPayload>::new(
+        author,
+        key_manager,
+        dir.path(),
+        validator.clone()
+    )
+    .is_err());
+
+    // The votes are signed with the consensus key imported into the secret service, and verify
+    // against the validators.
+    let key_manager =
+        ConsensusKeyManager::import(Arc::clone(&client), private_key.clone()).unwrap();
+    let service =
+        SafetyRulesService::new(author, key_manager, dir.path(), validator.clone()).unwrap();
+    let (vote_msg, consensus_state) = vote(&service, &block_tree, a1.as_ref()).unwrap();
+    assert!(vote_msg.verify(&validator).is_ok());
+    assert_eq!(consensus_state, ConsensusState::new(1, 0));
+    assert!(vote(&service, &block_tree, a1.as_ref()).is_err());
+
+    // The state is recovered after a restart.
+    let key_manager =
+        ConsensusKeyManager::import(Arc::clone(&client), private_key.clone()).unwrap();
+    let service =
+        SafetyRulesService::new(author, key_manager, dir.path(), validator.clone()).unwrap();
+    assert_eq!(
+        service.consensus_state_inner(epoch).unwrap(),
+        consensus_state
+    );
+    assert!(vote(&service, &block_tree, a1.as_ref()).is_err());
+
+    // The proposals must be valid: a block changed after it was signed is refused.
+    let mut changed_block = a2.as_ref().clone().into_proto();
+    changed_block.set_round(3);
+    let changed_block = Block::<TestPayload>::from_proto(changed_block).unwrap();
+    assert!(service
+        .construct_and_sign_vote_inner(
+            epoch,
+            &changed_block,
+            ExecutedState::state_for_genesis(),
+            &block_tree.ledger_info_placeholder(None),
+        )
+        .is_err());
+
+    // The vote for a2 commits the genesis block: a ledger info committing another block is
+    // refused, without voting at the round of a2.
+    assert!(service
+        .construct_and_sign_vote_inner(
+            epoch,
+            a2.as_ref(),
+            ExecutedState::state_for_genesis(),
+            &block_tree.ledger_info_placeholder(Some(a1.id())),
+        )
+        .is_err());
+    let (vote_msg, _) = vote(&service, &block_tree, a2.as_ref()).unwrap();
+    assert!(vote_msg.verify(&validator).is_ok());
+    assert_eq!(vote_msg.ledger_info().consensus_block_id(), genesis.id());
+    assert_eq!(vote_msg.ledger_info().epoch_num(), epoch);
+
+    // The quorum certificates must be signed by the validators.
+    let other_signer = ValidatorSigner::random(None);
+    let forged_qc = placeholder_certificate_for_block(
+        vec![&other_signer],
+        a3.id(),
+        a3.round(),
+        a2.id(),
+        a2.round(),
+        a1.id(),
+        a1.round(),
+    );
+    assert!(service.update_inner(epoch, &forged_qc).is_err());
+
+    // The votes must respect the preferred block round.
+    service.update_inner(epoch, a3.quorum_cert()).unwrap();
+    assert_eq!(
+        service.consensus_state_inner(epoch).unwrap(),
+        ConsensusState::new(2, 1)
+    );
+    assert!(vote(&service, &block_tree, b5.as_ref()).is_err());
+
+    // The proposals are only signed above the last vote round.
+    let a3_qc = placeholder_certificate_for_block(
+        vec![block_tree.signer()],
+        a3.id(),
+        a3.round(),
+        a2.id(),
+        a2.round(),
+        a1.id(),
+        a1.round(),
+    );
+    let proposal = Block::make_block(
+        a3.as_ref(),
+        vec![],
+        4,
+        a3.timestamp_usecs() + 1,
+        a3_qc.clone(),
+        block_tree.signer(),
+    );
+    let signed_proposal = service
+        .sign_proposal_inner(epoch, proposal.clone())
+        .unwrap();
+    assert_eq!(signed_proposal.round(), 4);
+    assert!(validator
+        .verify_signature(
+            author,
+            signed_proposal.id(),
+            signed_proposal.signature().unwrap()
+        )
+        .is_ok());
+    let proposal_at_voted_round = Block::make_block(
+        a1.as_ref(),
+        vec![],
+        2,
+        a1.timestamp_usecs() + 1,
+        a2.quorum_cert().clone(),
+        block_tree.signer(),
+    );
+    assert!(service
+        .sign_proposal_inner(epoch, proposal_at_voted_round)
+        .is_err());
+
+    // A timeout stops voting at its round.
+    let sync_info = SyncInfo::new(a3_qc.clone(), a3_qc, None);
+    let (timeout_msg, consensus_state) = service
+        .sign_timeout_inner(epoch, 4, None, sync_info.clone())
+        .unwrap();
+    assert!(timeout_msg.verify(&validator).is_ok());
+    assert_eq!(timeout_msg.pacemaker_timeout().round(), 4);
+    assert_eq!(consensus_state, Some(ConsensusState::new(4, 1)));
+    let (_, consensus_state) = service
+        .sign_timeout_inner(epoch, 4, None, sync_info.clone())
+        .unwrap();
+    assert_eq!(consensus_state, None);
+    assert!(service.sign_proposal_inner(epoch, proposal).is_err());
+
+    // A new epoch only starts with the ledger info ending the current one, signed by its
+    // validators.
+    assert!(service.consensus_state_inner(epoch + 1).is_err());
+    let (_, network_identity_public_key) = x25519::compat::generate_keypair(None);
+    let next_validator_set = ValidatorSet::new(vec![ValidatorPublicKeys::new(
+        author,
+        public_key.clone(),
+        public_key,
+        network_identity_public_key,
+    )]);
+    let epoch_ending_ledger_info = LedgerInfo::new(
+        0,
+        HashValue::random(),
+        HashValue::random(),
+        HashValue::random(),
+        epoch,
+        0,
+        Some(next_validator_set),
+    );
+    let unsigned_ledger_info =
+        LedgerInfoWithSignatures::new(epoch_ending_ledger_info.clone(), HashMap::new());
+    assert!(service
+        .start_new_epoch_inner(&unsigned_ledger_info)
+        .is_err());
+    let mut signatures = HashMap::new();
+    signatures.insert(
+        author,
+        block_tree
+            .signer()
+            .sign_message(epoch_ending_ledger_info.hash())
+            .unwrap(),
+    );
+    let signed_ledger_info = LedgerInfoWithSignatures::new(epoch_ending_ledger_info, signatures);
+    service.start_new_epoch_inner(&signed_ledger_info).unwrap();
+    assert_eq!(
+        service.consensus_state_inner(epoch + 1).unwrap(),
+        ConsensusState::default()
+    );
+
+    // Starting the epoch again has no effect, and the requests of older epochs are rejected.
+    service
+        .sign_timeout_inner(epoch + 1, 1, None, sync_info)
+        .unwrap();
+    service.start_new_epoch_inner(&signed_ledger_info).unwrap();
+    assert_eq!(
+        service.consensus_state_inner(epoch + 1).unwrap(),
+        ConsensusState::new(1, 0)
+    );
+    assert!(service.consensus_state_inner(epoch).is_err());
+    assert!(vote(&service, &block_tree, a3.as_ref()).is_err());
+
+    // The epoch is recovered after a restart.
+    let key_manager = ConsensusKeyManager::import(Arc::clone(&client), private_key).unwrap();
+    let service = SafetyRulesService::new(author, key_manager, dir.path(), validator).unwrap();
+    assert_eq!(
+        service.consensus_state_inner(epoch + 1).unwrap(),
+        ConsensusState::new(1, 0)
+    );
+}
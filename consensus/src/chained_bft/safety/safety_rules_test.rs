@@ -225,6 +225,23 @@ fn test_preferred_block_rule() {
     );
 }
 
+#[test]
+fn test_proposal_rule() {
+    let mut safety_rules = SafetyRules::new(ConsensusState::default());
+    assert_eq!(safety_rules.proposal_rule(1), Ok(()));
+
+    // No proposal at or below the last vote round.
+    safety_rules.increase_last_vote_round(2);
+    assert_eq!(
+        safety_rules.proposal_rule(2),
+        Err(ProposalReject::OldProposal {
+            last_vote_round: 2,
+            proposal_round: 2,
+        })
+    );
+    assert_eq!(safety_rules.proposal_rule(3), Ok(()));
+}
+
 #[test]
 fn test_voting() {
     let block_tree = build_empty_tree();
@@ -361,7 +378,6 @@ fn test_voting_potential_commit_id() {
 fn test_commit_rule_consecutive_rounds() {
     let block_tree = build_empty_tree();
     let mut inserter = TreeInserter::new(block_tree.clone());
-    let safety_rules = SafetyRules::new(ConsensusState::default());
 
     // build a tree of the following form:
     //             ___________
@@ -381,27 +397,27 @@ fn test_commit_rule_consecutive_rounds() {
     let a4 = inserter.insert_block(a3.as_ref(), 6);
 
     assert_eq!(
-        safety_rules.commit_rule_for_certified_block(a1.quorum_cert(), a1.round()),
+        SafetyRules::commit_rule_for_certified_block(a1.quorum_cert(), a1.round()),
         None
     );
     assert_eq!(
-        safety_rules.commit_rule_for_certified_block(b1.quorum_cert(), b1.round()),
+        SafetyRules::commit_rule_for_certified_block(b1.quorum_cert(), b1.round()),
         None
     );
     assert_eq!(
-        safety_rules.commit_rule_for_certified_block(b2.quorum_cert(), b2.round()),
+        SafetyRules::commit_rule_for_certified_block(b2.quorum_cert(), b2.round()),
         None
     );
     assert_eq!(
-        safety_rules.commit_rule_for_certified_block(a2.quorum_cert(), a2.round()),
+        SafetyRules::commit_rule_for_certified_block(a2.quorum_cert(), a2.round()),
         None
     );
     assert_eq!(
-        safety_rules.commit_rule_for_certified_block(a3.quorum_cert(), a3.round()),
+        SafetyRules::commit_rule_for_certified_block(a3.quorum_cert(), a3.round()),
         None
     );
     assert_eq!(
-        safety_rules.commit_rule_for_certified_block(a4.quorum_cert(), a4.round()),
+        SafetyRules::commit_rule_for_certified_block(a4.quorum_cert(), a4.round()),
         Some(a2.id())
     );
 }
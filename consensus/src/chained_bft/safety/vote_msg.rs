@@ -132,9 +132,41 @@ impl VoteMsg {
         grandparent_block_id: HashValue,
         grandparent_block_round: Round,
         author: Author,
-        mut ledger_info_placeholder: LedgerInfo,
+        ledger_info_placeholder: LedgerInfo,
         validator_signer: &ValidatorSigner<Ed25519PrivateKey>,
     ) -> Self {
+        Self::new_with_signer(
+            proposed_block_id,
+            executed_state,
+            round,
+            parent_block_id,
+            parent_block_round,
+            grandparent_block_id,
+            grandparent_block_round,
+            author,
+            ledger_info_placeholder,
+            |hash| validator_signer.sign_message(hash),
+        )
+        .expect("Failed to sign LedgerInfo")
+    }
+
+    /// Same as `new`, with the LedgerInfo signed by the given function (e.g., by a signing service
+    /// that owns the consensus key).
+    pub fn new_with_signer<F>(
+        proposed_block_id: HashValue,
+        executed_state: ExecutedState,
+        round: Round,
+        parent_block_id: HashValue,
+        parent_block_round: Round,
+        grandparent_block_id: HashValue,
+        grandparent_block_round: Round,
+        author: Author,
+        mut ledger_info_placeholder: LedgerInfo,
+        sign: F,
+    ) -> failure::Result<Self>
+    where
+        F: FnOnce(HashValue) -> failure::Result<Ed25519Signature>,
+    {
         ledger_info_placeholder.set_consensus_data_hash(Self::vote_digest(
             proposed_block_id,
             executed_state,
@@ -144,10 +176,8 @@ impl VoteMsg {
             grandparent_block_id,
             grandparent_block_round,
         ));
-        let li_sig = validator_signer
-            .sign_message(ledger_info_placeholder.hash())
-            .expect("Failed to sign LedgerInfo");
-        Self {
+        let li_sig = sign(ledger_info_placeholder.hash())?;
+        Ok(Self {
             proposed_block_id,
             executed_state,
             round,
@@ -158,7 +188,7 @@ impl VoteMsg {
             author,
             ledger_info: ledger_info_placeholder,
             signature: li_sig,
-        }
+        })
     }
 
     /// Return the proposed block id
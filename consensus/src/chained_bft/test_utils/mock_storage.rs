@@ -141,6 +141,29 @@ impl<T: Payload> PersistentStorage<T> for MockStorage<T> {
         Ok(())
     }
 
+    fn reset_to_epoch_genesis(
+        &self,
+        genesis_block: Block<T>,
+        genesis_quorum_cert: QuorumCert,
+    ) -> Result<RecoveryData<T>> {
+        *self.shared_storage.block.lock().unwrap() = vec![(genesis_block.id(), genesis_block)]
+            .into_iter()
+            .collect();
+        *self.shared_storage.qc.lock().unwrap() = vec![(
+            genesis_quorum_cert.certified_block_id(),
+            genesis_quorum_cert,
+        )]
+        .into_iter()
+        .collect();
+        *self.shared_storage.state.lock().unwrap() = ConsensusState::default();
+        *self
+            .shared_storage
+            .highest_timeout_certificates
+            .lock()
+            .unwrap() = HighestTimeoutCertificates::new(None, None);
+        self.get_recovery_data()
+    }
+
     fn start(_config: &NodeConfig) -> (Arc<Self>, RecoveryData<T>) {
         let shared_storage = Arc::new(MockSharedStorage {
             block: Mutex::new(HashMap::new()),
@@ -198,6 +221,21 @@ impl<T: Payload> PersistentStorage<T> for EmptyStorage {
         Ok(())
     }
 
+    fn reset_to_epoch_genesis(
+        &self,
+        genesis_block: Block<T>,
+        genesis_quorum_cert: QuorumCert,
+    ) -> Result<RecoveryData<T>> {
+        let root_id = genesis_block.id();
+        RecoveryData::new(
+            ConsensusState::default(),
+            vec![genesis_block],
+            vec![genesis_quorum_cert],
+            root_id,
+            HighestTimeoutCertificates::new(None, None),
+        )
+    }
+
     fn start(_: &NodeConfig) -> (Arc<Self>, RecoveryData<T>) {
         let genesis = Block::make_genesis_block();
         let genesis_qc = QuorumCert::certificate_for_genesis();
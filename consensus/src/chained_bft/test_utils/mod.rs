@@ -46,7 +46,7 @@ pub fn build_empty_tree_with_custom_signing(
     Arc::new(block_on(BlockStore::new(
         storage,
         initial_data,
-        my_signer,
+        Arc::new(my_signer),
         Arc::new(MockStateComputer::new(commit_cb_sender)),
         true,
         10, // max pruned blocks in mem
@@ -150,6 +150,7 @@ pub fn placeholder_ledger_info() -> LedgerInfo {
         HashValue::zero(),
         0,
         0,
+        None,
     )
 }
 
@@ -3,15 +3,22 @@
 
 use config::config::NodeConfig;
 use failure::prelude::*;
-use network::validator_network::{ConsensusNetworkEvents, ConsensusNetworkSender};
+use network::{
+    validator_network::{ConsensusNetworkEvents, ConsensusNetworkSender},
+    NetworkPublicKeys,
+};
 
 use crate::chained_bft::chained_bft_consensus_provider::ChainedBftProvider;
 use execution_proto::proto::execution_grpc::ExecutionClient;
 use grpcio::{ChannelBuilder, EnvBuilder};
 use mempool::proto::mempool_grpc::MempoolClient;
 use state_synchronizer::StateSyncClient;
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
 use storage_client::{StorageRead, StorageReadServiceClient};
+use types::account_address::AccountAddress;
 
 /// Public interface to a consensus protocol.
 pub trait ConsensusProvider {
@@ -26,17 +33,20 @@ pub trait ConsensusProvider {
     fn stop(&mut self);
 }
 
-/// Helper function to create a ConsensusProvider based on configuration
+/// Helper function to create a ConsensusProvider based on configuration.
+/// The trusted network peers are updated with the validators of each new epoch.
 pub fn make_consensus_provider(
     node_config: &mut NodeConfig,
     network_sender: ConsensusNetworkSender,
     network_receiver: ConsensusNetworkEvents,
+    trusted_network_peers: Arc<RwLock<HashMap<AccountAddress, NetworkPublicKeys>>>,
     state_sync_client: Arc<StateSyncClient>,
 ) -> Box<dyn ConsensusProvider> {
     Box::new(ChainedBftProvider::new(
         node_config,
         network_sender,
         network_receiver,
+        trusted_network_peers,
         create_mempool_client(node_config),
         create_execution_client(node_config),
         state_sync_client,
@@ -195,6 +195,9 @@ pub static ref PENDING_NEW_ROUND_MESSAGES: IntGauge = OP_COUNTERS.gauge("pending
 /// Count of the pending outbound pacemaker timeouts
 pub static ref PENDING_PACEMAKER_TIMEOUTS: IntGauge = OP_COUNTERS.gauge("pending_pacemaker_timeouts");
 
+/// Count of the pending ends of the waits for proposals (VRF proposer election only)
+pub static ref PENDING_PROPOSAL_WAITS: IntGauge = OP_COUNTERS.gauge("pending_proposal_waits");
+
 /// Count of the pending new round events.
 pub static ref PENDING_NEW_ROUND_EVENTS: IntGauge = OP_COUNTERS.gauge("pending_new_round_events");
 
@@ -20,6 +20,12 @@ mod util;
 /// use in the Libra Core blockchain.
 pub mod consensus_provider;
 
+/// Runs the safety rules of a validator in their own process.
+pub use chained_bft::SafetyRulesNode;
+
+/// Looks into the ConsensusDB of a validator, and repairs it.
+pub use chained_bft::{ConsensusDBInspector, RootCheck};
+
 mod counters;
 
 mod state_computer;
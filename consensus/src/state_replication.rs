@@ -31,7 +31,6 @@ pub struct StateComputeResult {
     pub num_successful_txns: u64,
     /// If set, these are the validator public keys that will be used to start the next epoch
     /// immediately after this state is committed
-    /// TODO [Reconfiguration] the validators are currently ignored, no reconfiguration yet.
     pub validators: Option<ValidatorSet>,
 }
 
@@ -536,6 +536,24 @@ define_hasher! {
     (VoteMsgHasher, VOTE_MSG_HASHER, b"VoteMsg")
 }
 
+define_hasher! {
+    /// The hasher used to draw the proposer of a round in the leader reputation election.
+    (
+        ProposerElectionHasher,
+        PROPOSER_ELECTION_HASHER,
+        b"ProposerElection"
+    )
+}
+
+define_hasher! {
+    /// The hasher used to derive the key id of a key imported into the secret service.
+    (
+        SecretServiceKeyIdHasher,
+        SECRET_SERVICE_KEY_ID_HASHER,
+        b"SecretServiceKeyId"
+    )
+}
+
 define_hasher! {
     /// The hasher used to compute the hash of a ContractEvent object.
     (ContractEventHasher, CONTRACT_EVENT_HASHER, b"ContractEvent")
@@ -0,0 +1,228 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides a k-of-n multi-signature scheme built from Ed25519 signatures.
+//!
+//! A `MultiEd25519PublicKey` is a list of up to `MAX_NUM_OF_KEYS` Ed25519 public keys along with
+//! a threshold `k`. A `MultiEd25519Signature` is a list of Ed25519 signatures along with a bitmap
+//! telling which of the keys produced them: it is valid if at least `k` of them are valid
+//! signatures of the message by the keys designated by the bitmap.
+//!
+//! Bit `i` of the bitmap, i.e. the bit of mask `0b1000_0000 >> (i % 8)` of byte `i / 8`, designates
+//! the key `i`, which matches the layout used by the `Signature.ed25519_threshold_verify` native
+//! function.
+//!
+//! # Encoding
+//!
+//! * A public key is the concatenation of the Ed25519 public keys followed by the threshold byte.
+//! Its length is `32 * n + 1`, so it is never mistaken for a single Ed25519 public key.
+//! * A signature is the concatenation of the Ed25519 signatures, ordered by key index, followed
+//! by the `BITMAP_NUM_OF_BYTES` bytes of the bitmap.
+
+use crate::{
+    ed25519::{
+        Ed25519PublicKey, Ed25519Signature, ED25519_PUBLIC_KEY_LENGTH, ED25519_SIGNATURE_LENGTH,
+    },
+    traits::*,
+    HashValue,
+};
+use core::convert::TryFrom;
+use failure::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of Ed25519 public keys in a `MultiEd25519PublicKey`.
+pub const MAX_NUM_OF_KEYS: usize = 32;
+/// Length of the bitmap of a `MultiEd25519Signature`.
+pub const BITMAP_NUM_OF_BYTES: usize = MAX_NUM_OF_KEYS / 8;
+
+/// A k-of-n multi-signature public key.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct MultiEd25519PublicKey {
+    public_keys: Vec<Ed25519PublicKey>,
+    threshold: u8,
+}
+
+/// A multi-signature: the Ed25519 signatures of some of the keys of a `MultiEd25519PublicKey`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct MultiEd25519Signature {
+    /// Signatures ordered by the index of the key which produced them.
+    signatures: Vec<Ed25519Signature>,
+    bitmap: [u8; BITMAP_NUM_OF_BYTES],
+}
+
+impl MultiEd25519PublicKey {
+    /// Build a public key requiring `threshold` signatures out of `public_keys`.
+    pub fn new(
+        public_keys: Vec<Ed25519PublicKey>,
+        threshold: u8,
+    ) -> std::result::Result<Self, CryptoMaterialError> {
+        if threshold == 0
+            || usize::from(threshold) > public_keys.len()
+            || public_keys.len() > MAX_NUM_OF_KEYS
+        {
+            return Err(CryptoMaterialError::ValidationError);
+        }
+        Ok(MultiEd25519PublicKey {
+            public_keys,
+            threshold,
+        })
+    }
+
+    /// The Ed25519 public keys.
+    pub fn public_keys(&self) -> &[Ed25519PublicKey] {
+        &self.public_keys
+    }
+
+    /// The number of signatures required.
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Serialize the public key.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.public_keys.len() * ED25519_PUBLIC_KEY_LENGTH + 1);
+        for public_key in &self.public_keys {
+            bytes.extend_from_slice(&public_key.to_bytes());
+        }
+        bytes.push(self.threshold);
+        bytes
+    }
+
+    /// Verify that `signature` holds at least `threshold` valid signatures of `message`.
+    pub fn verify_signature(
+        &self,
+        message: &HashValue,
+        signature: &MultiEd25519Signature,
+    ) -> Result<()> {
+        let indexes = signature.indexes();
+        ensure!(
+            indexes.len() == signature.signatures.len(),
+            "Bitmap designates {} keys for {} signatures",
+            indexes.len(),
+            signature.signatures.len()
+        );
+        ensure!(
+            indexes.len() >= usize::from(self.threshold),
+            "Got {} signatures, {} are required",
+            indexes.len(),
+            self.threshold
+        );
+        for (index, ed25519_signature) in indexes.into_iter().zip(&signature.signatures) {
+            let public_key = self
+                .public_keys
+                .get(index)
+                .ok_or_else(|| format_err!("Bitmap designates unknown key {}", index))?;
+            public_key.verify_signature(message, ed25519_signature)?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&[u8]> for MultiEd25519PublicKey {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a MultiEd25519PublicKey, checking the validity of each Ed25519 public key.
+    fn try_from(bytes: &[u8]) -> std::result::Result<Self, CryptoMaterialError> {
+        let (threshold, keys_bytes) = bytes
+            .split_last()
+            .ok_or(CryptoMaterialError::WrongLengthError)?;
+        if keys_bytes.len() % ED25519_PUBLIC_KEY_LENGTH != 0 {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        let public_keys = keys_bytes
+            .chunks(ED25519_PUBLIC_KEY_LENGTH)
+            .map(Ed25519PublicKey::try_from)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Self::new(public_keys, *threshold)
+    }
+}
+
+impl MultiEd25519Signature {
+    /// Build a multi-signature out of signatures and the indexes of the keys which produced them.
+    pub fn new(
+        signatures: Vec<(Ed25519Signature, u8)>,
+    ) -> std::result::Result<Self, CryptoMaterialError> {
+        let mut multi_signature = MultiEd25519Signature {
+            signatures: vec![],
+            bitmap: [0u8; BITMAP_NUM_OF_BYTES],
+        };
+        for (signature, index) in signatures {
+            multi_signature.add_signature(signature, index)?;
+        }
+        Ok(multi_signature)
+    }
+
+    /// Add the signature of the key at `index`, e.g. when co-signing a transaction. The signature
+    /// replaces any previous signature of the same key.
+    pub fn add_signature(
+        &mut self,
+        signature: Ed25519Signature,
+        index: u8,
+    ) -> std::result::Result<(), CryptoMaterialError> {
+        let index = usize::from(index);
+        if index >= MAX_NUM_OF_KEYS {
+            return Err(CryptoMaterialError::ValidationError);
+        }
+        let position = self.indexes().iter().filter(|i| **i < index).count();
+        if self.is_signed_by(index) {
+            self.signatures[position] = signature;
+        } else {
+            self.bitmap[index / 8] |= 0b1000_0000 >> (index % 8);
+            self.signatures.insert(position, signature);
+        }
+        Ok(())
+    }
+
+    /// The Ed25519 signatures, ordered by key index.
+    pub fn signatures(&self) -> &[Ed25519Signature] {
+        &self.signatures
+    }
+
+    /// The indexes of the keys which produced the signatures, in increasing order.
+    pub fn indexes(&self) -> Vec<usize> {
+        (0..MAX_NUM_OF_KEYS)
+            .filter(|index| self.is_signed_by(*index))
+            .collect()
+    }
+
+    /// Serialize the signature.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            self.signatures.len() * ED25519_SIGNATURE_LENGTH + BITMAP_NUM_OF_BYTES,
+        );
+        for signature in &self.signatures {
+            bytes.extend_from_slice(&signature.to_bytes());
+        }
+        bytes.extend_from_slice(&self.bitmap);
+        bytes
+    }
+
+    fn is_signed_by(&self, index: usize) -> bool {
+        self.bitmap[index / 8] & (0b1000_0000 >> (index % 8)) != 0
+    }
+}
+
+impl TryFrom<&[u8]> for MultiEd25519Signature {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a MultiEd25519Signature. The number of signatures must match the bitmap.
+    fn try_from(bytes: &[u8]) -> std::result::Result<Self, CryptoMaterialError> {
+        if bytes.len() < BITMAP_NUM_OF_BYTES
+            || (bytes.len() - BITMAP_NUM_OF_BYTES) % ED25519_SIGNATURE_LENGTH != 0
+        {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        let (signatures_bytes, bitmap_bytes) = bytes.split_at(bytes.len() - BITMAP_NUM_OF_BYTES);
+        let mut bitmap = [0u8; BITMAP_NUM_OF_BYTES];
+        bitmap.copy_from_slice(bitmap_bytes);
+        let signatures = signatures_bytes
+            .chunks(ED25519_SIGNATURE_LENGTH)
+            .map(Ed25519Signature::try_from)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let multi_signature = MultiEd25519Signature { signatures, bitmap };
+        if multi_signature.indexes().len() != multi_signature.signatures.len() {
+            return Err(CryptoMaterialError::DeserializationError);
+        }
+        Ok(multi_signature)
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+    traits::*,
+    HashValue,
+};
+use core::convert::TryFrom;
+use rand::{rngs::StdRng, SeedableRng};
+
+fn generate_keys(n: usize) -> Vec<(Ed25519PrivateKey, Ed25519PublicKey)> {
+    let mut rng = StdRng::from_seed([7u8; 32]);
+    (0..n)
+        .map(|_| {
+            let private_key = Ed25519PrivateKey::generate_for_testing(&mut rng);
+            let public_key = (&private_key).into();
+            (private_key, public_key)
+        })
+        .collect()
+}
+
+fn multi_public_key(
+    keys: &[(Ed25519PrivateKey, Ed25519PublicKey)],
+    threshold: u8,
+) -> MultiEd25519PublicKey {
+    MultiEd25519PublicKey::new(
+        keys.iter()
+            .map(|(_, public_key)| public_key.clone())
+            .collect(),
+        threshold,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_threshold_verification() {
+    let keys = generate_keys(3);
+    let public_key = multi_public_key(&keys, 2);
+    let message = HashValue::random();
+
+    let mut signature =
+        MultiEd25519Signature::new(vec![(keys[2].0.sign_message(&message), 2)]).unwrap();
+    // One signature out of the two required.
+    assert!(public_key.verify_signature(&message, &signature).is_err());
+
+    // Co-sign with another key.
+    signature
+        .add_signature(keys[0].0.sign_message(&message), 0)
+        .unwrap();
+    assert_eq!(signature.indexes(), vec![0, 2]);
+    assert!(public_key.verify_signature(&message, &signature).is_ok());
+
+    // Signature attributed to the wrong key.
+    let wrong_signature = MultiEd25519Signature::new(vec![
+        (keys[0].0.sign_message(&message), 1),
+        (keys[2].0.sign_message(&message), 2),
+    ])
+    .unwrap();
+    assert!(public_key
+        .verify_signature(&message, &wrong_signature)
+        .is_err());
+
+    // Signature of a key which is not part of the public key.
+    let unknown_key_signature = MultiEd25519Signature::new(vec![
+        (keys[0].0.sign_message(&message), 0),
+        (keys[0].0.sign_message(&message), 5),
+    ])
+    .unwrap();
+    assert!(public_key
+        .verify_signature(&message, &unknown_key_signature)
+        .is_err());
+}
+
+#[test]
+fn test_invalid_threshold() {
+    let keys = generate_keys(2);
+    let public_keys: Vec<_> = keys
+        .iter()
+        .map(|(_, public_key)| public_key.clone())
+        .collect();
+    assert!(MultiEd25519PublicKey::new(public_keys.clone(), 0).is_err());
+    assert!(MultiEd25519PublicKey::new(public_keys.clone(), 3).is_err());
+    assert!(MultiEd25519PublicKey::new(public_keys, 2).is_ok());
+}
+
+#[test]
+fn test_serialization_round_trip() {
+    let keys = generate_keys(4);
+    let public_key = multi_public_key(&keys, 3);
+    let public_key_bytes = public_key.to_bytes();
+    assert_eq!(public_key_bytes.len(), 4 * 32 + 1);
+    assert_eq!(
+        MultiEd25519PublicKey::try_from(&public_key_bytes[..]).unwrap(),
+        public_key
+    );
+    // A single Ed25519 public key is not a multi-signature public key.
+    assert!(MultiEd25519PublicKey::try_from(&keys[0].1.to_bytes()[..]).is_err());
+
+    let message = HashValue::random();
+    let signature = MultiEd25519Signature::new(
+        [3, 0, 1]
+            .iter()
+            .map(|index| (keys[*index as usize].0.sign_message(&message), *index))
+            .collect(),
+    )
+    .unwrap();
+    let signature_bytes = signature.to_bytes();
+    assert_eq!(
+        MultiEd25519Signature::try_from(&signature_bytes[..]).unwrap(),
+        signature
+    );
+    assert!(public_key.verify_signature(&message, &signature).is_ok());
+
+    // Bitmap not matching the number of signatures.
+    let mut bad_bytes = signature_bytes.clone();
+    *bad_bytes.last_mut().unwrap() = 1;
+    assert!(MultiEd25519Signature::try_from(&bad_bytes[..]).is_err());
+}
@@ -45,7 +45,10 @@
 //! let output: Output = (&proof).into();
 //! ```
 
-use crate::traits::*;
+use crate::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    traits::*,
+};
 use core::convert::TryFrom;
 use curve25519_dalek::{
     constants::ED25519_BASEPOINT_POINT,
@@ -134,6 +137,12 @@ impl TryFrom<&[u8]> for VRFPrivateKey {
     }
 }
 
+impl<'a> From<&'a Ed25519PrivateKey> for VRFPrivateKey {
+    fn from(private_key: &'a Ed25519PrivateKey) -> Self {
+        VRFPrivateKey(ed25519_PrivateKey::from_bytes(&private_key.to_bytes()).unwrap())
+    }
+}
+
 impl Deref for VRFPrivateKey {
     type Target = ed25519_PrivateKey;
 
@@ -168,6 +177,14 @@ impl TryFrom<&[u8]> for VRFPublicKey {
     }
 }
 
+impl TryFrom<&Ed25519PublicKey> for VRFPublicKey {
+    type Error = CryptoMaterialError;
+
+    fn try_from(public_key: &Ed25519PublicKey) -> std::result::Result<VRFPublicKey, Self::Error> {
+        VRFPublicKey::try_from(&public_key.to_bytes()[..])
+    }
+}
+
 impl VRFPublicKey {
     /// Given a [`Proof`] and an input, returns whether or not the proof is valid for the input
     /// and public key
@@ -253,6 +270,7 @@ impl<'a> From<&'a VRFPrivateKey> for VRFExpandedPrivateKey {
 }
 
 /// A VRF proof that can be used to validate an input with a public key
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Proof {
     gamma: EdwardsPoint,
     c: ed25519_Scalar,
@@ -279,6 +297,9 @@ impl TryFrom<&[u8]> for Proof {
     type Error = CryptoMaterialError;
 
     fn try_from(bytes: &[u8]) -> std::result::Result<Proof, CryptoMaterialError> {
+        if bytes.len() != PROOF_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
         let mut c_buf = [0u8; 32];
         c_buf[..16].copy_from_slice(&bytes[32..48]);
         let mut s_buf = [0u8; 32];
@@ -286,7 +307,7 @@ impl TryFrom<&[u8]> for Proof {
         Ok(Proof {
             gamma: CompressedEdwardsY::from_slice(&bytes[..32])
                 .decompress()
-                .unwrap(),
+                .ok_or(CryptoMaterialError::DeserializationError)?,
             c: ed25519_Scalar::from_bits(c_buf),
             s: ed25519_Scalar::from_bits(s_buf),
         })
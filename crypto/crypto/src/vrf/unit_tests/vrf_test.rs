@@ -1,7 +1,7 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{hash::HashValue, unit_tests::uniform_keypair_strategy, vrf::ecvrf::*};
+use crate::{ed25519::*, hash::HashValue, unit_tests::uniform_keypair_strategy, vrf::ecvrf::*};
 use core::convert::TryFrom;
 use curve25519_dalek::{
     constants::ED25519_BASEPOINT_POINT, edwards::CompressedEdwardsY,
@@ -192,4 +192,18 @@ proptest! {
         prop_assert!(pk.verify(&proof1, input1).is_ok());
         prop_assert!(pk.verify(&proof1, input2).is_err());
     }
+
+    #[test]
+    fn test_ed25519_keys(
+        hash in any::<HashValue>(),
+        keypair in uniform_keypair_strategy::<Ed25519PrivateKey, Ed25519PublicKey>()
+    ) {
+        let sk = VRFPrivateKey::from(&keypair.private_key);
+        let pk = VRFPublicKey::try_from(&keypair.public_key).unwrap();
+        prop_assert_eq!(&pk, &VRFPublicKey::from(&sk));
+        let proof = sk.prove(hash.as_ref());
+        prop_assert!(pk.verify(&proof, hash.as_ref()).is_ok());
+        prop_assert_eq!(&Proof::try_from(&proof.to_bytes()[..]).unwrap(), &proof);
+        prop_assert!(Proof::try_from(&proof.to_bytes()[1..]).is_err());
+    }
 }
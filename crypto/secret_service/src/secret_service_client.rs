@@ -3,19 +3,22 @@
 
 //! ConsensusKeyManager gives a simple interface for consensus to interact with the secret service.
 //! This simple key manager will become more complicated in future versions,
-//! now it asks the secret service to generate an ed25519 key on creation,
+//! now it asks the secret service to generate an ed25519 key or to import the consensus key of the
+//! validator on creation,
 //! it can then transfer to the secret service the requests to get consensus public key and to sign
 //! a consensus message.
 
 use crate::{
     crypto_wrappers::{GenericPublicKey, GenericSignature, KeyID},
     proto::{
-        secret_service::{GenerateKeyRequest, KeyType, PublicKeyRequest, SignRequest},
+        secret_service::{
+            ErrorCode, GenerateKeyRequest, ImportKeyRequest, KeyType, PublicKeyRequest, SignRequest,
+        },
         secret_service_grpc::SecretServiceClient,
     },
 };
 use crypto::{
-    ed25519::{Ed25519PublicKey, Ed25519Signature},
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
     hash::HashValue,
 };
 use failure::prelude::*;
@@ -44,6 +47,29 @@ impl ConsensusKeyManager {
         })
     }
 
+    /// Saves a reference to the secret service and imports the given signing key into it, e.g. the
+    /// consensus key of the validator from its config. The key keeps the same keyid whenever it is
+    /// imported again.
+    pub fn import(
+        secret_service: Arc<SecretServiceClient>,
+        private_key: Ed25519PrivateKey,
+    ) -> Result<Self> {
+        let mut import_req: ImportKeyRequest = ImportKeyRequest::new();
+        import_req.set_spec(KeyType::Ed25519);
+        import_req.set_private_key(private_key.to_bytes().to_vec());
+
+        let response = secret_service.import_key(&import_req)?;
+        ensure!(
+            response.get_code() == ErrorCode::Success,
+            "The secret service failed to import the consensus key: {:?}",
+            response.get_code()
+        );
+        Ok(Self {
+            secret_service,
+            signing_keyid: KeyID(HashValue::from_slice(response.get_key_id())?),
+        })
+    }
+
     /// Asks the secret service for the public key and returns it.
     pub fn get_consensus_public_key(&self) -> Result<GenericPublicKey> {
         let mut pk_req: PublicKeyRequest = PublicKeyRequest::new();
@@ -2,21 +2,25 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! The Secret service server stores the secret key and performs operations on these keys.
-//! Right now the service supports requests to generate the secret key (of Ed25519 or BLS12-381
-//! type), return the corresponding public key and sign.
+//! Right now the service supports requests to generate or import the secret key (of Ed25519 or
+//! BLS12-381 type), return the corresponding public key and sign.
 
 use crate::{
     crypto_wrappers::{GenericPrivateKey, GenericPublicKey, GenericSignature, KeyID},
     proto::{
         secret_service::{
-            ErrorCode, GenerateKeyRequest, GenerateKeyResponse, KeyType, PublicKeyRequest,
-            PublicKeyResponse, SignRequest, SignResponse,
+            ErrorCode, GenerateKeyRequest, GenerateKeyResponse, ImportKeyRequest,
+            ImportKeyResponse, KeyType, PublicKeyRequest, PublicKeyResponse, SignRequest,
+            SignResponse,
         },
         secret_service_grpc,
     },
 };
 use crypto::{
-    bls12381::BLS12381PrivateKey, ed25519::Ed25519PrivateKey, hash::HashValue, traits::*,
+    bls12381::BLS12381PrivateKey,
+    ed25519::Ed25519PrivateKey,
+    hash::{CryptoHasher, HashValue, SecretServiceKeyIdHasher},
+    traits::*,
 };
 use failure::prelude::*;
 use grpc_helpers::provide_grpc_response;
@@ -24,6 +28,7 @@ use rand::{rngs::EntropyRng, Rng, SeedableRng};
 use rand_chacha::ChaChaRng;
 use std::{
     collections::HashMap,
+    convert::TryFrom,
     sync::{Arc, RwLock},
 };
 
@@ -90,6 +95,24 @@ impl SecretServiceServer {
         Ok(result)
     }
 
+    /// Imports an existing secret key, e.g., the consensus key of a validator.
+    pub fn import_key_inner(&mut self, private_key: GenericPrivateKey) -> Result<KeyID> {
+        // The keyid of an imported key is derived from the secret key, so that importing the key
+        // again (e.g. after a restart of the service) gives the same keyid, which still can't be
+        // guessed from the public key.
+        let mut hasher = SecretServiceKeyIdHasher::default();
+        hasher.write(&private_key.to_bytes());
+        let keyid = KeyID(hasher.finish());
+
+        let result = keyid.clone();
+        let mut keys = self
+            .keys
+            .write()
+            .expect("[importing key] acquire keys lock");
+        keys.insert(keyid, private_key);
+        Ok(result)
+    }
+
     /// Computes and returns the public key of the corresponding secret key.
     pub fn get_public_key_inner(&self, keyid: &KeyID) -> Option<GenericPublicKey> {
         let keys = self
@@ -133,6 +156,36 @@ impl secret_service_grpc::SecretService for SecretServiceServer {
         provide_grpc_response(Ok(response), ctx, sink);
     }
 
+    /// Imports a key answering an ImportKeyRequest with an ImportKeyResponse.
+    fn import_key(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        req: ImportKeyRequest,
+        sink: ::grpcio::UnarySink<ImportKeyResponse>,
+    ) {
+        let mut response = ImportKeyResponse::new();
+        let private_key_raw_bytes = req.get_private_key();
+        let private_key = match req.get_spec() {
+            KeyType::Ed25519 => {
+                Ed25519PrivateKey::try_from(private_key_raw_bytes).map(GenericPrivateKey::Ed)
+            }
+            KeyType::BLS12381 => {
+                BLS12381PrivateKey::try_from(private_key_raw_bytes).map(GenericPrivateKey::BLS)
+            }
+        };
+        if let Ok(private_key) = private_key {
+            if let Ok(key_identity) = self.import_key_inner(private_key) {
+                response.set_code(ErrorCode::Success);
+                response.set_key_id(key_identity.to_vec());
+            } else {
+                response.set_code(ErrorCode::Unspecified);
+            }
+        } else {
+            response.set_code(ErrorCode::InvalidParameters);
+        }
+        provide_grpc_response(Ok(response), ctx, sink);
+    }
+
     /// Returns a corresponding public key answering a PublicKeyRequest with a PublicKeyResponse.
     fn get_public_key(
         &mut self,
@@ -2,13 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    crypto_wrappers::{GenericPrivateKey, GenericPublicKey},
     proto::secret_service::KeyType,
     secret_service_server::{KeyID, SecretServiceServer},
 };
 use crypto::{
+    ed25519::Ed25519PrivateKey,
     hash::HashValue,
-    traits::{Signature, ValidKey},
+    traits::{Signature, Uniform, ValidKey},
 };
+use rand::{rngs::StdRng, SeedableRng};
+use std::convert::TryFrom;
 
 /////////////////////////////////////////////////////////////////////////////////////
 // These tests check interoperability of key_generation,                           //
@@ -162,3 +166,59 @@ fn test_ed25519_sign() {
         );
     }
 }
+
+#[test]
+fn test_import_key() {
+    let mut ss_service = SecretServiceServer::new();
+    let mut rng = StdRng::from_seed([0u8; 32]);
+    let private_key = Ed25519PrivateKey::generate_for_testing(&mut rng);
+    let public_key = GenericPublicKey::Ed((&private_key).into());
+    let private_key_bytes = private_key.to_bytes();
+
+    let keyid1 = ss_service
+        .import_key_inner(GenericPrivateKey::Ed(private_key))
+        .unwrap();
+    let public_key1 = ss_service.get_public_key_inner(&keyid1);
+    assert!(public_key1.is_some(), "SecretService does not return a key");
+    assert_eq!(
+        public_key1.unwrap().to_bytes(),
+        public_key.to_bytes(),
+        "SecretService does not return the imported key"
+    );
+
+    /* signature obtained verifies with the public key of the imported key */
+    let message_hash = HashValue::random();
+    let signature = ss_service.sign_inner(&keyid1, &message_hash);
+    assert!(
+        signature.is_some(),
+        "SecretService does not return a signature"
+    );
+    assert!(
+        signature
+            .unwrap()
+            .verify(&message_hash, &public_key)
+            .is_ok(),
+        "Correct signature does not verify"
+    );
+
+    /* the same key gets the same key id when imported again, in any service */
+    let mut other_ss_service = SecretServiceServer::new();
+    let private_key = Ed25519PrivateKey::try_from(&private_key_bytes[..]).unwrap();
+    let keyid2 = other_ss_service
+        .import_key_inner(GenericPrivateKey::Ed(private_key))
+        .unwrap();
+    assert!(
+        keyid1 == keyid2,
+        "SecretService key id of imported key changes"
+    );
+
+    /* a different key gets a different key id */
+    let other_private_key = Ed25519PrivateKey::generate_for_testing(&mut rng);
+    let keyid3 = ss_service
+        .import_key_inner(GenericPrivateKey::Ed(other_private_key))
+        .unwrap();
+    assert!(
+        keyid1 != keyid3,
+        "SecretService returns same key ids on different keys"
+    );
+}
@@ -7,6 +7,7 @@ use crate::{
     Command, OP_COUNTERS,
 };
 use backoff::{ExponentialBackoff, Operation};
+use canonical_serialization::SimpleDeserializer;
 use config::config::VMConfig;
 use crypto::{
     ed25519::*,
@@ -27,6 +28,7 @@ use std::{
 };
 use storage_client::{StorageRead, StorageWrite, VerifiedStateView};
 use types::{
+    access_path::VALIDATOR_SET_ACCESS_PATH,
     account_address::AccountAddress,
     account_state_blob::AccountStateBlob,
     ledger_info::LedgerInfoWithSignatures,
@@ -35,6 +37,7 @@ use types::{
         SignedTransaction, TransactionInfo, TransactionListWithProof, TransactionOutput,
         TransactionPayload, TransactionStatus, TransactionToCommit, Version,
     },
+    validator_set::ValidatorSet,
     write_set::{WriteOp, WriteSet},
 };
 use vm_runtime::VMExecutor;
@@ -645,14 +648,14 @@ where
                 let accumulator = output.clone_transaction_accumulator();
                 let root_hash = accumulator.root_hash();
                 let version = accumulator.num_leaves() - 1;
+                let validators = output.validators().clone();
                 block_to_execute.set_output(output);
 
                 // Now that we have the root hash and execution status we can send the response to
-                // consensus.
-                // TODO: The VM will support a special transaction to set the validators for the
-                // next epoch that is part of a block execution.
+                // consensus, along with the validators of the next epoch if the block reconfigures
+                // them.
                 let execute_block_response =
-                    ExecuteBlockResponse::new(root_hash, status, version, None);
+                    ExecuteBlockResponse::new(root_hash, status, version, validators);
                 block_to_execute.set_execute_block_response(execute_block_response);
             }
             Err(err) => {
@@ -711,6 +714,8 @@ where
         // transactions that will be discarded, since they do not go into the transaction
         // accumulator.
         let mut txn_info_hashes = vec![];
+        // The validator set of the next epoch, if a transaction writes it.
+        let mut validators = None;
 
         let proof_reader = ProofReader::new(account_to_proof);
         for (vm_output, signed_txn) in
@@ -742,6 +747,9 @@ where
                         vm_output.gas_used(),
                     );
                     txn_info_hashes.push(txn_info.hash());
+                    if let Some(validator_set) = Self::find_validator_set(vm_output.write_set())? {
+                        validators = Some(validator_set);
+                    }
                 }
                 TransactionStatus::Discard(_) => {
                     ensure!(
@@ -773,9 +781,25 @@ where
             txn_data,
             Rc::new(current_transaction_accumulator),
             current_state_tree,
+            validators,
         ))
     }
 
+    /// Returns the validator set a transaction writes, which makes it a reconfiguration
+    /// transaction.
+    fn find_validator_set(write_set: &WriteSet) -> Result<Option<ValidatorSet>> {
+        for (access_path, write_op) in write_set {
+            if *access_path != *VALIDATOR_SET_ACCESS_PATH {
+                continue;
+            }
+            return match write_op {
+                WriteOp::Value(blob) => Ok(Some(SimpleDeserializer::deserialize(blob)?)),
+                WriteOp::Deletion => bail!("The validator set can not be deleted."),
+            };
+        }
+        Ok(None)
+    }
+
     /// For all accounts modified by this transaction, find the previous blob and update it based
     /// on the write set. Returns the blob value of all these accounts as well as the newly
     /// constructed state tree.
@@ -3,7 +3,8 @@
 
 use crate::{
     mock_vm::{
-        encode_mint_transaction, encode_transfer_transaction, MockVM, DISCARD_STATUS, KEEP_STATUS,
+        encode_mint_transaction, encode_reconfiguration_transaction, encode_transfer_transaction,
+        MockVM, DISCARD_STATUS, KEEP_STATUS,
     },
     Executor, OP_COUNTERS,
 };
@@ -27,6 +28,7 @@ use types::{
     account_address::{AccountAddress, ADDRESS_LENGTH},
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     transaction::{SignedTransaction, TransactionListWithProof, Version},
+    validator_set::ValidatorSet,
 };
 use vm_genesis::{encode_genesis_transaction, GENESIS_KEYPAIR};
 
@@ -165,6 +167,7 @@ fn gen_ledger_info(
         commit_block_id,
         /* epoch_num = */ 0,
         timestamp_usecs,
+        /* next_validator_set = */ None,
     );
     LedgerInfoWithSignatures::new(ledger_info, /* signatures = */ HashMap::new())
 }
@@ -607,4 +610,28 @@ proptest! {
         drop(storage_server);
         shutdown_receiver.recv().unwrap();
     }
+
+    #[test]
+    fn test_executor_reconfiguration(validator_set in any::<ValidatorSet>()) {
+        let executor = TestExecutor::new();
+
+        // A block without reconfiguration transaction keeps the validators.
+        let response_a = block_on(executor.execute_block(
+            vec![encode_mint_transaction(gen_address(0), 100)],
+            *GENESIS_BLOCK_ID,
+            gen_block_id(1),
+        )).unwrap().unwrap();
+        prop_assert_eq!(response_a.validators(), &None);
+
+        // The validators written by a reconfiguration transaction are the ones of the next epoch.
+        let response_b = block_on(executor.execute_block(
+            vec![
+                encode_reconfiguration_transaction(gen_address(1), &validator_set),
+                encode_mint_transaction(gen_address(2), 100),
+            ],
+            gen_block_id(1),
+            gen_block_id(2),
+        )).unwrap().unwrap();
+        prop_assert_eq!(response_b.validators(), &Some(validator_set));
+    }
 }
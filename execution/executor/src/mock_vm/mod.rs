@@ -4,18 +4,20 @@
 #[cfg(test)]
 mod mock_vm_test;
 
+use canonical_serialization::SimpleSerializer;
 use config::config::VMConfig;
 use crypto::ed25519::compat;
 use state_view::StateView;
 use std::collections::HashMap;
 use types::{
-    access_path::AccessPath,
+    access_path::{AccessPath, VALIDATOR_SET_ACCESS_PATH},
     account_address::{AccountAddress, ADDRESS_LENGTH},
     contract_event::ContractEvent,
     transaction::{
         Program, RawTransaction, SignedTransaction, TransactionArgument, TransactionOutput,
         TransactionPayload, TransactionStatus,
     },
+    validator_set::ValidatorSet,
     vm_error::{ExecutionStatus, VMStatus},
     write_set::{WriteOp, WriteSet, WriteSetMut},
 };
@@ -32,6 +34,7 @@ enum Transaction {
         recipient: AccountAddress,
         amount: u64,
     },
+    WriteSet(WriteSet),
 }
 
 pub const KEEP_STATUS: TransactionStatus =
@@ -120,6 +123,9 @@ impl VMExecutor for MockVM {
                         TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed)),
                     ));
                 }
+                Transaction::WriteSet(write_set) => {
+                    outputs.push(TransactionOutput::new(write_set, vec![], 0, KEEP_STATUS));
+                }
             }
         }
 
@@ -263,6 +269,34 @@ pub fn encode_transfer_transaction(
     encode_transaction(sender, encode_transfer_program(recipient, amount))
 }
 
+/// Encodes a write set transaction which sets the validators of the next epoch.
+pub fn encode_reconfiguration_transaction(
+    sender: AccountAddress,
+    validator_set: &ValidatorSet,
+) -> SignedTransaction {
+    let mut write_set = WriteSetMut::default();
+    write_set.push((
+        VALIDATOR_SET_ACCESS_PATH.clone(),
+        WriteOp::Value(
+            SimpleSerializer::<Vec<u8>>::serialize(validator_set)
+                .expect("Failed to serialize validator set."),
+        ),
+    ));
+    let raw_transaction = RawTransaction::new_write_set(
+        sender,
+        0,
+        write_set
+            .freeze()
+            .expect("reconfiguration writeset should be valid"),
+    );
+
+    let (privkey, pubkey) = compat::generate_keypair(None);
+    raw_transaction
+        .sign(&privkey, pubkey)
+        .expect("Failed to sign raw transaction.")
+        .into_inner()
+}
+
 fn encode_transaction(sender: AccountAddress, program: Program) -> SignedTransaction {
     let raw_transaction =
         RawTransaction::new(sender, 0, program, 0, 0, std::time::Duration::from_secs(0));
@@ -303,9 +337,7 @@ fn decode_transaction(txn: &SignedTransaction) -> Transaction {
                 _ => unimplemented!("Transaction must have one or two arguments."),
             }
         }
-        TransactionPayload::WriteSet(_) => {
-            unimplemented!("MockVM does not support WriteSet transaction payload.")
-        }
+        TransactionPayload::WriteSet(write_set) => Transaction::WriteSet(write_set.clone()),
         TransactionPayload::Script(_) => {
             unimplemented!("MockVM does not support Script transaction payload.")
         }
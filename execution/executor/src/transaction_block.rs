@@ -22,6 +22,7 @@ use types::{
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     transaction::{SignedTransaction, TransactionStatus},
+    validator_set::ValidatorSet,
 };
 
 /// `TransactionBlock` holds everything about the block of transactions.
@@ -342,6 +343,10 @@ pub struct ProcessedVMOutput {
     /// is only for convenience purpose so we do not need to deal with the special case of empty
     /// block.
     state_tree: Rc<SparseMerkleTree>,
+
+    /// The validator set written by the last reconfiguration transaction in this block, if any.
+    /// It is the validator set of the next epoch.
+    validators: Option<ValidatorSet>,
 }
 
 impl ProcessedVMOutput {
@@ -349,11 +354,13 @@ impl ProcessedVMOutput {
         transaction_data: Vec<TransactionData>,
         transaction_accumulator: Rc<Accumulator<TransactionAccumulatorHasher>>,
         state_tree: Rc<SparseMerkleTree>,
+        validators: Option<ValidatorSet>,
     ) -> Self {
         ProcessedVMOutput {
             transaction_data,
             transaction_accumulator,
             state_tree,
+            validators,
         }
     }
 
@@ -368,4 +375,8 @@ impl ProcessedVMOutput {
     pub fn clone_state_tree(&self) -> Rc<SparseMerkleTree> {
         Rc::clone(&self.state_tree)
     }
+
+    pub fn validators(&self) -> &Option<ValidatorSet> {
+        &self.validators
+    }
 }
@@ -16,6 +16,7 @@ mod function_call;
 mod genesis;
 mod mint;
 mod module_publishing;
+mod multi_signature;
 mod pack_unpack;
 mod peer_to_peer;
 mod rotate_key;
@@ -0,0 +1,137 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::{Account, AccountData},
+    common_transactions::{rotate_key_txn, PEER_TO_PEER},
+    executor::FakeExecutor,
+    gas_costs,
+};
+use crypto::{
+    ed25519::*,
+    multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+    traits::SigningKey,
+};
+use std::time::Duration;
+use types::{
+    account_address::AccountAddress,
+    transaction::{
+        Program, RawTransaction, SignedTransaction, TransactionArgument, TransactionStatus,
+    },
+    vm_error::{ExecutionStatus, VMStatus, VMValidationStatus},
+};
+
+fn multi_signed_transfer(
+    sender: &AccountAddress,
+    receiver: &AccountAddress,
+    seq_num: u64,
+    public_key: &MultiEd25519PublicKey,
+    signers: &[&Ed25519PrivateKey],
+) -> SignedTransaction {
+    let raw_txn = RawTransaction::new(
+        *sender,
+        seq_num,
+        Program::new(
+            PEER_TO_PEER.clone(),
+            vec![],
+            vec![
+                TransactionArgument::Address(*receiver),
+                TransactionArgument::U64(1_000),
+            ],
+        ),
+        gas_costs::TXN_RESERVED,
+        1,
+        Duration::from_secs(u64::max_value()),
+    );
+    let mut signed_txn = SignedTransaction::craft_multi_signed_transaction_for_client(
+        raw_txn,
+        public_key.clone(),
+        MultiEd25519Signature::new(vec![]).unwrap(),
+    );
+    let message = signed_txn.signing_message();
+    for signer in signers {
+        let signer_public_key: Ed25519PublicKey = (*signer).into();
+        signed_txn
+            .add_cosigner_signature(&signer_public_key, signer.sign_message(&message))
+            .unwrap();
+    }
+    signed_txn
+}
+
+#[test]
+fn multi_signature_account() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    // Hand the control of the sender account over to 2 of 3 keys.
+    let cosigners: Vec<_> = (0..3).map(|_| Account::new()).collect();
+    let public_key = MultiEd25519PublicKey::new(
+        cosigners
+            .iter()
+            .map(|cosigner| cosigner.pubkey.clone())
+            .collect(),
+        2,
+    )
+    .unwrap();
+    let new_key_hash = AccountAddress::from_authentication_key_preimage(&public_key.to_bytes());
+    let output =
+        &executor.execute_block(vec![rotate_key_txn(sender.account(), new_key_hash, 10)])[0];
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed)),
+    );
+    executor.apply_write_set(output.write_set());
+
+    // A single signature is not enough.
+    let txn = multi_signed_transfer(
+        sender.address(),
+        receiver.address(),
+        11,
+        &public_key,
+        &[&cosigners[1].privkey],
+    );
+    assert_eq!(
+        executor.verify_transaction(txn),
+        Some(VMStatus::Validation(VMValidationStatus::InvalidSignature))
+    );
+
+    // The single key of a cosigner does not match the authentication key.
+    let txn = cosigners[0].create_signed_txn_with_args_and_sender(
+        *sender.address(),
+        PEER_TO_PEER.clone(),
+        vec![
+            TransactionArgument::Address(*receiver.address()),
+            TransactionArgument::U64(1_000),
+        ],
+        11,
+        gas_costs::TXN_RESERVED,
+        1,
+    );
+    assert_eq!(
+        executor.verify_transaction(txn),
+        Some(VMStatus::Validation(VMValidationStatus::InvalidAuthKey))
+    );
+
+    // Two signatures out of three are.
+    let txn = multi_signed_transfer(
+        sender.address(),
+        receiver.address(),
+        11,
+        &public_key,
+        &[&cosigners[0].privkey, &cosigners[2].privkey],
+    );
+    assert_eq!(executor.verify_transaction(txn.clone()), None);
+    let output = &executor.execute_block(vec![txn])[0];
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed)),
+    );
+    executor.apply_write_set(output.write_set());
+    let updated_receiver = executor
+        .read_account_resource(receiver.account())
+        .expect("receiver must exist");
+    assert_eq!(101_000, updated_receiver.balance());
+}
@@ -2,12 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::gas_schedule::{AbstractMemorySize, GasAlgebra, GasCarrier, GasPrice, GasUnits};
-use crypto::ed25519::{compat, Ed25519PublicKey};
+use crypto::ed25519::compat;
 use types::{account_address::AccountAddress, transaction::SignedTransaction};
 
 pub struct TransactionMetadata {
     pub sender: AccountAddress,
-    pub public_key: Ed25519PublicKey,
+    /// Preimage of the authentication key of the sender: an Ed25519 public key or the encoding of
+    /// a multi-signature public key.
+    pub authentication_key_preimage: Vec<u8>,
     pub sequence_number: u64,
     pub max_gas_amount: GasUnits<GasCarrier>,
     pub gas_unit_price: GasPrice<GasCarrier>,
@@ -18,7 +20,7 @@ impl TransactionMetadata {
     pub fn new(txn: &SignedTransaction) -> Self {
         Self {
             sender: txn.sender(),
-            public_key: txn.public_key().into(),
+            authentication_key_preimage: txn.authentication_key_preimage(),
             sequence_number: txn.sequence_number(),
             max_gas_amount: GasUnits::new(txn.max_gas_amount()),
             gas_unit_price: GasPrice::new(txn.gas_unit_price()),
@@ -38,8 +40,8 @@ impl TransactionMetadata {
         self.sender.to_owned()
     }
 
-    pub fn public_key(&self) -> &Ed25519PublicKey {
-        &self.public_key
+    pub fn authentication_key_preimage(&self) -> &[u8] {
+        &self.authentication_key_preimage
     }
 
     pub fn sequence_number(&self) -> u64 {
@@ -56,7 +58,7 @@ impl Default for TransactionMetadata {
         let (_, public_key) = compat::generate_genesis_keypair();
         TransactionMetadata {
             sender: AccountAddress::default(),
-            public_key,
+            authentication_key_preimage: public_key.to_bytes().to_vec(),
             sequence_number: 0,
             max_gas_amount: GasUnits::new(100_000_000),
             gas_unit_price: GasPrice::new(0),
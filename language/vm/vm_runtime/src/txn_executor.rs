@@ -510,7 +510,7 @@ where
                 }
                 Bytecode::GetTxnPublicKey => {
                     self.execution_stack.push(Local::bytearray(ByteArray::new(
-                        self.txn_data.public_key().to_bytes().to_vec(),
+                        self.txn_data.authentication_key_preimage().to_vec(),
                     )));
                 }
                 Bytecode::BorrowGlobal(idx, _) => {
@@ -716,7 +716,7 @@ fn test_transaction_info() {
         let (_, public_key) = compat::generate_genesis_keypair();
         TransactionMetadata {
             sender: AccountAddress::default(),
-            public_key,
+            authentication_key_preimage: public_key.to_bytes().to_vec(),
             sequence_number: 10,
             max_gas_amount: GasUnits::new(100_000_009),
             gas_unit_price: GasPrice::new(5),
@@ -4,7 +4,9 @@
 use admission_control_proto::proto::admission_control_grpc::{
     create_admission_control, AdmissionControlClient,
 };
-use admission_control_service::admission_control_service::AdmissionControlService;
+use admission_control_service::{
+    admission_control_service::AdmissionControlService, event_watcher::EventWatchConfig,
+};
 use config::config::{NodeConfig, RoleType};
 use consensus::consensus_provider::{make_consensus_provider, ConsensusProvider};
 use crypto::ed25519::*;
@@ -29,8 +31,9 @@ use network::{
 use state_synchronizer::StateSynchronizer;
 use std::{
     cmp::min,
+    collections::HashMap,
     convert::{TryFrom, TryInto},
-    sync::Arc,
+    sync::{Arc, RwLock},
     thread,
     time::Instant,
 };
@@ -96,6 +99,7 @@ fn setup_ac(config: &NodeConfig) -> (::grpcio::Server, AdmissionControlClient) {
         config
             .admission_control
             .need_to_check_mempool_before_validation,
+        EventWatchConfig::from(&config.admission_control),
     );
     let service = create_admission_control(handle);
     let server = ServerBuilder::new(Arc::clone(&env))
@@ -147,7 +151,13 @@ fn setup_debug_interface(config: &NodeConfig) -> ::grpcio::Server {
         .expect("Unable to create grpc server")
 }
 
-pub fn setup_network(config: &mut NodeConfig) -> (Runtime, Box<dyn LibraNetworkProvider>) {
+pub fn setup_network(
+    config: &mut NodeConfig,
+) -> (
+    Runtime,
+    Box<dyn LibraNetworkProvider>,
+    Arc<RwLock<HashMap<PeerId, NetworkPublicKeys>>>,
+) {
     let runtime = Builder::new()
         .name_prefix("network-")
         .build()
@@ -184,29 +194,30 @@ pub fn setup_network(config: &mut NodeConfig) -> (Runtime, Box<dyn LibraNetworkP
 
     let network_signing_public: Ed25519PublicKey = (&network_signing_private).into();
     let network_identity_keypair = config.base.peer_keypairs.get_network_identity_keypair();
-    let (_listen_addr, network_provider) =
-        NetworkBuilder::new(runtime.executor(), peer_id, listen_addr)
-            .transport(if config.network.enable_encryption_and_authentication {
-                TransportType::TcpNoise
-            } else {
-                TransportType::Tcp
-            })
-            .advertised_address(advertised_addr)
-            .seed_peers(seed_peers)
-            .signing_keys((network_signing_private, network_signing_public))
-            .identity_keys(network_identity_keypair)
-            .trusted_peers(trusted_peers)
-            .discovery_interval_ms(config.network.discovery_interval_ms)
-            .connectivity_check_interval_ms(config.network.connectivity_check_interval_ms)
-            .direct_send_protocols(vec![
-                ProtocolId::from_static(CONSENSUS_DIRECT_SEND_PROTOCOL),
-                ProtocolId::from_static(MEMPOOL_DIRECT_SEND_PROTOCOL),
-                ProtocolId::from_static(STATE_SYNCHRONIZER_MSG_PROTOCOL),
-            ])
-            .rpc_protocols(vec![ProtocolId::from_static(CONSENSUS_RPC_PROTOCOL)])
-            .build();
+    let mut network_builder = NetworkBuilder::new(runtime.executor(), peer_id, listen_addr);
+    network_builder
+        .transport(if config.network.enable_encryption_and_authentication {
+            TransportType::TcpNoise
+        } else {
+            TransportType::Tcp
+        })
+        .advertised_address(advertised_addr)
+        .seed_peers(seed_peers)
+        .signing_keys((network_signing_private, network_signing_public))
+        .identity_keys(network_identity_keypair)
+        .trusted_peers(trusted_peers)
+        .discovery_interval_ms(config.network.discovery_interval_ms)
+        .connectivity_check_interval_ms(config.network.connectivity_check_interval_ms)
+        .direct_send_protocols(vec![
+            ProtocolId::from_static(CONSENSUS_DIRECT_SEND_PROTOCOL),
+            ProtocolId::from_static(MEMPOOL_DIRECT_SEND_PROTOCOL),
+            ProtocolId::from_static(STATE_SYNCHRONIZER_MSG_PROTOCOL),
+        ])
+        .rpc_protocols(vec![ProtocolId::from_static(CONSENSUS_RPC_PROTOCOL)]);
+    let trusted_peers = network_builder.shared_trusted_peers();
+    let (_listen_addr, network_provider) = network_builder.build();
 
-    (runtime, network_provider)
+    (runtime, network_provider, trusted_peers)
 }
 
 pub fn setup_environment(node_config: &mut NodeConfig) -> (AdmissionControlClient, LibraHandle) {
@@ -232,7 +243,7 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> (AdmissionControlClien
     debug!("AC started in {} ms", instant.elapsed().as_millis());
 
     instant = Instant::now();
-    let (runtime, mut network_provider) = setup_network(node_config);
+    let (runtime, mut network_provider, trusted_network_peers) = setup_network(node_config);
     debug!("Network started in {} ms", instant.elapsed().as_millis());
 
     let (state_sync_network_sender, state_sync_network_events) = network_provider
@@ -269,6 +280,7 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> (AdmissionControlClien
             node_config,
             consensus_network_sender,
             consensus_network_events,
+            trusted_network_peers,
             state_synchronizer.create_client(),
         );
         consensus_provider
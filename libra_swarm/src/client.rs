@@ -168,6 +168,11 @@ impl InProcessTestClient {
                 false,
                 /* faucet server */ None,
                 Some(mnemonic_file_path.to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
             )
             .unwrap(),
             alias_to_cmd,
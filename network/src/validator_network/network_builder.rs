@@ -146,6 +146,12 @@ impl NetworkBuilder {
         self
     }
 
+    /// The trusted peers shared with the network components: updating them, e.g. with the
+    /// validators of a new epoch, takes effect on the running network.
+    pub fn shared_trusted_peers(&self) -> Arc<RwLock<HashMap<PeerId, NetworkPublicKeys>>> {
+        Arc::clone(&self.trusted_peers)
+    }
+
     /// Set signing keys of local node.
     pub fn signing_keys(&mut self, keys: (Ed25519PrivateKey, Ed25519PublicKey)) -> &mut Self {
         self.signing_keys = Some(keys);
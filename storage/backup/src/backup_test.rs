@@ -0,0 +1,91 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use libradb::{mock_genesis::db_with_mock_genesis, test_helper::arb_blocks_to_commit};
+use proptest::prelude::*;
+use tempfile::tempdir;
+use types::transaction::TransactionToCommit;
+
+fn test_backup_restore_impl(
+    blocks: Vec<(
+        Vec<TransactionToCommit>,
+        LedgerInfoWithSignatures<Ed25519Signature>,
+    )>,
+) {
+    let tmp_dir = tempdir().unwrap();
+    let db = db_with_mock_genesis(&tmp_dir).unwrap();
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in &blocks {
+        db.save_transactions(
+            &txns_to_commit,
+            cur_ver + 1, /* first_version */
+            &Some(ledger_info_with_sigs.clone()),
+        )
+        .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+
+    let backup_dir = tempdir().unwrap();
+    let summary = backup(&db, backup_dir.path()).unwrap();
+    assert_eq!(summary.version, cur_ver);
+    assert!(backup(&db, backup_dir.path()).is_err());
+
+    let restore_dir = tempdir().unwrap();
+    let restored_db = LibraDB::new(&restore_dir);
+    assert_eq!(
+        restore(backup_dir.path(), &restored_db, None /* verifier */).unwrap(),
+        summary
+    );
+    assert!(restore(backup_dir.path(), &restored_db, None /* verifier */).is_err());
+
+    assert_eq!(
+        restored_db.get_executor_startup_info().unwrap(),
+        db.get_executor_startup_info().unwrap()
+    );
+    assert_eq!(
+        restored_db
+            .get_transactions(0, cur_ver + 1, cur_ver, true /* fetch_events */)
+            .unwrap(),
+        db.get_transactions(0, cur_ver + 1, cur_ver, true /* fetch_events */)
+            .unwrap()
+    );
+    let account_states = db
+        .get_account_iter(cur_ver)
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    let restored_account_states = restored_db
+        .get_account_iter(cur_ver)
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(restored_account_states, account_states);
+    assert_eq!(summary.num_accounts, account_states.len());
+}
+
+#[test]
+fn test_restore_tampered_backup() {
+    let tmp_dir = tempdir().unwrap();
+    let db = db_with_mock_genesis(&tmp_dir).unwrap();
+    let backup_dir = tempdir().unwrap();
+    backup(&db, backup_dir.path()).unwrap();
+
+    let chunk_file = state_chunk_file(backup_dir.path(), 0);
+    let mut account_states = read_account_state_chunk(&chunk_file).unwrap();
+    account_states.pop();
+    write_account_state_chunk(&chunk_file, account_states).unwrap();
+
+    let restore_dir = tempdir().unwrap();
+    let restored_db = LibraDB::new(&restore_dir);
+    assert!(restore(backup_dir.path(), &restored_db, None /* verifier */).is_err());
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(5))]
+
+    #[test]
+    fn test_backup_restore(blocks in arb_blocks_to_commit()) {
+        test_backup_restore_impl(blocks);
+    }
+}
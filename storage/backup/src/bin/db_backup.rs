@@ -0,0 +1,42 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use backup::backup;
+use grpcio::EnvBuilder;
+use std::{path::Path, sync::Arc};
+use storage_client::StorageReadServiceClient;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "db-backup",
+    author = "Libra",
+    about = "Back up the ledger and the latest state of a running storage service"
+)]
+struct Args {
+    /// Host of the storage service
+    #[structopt(short = "a", long = "host", default_value = "localhost")]
+    pub host: String,
+    /// Port of the storage service
+    #[structopt(short = "p", long = "port")]
+    pub port: u16,
+    /// Directory to write the backup to. It is created if it does not exist.
+    #[structopt(short = "d", long = "backup_dir")]
+    pub backup_dir: String,
+}
+
+fn main() {
+    let args = Args::from_args();
+    let env = Arc::new(EnvBuilder::new().name_prefix("grpc-backup-").build());
+    let client = StorageReadServiceClient::new(env, &args.host, args.port);
+    match backup(&client, Path::new(&args.backup_dir)) {
+        Ok(summary) => println!(
+            "Backed up version {} with {} accounts to {}",
+            summary.version, summary.num_accounts, args.backup_dir
+        ),
+        Err(e) => {
+            eprintln!("Backup failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use backup::{load_validator_verifier, restore};
+use libradb::LibraDB;
+use std::path::Path;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "db-restore",
+    author = "Libra",
+    about = "Restore a new LibraDB from a backup"
+)]
+struct Args {
+    /// Directory holding the backup
+    #[structopt(short = "b", long = "backup_dir")]
+    pub backup_dir: String,
+    /// Storage directory to restore LibraDB to. It must not hold a LibraDB yet.
+    #[structopt(short = "d", long = "db_dir")]
+    pub db_dir: String,
+    /// Trusted peers file of the validators who signed the ledger info of the backup. Signatures
+    /// are not verified if unspecified.
+    #[structopt(short = "s", long = "trusted_peers")]
+    pub trusted_peers: Option<String>,
+}
+
+fn main() {
+    let args = Args::from_args();
+    let verifier = args
+        .trusted_peers
+        .as_ref()
+        .map(|path| load_validator_verifier(Path::new(path)))
+        .transpose()
+        .unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1)
+        });
+    let db = LibraDB::new(&args.db_dir);
+    match restore(Path::new(&args.backup_dir), &db, verifier.as_ref()) {
+        Ok(summary) => println!(
+            "Restored version {} with {} accounts to {}",
+            summary.version, summary.num_accounts, args.db_dir
+        ),
+        Err(e) => {
+            eprintln!("Restore failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,349 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This crate backs up the ledger of a [`LibraDB`] to a local directory and restores a new
+//! [`LibraDB`] from it.
+//!
+//! A backup holds all the transactions up to the version of a [`LedgerInfoWithSignatures`] and
+//! the state snapshot at that version. Everything in it is verified against the ledger info on
+//! restore, so a backup can be stored anywhere: only the ledger info needs to be trusted, which
+//! is checked by verifying its signatures with the validator set of a trusted peers file.
+//!
+//! All the files are Protocol Buffers messages:
+//!   * `ledger_info.pb`: the `LedgerInfoWithSignatures`, written last so that an interrupted
+//! backup is never mistaken for a complete one.
+//!   * `transactions/<first_version>.pb`: a `TransactionListWithProof` of consecutive transactions
+//! with their events, proven against the ledger info.
+//!   * `state_snapshot/proof.pb`: a `TransactionListWithProof` of the transaction at the version
+//! of the ledger info, which carries the root hash of the state.
+//!   * `state_snapshot/<index>.pb`: an `AccountStateChunk` of consecutive account states, in the
+//! order of the hashes of the account addresses.
+
+use config::trusted_peers::TrustedPeersConfig;
+use crypto::{ed25519::*, HashValue};
+use failure::prelude::*;
+use libradb::LibraDB;
+use proto_conv::{FromProto, IntoProto};
+use protobuf::{Message, RepeatedField};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use storage_client::{StorageRead, StorageReadServiceClient};
+use storage_proto::{proto::storage::AccountStateChunk, BackupAccountStateResponse};
+use types::{
+    account_state_blob::AccountStateBlob,
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{TransactionListWithProof, Version},
+    validator_verifier::ValidatorVerifier,
+};
+
+#[cfg(test)]
+mod backup_test;
+
+/// Number of transactions in a transaction file. This is the maximum number of transactions
+/// storage returns at once.
+const TXN_CHUNK_SIZE: u64 = 1000;
+/// Number of account states in a state snapshot file.
+const ACCOUNT_STATE_CHUNK_SIZE: usize = 10_000;
+
+const LEDGER_INFO_FILE: &str = "ledger_info.pb";
+const TRANSACTIONS_DIR: &str = "transactions";
+const STATE_SNAPSHOT_DIR: &str = "state_snapshot";
+const STATE_SNAPSHOT_PROOF_FILE: &str = "proof.pb";
+
+/// The data a backup is made of, as served by storage.
+pub trait BackupSource {
+    /// The latest ledger info. Everything backed up is proven against it.
+    fn get_latest_ledger_info(&self) -> Result<LedgerInfoWithSignatures<Ed25519Signature>>;
+
+    /// Up to `limit` transactions starting at `start_version`, with their events and proofs
+    /// relative to `ledger_version`.
+    fn get_transactions(
+        &self,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<TransactionListWithProof>;
+
+    /// All the account states at `version`, keyed by the hashes of the account addresses and in
+    /// their order.
+    fn get_account_states<'a>(
+        &'a self,
+        version: Version,
+    ) -> Result<Box<dyn Iterator<Item = Result<(HashValue, AccountStateBlob)>> + 'a>>;
+}
+
+impl BackupSource for StorageReadServiceClient {
+    fn get_latest_ledger_info(&self) -> Result<LedgerInfoWithSignatures<Ed25519Signature>> {
+        let (_, ledger_info_with_sigs, _) = self.update_to_latest_ledger(0, vec![])?;
+        Ok(ledger_info_with_sigs)
+    }
+
+    fn get_transactions(
+        &self,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<TransactionListWithProof> {
+        StorageRead::get_transactions(
+            self,
+            start_version,
+            limit,
+            ledger_version,
+            true, /* fetch_events */
+        )
+    }
+
+    fn get_account_states<'a>(
+        &'a self,
+        version: Version,
+    ) -> Result<Box<dyn Iterator<Item = Result<(HashValue, AccountStateBlob)>> + 'a>> {
+        Ok(Box::new(self.backup_account_state(version)?))
+    }
+}
+
+impl BackupSource for LibraDB {
+    fn get_latest_ledger_info(&self) -> Result<LedgerInfoWithSignatures<Ed25519Signature>> {
+        let (_, ledger_info_with_sigs, _) = self.update_to_latest_ledger(0, vec![])?;
+        Ok(ledger_info_with_sigs)
+    }
+
+    fn get_transactions(
+        &self,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<TransactionListWithProof> {
+        LibraDB::get_transactions(
+            self,
+            start_version,
+            limit,
+            ledger_version,
+            true, /* fetch_events */
+        )
+    }
+
+    fn get_account_states<'a>(
+        &'a self,
+        version: Version,
+    ) -> Result<Box<dyn Iterator<Item = Result<(HashValue, AccountStateBlob)>> + 'a>> {
+        Ok(Box::new(self.get_account_iter(version)?))
+    }
+}
+
+/// What a backup holds.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BackupSummary {
+    /// Version of the ledger info, of the last transaction and of the state snapshot.
+    pub version: Version,
+    /// Number of accounts in the state snapshot.
+    pub num_accounts: usize,
+}
+
+/// Back up everything up to the latest ledger info of `source` to `backup_dir`, which must not
+/// hold a backup already.
+pub fn backup<S: BackupSource>(source: &S, backup_dir: &Path) -> Result<BackupSummary> {
+    ensure!(
+        !backup_dir.join(LEDGER_INFO_FILE).exists(),
+        "{} already holds a backup.",
+        backup_dir.display()
+    );
+    fs::create_dir_all(backup_dir.join(TRANSACTIONS_DIR))?;
+    fs::create_dir_all(backup_dir.join(STATE_SNAPSHOT_DIR))?;
+
+    let ledger_info_with_sigs = source.get_latest_ledger_info()?;
+    let version = ledger_info_with_sigs.ledger_info().version();
+
+    let mut start_version = 0;
+    while start_version <= version {
+        let limit = std::cmp::min(TXN_CHUNK_SIZE, version - start_version + 1);
+        let txn_list = source.get_transactions(start_version, limit, version)?;
+        ensure!(
+            txn_list.transaction_and_infos.len() as u64 == limit,
+            "Expected {} transactions from version {}, got {}.",
+            limit,
+            start_version,
+            txn_list.transaction_and_infos.len()
+        );
+        write_proto(&transactions_file(backup_dir, start_version), txn_list)?;
+        start_version += limit;
+    }
+
+    let proof = source.get_transactions(version, 1, version)?;
+    write_proto(
+        &backup_dir
+            .join(STATE_SNAPSHOT_DIR)
+            .join(STATE_SNAPSHOT_PROOF_FILE),
+        proof,
+    )?;
+    let mut num_accounts = 0;
+    let mut chunk = Vec::with_capacity(ACCOUNT_STATE_CHUNK_SIZE);
+    let mut chunk_index = 0;
+    for account_state in source.get_account_states(version)? {
+        chunk.push(account_state?);
+        num_accounts += 1;
+        if chunk.len() == ACCOUNT_STATE_CHUNK_SIZE {
+            write_account_state_chunk(&state_chunk_file(backup_dir, chunk_index), chunk)?;
+            chunk = Vec::with_capacity(ACCOUNT_STATE_CHUNK_SIZE);
+            chunk_index += 1;
+        }
+    }
+    if !chunk.is_empty() {
+        write_account_state_chunk(&state_chunk_file(backup_dir, chunk_index), chunk)?;
+    }
+
+    write_proto(&backup_dir.join(LEDGER_INFO_FILE), ledger_info_with_sigs)?;
+    Ok(BackupSummary {
+        version,
+        num_accounts,
+    })
+}
+
+/// Restore the backup in `backup_dir` to `db`, which must be empty. Everything is verified against
+/// the ledger info of the backup, whose signatures are verified first if a `verifier` is given.
+///
+/// The state snapshot is held in memory as a whole. If the restore fails, `db` is left partially
+/// restored and must be discarded.
+pub fn restore(
+    backup_dir: &Path,
+    db: &LibraDB,
+    verifier: Option<&ValidatorVerifier<Ed25519PublicKey>>,
+) -> Result<BackupSummary> {
+    ensure!(
+        db.get_executor_startup_info()?.is_none(),
+        "Can only restore to an empty DB."
+    );
+    let ledger_info_with_sigs: LedgerInfoWithSignatures<Ed25519Signature> =
+        read_proto(&backup_dir.join(LEDGER_INFO_FILE))?;
+    if let Some(verifier) = verifier {
+        ledger_info_with_sigs
+            .verify(verifier)
+            .map_err(|e| format_err!("Invalid ledger info signatures: {}", e))?;
+    }
+    let ledger_info = ledger_info_with_sigs.ledger_info();
+    let version = ledger_info.version();
+
+    // The state snapshot.
+    let proof: TransactionListWithProof = read_proto(
+        &backup_dir
+            .join(STATE_SNAPSHOT_DIR)
+            .join(STATE_SNAPSHOT_PROOF_FILE),
+    )?;
+    proof.verify(ledger_info, Some(version))?;
+    let expected_root_hash = proof
+        .transaction_and_infos
+        .first()
+        .map(|(_, txn_info)| txn_info.state_root_hash())
+        .ok_or_else(|| format_err!("State snapshot proof is empty."))?;
+    let mut account_states: Vec<(HashValue, AccountStateBlob)> = vec![];
+    let mut chunk_index = 0;
+    while state_chunk_file(backup_dir, chunk_index).exists() {
+        for account_state in read_account_state_chunk(&state_chunk_file(backup_dir, chunk_index))? {
+            if let Some((last_key, _)) = account_states.last() {
+                ensure!(
+                    *last_key < account_state.0,
+                    "Account states are not in strictly increasing key order in chunk {}.",
+                    chunk_index
+                );
+            }
+            account_states.push(account_state);
+        }
+        chunk_index += 1;
+    }
+    let num_accounts = account_states.len();
+    db.restore_account_state(version, account_states, expected_root_hash)?;
+
+    // The transactions, the last batch with the ledger info.
+    let mut next_version = 0;
+    while next_version <= version {
+        let path = transactions_file(backup_dir, next_version);
+        let txn_list: TransactionListWithProof = read_proto(&path)
+            .map_err(|e| format_err!("Failed to read {}: {}", path.display(), e))?;
+        txn_list.verify(ledger_info, Some(next_version))?;
+        next_version += txn_list.transaction_and_infos.len() as u64;
+        ensure!(
+            next_version <= version + 1,
+            "{} holds transactions after the ledger info.",
+            path.display()
+        );
+        let ledger_info_to_save = if next_version == version + 1 {
+            Some(ledger_info_with_sigs.clone())
+        } else {
+            None
+        };
+        db.restore_transactions(&txn_list, &ledger_info_to_save)?;
+    }
+
+    Ok(BackupSummary {
+        version,
+        num_accounts,
+    })
+}
+
+/// The verifier of the signatures of the validators in the trusted peers file at `path`.
+pub fn load_validator_verifier(path: &Path) -> Result<ValidatorVerifier<Ed25519PublicKey>> {
+    let validators = TrustedPeersConfig::load_config(path).get_trusted_consensus_peers();
+    ensure!(
+        !validators.is_empty(),
+        "Not able to load validators from trusted peers config {}.",
+        path.display()
+    );
+    Ok(ValidatorVerifier::new(validators))
+}
+
+fn transactions_file(backup_dir: &Path, first_version: Version) -> PathBuf {
+    backup_dir
+        .join(TRANSACTIONS_DIR)
+        .join(format!("{}.pb", first_version))
+}
+
+fn state_chunk_file(backup_dir: &Path, chunk_index: usize) -> PathBuf {
+    backup_dir
+        .join(STATE_SNAPSHOT_DIR)
+        .join(format!("{}.pb", chunk_index))
+}
+
+fn write_proto<T: IntoProto>(path: &Path, value: T) -> Result<()>
+where
+    T::ProtoType: Message,
+{
+    fs::write(path, value.into_proto().write_to_bytes()?)?;
+    Ok(())
+}
+
+fn read_proto<T: FromProto>(path: &Path) -> Result<T>
+where
+    T::ProtoType: Message,
+{
+    T::from_proto(protobuf::parse_from_bytes(&fs::read(path)?)?)
+}
+
+fn write_account_state_chunk(
+    path: &Path,
+    account_states: Vec<(HashValue, AccountStateBlob)>,
+) -> Result<()> {
+    let mut chunk = AccountStateChunk::new();
+    chunk.set_account_states(RepeatedField::from_vec(
+        account_states
+            .into_iter()
+            .map(|(account_key, account_state_blob)| {
+                BackupAccountStateResponse::new(account_key, account_state_blob).into_proto()
+            })
+            .collect(),
+    ));
+    fs::write(path, chunk.write_to_bytes()?)?;
+    Ok(())
+}
+
+fn read_account_state_chunk(path: &Path) -> Result<Vec<(HashValue, AccountStateBlob)>> {
+    let mut chunk: AccountStateChunk = protobuf::parse_from_bytes(&fs::read(path)?)?;
+    chunk
+        .take_account_states()
+        .into_iter()
+        .map(|account_state| {
+            let account_state = BackupAccountStateResponse::from_proto(account_state)?;
+            Ok((account_state.account_key, account_state.account_state_blob))
+        })
+        .collect()
+}
@@ -0,0 +1,116 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{mock_tree_store::MockTreeStore, JellyfishMerkleTree};
+use rand::{rngs::StdRng, SeedableRng};
+use std::collections::BTreeMap;
+
+fn collect_leaves<R: TreeReader>(
+    reader: &R,
+    version: Version,
+) -> Vec<(HashValue, AccountStateBlob)> {
+    JellyfishMerkleIterator::new(reader, version, HashValue::zero())
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap()
+}
+
+#[test]
+fn test_iterate_empty_tree() {
+    let db = MockTreeStore::default();
+    db.put_node(NodeKey::new_empty_path(0), Node::new_null())
+        .unwrap();
+    assert!(collect_leaves(&db, 0).is_empty());
+}
+
+#[test]
+fn test_iterate_single_leaf() {
+    let db = MockTreeStore::default();
+    let tree = JellyfishMerkleTree::new(&db);
+    let key = HashValue::random();
+    let blob = AccountStateBlob::from(vec![1u8, 2, 3]);
+    let (_root, batch) = tree
+        .put_blob_set(vec![(key, blob.clone())], 0 /* version */)
+        .unwrap();
+    db.write_tree_update_batch(batch).unwrap();
+    assert_eq!(collect_leaves(&db, 0), vec![(key, blob)]);
+}
+
+#[test]
+fn test_iterate_in_key_order_at_each_version() {
+    let mut rng: StdRng = StdRng::from_seed([9u8; 32]);
+    let db = MockTreeStore::default();
+    let tree = JellyfishMerkleTree::new(&db);
+
+    let mut expected = BTreeMap::new();
+    let mut expected_by_version = vec![];
+    for version in 0..10 {
+        let mut blob_set = vec![];
+        for _i in 0..100 {
+            let key = HashValue::random_with_rng(&mut rng);
+            let blob = AccountStateBlob::from(HashValue::random_with_rng(&mut rng).to_vec());
+            blob_set.push((key, blob));
+        }
+        // Also update one of the existing leaves.
+        if let Some((&key, _)) = expected.iter().next() {
+            blob_set.push((key, AccountStateBlob::from(vec![version as u8])));
+        }
+        let (_root, batch) = tree.put_blob_set(blob_set.clone(), version).unwrap();
+        db.write_tree_update_batch(batch).unwrap();
+        expected.extend(blob_set);
+        expected_by_version.push(expected.clone());
+    }
+
+    for (version, expected) in expected_by_version.into_iter().enumerate() {
+        assert_eq!(
+            collect_leaves(&db, version as Version),
+            expected.into_iter().collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn test_iterate_from_key() {
+    let mut rng: StdRng = StdRng::from_seed([7u8; 32]);
+    let db = MockTreeStore::default();
+    let tree = JellyfishMerkleTree::new(&db);
+
+    let mut blob_set = BTreeMap::new();
+    for _i in 0..200 {
+        let key = HashValue::random_with_rng(&mut rng);
+        let blob = AccountStateBlob::from(HashValue::random_with_rng(&mut rng).to_vec());
+        blob_set.insert(key, blob);
+    }
+    let (_root, batch) = tree
+        .put_blob_set(blob_set.clone().into_iter().collect(), 0 /* version */)
+        .unwrap();
+    db.write_tree_update_batch(batch).unwrap();
+
+    let expected = blob_set.into_iter().collect::<Vec<_>>();
+    // Start from existing keys, and from keys between or after them.
+    for i in (0..expected.len()).step_by(17) {
+        let leaves = JellyfishMerkleIterator::new(&db, 0, expected[i].0)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(leaves, &expected[i..]);
+    }
+    for _i in 0..20 {
+        let starting_key = HashValue::random_with_rng(&mut rng);
+        let leaves = JellyfishMerkleIterator::new(&db, 0, starting_key)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let expected_leaves = expected
+            .iter()
+            .filter(|(key, _)| *key >= starting_key)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(leaves, expected_leaves);
+    }
+    let max_key = HashValue::new([0xff; HashValue::LENGTH]);
+    assert!(JellyfishMerkleIterator::new(&db, 0, max_key)
+        .unwrap()
+        .all(|leaf| leaf.unwrap().0 == max_key));
+}
@@ -0,0 +1,162 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements [`JellyfishMerkleIterator`], which iterates over all the leaves of a
+//! [`JellyfishMerkleTree`](crate::JellyfishMerkleTree) at a given version, in the order of their
+//! keys, starting from an arbitrary key. The iterator walks the tree depth first: it holds the path
+//! of internal nodes from the root to the latest visited leaf, and for each of them the next child
+//! to visit.
+
+#[cfg(test)]
+mod iterator_test;
+
+use crate::{
+    nibble::{Nibble, NibblePath},
+    node_type::{InternalNode, Node, NodeKey},
+    TreeReader,
+};
+use crypto::HashValue;
+use failure::prelude::*;
+use types::{account_state_blob::AccountStateBlob, transaction::Version};
+
+/// The number of children of an internal node.
+const NUM_CHILDREN: u8 = 16;
+
+/// An internal node on the path to the latest visited leaf.
+struct InternalNodeVisit {
+    /// The key of the node.
+    node_key: NodeKey,
+    /// The node.
+    node: InternalNode,
+    /// The index of the next child to visit. All the children before it were visited.
+    next_child_index: u8,
+}
+
+/// Iterates over the leaves of the tree at a version, yielding the key and the account state blob
+/// of each of them in ascending key order.
+pub struct JellyfishMerkleIterator<'a, R: 'a + TreeReader> {
+    reader: &'a R,
+    /// The internal nodes from the root to the latest visited leaf.
+    parent_stack: Vec<InternalNodeVisit>,
+    /// A leaf to yield before visiting `parent_stack`: the root if it is a leaf, or the leaf
+    /// found where the starting key would be.
+    pending_leaf: Option<(HashValue, AccountStateBlob)>,
+    /// Whether an error was returned, after which the iterator is done.
+    failed: bool,
+}
+
+impl<'a, R> JellyfishMerkleIterator<'a, R>
+where
+    R: 'a + TreeReader,
+{
+    /// Constructs an iterator over the leaves of the tree at `version`, starting from the
+    /// leftmost one with a key greater than or equal to `starting_key`.
+    pub fn new(reader: &'a R, version: Version, starting_key: HashValue) -> Result<Self> {
+        let nibble_path = NibblePath::new(starting_key.to_vec());
+        let mut nibble_iter = nibble_path.nibbles();
+        let mut node_key = NodeKey::new_empty_path(version);
+        let mut parent_stack = vec![];
+        let mut pending_leaf = None;
+
+        // Walk down towards `starting_key`. Along the way, the children before the one on the
+        // path only have smaller keys, so each visit resumes after the child on the path.
+        loop {
+            match reader.get_node(&node_key)? {
+                Node::Internal(node) => {
+                    let index = nibble_iter
+                        .next()
+                        .ok_or_else(|| format_err!("Ran out of nibbles at {:?}", node_key))?;
+                    let child_node_key = node
+                        .child(index)
+                        .map(|child| node_key.gen_child_node_key(child.version, index));
+                    parent_stack.push(InternalNodeVisit {
+                        node_key,
+                        node,
+                        next_child_index: u8::from(index) + 1,
+                    });
+                    match child_node_key {
+                        Some(child_node_key) => node_key = child_node_key,
+                        None => break,
+                    }
+                }
+                Node::Leaf(leaf_node) => {
+                    if leaf_node.account_key() >= starting_key {
+                        pending_leaf = Some((leaf_node.account_key(), leaf_node.blob().clone()));
+                    }
+                    break;
+                }
+                Node::Null => {
+                    ensure!(
+                        parent_stack.is_empty(),
+                        "Non-root null node exists with node key {:?}",
+                        node_key
+                    );
+                    break;
+                }
+            }
+        }
+        Ok(Self {
+            reader,
+            parent_stack,
+            pending_leaf,
+            failed: false,
+        })
+    }
+
+    fn next_leaf(&mut self) -> Result<Option<(HashValue, AccountStateBlob)>> {
+        if let Some(leaf) = self.pending_leaf.take() {
+            return Ok(Some(leaf));
+        }
+
+        // Visit the next child of the deepest internal node, or go up when it has no more.
+        while let Some(visit) = self.parent_stack.last_mut() {
+            let next_child = (visit.next_child_index..NUM_CHILDREN)
+                .find_map(|index| visit.node.child(Nibble::from(index)).map(|c| (index, c)));
+            let (index, child) = match next_child {
+                Some(next_child) => next_child,
+                None => {
+                    self.parent_stack.pop();
+                    continue;
+                }
+            };
+            visit.next_child_index = index + 1;
+            let child_node_key = visit
+                .node_key
+                .gen_child_node_key(child.version, Nibble::from(index));
+            match self.reader.get_node(&child_node_key)? {
+                Node::Internal(node) => self.parent_stack.push(InternalNodeVisit {
+                    node_key: child_node_key,
+                    node,
+                    next_child_index: 0,
+                }),
+                Node::Leaf(leaf_node) => {
+                    return Ok(Some((leaf_node.account_key(), leaf_node.blob().clone())))
+                }
+                Node::Null => bail!(
+                    "Non-root null node exists with node key {:?}",
+                    child_node_key
+                ),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a, R> Iterator for JellyfishMerkleIterator<'a, R>
+where
+    R: 'a + TreeReader,
+{
+    type Item = Result<(HashValue, AccountStateBlob)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = self.next_leaf().transpose();
+        self.failed = match &result {
+            Some(Err(_)) => true,
+            _ => false,
+        };
+        result
+    }
+}
@@ -9,7 +9,11 @@
 //! [`put_blob_set`](JellyfishMerkleTree::put_blob_set) and
 //! [`get_with_proof`](JellyfishMerkleTree::get_with_proof). After each put with a `blob_set`
 //! based on a known version, the tree will return a new root hash with a [`TreeUpdateBatch`]
-//! containing all the new nodes and indices of stale nodes.
+//! containing all the new nodes and indices of stale nodes. All the leaves of the tree at a version
+//! can be read in key order with a [`JellyfishMerkleIterator`](iterator::JellyfishMerkleIterator).
+//! Conversely, a tree can be rebuilt at a version from all its leaves in key order, chunk by chunk,
+//! with a [`JellyfishMerkleRestore`](restore::JellyfishMerkleRestore), each chunk being verified
+//! with a proof from [`get_range_proof`](JellyfishMerkleTree::get_range_proof).
 //!
 //! A Jellyfish Merkle Tree itself logically is a 256-bit sparse Merkle tree with an optimization
 //! that any subtree containing 0 or 1 leaf node will be replaced by that leaf node or a placeholder
@@ -63,10 +67,12 @@
 
 #[cfg(test)]
 mod jellyfish_merkle_test;
+pub mod iterator;
 #[cfg(test)]
 mod mock_tree_store;
 mod nibble;
 pub mod node_type;
+pub mod restore;
 mod tree_cache;
 
 use crypto::{hash::CryptoHash, HashValue};
@@ -76,7 +82,11 @@ use node_type::{Child, Children, InternalNode, LeafNode, Node, NodeKey};
 use proptest_derive::Arbitrary;
 use std::collections::{HashMap, HashSet};
 use tree_cache::TreeCache;
-use types::{account_state_blob::AccountStateBlob, proof::SparseMerkleProof, transaction::Version};
+use types::{
+    account_state_blob::AccountStateBlob,
+    proof::{SparseMerkleProof, SparseMerkleRangeProof},
+    transaction::Version,
+};
 
 /// The hardcoded maximum height of a [`JellyfishMerkleTree`] in nibbles.
 const ROOT_NIBBLE_HEIGHT: usize = HashValue::LENGTH * 2;
@@ -88,6 +98,13 @@ pub trait TreeReader {
     fn get_node(&self, node_key: &NodeKey) -> Result<Node>;
 }
 
+/// `TreeWriter` defines the interface to persist nodes without going through a
+/// [`TreeUpdateBatch`], e.g. while restoring a tree.
+pub trait TreeWriter {
+    /// Writes a node batch into storage.
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<()>;
+}
+
 /// Node batch that will be written into db atomically with other batches.
 pub type NodeBatch = HashMap<NodeKey, Node>;
 /// [`RetireNodeIndex`] batch that will be written into db atomically with other batches.
@@ -518,6 +535,32 @@ where
         bail!("Jellyfish Merkle tree has cyclic graph inside.");
     }
 
+    /// Returns the proof of the range of leaves from the leftmost one to `rightmost_key_to_prove`,
+    /// which must exist at `version`.
+    pub fn get_range_proof(
+        &self,
+        rightmost_key_to_prove: HashValue,
+        version: Version,
+    ) -> Result<SparseMerkleRangeProof> {
+        let (blob, proof) = self.get_with_proof(rightmost_key_to_prove, version)?;
+        ensure!(
+            blob.is_some(),
+            "Key {:x} does not exist at version {}.",
+            rightmost_key_to_prove,
+            version
+        );
+
+        // The siblings on the left can be computed from the leaves in the range, keep the ones
+        // on the right only.
+        let right_siblings = proof
+            .siblings()
+            .iter()
+            .zip(rightmost_key_to_prove.iter_bits())
+            .filter_map(|(sibling, bit)| if bit { None } else { Some(*sibling) })
+            .collect();
+        Ok(SparseMerkleRangeProof::new(right_siblings))
+    }
+
     #[cfg(test)]
     pub fn get(&self, key: HashValue, version: Version) -> Result<Option<AccountStateBlob>> {
         Ok(self.get_with_proof(key, version)?.0)
@@ -3,7 +3,7 @@
 
 use crate::{
     node_type::{Node, NodeKey},
-    StaleNodeIndex, TreeReader, TreeUpdateBatch,
+    NodeBatch, StaleNodeIndex, TreeReader, TreeUpdateBatch, TreeWriter,
 };
 use failure::prelude::*;
 use std::{
@@ -28,6 +28,15 @@ impl TreeReader for MockTreeStore {
     }
 }
 
+impl TreeWriter for MockTreeStore {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<()> {
+        for (node_key, node) in node_batch.clone() {
+            self.put_node(node_key, node)?;
+        }
+        Ok(())
+    }
+}
+
 impl MockTreeStore {
     pub fn put_node(&self, node_key: NodeKey, node: Node) -> Result<()> {
         match self.0.write().unwrap().0.entry(node_key) {
@@ -83,4 +92,8 @@ impl MockTreeStore {
     pub fn num_nodes(&self) -> usize {
         self.0.read().unwrap().0.len()
     }
+
+    pub fn nodes(&self) -> HashMap<NodeKey, Node> {
+        self.0.read().unwrap().0.clone()
+    }
 }
@@ -0,0 +1,299 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements [`JellyfishMerkleRestore`], which builds a
+//! [`JellyfishMerkleTree`](crate::JellyfishMerkleTree) at a version from all its leaves, received
+//! in key order and in chunks, e.g. from a state snapshot. Each chunk is verified against the
+//! expected root hash with a [`SparseMerkleRangeProof`] before the nodes it completes are
+//! persisted, so that neither the whole state needs to be held in memory nor the source of the
+//! chunks needs to be trusted.
+//!
+//! The tree is built from left to right. The internal nodes on the path from the root to the
+//! latest leaf may still get children on the right, and the latest leaf itself may be pushed down
+//! by the next one, so they are kept in memory. Everything on their left is complete and
+//! persisted.
+
+#[cfg(test)]
+mod restore_test;
+
+use crate::{
+    nibble::{Nibble, NibblePath},
+    node_type::{Child, Children, InternalNode, LeafNode, Node, NodeKey},
+    NodeBatch, TreeWriter,
+};
+use crypto::{
+    hash::{CryptoHash, SPARSE_MERKLE_PLACEHOLDER_HASH},
+    HashValue,
+};
+use failure::prelude::*;
+use std::cmp::max;
+use types::{
+    account_state_blob::AccountStateBlob,
+    proof::{verify_sparse_merkle_range, SparseMerkleInternalNode, SparseMerkleRangeProof},
+    transaction::Version,
+};
+
+/// An internal node on the path from the root to the latest leaf.
+struct PartialInternalNode {
+    /// The key of the node.
+    node_key: NodeKey,
+    /// The children persisted already, all on the left of the path.
+    children: Children,
+}
+
+/// Rebuilds a tree at a version from its leaves. See [`crate::restore`] for details.
+pub struct JellyfishMerkleRestore<'a, S: 'a + TreeWriter> {
+    store: &'a S,
+    version: Version,
+    expected_root_hash: HashValue,
+    /// The internal nodes from the root to the latest leaf. The one at index `i` is at nibble
+    /// depth `i`.
+    partial_nodes: Vec<PartialInternalNode>,
+    /// The latest leaf, which is a child of the last of `partial_nodes`, if any.
+    previous_leaf: Option<LeafNode>,
+    /// The depth in bits at which `previous_leaf` would be if it were the last leaf of the tree:
+    /// just below where it forks from the leaf before it.
+    previous_leaf_min_depth: usize,
+    /// The nodes completed since the last chunk was persisted.
+    frozen_nodes: NodeBatch,
+}
+
+impl<'a, S> JellyfishMerkleRestore<'a, S>
+where
+    S: 'a + TreeWriter,
+{
+    /// Starts restoring the tree at `version` whose root hash is `expected_root_hash` into
+    /// `store`, which holds no node at `version` yet.
+    pub fn new(store: &'a S, version: Version, expected_root_hash: HashValue) -> Self {
+        Self {
+            store,
+            version,
+            expected_root_hash,
+            partial_nodes: vec![],
+            previous_leaf: None,
+            previous_leaf_min_depth: 0,
+            frozen_nodes: NodeBatch::new(),
+        }
+    }
+
+    /// Adds the next chunk of leaves, which come in increasing key order after the ones already
+    /// added, and persists the nodes it completes once `proof` shows that all the leaves from the
+    /// leftmost one to the last one of `chunk` are the ones of the expected tree.
+    ///
+    /// If an error is returned, nothing more can be added: the restore starts over.
+    pub fn add_chunk(
+        &mut self,
+        chunk: Vec<(HashValue, AccountStateBlob)>,
+        proof: SparseMerkleRangeProof,
+    ) -> Result<()> {
+        ensure!(!chunk.is_empty(), "Empty chunk of leaves.");
+        for (key, blob) in chunk {
+            if let Some(previous_leaf) = &self.previous_leaf {
+                ensure!(
+                    key > previous_leaf.account_key(),
+                    "Keys must come in increasing order. Got {:x} after {:x}.",
+                    key,
+                    previous_leaf.account_key()
+                );
+            }
+            self.add_one(key, blob);
+        }
+
+        self.verify(&proof)?;
+        self.store.write_node_batch(&self.frozen_nodes)?;
+        self.frozen_nodes.clear();
+        Ok(())
+    }
+
+    /// Completes the tree once all the leaves are added, and checks its root hash.
+    pub fn finish(mut self) -> Result<()> {
+        let root_hash = match self.previous_leaf.take() {
+            Some(leaf) => {
+                let mut root_hash = self.freeze_leaf(leaf);
+                while !self.partial_nodes.is_empty() {
+                    root_hash = self.freeze_internal_node();
+                }
+                root_hash
+            }
+            None => {
+                self.frozen_nodes
+                    .insert(NodeKey::new_empty_path(self.version), Node::new_null());
+                *SPARSE_MERKLE_PLACEHOLDER_HASH
+            }
+        };
+        ensure!(
+            root_hash == self.expected_root_hash,
+            "Restored root hash doesn't match expected. {:x} vs {:x}",
+            root_hash,
+            self.expected_root_hash
+        );
+        self.store.write_node_batch(&self.frozen_nodes)
+    }
+
+    fn add_one(&mut self, key: HashValue, blob: AccountStateBlob) {
+        let new_leaf = LeafNode::new(key, blob);
+        let previous_leaf = match self.previous_leaf.replace(new_leaf) {
+            Some(previous_leaf) => previous_leaf,
+            None => return,
+        };
+        let previous_key = previous_leaf.account_key();
+        let common_prefix_bits_len = previous_key.common_prefix_bits_len(key);
+        let common_prefix_nibbles_len = common_prefix_bits_len / 4;
+
+        // Both leaves go below the deepest internal node on their common path, which is created
+        // if it doesn't exist yet.
+        while self.partial_nodes.len() <= common_prefix_nibbles_len {
+            let node_key = NodeKey::new(
+                self.version,
+                nibble_path_prefix(previous_key, self.partial_nodes.len()),
+            );
+            self.partial_nodes.push(PartialInternalNode {
+                node_key,
+                children: Children::new(),
+            });
+        }
+        // The next leaves all go on the right of the previous one, which is complete. So are the
+        // internal nodes below where the two keys fork.
+        self.freeze_leaf(previous_leaf);
+        while self.partial_nodes.len() > common_prefix_nibbles_len + 1 {
+            self.freeze_internal_node();
+        }
+        self.previous_leaf_min_depth = common_prefix_bits_len + 1;
+    }
+
+    /// Moves `leaf`, a child of the last partial node if any, to the frozen nodes. Returns its
+    /// hash.
+    fn freeze_leaf(&mut self, leaf: LeafNode) -> HashValue {
+        let key = leaf.account_key();
+        let depth = self.partial_nodes.len();
+        let hash = leaf.hash();
+        if let Some(parent) = self.partial_nodes.last_mut() {
+            parent.children.insert(
+                get_nibble(key, depth - 1),
+                Child::new(hash, self.version, true /* is_leaf */),
+            );
+        }
+        self.frozen_nodes.insert(
+            NodeKey::new(self.version, nibble_path_prefix(key, depth)),
+            leaf.into(),
+        );
+        hash
+    }
+
+    /// Moves the last partial node, which is complete, to the frozen nodes. Returns its hash.
+    fn freeze_internal_node(&mut self) -> HashValue {
+        let PartialInternalNode { node_key, children } = self
+            .partial_nodes
+            .pop()
+            .expect("A partial node must exist.");
+        let node = InternalNode::new(children);
+        let hash = node.hash();
+        if let Some(parent) = self.partial_nodes.last_mut() {
+            let mut nibble_path = node_key.nibble_path().clone();
+            let nibble = nibble_path.pop().expect("A child has a non-empty path.");
+            parent
+                .children
+                .insert(nibble, Child::new(hash, self.version, false /* is_leaf */));
+        }
+        self.frozen_nodes.insert(node_key, node.into());
+        hash
+    }
+
+    /// Verifies that the leaves added so far are the leftmost ones of the expected tree.
+    fn verify(&self, proof: &SparseMerkleRangeProof) -> Result<()> {
+        let leaf = self
+            .previous_leaf
+            .as_ref()
+            .expect("At least one leaf was added.");
+        let key = leaf.account_key();
+
+        // The leaves on the right of the previous leaf may push it further down than the ones on
+        // its left do, down to where all its siblings on the right are in the proof.
+        let num_right_siblings = proof.right_siblings().len();
+        let mut num_left_children = 0;
+        let depth_of_right_siblings = key
+            .iter_bits()
+            .take_while(|bit| {
+                if num_left_children == num_right_siblings {
+                    return false;
+                }
+                if !bit {
+                    num_left_children += 1;
+                }
+                true
+            })
+            .count();
+        ensure!(
+            num_left_children == num_right_siblings,
+            "Too many ({}) siblings on the right for key {:x}.",
+            num_right_siblings,
+            key
+        );
+        let depth = max(depth_of_right_siblings, self.previous_leaf_min_depth);
+
+        let left_siblings = key
+            .iter_bits()
+            .take(depth)
+            .enumerate()
+            .filter(|(_, bit)| *bit)
+            .map(|(bit_depth, _)| self.compute_left_sibling(key, bit_depth))
+            .collect::<Vec<_>>();
+        verify_sparse_merkle_range(
+            self.expected_root_hash,
+            key,
+            leaf.blob_hash(),
+            &left_siblings,
+            proof,
+        )
+    }
+
+    /// Computes the sibling on the left of the path to `key` at `bit_depth`, from the children of
+    /// the partial nodes, which hold everything on the left of the path.
+    fn compute_left_sibling(&self, key: HashValue, bit_depth: usize) -> HashValue {
+        let nibble_depth = bit_depth / 4;
+        match self.partial_nodes.get(nibble_depth) {
+            Some(node) => {
+                // See `InternalNode::get_child_with_siblings` for the layout of the children.
+                let height = 3 - (bit_depth % 4) as u8;
+                let width = 1 << height;
+                let child_half_start = (0xff << height) & u8::from(get_nibble(key, nibble_depth));
+                let sibling_half_start = child_half_start ^ width;
+                merkle_hash(&node.children, sibling_half_start, width)
+            }
+            // No other leaf shares that long a prefix with `key` on the left.
+            None => *SPARSE_MERKLE_PLACEHOLDER_HASH,
+        }
+    }
+}
+
+/// Computes the hash of the subtree of the children in [start, start + width), following the
+/// same rules as the hash of an [`InternalNode`].
+fn merkle_hash(children: &Children, start: u8, width: u8) -> HashValue {
+    let mut range_children =
+        (start..start + width).filter_map(|index| children.get(&Nibble::from(index)));
+    match (range_children.next(), range_children.next()) {
+        (None, _) => *SPARSE_MERKLE_PLACEHOLDER_HASH,
+        (Some(child), None) if child.is_leaf || width == 1 => child.hash,
+        _ => SparseMerkleInternalNode::new(
+            merkle_hash(children, start, width / 2),
+            merkle_hash(children, start + width / 2, width / 2),
+        )
+        .hash(),
+    }
+}
+
+/// Returns the nibble of `key` at `index`.
+fn get_nibble(key: HashValue, index: usize) -> Nibble {
+    let byte = key[index / 2];
+    let nibble = if index % 2 == 0 {
+        byte >> 4
+    } else {
+        byte & 0x0f
+    };
+    Nibble::from(nibble)
+}
+
+/// Returns the path made of the first `num_nibbles` nibbles of `key`.
+fn nibble_path_prefix(key: HashValue, num_nibbles: usize) -> NibblePath {
+    (0..num_nibbles).map(|i| get_nibble(key, i)).collect()
+}
@@ -0,0 +1,163 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{mock_tree_store::MockTreeStore, JellyfishMerkleTree};
+use proptest::{collection::btree_map, prelude::*};
+use std::collections::BTreeMap;
+
+/// Builds a tree with `blobs` at `version` and returns it with its root hash.
+fn init_store(
+    blobs: &BTreeMap<HashValue, AccountStateBlob>,
+    version: Version,
+) -> (MockTreeStore, HashValue) {
+    let db = MockTreeStore::default();
+    let tree = JellyfishMerkleTree::new(&db);
+    let (root_hash, batch) = tree
+        .put_blob_set(blobs.clone().into_iter().collect(), version)
+        .unwrap();
+    db.write_tree_update_batch(batch).unwrap();
+    (db, root_hash)
+}
+
+/// Restores the tree of `db` at `version` into a new store, with chunks of at most `chunk_size`
+/// leaves, each with its range proof.
+fn restore(
+    db: &MockTreeStore,
+    blobs: &BTreeMap<HashValue, AccountStateBlob>,
+    version: Version,
+    root_hash: HashValue,
+    chunk_size: usize,
+) -> Result<MockTreeStore> {
+    let tree = JellyfishMerkleTree::new(db);
+    let restore_db = MockTreeStore::default();
+    {
+        let mut restore = JellyfishMerkleRestore::new(&restore_db, version, root_hash);
+        let leaves: Vec<_> = blobs.clone().into_iter().collect();
+        for chunk in leaves.chunks(chunk_size) {
+            let (rightmost_key, _) = chunk.last().unwrap();
+            let proof = tree.get_range_proof(*rightmost_key, version)?;
+            restore.add_chunk(chunk.to_vec(), proof)?;
+        }
+        restore.finish()?;
+    }
+    Ok(restore_db)
+}
+
+fn update_nibble(original_key: &HashValue, n: usize, nibble: u8) -> HashValue {
+    let mut key = original_key.to_vec();
+    key[n / 2] = if n % 2 == 0 {
+        key[n / 2] & 0x0f | nibble << 4
+    } else {
+        key[n / 2] & 0xf0 | nibble
+    };
+    HashValue::from_slice(&key).unwrap()
+}
+
+#[test]
+fn test_restore_empty_tree() {
+    let db = MockTreeStore::default();
+    db.put_node(NodeKey::new_empty_path(0), Node::new_null())
+        .unwrap();
+    let restore_db = restore(
+        &db,
+        &BTreeMap::new(),
+        0, /* version */
+        *SPARSE_MERKLE_PLACEHOLDER_HASH,
+        1,
+    )
+    .unwrap();
+    assert_eq!(restore_db.nodes(), db.nodes());
+}
+
+#[test]
+fn test_restore_single_leaf() {
+    let mut blobs = BTreeMap::new();
+    blobs.insert(HashValue::random(), AccountStateBlob::from(vec![1u8, 2, 3]));
+    let (db, root_hash) = init_store(&blobs, 0 /* version */);
+    let restore_db = restore(&db, &blobs, 0 /* version */, root_hash, 1).unwrap();
+    assert_eq!(restore_db.nodes(), db.nodes());
+}
+
+#[test]
+fn test_restore_long_common_prefixes() {
+    // Leaves forking at various depths, including deep below the root and right below a fork on
+    // the left.
+    let key = HashValue::zero();
+    let keys = vec![
+        key,
+        update_nibble(&key, 40, 1),
+        update_nibble(&update_nibble(&key, 40, 1), 41, 8),
+        update_nibble(&key, 40, 15),
+        update_nibble(&key, 2, 7),
+        update_nibble(&key, 0, 8),
+        update_nibble(&update_nibble(&key, 0, 8), 63, 1),
+    ];
+    let blobs: BTreeMap<_, _> = keys
+        .into_iter()
+        .enumerate()
+        .map(|(i, key)| (key, AccountStateBlob::from(vec![i as u8])))
+        .collect();
+    let (db, root_hash) = init_store(&blobs, 0 /* version */);
+    for chunk_size in 1..=blobs.len() {
+        let restore_db = restore(&db, &blobs, 0 /* version */, root_hash, chunk_size).unwrap();
+        assert_eq!(restore_db.nodes(), db.nodes());
+    }
+}
+
+#[test]
+fn test_restore_missing_leaf() {
+    let blobs: BTreeMap<_, _> = (0..10u8)
+        .map(|i| (HashValue::random(), AccountStateBlob::from(vec![i])))
+        .collect();
+    let (db, root_hash) = init_store(&blobs, 0 /* version */);
+    let tree = JellyfishMerkleTree::new(&db);
+    let mut leaves: Vec<_> = blobs.into_iter().collect();
+
+    // A leaf is missing in the middle of the chunk.
+    let restore_db = MockTreeStore::default();
+    let mut restore = JellyfishMerkleRestore::new(&restore_db, 0 /* version */, root_hash);
+    let removed = leaves.remove(3);
+    let proof = tree.get_range_proof(leaves[5].0, 0).unwrap();
+    assert!(restore.add_chunk(leaves[..6].to_vec(), proof).is_err());
+    assert!(restore_db.nodes().is_empty());
+    leaves.insert(3, removed);
+
+    // The last leaf is missing, which is only noticed once all the leaves are added.
+    let restore_db = MockTreeStore::default();
+    let mut restore = JellyfishMerkleRestore::new(&restore_db, 0 /* version */, root_hash);
+    let proof = tree.get_range_proof(leaves[8].0, 0).unwrap();
+    restore.add_chunk(leaves[..9].to_vec(), proof).unwrap();
+    assert!(restore.finish().is_err());
+}
+
+#[test]
+fn test_restore_unordered_chunk() {
+    let blobs: BTreeMap<_, _> = (0..3u8)
+        .map(|i| (HashValue::random(), AccountStateBlob::from(vec![i])))
+        .collect();
+    let (db, root_hash) = init_store(&blobs, 0 /* version */);
+    let tree = JellyfishMerkleTree::new(&db);
+    let mut leaves: Vec<_> = blobs.into_iter().collect();
+    leaves.swap(0, 1);
+
+    let restore_db = MockTreeStore::default();
+    let mut restore = JellyfishMerkleRestore::new(&restore_db, 0 /* version */, root_hash);
+    let proof = tree.get_range_proof(leaves[2].0, 0).unwrap();
+    assert!(restore.add_chunk(leaves, proof).is_err());
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_restore_in_chunks(
+        blobs in btree_map(any::<HashValue>(), any::<AccountStateBlob>(), 1..500),
+        chunk_size in 1usize..100,
+    ) {
+        let version = 0;
+        let (db, root_hash) = init_store(&blobs, version);
+        let restore_db = restore(&db, &blobs, version, root_hash, chunk_size).unwrap();
+        prop_assert_eq!(restore_db.nodes(), db.nodes());
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use config::trusted_peers::TrustedPeersConfig;
+use crypto::ed25519::*;
+use failure::prelude::*;
+use libradb::inspector::LibraDBInspector;
+use std::path::Path;
+use structopt::StructOpt;
+use types::{
+    account_address::AccountAddress, account_config::get_account_resource_or_default,
+    transaction::Version, validator_verifier::ValidatorVerifier,
+};
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "libradb-tool",
+    author = "Libra",
+    about = "Look into a LibraDB, read-only, even while a node runs on it"
+)]
+struct Args {
+    /// Storage directory of the LibraDB
+    #[structopt(short = "d", long = "db_dir")]
+    pub db_dir: String,
+    #[structopt(subcommand)]
+    pub cmd: Command,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Print the latest ledger info
+    #[structopt(name = "ledger-info")]
+    LedgerInfo,
+    /// Print the transaction at a version, with its transaction info and events
+    #[structopt(name = "transaction")]
+    Transaction {
+        #[structopt(name = "VERSION")]
+        version: Version,
+    },
+    /// Print the account resource of an account
+    #[structopt(name = "account")]
+    Account {
+        /// Hex encoded address of the account
+        #[structopt(name = "ADDRESS")]
+        address: AccountAddress,
+        /// Version to read the account state at. Defaults to the latest version.
+        #[structopt(short = "v", long = "version")]
+        version: Option<Version>,
+    },
+    /// Verify the transaction accumulator and the root hashes of the ledger infos over a range of
+    /// versions
+    #[structopt(name = "verify-accumulator")]
+    VerifyAccumulator {
+        /// First version to verify. The accumulator nodes before it are trusted.
+        #[structopt(short = "s", long = "start", default_value = "0")]
+        start_version: Version,
+        /// Last version to verify. Defaults to the latest version.
+        #[structopt(short = "e", long = "end")]
+        end_version: Option<Version>,
+    },
+    /// Verify the consistency of the whole ledger and report the first divergent version
+    #[structopt(name = "verify")]
+    Verify {
+        /// Trusted peers file of the validators who signed the oldest ledger info of the DB. The
+        /// signatures of the ledger infos are not verified if unspecified.
+        #[structopt(short = "s", long = "trusted_peers")]
+        trusted_peers: Option<String>,
+    },
+    /// Print the approximate size of each column family
+    #[structopt(name = "cf-sizes")]
+    CfSizes,
+}
+
+fn latest_version(inspector: &LibraDBInspector) -> Result<Version> {
+    inspector
+        .get_latest_version()?
+        .ok_or_else(|| format_err!("The DB holds no transaction."))
+}
+
+/// The verifier of the signatures of the validators in the trusted peers file at `path`.
+fn load_validator_verifier(path: &Path) -> Result<ValidatorVerifier<Ed25519PublicKey>> {
+    let validators = TrustedPeersConfig::load_config(path).get_trusted_consensus_peers();
+    ensure!(
+        !validators.is_empty(),
+        "Not able to load validators from trusted peers config {}.",
+        path.display()
+    );
+    Ok(ValidatorVerifier::new(validators))
+}
+
+fn run(args: Args) -> Result<()> {
+    let inspector = LibraDBInspector::open(&args.db_dir)?;
+    match args.cmd {
+        Command::LedgerInfo => match inspector.get_latest_ledger_info()? {
+            Some(ledger_info_with_sigs) => println!("{:#?}", ledger_info_with_sigs),
+            None => println!("No ledger info."),
+        },
+        Command::Transaction { version } => {
+            match inspector.get_transaction(version)? {
+                Some(txn) => println!("Transaction: {:#?}", txn),
+                None => println!("No transaction at version {}.", version),
+            }
+            match inspector.get_transaction_info(version)? {
+                Some(txn_info) => println!("Transaction info: {:#?}", txn_info),
+                None => println!("No transaction info at version {}.", version),
+            }
+            println!("Events: {:#?}", inspector.get_events(version)?);
+        }
+        Command::Account { address, version } => {
+            let version = match version {
+                Some(version) => version,
+                None => latest_version(&inspector)?,
+            };
+            match inspector.get_account_state(address, version)? {
+                Some(blob) => println!(
+                    "Account resource at version {}: {:#?}",
+                    version,
+                    get_account_resource_or_default(&Some(blob))?
+                ),
+                None => println!("No account {} at version {}.", address, version),
+            }
+        }
+        Command::VerifyAccumulator {
+            start_version,
+            end_version,
+        } => {
+            let end_version = match end_version {
+                Some(end_version) => end_version,
+                None => latest_version(&inspector)?,
+            };
+            let verification =
+                inspector.verify_transaction_accumulator(start_version, end_version)?;
+            println!(
+                "Verified versions {} to {} and {} ledger infos. Root hash: {:x}",
+                start_version, end_version, verification.num_ledger_infos, verification.root_hash
+            );
+        }
+        Command::Verify { trusted_peers } => {
+            let verifier = trusted_peers
+                .map(|path| load_validator_verifier(Path::new(&path)))
+                .transpose()?;
+            let verification = inspector.verify_ledger(verifier)?;
+            println!(
+                "Verified {} versions from version {} and {} ledger infos{}.",
+                verification.num_versions,
+                verification.first_version,
+                verification.num_ledger_infos,
+                if verification.signatures_verified {
+                    " with their signatures"
+                } else {
+                    ""
+                }
+            );
+            match verification.first_state_version {
+                Some(first_state_version) if first_state_version > verification.first_version => {
+                    println!(
+                        "State trees are pruned before version {}.",
+                        first_state_version
+                    )
+                }
+                _ => (),
+            }
+            if let Some(divergence) = verification.divergence {
+                bail!(
+                    "Divergence at version {}: {}",
+                    divergence.version,
+                    divergence.reason
+                );
+            }
+        }
+        Command::CfSizes => {
+            for (cf_name, size) in inspector.get_approximate_sizes_cf()? {
+                println!("{}: {} bytes", cf_name, size);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(Args::from_args()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -12,7 +12,7 @@ use crate::{
     ledger_counters::LedgerCounter,
     schema::{
         event::EventSchema, event_accumulator::EventAccumulatorSchema,
-        event_by_access_path::EventByAccessPathSchema,
+        event_by_access_path::EventByAccessPathSchema, event_by_address::EventByAddressSchema,
     },
 };
 use accumulator::{HashReader, MerkleAccumulator};
@@ -25,6 +25,7 @@ use schemadb::{schema::ValueCodec, ReadOptions, DB};
 use std::sync::Arc;
 use types::{
     access_path::AccessPath,
+    account_address::AccountAddress,
     contract_event::ContractEvent,
     proof::{position::Position, AccumulatorProof, EventProof},
     transaction::Version,
@@ -184,6 +185,54 @@ impl EventStore {
         Ok(result)
     }
 
+    /// Returns the events emitted by the transactions from `start_version` to `end_version`
+    /// included, each with the version of its transaction and its index among the events of that
+    /// transaction, in the order they were emitted. When `address` is given, only the events
+    /// emitted to access paths under it are returned, found through the index.
+    pub fn get_events_by_version_range(
+        &self,
+        address: Option<AccountAddress>,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<
+        Vec<(
+            Version, // transaction version it belongs to
+            u64,     // index among events for the same transaction
+            ContractEvent,
+        )>,
+    > {
+        let mut result = Vec::new();
+        match address {
+            Some(address) => {
+                let mut iter = self
+                    .db
+                    .iter::<EventByAddressSchema>(ReadOptions::default())?;
+                iter.seek(&(address, start_version, 0))?;
+                while let Some(((addr, ver, idx), ())) = iter.next().transpose()? {
+                    if addr != address || ver > end_version {
+                        break;
+                    }
+                    let event = self.db.get::<EventSchema>(&(ver, idx))?.ok_or_else(|| {
+                        LibraDbError::NotFound(format!("Event {} of Txn {}", idx, ver))
+                    })?;
+                    result.push((ver, idx, event));
+                }
+            }
+            None => {
+                let mut iter = self.db.iter::<EventSchema>(ReadOptions::default())?;
+                iter.seek(&start_version)?;
+                while let Some(((ver, idx), event)) = iter.next().transpose()? {
+                    if ver > end_version {
+                        break;
+                    }
+                    result.push((ver, idx, event));
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
     /// Save contract events yielded by the transaction at `version` and return root hash of the
     /// event accumulator formed by these events.
     pub fn put_events(
@@ -195,7 +244,7 @@ impl EventStore {
         cs.counter_bumps
             .bump(LedgerCounter::EventsCreated, events.len());
 
-        // EventSchema and EventByAccessPathSchema updates
+        // EventSchema, EventByAccessPathSchema and EventByAddressSchema updates
         events
             .iter()
             .enumerate()
@@ -205,6 +254,10 @@ impl EventStore {
                     &(event.access_path().clone(), event.sequence_number()),
                     &(version, idx as u64),
                 )?;
+                cs.batch.put::<EventByAddressSchema>(
+                    &(event.access_path().address, version, idx as u64),
+                    &(),
+                )?;
                 Ok(())
             })
             .collect::<Result<()>>()?;
@@ -246,9 +299,9 @@ impl<'a> HashReader for EventHashReader<'a> {
     }
 }
 
-type EmptyAccumulator = MerkleAccumulator<EmptyReader, EventAccumulatorHasher>;
+pub(crate) type EmptyAccumulator = MerkleAccumulator<EmptyReader, EventAccumulatorHasher>;
 
-struct EmptyReader;
+pub(crate) struct EmptyReader;
 
 // Asserts `get()` is never called.
 impl HashReader for EmptyReader {
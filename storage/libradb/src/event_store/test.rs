@@ -233,3 +233,62 @@ fn test_get_events_by_access_path_impl(
             assert_eq!(events, traversed);
         });
 }
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_get_events_by_version_range(
+        (access_paths, event_batches) in arb_event_batches().no_shrink(),
+        start_version in 0..100u64,
+        num_versions in 0..100u64,
+    ) {
+        let tmp_dir = tempdir().unwrap();
+        let db = LibraDB::new(&tmp_dir);
+        let store = &db.event_store;
+
+        let mut cs = ChangeSet::new();
+        event_batches.iter().enumerate().for_each(|(ver, events)| {
+            store.put_events(ver as u64, events, &mut cs).unwrap();
+        });
+        store.db.write_schemas(cs.batch).unwrap();
+
+        // The events of the versions from `start_version` to `end_version` included, in order.
+        let end_version = start_version + num_versions;
+        let expected = event_batches
+            .iter()
+            .enumerate()
+            .filter(|(ver, _)| (start_version..=end_version).contains(&(*ver as u64)))
+            .flat_map(|(ver, events)| {
+                events
+                    .iter()
+                    .enumerate()
+                    .map(move |(idx, event)| (ver as u64, idx as u64, event.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        prop_assert_eq!(
+            &store
+                .get_events_by_version_range(None, start_version, end_version)
+                .unwrap(),
+            &expected
+        );
+        for access_path in access_paths {
+            let expected_by_address = expected
+                .iter()
+                .filter(|(_, _, event)| event.access_path().address == access_path.address)
+                .cloned()
+                .collect::<Vec<_>>();
+            prop_assert_eq!(
+                store
+                    .get_events_by_version_range(
+                        Some(access_path.address),
+                        start_version,
+                        end_version
+                    )
+                    .unwrap(),
+                expected_by_address
+            );
+        }
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides [`LibraDBInspector`], which reads the data of a LibraDB right from its
+//! schemas in order to look into a database when a node misbehaves. Unlike [`LibraDB`], it never
+//! writes and doesn't run a pruner, and it reports missing data instead of failing on it, since
+//! the database may be corrupted. It backs the `libradb-tool` binary.
+
+use crate::{
+    column_families,
+    errors::LibraDbError,
+    event_store::EventStore,
+    ledger_store::LedgerStore,
+    schema::{
+        ledger_info::LedgerInfoSchema, signed_transaction::SignedTransactionSchema,
+        transaction_accumulator::TransactionAccumulatorSchema,
+        transaction_info::TransactionInfoSchema,
+    },
+    state_store::StateStore,
+    LIBRADB_DIR_NAME,
+};
+use accumulator::MerkleAccumulator;
+use crypto::{
+    ed25519::*,
+    hash::{CryptoHash, TransactionAccumulatorHasher},
+    HashValue,
+};
+use failure::prelude::*;
+use schemadb::{ReadOptions, DB};
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+use types::{
+    account_address::AccountAddress,
+    account_state_blob::AccountStateBlob,
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{SignedTransaction, TransactionInfo, Version},
+};
+
+#[cfg(test)]
+mod test;
+pub mod verifier;
+
+type Accumulator = MerkleAccumulator<LedgerStore, TransactionAccumulatorHasher>;
+
+/// The result of [`LibraDBInspector::verify_transaction_accumulator`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccumulatorVerification {
+    /// The number of versions whose accumulator nodes were verified.
+    pub num_versions: u64,
+    /// The number of ledger infos whose accumulator root hashes were verified.
+    pub num_ledger_infos: u64,
+    /// The root hash of the transaction accumulator at the last version verified.
+    pub root_hash: HashValue,
+}
+
+/// Reads a LibraDB without going through [`LibraDB`](crate::LibraDB).
+pub struct LibraDBInspector {
+    db: Arc<DB>,
+    ledger_store: LedgerStore,
+    event_store: EventStore,
+    state_store: StateStore,
+}
+
+impl LibraDBInspector {
+    /// Opens the LibraDB under `db_root_path`, which must exist, read-only. It can be in use by a
+    /// running node, in which case it is read as of the time it is opened.
+    pub fn open<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        let path = db_root_path.as_ref().join(LIBRADB_DIR_NAME);
+        ensure!(path.is_dir(), "No LibraDB at {:?}.", path);
+        Ok(Self::new(Arc::new(DB::open_readonly(
+            path,
+            column_families(),
+        )?)))
+    }
+
+    pub(crate) fn new(db: Arc<DB>) -> Self {
+        Self {
+            db: Arc::clone(&db),
+            ledger_store: LedgerStore::new(Arc::clone(&db)),
+            event_store: EventStore::new(Arc::clone(&db)),
+            state_store: StateStore::new(db),
+        }
+    }
+
+    /// Returns the ledger info with the highest version, if any.
+    pub fn get_latest_ledger_info(
+        &self,
+    ) -> Result<Option<LedgerInfoWithSignatures<Ed25519Signature>>> {
+        let mut iter = self.db.iter::<LedgerInfoSchema>(ReadOptions::default())?;
+        iter.seek_to_last();
+        Ok(iter.next().transpose()?.map(|(_, ledger_info)| ledger_info))
+    }
+
+    /// Returns the version of the latest transaction, if any. It can be greater than the one of
+    /// the latest ledger info while the node is syncing.
+    pub fn get_latest_version(&self) -> Result<Option<Version>> {
+        Ok(self
+            .ledger_store
+            .get_latest_transaction_info_option()?
+            .map(|(version, _)| version))
+    }
+
+    /// Returns the transaction at `version`, if any.
+    pub fn get_transaction(&self, version: Version) -> Result<Option<SignedTransaction>> {
+        self.db.get::<SignedTransactionSchema>(&version)
+    }
+
+    /// Returns the transaction info at `version`, if any.
+    pub fn get_transaction_info(&self, version: Version) -> Result<Option<TransactionInfo>> {
+        self.db.get::<TransactionInfoSchema>(&version)
+    }
+
+    /// Returns the events emitted by the transaction at `version`, in order.
+    pub fn get_events(&self, version: Version) -> Result<Vec<ContractEvent>> {
+        self.event_store.get_events_by_version(version)
+    }
+
+    /// Returns the state of the account at `address` at `version`, if it exists.
+    pub fn get_account_state(
+        &self,
+        address: AccountAddress,
+        version: Version,
+    ) -> Result<Option<AccountStateBlob>> {
+        let (blob, _proof) = self
+            .state_store
+            .get_account_state_with_proof_by_version(address, version)?;
+        Ok(blob)
+    }
+
+    /// Verifies the transaction accumulator from `start_version` to `end_version` included: the
+    /// nodes frozen by the transaction info at each version must be the ones persisted, and the
+    /// ledger infos at these versions must carry the root hash of the accumulator. The nodes
+    /// persisted before `start_version` are trusted.
+    ///
+    /// Fails at the first version where a check fails.
+    pub fn verify_transaction_accumulator(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<AccumulatorVerification> {
+        ensure!(
+            start_version <= end_version,
+            "Start version {} is greater than end version {}.",
+            start_version,
+            end_version
+        );
+
+        let mut num_ledger_infos = 0;
+        let mut root_hash = HashValue::zero();
+        for version in start_version..=end_version {
+            let txn_info = self
+                .get_transaction_info(version)?
+                .ok_or_else(|| LibraDbError::NotFound(format!("TransactionInfo {}", version)))?;
+            let (new_root_hash, frozen_nodes) = Accumulator::append(
+                &self.ledger_store,
+                version, /* num_existing_leaves */
+                &[txn_info.hash()],
+            )?;
+            for (position, hash) in frozen_nodes {
+                let persisted_hash = self.db.get::<TransactionAccumulatorSchema>(&position)?;
+                ensure!(
+                    persisted_hash == Some(hash),
+                    "Version {}: accumulator node at position {} is {:?}, expected {:x}.",
+                    version,
+                    position.to_inorder_index(),
+                    persisted_hash,
+                    hash
+                );
+            }
+            if let Some(ledger_info_with_sigs) = self.db.get::<LedgerInfoSchema>(&version)? {
+                let expected_root_hash = ledger_info_with_sigs
+                    .ledger_info()
+                    .transaction_accumulator_hash();
+                ensure!(
+                    new_root_hash == expected_root_hash,
+                    "Version {}: accumulator root hash is {:x}, ledger info has {:x}.",
+                    version,
+                    new_root_hash,
+                    expected_root_hash
+                );
+                num_ledger_infos += 1;
+            }
+            root_hash = new_root_hash;
+        }
+
+        Ok(AccumulatorVerification {
+            num_versions: end_version - start_version + 1,
+            num_ledger_infos,
+            root_hash,
+        })
+    }
+
+    /// Returns the approximate size in bytes of each column family.
+    pub fn get_approximate_sizes_cf(&self) -> Result<BTreeMap<String, u64>> {
+        self.db.get_approximate_sizes_cf()
+    }
+}
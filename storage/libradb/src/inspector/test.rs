@@ -0,0 +1,310 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{verifier::LedgerVerification, *};
+use crate::{
+    mock_genesis::db_with_mock_genesis, schema::jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+    test_helper::arb_blocks_to_commit, LibraDB,
+};
+use crypto::x25519;
+use jellyfish_merkle::node_type::NodeKey;
+use proptest::prelude::*;
+use schemadb::SchemaBatch;
+use std::collections::{BTreeSet, HashMap};
+use tempfile::tempdir;
+use types::{
+    ledger_info::LedgerInfo, transaction::TransactionToCommit,
+    validator_public_keys::ValidatorPublicKeys, validator_set::ValidatorSet,
+    validator_signer::ValidatorSigner, validator_verifier::ValidatorVerifier,
+};
+
+#[test]
+fn test_open_missing_db() {
+    let tmp_dir = tempdir().unwrap();
+    assert!(LibraDBInspector::open(&tmp_dir).is_err());
+}
+
+#[test]
+fn test_open() {
+    let tmp_dir = tempdir().unwrap();
+    // The DB can be inspected while in use.
+    let db = db_with_mock_genesis(&tmp_dir).unwrap();
+    let genesis_ledger_info = db.ledger_store.get_latest_ledger_info().unwrap();
+    let inspector = LibraDBInspector::open(&tmp_dir).unwrap();
+    assert_eq!(
+        inspector.get_latest_ledger_info().unwrap(),
+        Some(genesis_ledger_info)
+    );
+    assert_eq!(inspector.get_latest_version().unwrap(), Some(0));
+    assert!(inspector.get_transaction(1).unwrap().is_none());
+}
+
+fn test_inspect_impl(
+    blocks: Vec<(
+        Vec<TransactionToCommit>,
+        LedgerInfoWithSignatures<Ed25519Signature>,
+    )>,
+) {
+    let tmp_dir = tempdir().unwrap();
+    let db = db_with_mock_genesis(&tmp_dir).unwrap();
+    let inspector = LibraDBInspector::new(Arc::clone(&db.db));
+
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in &blocks {
+        db.save_transactions(
+            &txns_to_commit,
+            cur_ver + 1, /* first_version */
+            &Some(ledger_info_with_sigs.clone()),
+        )
+        .unwrap();
+
+        for txn_to_commit in txns_to_commit {
+            cur_ver += 1;
+            assert_eq!(
+                inspector.get_transaction(cur_ver).unwrap().as_ref(),
+                Some(txn_to_commit.signed_txn())
+            );
+            assert_eq!(
+                inspector.get_transaction_info(cur_ver).unwrap(),
+                Some(db.ledger_store.get_transaction_info(cur_ver).unwrap())
+            );
+            assert_eq!(
+                inspector.get_events(cur_ver).unwrap(),
+                txn_to_commit.events().to_vec()
+            );
+            for (address, blob) in txn_to_commit.account_states() {
+                assert_eq!(
+                    inspector.get_account_state(*address, cur_ver).unwrap(),
+                    Some(blob.clone())
+                );
+            }
+        }
+    }
+    let (_, latest_ledger_info) = blocks.last().unwrap();
+    assert_eq!(
+        inspector.get_latest_ledger_info().unwrap().as_ref(),
+        Some(latest_ledger_info)
+    );
+    assert_eq!(inspector.get_latest_version().unwrap(), Some(cur_ver));
+
+    let verification = inspector
+        .verify_transaction_accumulator(0, cur_ver)
+        .unwrap();
+    assert_eq!(verification.num_versions, cur_ver + 1);
+    // Empty blocks have the ledger info of the previous version.
+    let ledger_info_versions: BTreeSet<_> = blocks
+        .iter()
+        .map(|(_, ledger_info_with_sigs)| ledger_info_with_sigs.ledger_info().version())
+        .chain(std::iter::once(0))
+        .collect();
+    assert_eq!(
+        verification.num_ledger_infos,
+        ledger_info_versions.len() as u64
+    );
+    assert_eq!(
+        verification.root_hash,
+        latest_ledger_info
+            .ledger_info()
+            .transaction_accumulator_hash()
+    );
+
+    // Corrupt a transaction info: its accumulator leaf doesn't match anymore.
+    let txn_info = db.ledger_store.get_transaction_info(cur_ver).unwrap();
+    let corrupted_txn_info = TransactionInfo::new(
+        txn_info.signed_transaction_hash(),
+        txn_info.state_root_hash(),
+        txn_info.event_root_hash(),
+        txn_info.gas_used() + 1,
+    );
+    db.db
+        .put::<TransactionInfoSchema>(&cur_ver, &corrupted_txn_info)
+        .unwrap();
+    assert!(inspector
+        .verify_transaction_accumulator(0, cur_ver)
+        .is_err());
+    if cur_ver > 0 {
+        assert!(inspector
+            .verify_transaction_accumulator(0, cur_ver - 1)
+            .is_ok());
+    }
+}
+
+fn test_verify_ledger_impl(
+    blocks: Vec<(
+        Vec<TransactionToCommit>,
+        LedgerInfoWithSignatures<Ed25519Signature>,
+    )>,
+) {
+    let tmp_dir = tempdir().unwrap();
+    let db = db_with_mock_genesis(&tmp_dir).unwrap();
+    let inspector = LibraDBInspector::new(Arc::clone(&db.db));
+
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in &blocks {
+        db.save_transactions(
+            &txns_to_commit,
+            cur_ver + 1, /* first_version */
+            &Some(ledger_info_with_sigs.clone()),
+        )
+        .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    let ledger_info_versions: BTreeSet<_> = blocks
+        .iter()
+        .map(|(_, ledger_info_with_sigs)| ledger_info_with_sigs.ledger_info().version())
+        .chain(std::iter::once(0))
+        .collect();
+    assert_eq!(
+        inspector.verify_ledger(None).unwrap(),
+        LedgerVerification {
+            first_version: 0,
+            num_versions: cur_ver + 1,
+            first_state_version: Some(0),
+            num_ledger_infos: ledger_info_versions.len() as u64,
+            signatures_verified: false,
+            divergence: None,
+        }
+    );
+    if cur_ver < 2 {
+        return;
+    }
+
+    // The state tree of the first version is pruned.
+    let delete_state_root = |version| {
+        let mut batch = SchemaBatch::new();
+        batch
+            .delete::<JellyfishMerkleNodeSchema>(&NodeKey::new_empty_path(version))
+            .unwrap();
+        db.db.write_schemas(batch).unwrap();
+    };
+    delete_state_root(0);
+    let verification = inspector.verify_ledger(None).unwrap();
+    assert_eq!(verification.first_state_version, Some(1));
+    assert_eq!(verification.divergence, None);
+
+    // The state tree of the latest version is missing.
+    delete_state_root(cur_ver);
+    let verification = inspector.verify_ledger(None).unwrap();
+    assert_eq!(verification.num_versions, cur_ver);
+    assert_eq!(verification.divergence.unwrap().version, cur_ver);
+
+    // A transaction is replaced by another one.
+    let txn = inspector.get_transaction(2).unwrap().unwrap();
+    db.db.put::<SignedTransactionSchema>(&1, &txn).unwrap();
+    let verification = inspector.verify_ledger(None).unwrap();
+    assert_eq!(verification.num_versions, 1);
+    assert_eq!(verification.divergence.unwrap().version, 1);
+}
+
+fn test_verify_ledger_signatures_impl(
+    blocks: Vec<(
+        Vec<TransactionToCommit>,
+        LedgerInfoWithSignatures<Ed25519Signature>,
+    )>,
+) {
+    let tmp_dir = tempdir().unwrap();
+    let db = db_with_mock_genesis(&tmp_dir).unwrap();
+    let inspector = LibraDBInspector::new(Arc::clone(&db.db));
+
+    // The first ledger info after genesis ends the epoch of `signer`, the next ones are signed by
+    // `next_signer`. Empty blocks save their ledger info over the one of the previous block.
+    let epoch_end_version = match blocks
+        .iter()
+        .map(|(_, ledger_info_with_sigs)| ledger_info_with_sigs.ledger_info().version())
+        .find(|version| *version > 0)
+    {
+        Some(version) => version,
+        None => return,
+    };
+    let epoch_end = blocks
+        .iter()
+        .rposition(|(_, ledger_info_with_sigs)| {
+            ledger_info_with_sigs.ledger_info().version() == epoch_end_version
+        })
+        .unwrap();
+    let signer = ValidatorSigner::<Ed25519PrivateKey>::random([0; 32]);
+    let next_signer = ValidatorSigner::<Ed25519PrivateKey>::random([1; 32]);
+    let (_, network_identity_public_key) = x25519::compat::generate_keypair(None);
+    let next_validator_set = ValidatorSet::new(vec![ValidatorPublicKeys::new(
+        next_signer.author(),
+        next_signer.public_key(),
+        next_signer.public_key(),
+        network_identity_public_key,
+    )]);
+    let mut cur_ver = 0;
+    for (i, (txns_to_commit, ledger_info_with_sigs)) in blocks.iter().enumerate() {
+        let ledger_info = ledger_info_with_sigs.ledger_info();
+        let ledger_info = LedgerInfo::new(
+            ledger_info.version(),
+            ledger_info.transaction_accumulator_hash(),
+            ledger_info.consensus_data_hash(),
+            ledger_info.consensus_block_id(),
+            ledger_info.epoch_num(),
+            ledger_info.timestamp_usecs(),
+            if i == epoch_end {
+                Some(next_validator_set.clone())
+            } else {
+                None
+            },
+        );
+        let signer = if i <= epoch_end {
+            &signer
+        } else {
+            &next_signer
+        };
+        let mut signatures = HashMap::new();
+        signatures.insert(
+            signer.author(),
+            signer.sign_message(ledger_info.hash()).unwrap(),
+        );
+        db.save_transactions(
+            &txns_to_commit,
+            cur_ver + 1, /* first_version */
+            &Some(LedgerInfoWithSignatures::new(ledger_info, signatures)),
+        )
+        .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+
+    let verifier = |signer: &ValidatorSigner<Ed25519PrivateKey>| {
+        Some(ValidatorVerifier::new_single(
+            signer.author(),
+            signer.public_key(),
+        ))
+    };
+    let verification = inspector.verify_ledger(verifier(&signer)).unwrap();
+    assert!(verification.signatures_verified);
+    assert_eq!(verification.divergence, None);
+
+    // The ledger info ending the first epoch is not signed by the validators of the next one.
+    let verification = inspector.verify_ledger(verifier(&next_signer)).unwrap();
+    assert_eq!(verification.divergence.unwrap().version, epoch_end_version);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(5))]
+
+    #[test]
+    fn test_inspect(blocks in arb_blocks_to_commit()) {
+        test_inspect_impl(blocks);
+    }
+
+    #[test]
+    fn test_verify_ledger(blocks in arb_blocks_to_commit()) {
+        test_verify_ledger_impl(blocks);
+    }
+
+    #[test]
+    fn test_verify_ledger_signatures(blocks in arb_blocks_to_commit()) {
+        test_verify_ledger_signatures_impl(blocks);
+    }
+}
+
+#[test]
+fn test_approximate_sizes_cf() {
+    let tmp_dir = tempdir().unwrap();
+    let db = LibraDB::new(&tmp_dir);
+    let inspector = LibraDBInspector::new(Arc::clone(&db.db));
+    let cf_sizes = inspector.get_approximate_sizes_cf().unwrap();
+    assert_eq!(cf_sizes.len(), column_families().len());
+}
@@ -0,0 +1,312 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements [`LibraDBInspector::verify_ledger`], which checks that the data of a
+//! LibraDB is consistent at every version, in order to tell whether a replica can be trusted after
+//! a disk corruption.
+//!
+//! Nothing persisted is trusted but what was pruned: the transaction accumulator and the event
+//! accumulators are recomputed from the transaction infos and the events, and compared with both
+//! the nodes persisted, which proofs are made of, and the root hashes the transaction infos and
+//! the ledger infos carry. Given the validators of the first epoch, the signatures of the ledger
+//! infos are verified too, following the validator set changes that end the epochs.
+
+use super::LibraDBInspector;
+use crate::{
+    event_store::{EmptyAccumulator, EmptyReader},
+    schema::{
+        event_accumulator::EventAccumulatorSchema,
+        jellyfish_merkle_node::JellyfishMerkleNodeSchema, ledger_info::LedgerInfoSchema,
+        signed_transaction::SignedTransactionSchema,
+        transaction_accumulator::TransactionAccumulatorSchema,
+        transaction_info::TransactionInfoSchema,
+    },
+};
+use accumulator::{HashReader, MerkleAccumulator};
+use crypto::{
+    ed25519::*,
+    hash::{CryptoHash, TransactionAccumulatorHasher},
+    HashValue,
+};
+use failure::prelude::*;
+use jellyfish_merkle::node_type::NodeKey;
+use schemadb::ReadOptions;
+use std::collections::{HashMap, HashSet};
+use types::{
+    contract_event::ContractEvent,
+    proof::position::{FrozenSubTreeIterator, Position},
+    transaction::{TransactionInfo, Version},
+    validator_verifier::ValidatorVerifier,
+};
+
+/// The first inconsistency found by [`LibraDBInspector::verify_ledger`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Divergence {
+    /// The version at which the data is inconsistent.
+    pub version: Version,
+    /// What is inconsistent.
+    pub reason: String,
+}
+
+/// The result of [`LibraDBInspector::verify_ledger`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LedgerVerification {
+    /// The first version verified. The transactions before it were pruned, so the accumulator
+    /// nodes persisted for them are trusted.
+    pub first_version: Version,
+    /// The number of consistent versions from `first_version` on.
+    pub num_versions: u64,
+    /// The first version whose state tree was found. The state trees before it were pruned.
+    pub first_state_version: Option<Version>,
+    /// The number of ledger infos verified.
+    pub num_ledger_infos: u64,
+    /// Whether the signatures of the ledger infos were verified.
+    pub signatures_verified: bool,
+    /// The first inconsistency, if any. Versions after it are not verified.
+    pub divergence: Option<Divergence>,
+}
+
+type FrozenSubtreesAccumulator = MerkleAccumulator<FrozenSubtrees, TransactionAccumulatorHasher>;
+
+/// The roots of the frozen subtrees of a transaction accumulator, which is all it takes to append
+/// leaves to it.
+struct FrozenSubtrees {
+    num_leaves: u64,
+    hashes: HashMap<Position, HashValue>,
+}
+
+impl FrozenSubtrees {
+    /// Appends `leaf` and returns the new root hash and the nodes it freezes.
+    fn append(&mut self, leaf: HashValue) -> Result<(HashValue, Vec<(Position, HashValue)>)> {
+        let (root_hash, frozen_nodes) =
+            FrozenSubtreesAccumulator::append(self, self.num_leaves, &[leaf])?;
+        self.num_leaves += 1;
+        self.hashes.extend(frozen_nodes.iter().cloned());
+        let roots: HashSet<_> = FrozenSubTreeIterator::new(self.num_leaves).collect();
+        self.hashes.retain(|position, _| roots.contains(position));
+        Ok((root_hash, frozen_nodes))
+    }
+}
+
+impl HashReader for FrozenSubtrees {
+    fn get(&self, position: Position) -> Result<HashValue> {
+        self.hashes
+            .get(&position)
+            .cloned()
+            .ok_or_else(|| format_err!("Position {:?} is not a frozen subtree root.", position))
+    }
+}
+
+impl LibraDBInspector {
+    /// Walks every version from the first one which is not pruned to the latest one, and verifies
+    /// that:
+    ///   * the transaction matches the hash in its transaction info,
+    ///   * the event accumulator recomputed from its events matches both the nodes persisted and
+    /// the root hash in the transaction info,
+    ///   * the root of the state tree has the state root hash of the transaction info, once state
+    /// trees are not pruned anymore,
+    ///   * the transaction accumulator recomputed from the transaction infos matches both the nodes
+    /// persisted and the root hashes of the ledger infos,
+    ///   * the ledger infos are signed by a quorum of the validators of their epoch, if `verifier`
+    /// verifies the signatures of the validators of the epoch of the first ledger info walked. The
+    /// validators of the next epochs are the ones the ledger infos ending the epochs carry.
+    ///
+    /// Returns the first version at which any of these fails, if any.
+    pub fn verify_ledger(
+        &self,
+        mut verifier: Option<ValidatorVerifier<Ed25519PublicKey>>,
+    ) -> Result<LedgerVerification> {
+        let mut iter = self
+            .db
+            .iter::<TransactionInfoSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        let mut iter = iter.peekable();
+        // A read error is returned by the walk below.
+        let first_version = match iter.peek() {
+            Some(Ok((version, _))) => *version,
+            _ => 0,
+        };
+
+        let mut result = LedgerVerification {
+            first_version,
+            num_versions: 0,
+            first_state_version: None,
+            num_ledger_infos: 0,
+            signatures_verified: verifier.is_some(),
+            divergence: None,
+        };
+        let mut frozen_subtrees = FrozenSubtrees {
+            num_leaves: first_version,
+            hashes: HashMap::new(),
+        };
+        for position in FrozenSubTreeIterator::new(first_version) {
+            let hash = self
+                .db
+                .get::<TransactionAccumulatorSchema>(&position)?
+                .ok_or_else(|| {
+                    format_err!(
+                        "Accumulator node at position {} before the first version {} is missing.",
+                        position.to_inorder_index(),
+                        first_version
+                    )
+                })?;
+            frozen_subtrees.hashes.insert(position, hash);
+        }
+
+        for item in iter {
+            let (version, txn_info) = item?;
+            let expected_version = first_version + result.num_versions;
+            if version != expected_version {
+                result.divergence = Some(Divergence {
+                    version: expected_version,
+                    reason: "TransactionInfo is missing.".to_string(),
+                });
+                return Ok(result);
+            }
+            if let Some(reason) = self.verify_version(
+                version,
+                &txn_info,
+                &mut frozen_subtrees,
+                &mut verifier,
+                &mut result,
+            )? {
+                result.divergence = Some(Divergence { version, reason });
+                return Ok(result);
+            }
+            result.num_versions += 1;
+        }
+
+        if result.num_versions == 0 {
+            return Ok(result);
+        }
+        let latest_version = first_version + result.num_versions - 1;
+        if result.first_state_version.is_none() {
+            result.divergence = Some(Divergence {
+                version: latest_version,
+                reason: "No state tree is found.".to_string(),
+            });
+            return Ok(result);
+        }
+        let mut ledger_info_iter = self.db.iter::<LedgerInfoSchema>(ReadOptions::default())?;
+        ledger_info_iter.seek(&(latest_version + 1))?;
+        if let Some((version, _)) = ledger_info_iter.next().transpose()? {
+            result.divergence = Some(Divergence {
+                version,
+                reason: "LedgerInfo is after the latest TransactionInfo.".to_string(),
+            });
+        }
+        Ok(result)
+    }
+
+    /// Verifies the data at `version`. Returns what is inconsistent, if anything.
+    fn verify_version(
+        &self,
+        version: Version,
+        txn_info: &TransactionInfo,
+        frozen_subtrees: &mut FrozenSubtrees,
+        verifier: &mut Option<ValidatorVerifier<Ed25519PublicKey>>,
+        result: &mut LedgerVerification,
+    ) -> Result<Option<String>> {
+        match self.db.get::<SignedTransactionSchema>(&version)? {
+            Some(txn) => {
+                if txn.hash() != txn_info.signed_transaction_hash() {
+                    return Ok(Some(format!(
+                        "Transaction hash is {:x}, TransactionInfo has {:x}.",
+                        txn.hash(),
+                        txn_info.signed_transaction_hash()
+                    )));
+                }
+            }
+            None => return Ok(Some("Transaction is missing.".to_string())),
+        }
+
+        let event_hashes: Vec<_> = self
+            .event_store
+            .get_events_by_version(version)?
+            .iter()
+            .map(ContractEvent::hash)
+            .collect();
+        let (event_root_hash, event_nodes) =
+            EmptyAccumulator::append(&EmptyReader, 0, &event_hashes)?;
+        if event_root_hash != txn_info.event_root_hash() {
+            return Ok(Some(format!(
+                "Event root hash is {:x}, TransactionInfo has {:x}.",
+                event_root_hash,
+                txn_info.event_root_hash()
+            )));
+        }
+        for (position, hash) in event_nodes {
+            let persisted_hash = self
+                .db
+                .get::<EventAccumulatorSchema>(&(version, position))?;
+            if persisted_hash != Some(hash) {
+                return Ok(Some(format!(
+                    "Event accumulator node at position {} is {:?}, expected {:x}.",
+                    position.to_inorder_index(),
+                    persisted_hash,
+                    hash
+                )));
+            }
+        }
+
+        // State trees are pruned from the oldest version on, so once one is found, all the later
+        // ones must be there.
+        match self
+            .db
+            .get::<JellyfishMerkleNodeSchema>(&NodeKey::new_empty_path(version))?
+        {
+            Some(root) => {
+                if root.hash() != txn_info.state_root_hash() {
+                    return Ok(Some(format!(
+                        "State tree root hash is {:x}, TransactionInfo has {:x}.",
+                        root.hash(),
+                        txn_info.state_root_hash()
+                    )));
+                }
+                result.first_state_version.get_or_insert(version);
+            }
+            None => {
+                if result.first_state_version.is_some() {
+                    return Ok(Some("State tree root is missing.".to_string()));
+                }
+            }
+        }
+
+        let (root_hash, frozen_nodes) = frozen_subtrees.append(txn_info.hash())?;
+        for (position, hash) in frozen_nodes {
+            let persisted_hash = self.db.get::<TransactionAccumulatorSchema>(&position)?;
+            if persisted_hash != Some(hash) {
+                return Ok(Some(format!(
+                    "Accumulator node at position {} is {:?}, expected {:x}.",
+                    position.to_inorder_index(),
+                    persisted_hash,
+                    hash
+                )));
+            }
+        }
+        if let Some(ledger_info_with_sigs) = self.db.get::<LedgerInfoSchema>(&version)? {
+            let expected_root_hash = ledger_info_with_sigs
+                .ledger_info()
+                .transaction_accumulator_hash();
+            if root_hash != expected_root_hash {
+                return Ok(Some(format!(
+                    "Accumulator root hash is {:x}, LedgerInfo has {:x}.",
+                    root_hash, expected_root_hash
+                )));
+            }
+            if let Some(verifier) = verifier {
+                if let Err(e) = ledger_info_with_sigs.verify(verifier) {
+                    return Ok(Some(format!("LedgerInfo signatures are invalid: {}.", e)));
+                }
+                if let Some(validator_set) =
+                    ledger_info_with_sigs.ledger_info().next_validator_set()
+                {
+                    *verifier = ValidatorVerifier::from(validator_set);
+                }
+            }
+            result.num_ledger_infos += 1;
+        }
+
+        Ok(None)
+    }
+}
@@ -12,6 +12,7 @@ use proptest_derive::Arbitrary;
 use std::collections::BTreeMap;
 use strum::IntoEnumIterator;
 use strum_macros::{AsRefStr, EnumIter};
+use types::{ledger_statistics::LedgerStatistics, transaction::Version};
 
 /// Types of ledger counters.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, ToPrimitive, EnumIter, AsRefStr)]
@@ -128,6 +129,18 @@ impl LedgerCounters {
     pub fn get(&self, counter: LedgerCounter) -> usize {
         self.counters.get(counter)
     }
+
+    /// Exposes the counters as of `version`.
+    pub fn to_ledger_statistics(&self, version: Version) -> LedgerStatistics {
+        LedgerStatistics {
+            version,
+            events_created: self.get(LedgerCounter::EventsCreated) as u64,
+            new_state_leaves: self.get(LedgerCounter::NewStateLeaves) as u64,
+            stale_state_leaves: self.get(LedgerCounter::StaleStateLeaves) as u64,
+            new_state_nodes: self.get(LedgerCounter::NewStateNodes) as u64,
+            stale_state_nodes: self.get(LedgerCounter::StaleStateNodes) as u64,
+        }
+    }
 }
 
 impl CanonicalSerialize for LedgerCounters {
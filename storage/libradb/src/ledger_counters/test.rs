@@ -25,3 +25,27 @@ fn test_ledger_counters() {
     assert_eq!(counters.get(LedgerCounter::NewStateLeaves), 2);
     assert_eq!(counters.get(LedgerCounter::StaleStateLeaves), 1);
 }
+
+#[test]
+fn test_to_ledger_statistics() {
+    let mut bumps = LedgerCounterBumps::new();
+    bumps
+        .bump(LedgerCounter::EventsCreated, 3)
+        .bump(LedgerCounter::NewStateLeaves, 4)
+        .bump(LedgerCounter::StaleStateLeaves, 1)
+        .bump(LedgerCounter::NewStateNodes, 9);
+    let mut counters = LedgerCounters::new();
+    counters.bump(bumps);
+
+    assert_eq!(
+        counters.to_ledger_statistics(7),
+        LedgerStatistics {
+            version: 7,
+            events_created: 3,
+            new_state_leaves: 4,
+            stale_state_leaves: 1,
+            new_state_nodes: 9,
+            stale_state_nodes: 0,
+        }
+    );
+}
@@ -4,8 +4,9 @@
 use super::*;
 use crate::{change_set::ChangeSet, LibraDB};
 use proptest::{collection::vec, prelude::*};
+use std::collections::HashMap;
 use tempfile::tempdir;
-use types::ledger_info::LedgerInfo;
+use types::{ledger_info::LedgerInfo, validator_set::ValidatorSet};
 
 prop_compose! {
     fn arb_partial_ledger_info()(accu_hash in any::<HashValue>(),
@@ -35,6 +36,7 @@ prop_compose! {
                         HashValue::zero(),
                         ledger_info.epoch_num(),
                         ledger_info.timestamp_usecs(),
+                        None,
                     ),
                     p.signatures().clone(),
                 )
@@ -65,3 +67,65 @@ proptest! {
         prop_assert_eq!(db.ledger_store.get_ledger_infos(start_version).unwrap(), ledger_infos_with_sigs);
     }
 }
+
+#[test]
+fn test_get_epoch_change_ledger_infos() {
+    let tmp_dir = tempdir().unwrap();
+    let db = LibraDB::new(&tmp_dir);
+    let store = &db.ledger_store;
+
+    // Epoch 0 ends at version 5, epoch 1 at version 12, and epoch 2 is still going on.
+    let ledger_infos_with_sigs: Vec<_> = (0..=20)
+        .map(|version| {
+            let (epoch_num, ends_epoch) = match version {
+                0..=5 => (0, version == 5),
+                6..=12 => (1, version == 12),
+                _ => (2, false),
+            };
+            LedgerInfoWithSignatures::new(
+                LedgerInfo::new(
+                    version,
+                    HashValue::random(),
+                    HashValue::random(),
+                    HashValue::zero(),
+                    epoch_num,
+                    version,
+                    if ends_epoch {
+                        Some(ValidatorSet::new(vec![]))
+                    } else {
+                        None
+                    },
+                ),
+                HashMap::new(),
+            )
+        })
+        .collect();
+    let mut cs = ChangeSet::new();
+    for ledger_info_with_sigs in &ledger_infos_with_sigs {
+        store
+            .put_ledger_info(ledger_info_with_sigs, &mut cs)
+            .unwrap();
+    }
+    store.db.write_schemas(cs.batch).unwrap();
+
+    let epoch_changes = |start_version, end_version| {
+        store
+            .get_epoch_change_ledger_infos(start_version, end_version)
+            .unwrap()
+    };
+    let epoch_0_end = ledger_infos_with_sigs[5].clone();
+    let epoch_1_end = ledger_infos_with_sigs[12].clone();
+    assert_eq!(
+        epoch_changes(0, 20),
+        vec![epoch_0_end.clone(), epoch_1_end.clone()]
+    );
+    assert_eq!(
+        epoch_changes(5, 20),
+        vec![epoch_0_end.clone(), epoch_1_end.clone()]
+    );
+    assert_eq!(epoch_changes(6, 20), vec![epoch_1_end.clone()]);
+    // The changes after the end version are not known yet.
+    assert_eq!(epoch_changes(0, 11), vec![epoch_0_end]);
+    assert_eq!(epoch_changes(12, 12), vec![epoch_1_end]);
+    assert!(epoch_changes(13, 20).is_empty());
+}
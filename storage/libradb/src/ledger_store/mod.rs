@@ -75,6 +75,67 @@ impl LedgerStore {
         Ok(iter.map(|kv| Ok(kv?.1)).collect::<Result<Vec<_>>>()?)
     }
 
+    /// Return the ledger infos ending the epochs from the one of the first ledger info at or after
+    /// `start_version` up to `end_version`, in order. Each of them is signed by the validators of
+    /// the epoch it ends and carries the validator set of the next one.
+    pub fn get_epoch_change_ledger_infos(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<Vec<LedgerInfoWithSignatures<Ed25519Signature>>> {
+        let mut ledger_infos = vec![];
+        let mut next_version = start_version;
+        while let Some(mut last_ledger_info) = self.get_ledger_info_at_or_after(next_version)? {
+            let first_version = last_ledger_info.ledger_info().version();
+            if first_version > end_version {
+                break;
+            }
+            // The epochs increase with the versions: binary search the last ledger info of the
+            // epoch, which is the one ending it.
+            let epoch_num = last_ledger_info.ledger_info().epoch_num();
+            let (mut low, mut high) = (first_version, end_version);
+            while low < high {
+                let mid = low + (high - low + 1) / 2;
+                match self.get_ledger_info_at_or_before(mid)? {
+                    Some(ledger_info) if ledger_info.ledger_info().epoch_num() == epoch_num => {
+                        low = mid;
+                        last_ledger_info = ledger_info;
+                    }
+                    _ => high = mid - 1,
+                }
+            }
+            if last_ledger_info
+                .ledger_info()
+                .next_validator_set()
+                .is_none()
+            {
+                // The epoch didn't end yet.
+                break;
+            }
+            next_version = last_ledger_info.ledger_info().version() + 1;
+            ledger_infos.push(last_ledger_info);
+        }
+        Ok(ledger_infos)
+    }
+
+    fn get_ledger_info_at_or_after(
+        &self,
+        version: Version,
+    ) -> Result<Option<LedgerInfoWithSignatures<Ed25519Signature>>> {
+        let mut iter = self.db.iter::<LedgerInfoSchema>(ReadOptions::default())?;
+        iter.seek(&version)?;
+        Ok(iter.next().transpose()?.map(|(_, ledger_info)| ledger_info))
+    }
+
+    fn get_ledger_info_at_or_before(
+        &self,
+        version: Version,
+    ) -> Result<Option<LedgerInfoWithSignatures<Ed25519Signature>>> {
+        let mut iter = self.db.iter::<LedgerInfoSchema>(ReadOptions::default())?;
+        iter.seek_for_prev(&version)?;
+        Ok(iter.next().transpose()?.map(|(_, ledger_info)| ledger_info))
+    }
+
     pub fn get_latest_ledger_info_option(
         &self,
     ) -> Option<LedgerInfoWithSignatures<Ed25519Signature>> {
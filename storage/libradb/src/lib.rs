@@ -14,6 +14,7 @@ pub mod mock_genesis;
 pub mod test_helper;
 
 pub mod errors;
+pub mod inspector;
 pub mod schema;
 
 mod change_set;
@@ -43,20 +44,22 @@ use crate::{
 use crypto::{ed25519::*, hash::CryptoHash, HashValue};
 use failure::prelude::*;
 use itertools::{izip, zip_eq};
+use jellyfish_merkle::iterator::JellyfishMerkleIterator;
 use lazy_static::lazy_static;
 use logger::prelude::*;
 use metrics::OpMetrics;
 use schemadb::{ColumnFamilyOptions, ColumnFamilyOptionsMap, DB, DEFAULT_CF_NAME};
 use std::{convert::TryInto, iter::Iterator, path::Path, sync::Arc, time::Instant};
-use storage_proto::ExecutorStartupInfo;
+use storage_proto::{ExecutorStartupInfo, PruneWindows};
 use types::{
     access_path::AccessPath,
     account_address::AccountAddress,
-    account_config::{get_account_resource_or_default, AccountResource},
+    account_config::AccountResource,
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
     contract_event::EventWithProof,
     get_with_proof::{RequestItem, ResponseItem},
     ledger_info::LedgerInfoWithSignatures,
+    ledger_statistics::LedgerStatistics,
     proof::{AccountStateProof, EventProof, SignedTransactionProof, SparseMerkleProof},
     transaction::{
         SignedTransactionWithProof, TransactionInfo, TransactionListWithProof, TransactionToCommit,
@@ -80,6 +83,43 @@ fn error_if_too_many_requested(num_requested: u64, max_allowed: u64) -> Result<(
     }
 }
 
+/// The directory holding the DB under the root path of a LibraDB.
+const LIBRADB_DIR_NAME: &str = "libradb";
+
+/// The column families of the DB, each holding the data of one schema.
+fn column_families() -> ColumnFamilyOptionsMap {
+    [
+        (
+            /* LedgerInfo CF = */ DEFAULT_CF_NAME,
+            ColumnFamilyOptions::default(),
+        ),
+        (EVENT_ACCUMULATOR_CF_NAME, ColumnFamilyOptions::default()),
+        (EVENT_BY_ACCESS_PATH_CF_NAME, ColumnFamilyOptions::default()),
+        (EVENT_BY_ADDRESS_CF_NAME, ColumnFamilyOptions::default()),
+        (EVENT_CF_NAME, ColumnFamilyOptions::default()),
+        (
+            JELLYFISH_MERKLE_NODE_CF_NAME,
+            ColumnFamilyOptions::default(),
+        ),
+        (LEDGER_COUNTERS_CF_NAME, ColumnFamilyOptions::default()),
+        (STALE_NODE_INDEX_CF_NAME, ColumnFamilyOptions::default()),
+        (SIGNED_TRANSACTION_CF_NAME, ColumnFamilyOptions::default()),
+        (
+            TRANSACTION_ACCUMULATOR_CF_NAME,
+            ColumnFamilyOptions::default(),
+        ),
+        (
+            TRANSACTION_BY_ACCOUNT_CF_NAME,
+            ColumnFamilyOptions::default(),
+        ),
+        (TRANSACTION_INFO_CF_NAME, ColumnFamilyOptions::default()),
+        (VALIDATOR_CF_NAME, ColumnFamilyOptions::default()),
+    ]
+    .iter()
+    .cloned()
+    .collect()
+}
+
 /// This holds a handle to the underlying DB responsible for physical storage and provides APIs for
 /// access to the core Libra data structures.
 pub struct LibraDB {
@@ -89,45 +129,30 @@ pub struct LibraDB {
     state_store: StateStore,
     event_store: EventStore,
     system_store: SystemStore,
-    pruner: Pruner,
+    /// Absent when the DB is opened read-only.
+    pruner: Option<Pruner>,
+    /// Whether the DB is opened as a secondary instance, which fails all the writes.
+    read_only: bool,
 }
 
 impl LibraDB {
-    /// Config parameter for the pruner.
-    const NUM_HISTORICAL_VERSIONS_TO_KEEP: u64 = 1_000_000;
-
     /// This creates an empty LibraDB instance on disk or opens one if it already exists.
     pub fn new<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
-        let cf_opts_map: ColumnFamilyOptionsMap = [
-            (
-                /* LedgerInfo CF = */ DEFAULT_CF_NAME,
-                ColumnFamilyOptions::default(),
-            ),
-            (EVENT_ACCUMULATOR_CF_NAME, ColumnFamilyOptions::default()),
-            (EVENT_BY_ACCESS_PATH_CF_NAME, ColumnFamilyOptions::default()),
-            (EVENT_CF_NAME, ColumnFamilyOptions::default()),
-            (
-                JELLYFISH_MERKLE_NODE_CF_NAME,
-                ColumnFamilyOptions::default(),
-            ),
-            (LEDGER_COUNTERS_CF_NAME, ColumnFamilyOptions::default()),
-            (STALE_NODE_INDEX_CF_NAME, ColumnFamilyOptions::default()),
-            (SIGNED_TRANSACTION_CF_NAME, ColumnFamilyOptions::default()),
-            (
-                TRANSACTION_ACCUMULATOR_CF_NAME,
-                ColumnFamilyOptions::default(),
-            ),
-            (TRANSACTION_INFO_CF_NAME, ColumnFamilyOptions::default()),
-            (VALIDATOR_CF_NAME, ColumnFamilyOptions::default()),
-        ]
-        .iter()
-        .cloned()
-        .collect();
-
-        let path = db_root_path.as_ref().join("libradb");
+        Self::new_with_prune_windows(db_root_path, PruneWindows::default())
+    }
+
+    /// Same as `new`, keeping as much history as `prune_windows` tells.
+    pub fn new_with_prune_windows<P: AsRef<Path> + Clone>(
+        db_root_path: P,
+        prune_windows: PruneWindows,
+    ) -> Self {
+        prune_windows
+            .validate()
+            .unwrap_or_else(|e| panic!("Invalid prune windows: {:?}", e));
+        let path = db_root_path.as_ref().join(LIBRADB_DIR_NAME);
         let instant = Instant::now();
         let db = Arc::new(
-            DB::open(path.clone(), cf_opts_map)
+            DB::open(path.clone(), column_families())
                 .unwrap_or_else(|e| panic!("LibraDB open failed: {:?}", e)),
         );
 
@@ -137,15 +162,74 @@ impl LibraDB {
             instant.elapsed().as_millis()
         );
 
-        LibraDB {
+        let libra_db = LibraDB {
             db: Arc::clone(&db),
             event_store: EventStore::new(Arc::clone(&db)),
             ledger_store: LedgerStore::new(Arc::clone(&db)),
             state_store: StateStore::new(Arc::clone(&db)),
             transaction_store: TransactionStore::new(Arc::clone(&db)),
             system_store: SystemStore::new(Arc::clone(&db)),
-            pruner: Pruner::new(Arc::clone(&db), Self::NUM_HISTORICAL_VERSIONS_TO_KEEP),
+            pruner: Some(Pruner::new(Arc::clone(&db), prune_windows)),
+            read_only: false,
+        };
+
+        // A DB written before transactions were indexed by account gets its index on first open.
+        let instant = Instant::now();
+        let num_indexed = libra_db
+            .transaction_store
+            .backfill_transactions_by_account()
+            .unwrap_or_else(|e| panic!("Indexing transactions by account failed: {:?}", e));
+        if num_indexed > 0 {
+            info!(
+                "Indexed {} transactions by account in {} ms",
+                num_indexed,
+                instant.elapsed().as_millis()
+            );
         }
+
+        libra_db
+    }
+
+    /// Opens the existing LibraDB under `db_root_path` for reads only, e.g. for analytics while a
+    /// node keeps writing to it. It is a RocksDB secondary instance, keeping its own info logs
+    /// under `secondary_path`: it serves the data as of the time it is opened, until
+    /// [`LibraDB::try_catch_up_with_primary`] catches up with the writer. Nothing is pruned by it,
+    /// and the write APIs fail.
+    pub fn open_as_secondary<P: AsRef<Path>, Q: AsRef<Path>>(
+        db_root_path: P,
+        secondary_path: Q,
+    ) -> Result<Self> {
+        let path = db_root_path.as_ref().join(LIBRADB_DIR_NAME);
+        let instant = Instant::now();
+        let db = Arc::new(DB::open_as_secondary(
+            path.clone(),
+            secondary_path,
+            column_families(),
+        )?);
+
+        info!(
+            "Opened LibraDB as secondary at {:?} in {} ms",
+            path,
+            instant.elapsed().as_millis()
+        );
+
+        Ok(LibraDB {
+            db: Arc::clone(&db),
+            event_store: EventStore::new(Arc::clone(&db)),
+            ledger_store: LedgerStore::new(Arc::clone(&db)),
+            state_store: StateStore::new(Arc::clone(&db)),
+            transaction_store: TransactionStore::new(Arc::clone(&db)),
+            system_store: SystemStore::new(Arc::clone(&db)),
+            pruner: None,
+            read_only: true,
+        })
+    }
+
+    /// Makes a LibraDB opened with [`LibraDB::open_as_secondary`] see what the node wrote since it
+    /// was opened or last caught up.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        ensure!(self.read_only, "LibraDB is not opened as secondary.");
+        self.db.try_catch_up_with_primary()
     }
 
     // ================================== Public API ==================================
@@ -272,9 +356,48 @@ impl LibraDB {
         Ok((events_with_proof, account_state))
     }
 
+    /// Returns the events emitted by up to `limit` transactions from `start_version`, not beyond
+    /// `ledger_version`, in the order they were emitted. When `access_path_prefix` is given, only
+    /// the events emitted to access paths starting with it are returned.
+    fn get_events_by_version_range(
+        &self,
+        start_version: Version,
+        limit: u64,
+        access_path_prefix: Option<&AccessPath>,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithProof>> {
+        error_if_too_many_requested(limit, MAX_LIMIT)?;
+
+        if start_version > ledger_version || limit == 0 {
+            return Ok(Vec::new());
+        }
+        let end_version = std::cmp::min(start_version + limit - 1, ledger_version);
+
+        self.event_store
+            .get_events_by_version_range(
+                access_path_prefix.map(|prefix| prefix.address),
+                start_version,
+                end_version,
+            )?
+            .into_iter()
+            .filter(|(_, _, event)| {
+                access_path_prefix.map_or(true, |prefix| event.access_path().starts_with(prefix))
+            })
+            .map(|(ver, idx, _)| {
+                let (event, event_proof) = self
+                    .event_store
+                    .get_event_with_proof_by_version_and_index(ver, idx)?;
+                let (txn_info, txn_info_proof) = self
+                    .ledger_store
+                    .get_transaction_info_with_proof(ver, ledger_version)?;
+                let proof = EventProof::new(txn_info_proof, txn_info, event_proof);
+                Ok(EventWithProof::new(ver, idx, event, proof))
+            })
+            .collect()
+    }
+
     /// Returns a signed transaction that is the `seq_num`-th one associated with the given account.
     /// If the signed transaction with given `seq_num` doesn't exist, returns `None`.
-    // TODO(gzh): Use binary search for now. We may create seq_num index in the future.
     fn get_txn_by_account_and_seq(
         &self,
         address: AccountAddress,
@@ -282,35 +405,55 @@ impl LibraDB {
         ledger_version: Version,
         fetch_events: bool,
     ) -> Result<Option<SignedTransactionWithProof>> {
-        // If txn with seq_num n is at some version, the corresponding account state at the
-        // same version will be the first account state that has seq_num n + 1.
-        let seq_num = seq_num + 1;
-        let (mut start_version, mut end_version) = (0, ledger_version);
-        while start_version < end_version {
-            let mid_version = start_version + (end_version - start_version) / 2;
-            let account_seq_num = self.get_account_seq_num_by_version(address, mid_version)?;
-            if account_seq_num >= seq_num {
-                end_version = mid_version;
-            } else {
-                start_version = mid_version + 1;
-            }
-        }
-        assert_eq!(start_version, end_version);
-
-        let seq_num_found = self.get_account_seq_num_by_version(address, start_version)?;
-        if seq_num_found < seq_num {
-            return Ok(None);
-        } else if seq_num_found > seq_num {
-            // log error
-            bail!("internal error: seq_num is not continuous.")
-        }
-        // start_version cannot be 0 (genesis version).
-        assert_eq!(
-            self.get_account_seq_num_by_version(address, start_version - 1)?,
-            seq_num_found - 1
+        self.transaction_store
+            .lookup_transaction_by_account(address, seq_num, ledger_version)?
+            .map(|version| self.get_transaction_with_proof(version, ledger_version, fetch_events))
+            .transpose()
+    }
+
+    /// Returns up to `limit` signed transactions sent by the given account, with consecutive
+    /// sequence numbers starting from `start_seq_num`, and proofs based on `ledger_version`. The
+    /// result is empty if the account did not send a transaction with `start_seq_num`.
+    pub fn get_txns_by_account(
+        &self,
+        address: AccountAddress,
+        start_seq_num: u64,
+        limit: u64,
+        ledger_version: Version,
+        fetch_events: bool,
+    ) -> Result<Vec<SignedTransactionWithProof>> {
+        error_if_too_many_requested(limit, MAX_LIMIT)?;
+
+        self.transaction_store
+            .lookup_transactions_by_account(address, start_seq_num, limit, ledger_version)?
+            .into_iter()
+            .map(|(_seq_num, version)| {
+                self.get_transaction_with_proof(version, ledger_version, fetch_events)
+            })
+            .collect()
+    }
+
+    /// Returns up to `limit` snapshots of the ledger counters recorded from `start_version` to
+    /// `end_version`. The counters are recorded at the last version of each batch of transactions
+    /// saved, so the first snapshot is the latest one recorded at or before `start_version`.
+    ///
+    /// The counters are not part of the ledger, so there is no proof for them.
+    pub fn get_ledger_statistics(
+        &self,
+        start_version: Version,
+        end_version: Version,
+        limit: u64,
+    ) -> Result<Vec<LedgerStatistics>> {
+        ensure!(
+            start_version <= end_version,
+            "Start version {} is greater than end version {}.",
+            start_version,
+            end_version
         );
-        self.get_transaction_with_proof(start_version, ledger_version, fetch_events)
-            .map(Some)
+        error_if_too_many_requested(limit, MAX_LIMIT)?;
+
+        self.system_store
+            .get_ledger_statistics(start_version, end_version, limit)
     }
 
     /// Gets the latest version number available in the ledger.
@@ -336,6 +479,7 @@ impl LibraDB {
         first_version: Version,
         ledger_info_with_sigs: &Option<LedgerInfoWithSignatures<Ed25519Signature>>,
     ) -> Result<()> {
+        self.ensure_writable()?;
         let num_txns = txns_to_commit.len() as u64;
         // ledger_info_with_sigs could be None if we are doing state synchronization. In this case
         // txns_to_commit should not be empty. Otherwise it is okay to commit empty blocks.
@@ -391,7 +535,9 @@ impl LibraDB {
                 .expect("Counters should be bumped with transactions being saved.")
                 .bump_op_counters();
 
-            self.pruner.wake(last_version);
+            if let Some(pruner) = &self.pruner {
+                pruner.wake(last_version);
+            }
         }
 
         Ok(())
@@ -445,10 +591,10 @@ impl LibraDB {
 
     /// This backs the `UpdateToLatestLedger` public read API which returns the latest
     /// [`LedgerInfoWithSignatures`] together with items requested and proofs relative to the same
-    /// ledger info.
+    /// ledger info, and the ledger infos ending the epochs since `client_known_version`.
     pub fn update_to_latest_ledger(
         &self,
-        _client_known_version: u64,
+        client_known_version: u64,
         request_items: Vec<RequestItem>,
     ) -> Result<(
         Vec<ResponseItem>,
@@ -530,13 +676,43 @@ impl LibraDB {
                         txn_list_with_proof,
                     })
                 }
+                RequestItem::GetAccountStateAtVersion { address, version } => {
+                    Ok(ResponseItem::GetAccountStateAtVersion {
+                        account_state_with_proof: self.get_account_state_with_proof(
+                            address,
+                            version,
+                            ledger_version,
+                        )?,
+                    })
+                }
+                RequestItem::GetEventsByVersionRange {
+                    start_version,
+                    limit,
+                    access_path_prefix,
+                } => Ok(ResponseItem::GetEventsByVersionRange {
+                    events_with_proof: self.get_events_by_version_range(
+                        start_version,
+                        limit,
+                        access_path_prefix.as_ref(),
+                        ledger_version,
+                    )?,
+                }),
             })
             .collect::<Result<Vec<_>>>()?;
 
+        // The changes of the validator set since the version known to the client, so that it can
+        // verify the ledger info of the response.
+        let validator_change_events = self
+            .ledger_store
+            .get_epoch_change_ledger_infos(client_known_version, ledger_version)?
+            .into_iter()
+            .map(ValidatorChangeEventWithProof::new)
+            .collect();
+
         Ok((
             response_items,
             ledger_info_with_sigs,
-            vec![], /* TODO: validator_change_events */
+            validator_change_events,
         ))
     }
 
@@ -641,7 +817,171 @@ impl LibraDB {
         ))
     }
 
+    // =========================== Backup and Restore APIs ========================================
+    /// Gets an iterator over all the account states at `version`, ordered by the hashes of the
+    /// account addresses.
+    ///
+    /// This is used by the backup service, to back up a state snapshot.
+    pub fn get_account_iter(
+        &self,
+        version: Version,
+    ) -> Result<impl Iterator<Item = Result<(HashValue, AccountStateBlob)>> + '_> {
+        JellyfishMerkleIterator::new(&self.state_store, version, HashValue::zero())
+    }
+
+    /// Restores a batch of backed up transactions, with their transaction infos and events.
+    ///
+    /// The transactions must directly follow the ones already in the DB, and the events must be
+    /// present. The account states are not restored: the state at a version is restored as a
+    /// whole by [`restore_account_state`](LibraDB::restore_account_state). When
+    /// `ledger_info_with_sigs` is provided, its version must be the one of the last transaction
+    /// and the transaction accumulator root hash it carries is verified before it is saved.
+    ///
+    /// `txn_list` is expected to be verified by the caller against `ledger_info_with_sigs` or a
+    /// later ledger info.
+    pub fn restore_transactions(
+        &self,
+        txn_list: &TransactionListWithProof,
+        ledger_info_with_sigs: &Option<LedgerInfoWithSignatures<Ed25519Signature>>,
+    ) -> Result<()> {
+        self.ensure_writable()?;
+        let num_txns = txn_list.transaction_and_infos.len() as u64;
+        ensure!(num_txns > 0, "Nothing to restore.");
+        let first_version = txn_list
+            .first_transaction_version
+            .ok_or_else(|| format_err!("First transaction version is absent."))?;
+        let expected_first_version = match self.ledger_store.get_latest_transaction_info_option()? {
+            Some((latest_version, _)) => latest_version + 1,
+            None => 0,
+        };
+        ensure!(
+            first_version == expected_first_version,
+            "Transactions not applicable: first version {}, expected {}",
+            first_version,
+            expected_first_version,
+        );
+        let events = txn_list
+            .events
+            .as_ref()
+            .ok_or_else(|| format_err!("Events are required to restore transactions."))?;
+        if let Some(x) = ledger_info_with_sigs {
+            let claimed_last_version = x.ledger_info().version();
+            ensure!(
+                claimed_last_version + 1 == first_version + num_txns,
+                "Ledger info not applicable: first_version {}, num_txns {}, last_version {}",
+                first_version,
+                num_txns,
+                claimed_last_version,
+            );
+        }
+
+        let mut cs = ChangeSet::new();
+        for (version, (txn, txn_info), txn_events) in izip!(
+            first_version..first_version + num_txns,
+            &txn_list.transaction_and_infos,
+            events
+        ) {
+            ensure!(
+                txn.hash() == txn_info.signed_transaction_hash(),
+                "Transaction hash doesn't match its transaction info at version {}.",
+                version,
+            );
+            self.transaction_store
+                .put_transaction(version, txn, &mut cs)?;
+            let event_root_hash = self.event_store.put_events(version, txn_events, &mut cs)?;
+            ensure!(
+                event_root_hash == txn_info.event_root_hash(),
+                "Event root hash doesn't match its transaction info at version {}.",
+                version,
+            );
+        }
+        let txn_infos = txn_list
+            .transaction_and_infos
+            .iter()
+            .map(|(_, txn_info)| txn_info.clone())
+            .collect::<Vec<_>>();
+        let new_root_hash =
+            self.ledger_store
+                .put_transaction_infos(first_version, &txn_infos, &mut cs)?;
+
+        if let Some(x) = ledger_info_with_sigs {
+            let expected_root_hash = x.ledger_info().transaction_accumulator_hash();
+            ensure!(
+                new_root_hash == expected_root_hash,
+                "Root hash calculated doesn't match expected. {:?} vs {:?}",
+                new_root_hash,
+                expected_root_hash,
+            );
+            self.ledger_store.put_ledger_info(x, &mut cs)?;
+        }
+
+        let (sealed_cs, _counters) = self.seal_change_set(first_version, num_txns, cs)?;
+        self.commit(sealed_cs)?;
+        if let Some(x) = ledger_info_with_sigs {
+            self.ledger_store.set_latest_ledger_info(x.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Restores the whole state snapshot at `version`, from all the account states keyed by the
+    /// hashes of their addresses. The root hash of the rebuilt state Merkle tree must be
+    /// `expected_root_hash`.
+    ///
+    /// All the account states are held in memory. The states of the other versions are not
+    /// restored, so the state can only be read at `version` and after.
+    pub fn restore_account_state(
+        &self,
+        version: Version,
+        account_states: Vec<(HashValue, AccountStateBlob)>,
+        expected_root_hash: HashValue,
+    ) -> Result<()> {
+        self.ensure_writable()?;
+        let mut cs = ChangeSet::new();
+        let root_hash =
+            self.state_store
+                .put_account_state_snapshot(version, account_states, &mut cs)?;
+        ensure!(
+            root_hash == expected_root_hash,
+            "State root hash calculated doesn't match expected. {:?} vs {:?}",
+            root_hash,
+            expected_root_hash,
+        );
+        self.commit(SealedChangeSet { batch: cs.batch })
+    }
+
+    // ================================== Admin APIs ==================================
+    /// Returns how much history is kept. A read-only instance doesn't prune, so it returns the
+    /// default windows.
+    pub fn get_prune_windows(&self) -> PruneWindows {
+        self.pruner
+            .as_ref()
+            .map_or_else(PruneWindows::default, Pruner::get_prune_windows)
+    }
+
+    /// Changes how much history is kept, pruning right away what falls out of the new windows.
+    /// Data that is pruned already is not brought back by larger windows.
+    pub fn set_prune_windows(&self, prune_windows: PruneWindows) -> Result<()> {
+        self.ensure_writable()?;
+        prune_windows.validate()?;
+        let pruner = self
+            .pruner
+            .as_ref()
+            .expect("A writable LibraDB has a pruner.");
+        pruner.set_prune_windows(prune_windows);
+        if let Some((latest_version, _)) = self.ledger_store.get_latest_transaction_info_option()? {
+            pruner.wake(latest_version);
+        }
+        Ok(())
+    }
+
     // ================================== Private APIs ==================================
+    /// Fails if the DB is opened read-only.
+    fn ensure_writable(&self) -> Result<()> {
+        ensure!(!self.read_only, "LibraDB is opened read-only.");
+        Ok(())
+    }
+
     /// Convert a `ChangeSet` to `SealedChangeSet`.
     ///
     /// Specifically, counter increases are added to current counter values and converted to DB
@@ -688,19 +1028,6 @@ impl LibraDB {
         Ok(())
     }
 
-    fn get_account_seq_num_by_version(
-        &self,
-        address: AccountAddress,
-        version: Version,
-    ) -> Result<u64> {
-        let (account_state_blob, _proof) = self
-            .state_store
-            .get_account_state_with_proof_by_version(address, version)?;
-
-        // If an account does not exist, we treat it as if it has sequence number 0.
-        Ok(get_account_resource_or_default(&account_state_blob)?.sequence_number())
-    }
-
     fn get_transaction_with_proof(
         &self,
         version: Version,
@@ -10,7 +10,11 @@ use crypto::{ed25519::*, hash::CryptoHash};
 use proptest::prelude::*;
 use rusty_fork::{rusty_fork_id, rusty_fork_test, rusty_fork_test_name};
 use std::{collections::HashMap, convert::TryFrom};
-use types::{contract_event::ContractEvent, ledger_info::LedgerInfo};
+use types::{
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfo,
+    transaction::{SignedTransaction, TransactionPayload},
+};
 
 fn test_save_blocks_impl(
     input: Vec<(
@@ -45,6 +49,17 @@ fn test_save_blocks_impl(
         cur_ver += txns_to_commit.len() as u64;
     }
 
+    // The ledger counters as of the latest version account for all the events, the mock genesis
+    // having none.
+    let num_events: u64 = input
+        .iter()
+        .flat_map(|(txns_to_commit, _)| txns_to_commit)
+        .map(|txn_to_commit| txn_to_commit.events().len() as u64)
+        .sum();
+    let statistics = db.get_ledger_statistics(cur_ver, cur_ver, 1 /* limit */)?;
+    assert_eq!(statistics.len(), 1);
+    assert_eq!(statistics[0].events_created, num_events);
+
     let first_batch = input.first().unwrap().0.clone();
     let first_batch_ledger_info = input.first().unwrap().1.clone();
     let latest_ledger_info = input.last().unwrap().1.clone();
@@ -292,6 +307,13 @@ fn group_events_by_query_path(
     query_path_to_events.into_iter().collect()
 }
 
+fn is_write_set(signed_txn: &SignedTransaction) -> bool {
+    match signed_txn.payload() {
+        TransactionPayload::WriteSet(_) => true,
+        _ => false,
+    }
+}
+
 fn verify_committed_transactions(
     db: &LibraDB,
     txns_to_commit: &[TransactionToCommit],
@@ -317,6 +339,29 @@ fn verify_committed_transactions(
             db.get_transactions(cur_ver, 1, ledger_version, true /* fetch_events */)?;
         txn_list_with_proof.verify(ledger_info, Some(cur_ver))?;
 
+        // Fetch and verify transaction by sender and sequence number.
+        let signed_txn = txn_to_commit.signed_txn();
+        if !is_write_set(signed_txn) {
+            let txn_with_proof = db
+                .get_txn_by_account_and_seq(
+                    signed_txn.sender(),
+                    signed_txn.sequence_number(),
+                    ledger_version,
+                    false, /* fetch_events */
+                )?
+                .expect("Transaction should be indexed.");
+            assert_eq!(txn_with_proof.version, cur_ver);
+            assert_eq!(txn_with_proof.signed_transaction, *signed_txn);
+            let txns_with_proof = db.get_txns_by_account(
+                signed_txn.sender(),
+                signed_txn.sequence_number(),
+                1, /* limit */
+                ledger_version,
+                false, /* fetch_events */
+            )?;
+            assert_eq!(txns_with_proof, vec![txn_with_proof]);
+        }
+
         // Fetch and verify account states.
         for (addr, expected_blob) in txn_to_commit.account_states() {
             let account_state_with_proof =
@@ -338,6 +383,135 @@ fn verify_committed_transactions(
     Ok(())
 }
 
+fn test_get_account_state_at_version_impl(
+    input: Vec<(
+        Vec<TransactionToCommit>,
+        LedgerInfoWithSignatures<Ed25519Signature>,
+    )>,
+) -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let db = db_with_mock_genesis(&tmp_dir)?;
+
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in &input {
+        db.save_transactions(
+            &txns_to_commit,
+            cur_ver + 1, /* first_version */
+            &Some(ledger_info_with_sigs.clone()),
+        )?;
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    let latest_ledger_info = input.last().unwrap().1.ledger_info();
+
+    // Every account state written is served as of the version it was written at, with proof
+    // relative to the latest ledger info.
+    let mut version = 0;
+    for txn_to_commit in input.iter().flat_map(|(txns_to_commit, _)| txns_to_commit) {
+        version += 1;
+        for (address, blob) in txn_to_commit.account_states() {
+            let (mut response_items, _, _) = db.update_to_latest_ledger(
+                0, /* client_known_version */
+                vec![RequestItem::GetAccountStateAtVersion {
+                    address: *address,
+                    version,
+                }],
+            )?;
+            let account_state_with_proof = response_items
+                .remove(0)
+                .into_get_account_state_at_version_response()?;
+            account_state_with_proof.verify(latest_ledger_info, version, *address)?;
+            assert_eq!(account_state_with_proof.blob, Some(blob.clone()));
+        }
+    }
+
+    // Versions after the latest ledger info can't be proven.
+    assert!(db
+        .update_to_latest_ledger(
+            0, /* client_known_version */
+            vec![RequestItem::GetAccountStateAtVersion {
+                address: AccountAddress::random(),
+                version: latest_ledger_info.version() + 1,
+            }],
+        )
+        .is_err());
+
+    Ok(())
+}
+
+fn test_get_events_by_version_range_impl(
+    input: Vec<(
+        Vec<TransactionToCommit>,
+        LedgerInfoWithSignatures<Ed25519Signature>,
+    )>,
+) -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let db = db_with_mock_genesis(&tmp_dir)?;
+
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in &input {
+        db.save_transactions(
+            &txns_to_commit,
+            cur_ver + 1, /* first_version */
+            &Some(ledger_info_with_sigs.clone()),
+        )?;
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    let latest_ledger_info = input.last().unwrap().1.ledger_info();
+
+    // All the events, the mock genesis having none, in the order they were emitted.
+    let events = input
+        .iter()
+        .flat_map(|(txns_to_commit, _)| txns_to_commit)
+        .map(TransactionToCommit::events)
+        .collect::<Vec<_>>();
+    let all_events = events.concat();
+
+    let get_events = |access_path_prefix: Option<AccessPath>| -> Result<Vec<ContractEvent>> {
+        let (mut response_items, _, _) = db.update_to_latest_ledger(
+            0, /* client_known_version */
+            vec![RequestItem::GetEventsByVersionRange {
+                start_version: 1,
+                limit: cur_ver,
+                access_path_prefix,
+            }],
+        )?;
+        response_items
+            .remove(0)
+            .into_get_events_by_version_range_response()?
+            .into_iter()
+            .map(|e| {
+                e.verify(
+                    latest_ledger_info,
+                    e.event.access_path(),
+                    e.event.sequence_number(),
+                    e.transaction_version,
+                    e.event_index,
+                )?;
+                assert_eq!(
+                    &e.event,
+                    &events[e.transaction_version as usize - 1][e.event_index as usize]
+                );
+                Ok(e.event)
+            })
+            .collect()
+    };
+
+    assert_eq!(get_events(None)?, all_events);
+    if let Some(event) = all_events.first() {
+        let access_path_prefix = AccessPath::new(event.access_path().address, vec![]);
+        assert_eq!(
+            get_events(Some(access_path_prefix.clone()))?,
+            all_events
+                .iter()
+                .filter(|e| e.access_path().starts_with(&access_path_prefix))
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    Ok(())
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
 
@@ -350,6 +524,16 @@ proptest! {
     fn test_sync_transactions(input in arb_blocks_to_commit()) {
         test_sync_transactions_impl(input).unwrap();
     }
+
+    #[test]
+    fn test_get_account_state_at_version(input in arb_blocks_to_commit()) {
+        test_get_account_state_at_version_impl(input).unwrap();
+    }
+
+    #[test]
+    fn test_get_events_by_version_range(input in arb_blocks_to_commit()) {
+        test_get_events_by_version_range_impl(input).unwrap();
+    }
 }
 
 #[test]
@@ -456,4 +640,35 @@ fn test_too_many_requested() {
             0
         )
         .is_err());
+    assert!(db
+        .get_events_by_version_range(0, 1001 /* limit */, None, 0)
+        .is_err());
+    assert!(db.get_ledger_statistics(0, 10, 1001 /* limit */).is_err());
+    assert!(db.get_ledger_statistics(10, 0, 1 /* limit */).is_err());
+}
+
+#[test]
+fn test_open_as_secondary() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let secondary_dir = tempfile::tempdir().unwrap();
+    assert!(LibraDB::open_as_secondary(&tmp_dir, &secondary_dir).is_err());
+
+    let db = db_with_mock_genesis(&tmp_dir).unwrap();
+    let secondary_db = LibraDB::open_as_secondary(&tmp_dir, &secondary_dir).unwrap();
+    assert_eq!(secondary_db.get_latest_version().unwrap(), 0);
+    assert_eq!(
+        secondary_db.get_executor_startup_info().unwrap(),
+        db.get_executor_startup_info().unwrap()
+    );
+    assert_eq!(secondary_db.get_prune_windows(), PruneWindows::default());
+    secondary_db.try_catch_up_with_primary().unwrap();
+    assert!(db.try_catch_up_with_primary().is_err());
+
+    // Writes fail.
+    assert!(secondary_db
+        .save_transactions(&[GENESIS_INFO.2.clone()], 0, &Some(GENESIS_INFO.1.clone()))
+        .is_err());
+    assert!(secondary_db
+        .set_prune_windows(PruneWindows::default())
+        .is_err());
 }
@@ -3,10 +3,22 @@
 
 //! This module provides `Pruner` which manages a thread pruning old data in the background and is
 //! meant to be triggered by other threads as they commit new data to the DB.
+//!
+//! Two kinds of data are pruned, each with its own window:
+//!   * the state, i.e. the Jellyfish Merkle nodes which are stale since a version out of the
+//! state window;
+//!   * the ledger, i.e. the transactions, transaction infos and events, with their indices, of
+//! the versions out of the ledger window. The transaction accumulator and the ledger infos are
+//! kept, since proofs for the versions which are still readable rely on them.
 
 use crate::{
     schema::{
-        jellyfish_merkle_node::JellyfishMerkleNodeSchema, stale_node_index::StaleNodeIndexSchema,
+        event::EventSchema, event_accumulator::EventAccumulatorSchema,
+        event_by_access_path::EventByAccessPathSchema, event_by_address::EventByAddressSchema,
+        jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+        signed_transaction::SignedTransactionSchema, stale_node_index::StaleNodeIndexSchema,
+        transaction_by_account::TransactionByAccountSchema,
+        transaction_info::TransactionInfoSchema,
     },
     OP_COUNTER,
 };
@@ -20,7 +32,11 @@ use std::{
     },
     thread::JoinHandle,
 };
-use types::transaction::Version;
+use storage_proto::PruneWindows;
+use types::{
+    proof::position::Position,
+    transaction::{TransactionPayload, Version},
+};
 
 use failure::_core::sync::atomic::Ordering;
 use std::sync::atomic::AtomicU64;
@@ -35,74 +51,132 @@ use std::time::{Duration, Instant};
 /// It creates a worker thread on construction and joins it on destruction. When destructed, it
 /// quits the worker thread eagerly without waiting for all pending work to be done.
 pub(crate) struct Pruner {
-    /// Other than the latest version, how many historical versions to keep being readable. For
-    /// example, this being 0 means keep only the latest version.
-    num_historical_versions_to_keep: u64,
+    /// Other than the latest version, how many historical versions of the state and of the ledger
+    /// to keep being readable. For example, a window being `Some(0)` means keep only the latest
+    /// version, and `None` means never prune. Shrinking a window takes effect on the next wake up,
+    /// while growing it doesn't bring back what's pruned already.
+    prune_windows: Mutex<PruneWindows>,
     /// The worker thread handle, created upon Pruner instance construction and joined upon its
     /// destruction. It only becomes `None` after joined in `drop()`.
     worker_thread: Option<JoinHandle<()>>,
     /// The sender side of the channel talking to the worker thread.
     command_sender: Mutex<Sender<Command>>,
-    /// (For tests) A way for the worker thread to inform the `Pruner` the pruning progress. If it
-    /// sets this atomic value to `V`, all versions before `V` can no longer be accessed.
+    /// (For tests) A way for the worker thread to inform the `Pruner` the state pruning progress.
+    /// If it sets this atomic value to `V`, all versions before `V` can no longer be accessed.
+    #[allow(dead_code)]
+    state_progress: Arc<AtomicU64>,
+    /// (For tests) A way for the worker thread to inform the `Pruner` the ledger pruning progress.
+    /// If it sets this atomic value to `V`, all versions before `V` have been pruned.
     #[allow(dead_code)]
-    worker_progress: Arc<AtomicU64>,
+    ledger_progress: Arc<AtomicU64>,
 }
 
 impl Pruner {
     /// Creates a worker thread that waits on a channel for pruning commands.
-    pub fn new(db: Arc<DB>, num_historical_versions_to_keep: u64) -> Self {
+    pub fn new(db: Arc<DB>, prune_windows: PruneWindows) -> Self {
         let (command_sender, command_receiver) = channel();
-        let worker_progress = Arc::new(AtomicU64::new(0));
-        let worker_progress_clone = Arc::clone(&worker_progress);
+        let state_progress = Arc::new(AtomicU64::new(0));
+        let ledger_progress = Arc::new(AtomicU64::new(0));
+        let state_progress_clone = Arc::clone(&state_progress);
+        let ledger_progress_clone = Arc::clone(&ledger_progress);
 
         let worker_thread = std::thread::Builder::new()
             .name("libradb_pruner".into())
-            .spawn(move || Worker::new(db, command_receiver, worker_progress_clone).work_loop())
+            .spawn(move || {
+                Worker::new(
+                    db,
+                    command_receiver,
+                    state_progress_clone,
+                    ledger_progress_clone,
+                )
+                .work_loop()
+            })
             .expect("Creating pruner thread should succeed.");
 
         Self {
-            num_historical_versions_to_keep,
+            prune_windows: Mutex::new(prune_windows),
             worker_thread: Some(worker_thread),
             command_sender: Mutex::new(command_sender),
-            worker_progress,
+            state_progress,
+            ledger_progress,
         }
     }
 
+    /// Returns the current prune windows.
+    pub fn get_prune_windows(&self) -> PruneWindows {
+        *self
+            .prune_windows
+            .lock()
+            .expect("prune_windows should lock.")
+    }
+
+    /// Replaces the prune windows, which are expected to be validated by the caller. They take
+    /// effect on the next call to `wake()`.
+    pub fn set_prune_windows(&self, prune_windows: PruneWindows) {
+        *self
+            .prune_windows
+            .lock()
+            .expect("prune_windows should lock.") = prune_windows;
+    }
+
+    /// Returns the least readable versions of the state and of the ledger given the latest
+    /// version, `None` if nothing needs to be pruned.
+    fn least_readable_versions(
+        &self,
+        latest_version: Version,
+    ) -> (Option<Version>, Option<Version>) {
+        let prune_windows = self.get_prune_windows();
+        let least_readable_version = |window: Option<u64>| match window {
+            Some(window) if latest_version > window => Some(latest_version - window),
+            _ => None,
+        };
+        (
+            least_readable_version(prune_windows.state_window),
+            least_readable_version(prune_windows.ledger_window),
+        )
+    }
+
     /// Sends pruning command to the worker thread when necessary.
     pub fn wake(&self, latest_version: Version) {
-        if latest_version > self.num_historical_versions_to_keep {
-            let least_readable_version = latest_version - self.num_historical_versions_to_keep;
+        let (least_readable_state_version, least_readable_ledger_version) =
+            self.least_readable_versions(latest_version);
+        if least_readable_state_version.is_some() || least_readable_ledger_version.is_some() {
             self.command_sender
                 .lock()
                 .expect("command_sender to pruner thread should lock.")
                 .send(Command::Prune {
-                    least_readable_version,
+                    least_readable_state_version,
+                    least_readable_ledger_version,
                 })
                 .expect("Receiver should not destruct prematurely.");
         }
     }
 
     /// (For tests only.) Notifies the worker thread and waits for it to finish its job by polling
-    /// an internal counter.
+    /// internal counters.
     #[cfg(test)]
     pub fn wake_and_wait(&self, latest_version: Version) -> Result<()> {
         self.wake(latest_version);
 
-        if latest_version > self.num_historical_versions_to_keep {
-            let least_readable_version = latest_version - self.num_historical_versions_to_keep;
-            const TIMEOUT: Duration = Duration::from_millis(100);
-            let end = Instant::now() + TIMEOUT;
+        let (least_readable_state_version, least_readable_ledger_version) =
+            self.least_readable_versions(latest_version);
+        const TIMEOUT: Duration = Duration::from_millis(100);
+        let end = Instant::now() + TIMEOUT;
 
-            while Instant::now() < end {
-                if self.worker_progress.load(Ordering::Relaxed) >= least_readable_version {
-                    return Ok(());
-                }
-                sleep(Duration::from_millis(1));
+        while Instant::now() < end {
+            let done = |least_readable_version: Option<Version>, progress: &AtomicU64| {
+                least_readable_version.map_or(true, |least_readable_version| {
+                    progress.load(Ordering::Relaxed) >= least_readable_version
+                })
+            };
+            if done(least_readable_state_version, &self.state_progress)
+                && done(least_readable_ledger_version, &self.ledger_progress)
+            {
+                return Ok(());
             }
-            bail!("Timeout waiting for pruner worker.");
+            sleep(Duration::from_millis(1));
         }
-        Ok(())
+        bail!("Timeout waiting for pruner worker.");
     }
 }
 
@@ -123,16 +197,23 @@ impl Drop for Pruner {
 
 enum Command {
     Quit,
-    Prune { least_readable_version: Version },
+    Prune {
+        least_readable_state_version: Option<Version>,
+        least_readable_ledger_version: Option<Version>,
+    },
 }
 
 struct Worker {
     db: Arc<DB>,
     command_receiver: Receiver<Command>,
-    least_readable_version: Version,
-    /// (For tests) a way for the worker thread to inform the `Pruner` the pruning progress. If we
-    /// set this atomic value to `V`, all versions before `V` can no longer be accessed.
-    progress: Arc<AtomicU64>,
+    least_readable_state_version: Version,
+    least_readable_ledger_version: Version,
+    /// (For tests) a way for the worker thread to inform the `Pruner` the state pruning progress.
+    /// If we set this atomic value to `V`, all versions before `V` can no longer be accessed.
+    state_progress: Arc<AtomicU64>,
+    /// (For tests) a way for the worker thread to inform the `Pruner` the ledger pruning progress.
+    /// If we set this atomic value to `V`, all versions before `V` have been pruned.
+    ledger_progress: Arc<AtomicU64>,
     /// indicates if there's NOT any pending work to do currently, to hint
     /// `Self::receive_commands()` to `recv()` blocking-ly.
     blocking_recv: bool,
@@ -141,12 +222,19 @@ struct Worker {
 impl Worker {
     const BATCH_SIZE: usize = 1024;
 
-    fn new(db: Arc<DB>, command_receiver: Receiver<Command>, progress: Arc<AtomicU64>) -> Self {
+    fn new(
+        db: Arc<DB>,
+        command_receiver: Receiver<Command>,
+        state_progress: Arc<AtomicU64>,
+        ledger_progress: Arc<AtomicU64>,
+    ) -> Self {
         Self {
             db,
             command_receiver,
-            progress,
-            least_readable_version: 0,
+            state_progress,
+            ledger_progress,
+            least_readable_state_version: 0,
+            least_readable_ledger_version: 0,
             blocking_recv: true,
         }
     }
@@ -155,29 +243,54 @@ impl Worker {
         while self.receive_commands() {
             // Process a reasonably small batch of work before trying to receive commands again,
             // in case `Command::Quit` is received (that's when we should quit.)
-            match prune_state(
+            let state_done = match prune_state(
                 Arc::clone(&self.db),
-                self.progress.load(Ordering::Relaxed),
-                self.least_readable_version,
+                self.state_progress.load(Ordering::Relaxed),
+                self.least_readable_state_version,
                 Self::BATCH_SIZE,
             ) {
                 Ok((num_pruned, last_seen_version)) => {
-                    // Make next recv() blocking if all done.
-                    self.blocking_recv = num_pruned < Self::BATCH_SIZE;
-
+                    // Log the progress. Nothing was seen if nothing was pruned, e.g. when only
+                    // the ledger has pending work.
+                    if num_pruned > 0 {
+                        self.state_progress
+                            .store(last_seen_version, Ordering::Relaxed);
+                        OP_COUNTER.set(
+                            "pruner.least_readable_state_version",
+                            last_seen_version as usize,
+                        );
+                    }
+                    num_pruned < Self::BATCH_SIZE
+                }
+                Err(e) => {
+                    crit!("Error purging db records. {:?}", e);
+                    true
+                }
+            };
+            let ledger_done = match prune_ledger(
+                Arc::clone(&self.db),
+                self.ledger_progress.load(Ordering::Relaxed),
+                self.least_readable_ledger_version,
+                Self::BATCH_SIZE,
+            ) {
+                Ok((num_pruned, least_unpruned_version)) => {
                     // Log the progress.
-                    self.progress.store(last_seen_version, Ordering::Relaxed);
+                    self.ledger_progress
+                        .store(least_unpruned_version, Ordering::Relaxed);
                     OP_COUNTER.set(
-                        "pruner.least_readable_state_version",
-                        last_seen_version as usize,
+                        "pruner.least_readable_ledger_version",
+                        least_unpruned_version as usize,
                     );
+                    num_pruned < Self::BATCH_SIZE
                 }
                 Err(e) => {
-                    crit!("Error purging db records. {:?}", e);
-                    // On error, stop retrying vigorously by making next recv() blocking.
-                    self.blocking_recv = true;
+                    crit!("Error purging ledger records. {:?}", e);
+                    true
                 }
-            }
+            };
+            // Make next recv() blocking if all done. On error, stop retrying vigorously by
+            // making next recv() blocking as well.
+            self.blocking_recv = state_done && ledger_done;
         }
     }
 
@@ -207,13 +320,22 @@ impl Worker {
                 // On `Command::Quit` inform the outer loop to quit by returning `false`.
                 Command::Quit => return false,
                 Command::Prune {
-                    least_readable_version,
+                    least_readable_state_version,
+                    least_readable_ledger_version,
                 } => {
-                    if least_readable_version > self.least_readable_version {
-                        self.least_readable_version = least_readable_version;
-                        // Switch to non-blocking to allow some work to be done after the
-                        // channel has drained.
-                        self.blocking_recv = false;
+                    if let Some(version) = least_readable_state_version {
+                        if version > self.least_readable_state_version {
+                            self.least_readable_state_version = version;
+                            // Switch to non-blocking to allow some work to be done after the
+                            // channel has drained.
+                            self.blocking_recv = false;
+                        }
+                    }
+                    if let Some(version) = least_readable_ledger_version {
+                        if version > self.least_readable_ledger_version {
+                            self.least_readable_ledger_version = version;
+                            self.blocking_recv = false;
+                        }
                     }
                 }
             }
@@ -258,5 +380,80 @@ pub fn prune_state(
     Ok((num_pruned, last_seen_version))
 }
 
+/// Prunes the transactions, transaction infos and events of the versions before
+/// `least_readable_version`, as many versions as `limit`. Returns the number of versions pruned
+/// and the least version not pruned yet.
+pub fn prune_ledger(
+    db: Arc<DB>,
+    // The least version which is possibly not pruned yet, see `prune_state()`.
+    min_unpruned_version_hint: Version,
+    least_readable_version: Version,
+    limit: usize,
+) -> Result<(usize, Version)> {
+    let mut batch = SchemaBatch::new();
+    let mut num_pruned = 0;
+    let mut iter = db.iter::<SignedTransactionSchema>(ReadOptions::default())?;
+    iter.seek(&min_unpruned_version_hint)?;
+
+    // Collect versions to prune, as many as `limit`.
+    let mut iter = iter.take(limit);
+    let mut least_unpruned_version = min_unpruned_version_hint;
+    while let Some((version, signed_txn)) = iter.next().transpose()? {
+        if version >= least_readable_version {
+            break;
+        }
+        match signed_txn.payload() {
+            TransactionPayload::WriteSet(_) => (),
+            _ => batch.delete::<TransactionByAccountSchema>(&(
+                signed_txn.sender(),
+                signed_txn.sequence_number(),
+            ))?,
+        }
+        batch.delete::<SignedTransactionSchema>(&version)?;
+        batch.delete::<TransactionInfoSchema>(&version)?;
+        prune_events(&db, version, &mut batch)?;
+        least_unpruned_version = version + 1;
+        num_pruned += 1;
+    }
+    if num_pruned < limit {
+        // Ran out of versions to prune.
+        least_unpruned_version = std::cmp::max(least_unpruned_version, least_readable_version);
+    }
+
+    // Persist.
+    if num_pruned > 0 {
+        db.write_schemas(batch)?;
+    }
+
+    Ok((num_pruned, least_unpruned_version))
+}
+
+/// Deletes the events of the transaction at `version`, with their index and accumulator.
+fn prune_events(db: &DB, version: Version, batch: &mut SchemaBatch) -> Result<()> {
+    let mut iter = db.iter::<EventSchema>(ReadOptions::default())?;
+    iter.seek(&version)?;
+    while let Some((key, event)) = iter.next().transpose()? {
+        if key.0 != version {
+            break;
+        }
+        batch.delete::<EventByAccessPathSchema>(&(
+            event.access_path().clone(),
+            event.sequence_number(),
+        ))?;
+        batch.delete::<EventByAddressSchema>(&(event.access_path().address, key.0, key.1))?;
+        batch.delete::<EventSchema>(&key)?;
+    }
+
+    let mut iter = db.iter::<EventAccumulatorSchema>(ReadOptions::default())?;
+    iter.seek(&(version, Position::from_inorder_index(0)))?;
+    while let Some((key, _)) = iter.next().transpose()? {
+        if key.0 != version {
+            break;
+        }
+        batch.delete::<EventAccumulatorSchema>(&key)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test;
@@ -2,13 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
-use crate::{change_set::ChangeSet, state_store::StateStore, LibraDB};
-use crypto::HashValue;
+use crate::{
+    change_set::ChangeSet, mock_genesis::GENESIS_INFO, state_store::StateStore,
+    test_helper::arb_blocks_to_commit, LibraDB,
+};
+use crypto::{ed25519::*, HashValue};
+use proptest::prelude::*;
 use std::collections::HashMap;
 use tempfile::tempdir;
 use types::{
     account_address::{AccountAddress, ADDRESS_LENGTH},
     account_state_blob::AccountStateBlob,
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::TransactionToCommit,
 };
 
 fn put_account_state_set(
@@ -54,7 +60,10 @@ fn test_pruner() {
     let state_store = &StateStore::new(Arc::clone(&db));
     let pruner = Pruner::new(
         Arc::clone(&db),
-        0, /* num_historical_versions_to_keep */
+        PruneWindows {
+            state_window: Some(0),
+            ledger_window: None,
+        },
     );
 
     let _root0 = put_account_state_set(
@@ -141,16 +150,19 @@ fn test_worker_quit_eagerly() {
         let worker = Worker::new(
             Arc::clone(&db),
             command_receiver,
-            Arc::new(AtomicU64::new(0)), /* progress */
+            Arc::new(AtomicU64::new(0)), /* state_progress */
+            Arc::new(AtomicU64::new(0)), /* ledger_progress */
         );
         command_sender
             .send(Command::Prune {
-                least_readable_version: 1,
+                least_readable_state_version: Some(1),
+                least_readable_ledger_version: None,
             })
             .unwrap();
         command_sender
             .send(Command::Prune {
-                least_readable_version: 2,
+                least_readable_state_version: Some(2),
+                least_readable_ledger_version: None,
             })
             .unwrap();
         command_sender.send(Command::Quit).unwrap();
@@ -161,3 +173,93 @@ fn test_worker_quit_eagerly() {
         verify_state_in_store(state_store, address, Some(&value2), 2);
     }
 }
+
+fn test_ledger_pruner_impl(
+    blocks: Vec<(
+        Vec<TransactionToCommit>,
+        LedgerInfoWithSignatures<Ed25519Signature>,
+    )>,
+) {
+    let tmp_dir = tempdir().unwrap();
+    let db = LibraDB::new_with_prune_windows(
+        &tmp_dir,
+        PruneWindows {
+            state_window: Some(1),
+            ledger_window: Some(1),
+        },
+    );
+    db.save_transactions(
+        &[GENESIS_INFO.2.clone()],
+        0, /* first_version */
+        &Some(GENESIS_INFO.1.clone()),
+    )
+    .unwrap();
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in &blocks {
+        db.save_transactions(
+            &txns_to_commit,
+            cur_ver + 1, /* first_version */
+            &Some(ledger_info_with_sigs.clone()),
+        )
+        .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    db.pruner.as_ref().unwrap().wake_and_wait(cur_ver).unwrap();
+
+    // Only the latest two versions are left.
+    let least_readable_version = cur_ver.saturating_sub(1);
+    for version in 0..=cur_ver {
+        let pruned = version < least_readable_version;
+        assert_eq!(
+            db.transaction_store.get_transaction(version).is_err(),
+            pruned
+        );
+        assert_eq!(
+            db.ledger_store.get_transaction_info(version).is_err(),
+            pruned
+        );
+        if pruned {
+            assert!(db
+                .event_store
+                .get_events_by_version(version)
+                .unwrap()
+                .is_empty());
+        }
+    }
+    let mut iter = db
+        .db
+        .iter::<TransactionByAccountSchema>(ReadOptions::default())
+        .unwrap();
+    iter.seek_to_first();
+    for item in iter {
+        let (_, version) = item.unwrap();
+        assert!(version >= least_readable_version);
+    }
+    let mut iter = db
+        .db
+        .iter::<EventAccumulatorSchema>(ReadOptions::default())
+        .unwrap();
+    iter.seek_to_first();
+    for item in iter {
+        let ((version, _), _) = item.unwrap();
+        assert!(version >= least_readable_version);
+    }
+
+    // Proofs of the latest version are still served.
+    db.get_transactions(
+        least_readable_version,
+        1, /* limit */
+        cur_ver,
+        true, /* fetch_events */
+    )
+    .unwrap();
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(5))]
+
+    #[test]
+    fn test_ledger_pruner(blocks in arb_blocks_to_commit()) {
+        test_ledger_pruner_impl(blocks);
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an event index via which the events emitted to
+//! the access paths under an address (represented by <txn_version, event_idx> tuples so that they
+//! can be fetched from `EventSchema`) can be found in order of the version of the transactions
+//! that emitted them.
+//!
+//! ```text
+//! |<-----------key---------->|
+//! | address | txn_ver | idx |
+//! ```
+//!
+//! `txn_ver` and `idx` are serialized in big endian so that the events emitted to an address are
+//! in the order they were emitted.
+
+use crate::schema::{ensure_slice_len_eq, EVENT_BY_ADDRESS_CF_NAME};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use failure::prelude::*;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::{convert::TryFrom, mem::size_of};
+use types::{
+    account_address::{AccountAddress, ADDRESS_LENGTH},
+    transaction::Version,
+};
+
+define_schema!(EventByAddressSchema, Key, (), EVENT_BY_ADDRESS_CF_NAME);
+
+type Index = u64;
+type Key = (AccountAddress, Version, Index);
+
+impl KeyCodec<EventByAddressSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref address, version, index) = *self;
+
+        let mut encoded = address.to_vec();
+        encoded.write_u64::<BigEndian>(version)?;
+        encoded.write_u64::<BigEndian>(index)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(
+            data,
+            ADDRESS_LENGTH + size_of::<Version>() + size_of::<Index>(),
+        )?;
+
+        let version_offset = ADDRESS_LENGTH;
+        let index_offset = version_offset + size_of::<Version>();
+        let address = AccountAddress::try_from(&data[..version_offset])?;
+        let version = (&data[version_offset..index_offset]).read_u64::<BigEndian>()?;
+        let index = (&data[index_offset..]).read_u64::<BigEndian>()?;
+
+        Ok((address, version, index))
+    }
+}
+
+impl ValueCodec<EventByAddressSchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 0)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -97,6 +97,44 @@ impl StateStore {
 
         Ok(new_root_hash_vec)
     }
+
+    /// Put the whole state Merkle tree at `version`, holding all the `account_states` keyed by the
+    /// hashes of the account addresses, to `batch` and return its root hash. The tree does not
+    /// depend on the nodes of the previous versions, which need not exist.
+    pub fn put_account_state_snapshot(
+        &self,
+        version: Version,
+        account_states: Vec<(HashValue, AccountStateBlob)>,
+        cs: &mut ChangeSet,
+    ) -> Result<HashValue> {
+        ensure!(!account_states.is_empty(), "State snapshot is empty.");
+        let (root_hash, tree_update_batch) = JellyfishMerkleTree::new(&EmptyTreeReader { version })
+            .put_blob_set(account_states, version)?;
+        tree_update_batch
+            .node_batch
+            .iter()
+            .map(|(node_key, node)| cs.batch.put::<JellyfishMerkleNodeSchema>(node_key, node))
+            .collect::<Result<Vec<()>>>()?;
+
+        Ok(root_hash)
+    }
+}
+
+/// Reads an empty tree at the version before `version`, so that a tree can be built from scratch
+/// at `version`.
+struct EmptyTreeReader {
+    version: Version,
+}
+
+impl TreeReader for EmptyTreeReader {
+    fn get_node(&self, node_key: &NodeKey) -> Result<Node> {
+        ensure!(
+            self.version > 0 && *node_key == NodeKey::new_empty_path(self.version - 1),
+            "Unexpected read of node_key {:?} while building a state snapshot",
+            node_key
+        );
+        Ok(Node::new_null())
+    }
 }
 
 impl TreeReader for StateStore {
@@ -223,3 +223,59 @@ fn test_retired_records() {
         verify_state_in_store(store, address3, Some(&value3_update), 2, root2);
     }
 }
+
+#[test]
+fn test_put_account_state_snapshot() {
+    let address1 = AccountAddress::new([1u8; ADDRESS_LENGTH]);
+    let address2 = AccountAddress::new([2u8; ADDRESS_LENGTH]);
+    let address3 = AccountAddress::new([3u8; ADDRESS_LENGTH]);
+    let value1 = AccountStateBlob::from(vec![0x01]);
+    let value2 = AccountStateBlob::from(vec![0x02]);
+    let value2_update = AccountStateBlob::from(vec![0x12]);
+    let value3 = AccountStateBlob::from(vec![0x03]);
+
+    let tmp_dir = tempdir().unwrap();
+    let db = LibraDB::new(&tmp_dir);
+    let store = &db.state_store;
+    put_account_state_set(
+        store,
+        vec![(address1, value1.clone()), (address2, value2.clone())],
+        0, /* version */
+        3, /* expected_nodes_created */
+        0, /* expected_nodes_retired */
+        0, /* expected_blobs_retired */
+    );
+    let root = put_account_state_set(
+        store,
+        vec![
+            (address2, value2_update.clone()),
+            (address3, value3.clone()),
+        ],
+        1, /* version */
+        3, /* expected_nodes_created */
+        2, /* expected_nodes_retired */
+        1, /* expected_blobs_retired */
+    );
+
+    // Rebuild the state at version 1 in another store, from the leaves only.
+    let tmp_dir = tempdir().unwrap();
+    let db = LibraDB::new(&tmp_dir);
+    let restored_store = &db.state_store;
+    let mut cs = ChangeSet::new();
+    let account_states = vec![
+        (address1.hash(), value1.clone()),
+        (address2.hash(), value2_update.clone()),
+        (address3.hash(), value3.clone()),
+    ];
+    let restored_root = restored_store
+        .put_account_state_snapshot(1, account_states, &mut cs)
+        .unwrap();
+    restored_store.db.write_schemas(cs.batch).unwrap();
+    assert_eq!(restored_root, root);
+    verify_state_in_store(restored_store, address1, Some(&value1), 1, root);
+    verify_state_in_store(restored_store, address2, Some(&value2_update), 1, root);
+    verify_state_in_store(restored_store, address3, Some(&value3), 1, root);
+    assert!(restored_store
+        .get_account_state_with_proof_by_version(address1, 0)
+        .is_err());
+}
@@ -7,9 +7,9 @@ use crate::{
 };
 use failure::prelude::*;
 use logger::prelude::*;
-use schemadb::{SchemaBatch, DB};
+use schemadb::{ReadOptions, SchemaBatch, DB};
 use std::sync::Arc;
-use types::transaction::Version;
+use types::{ledger_statistics::LedgerStatistics, transaction::Version};
 
 pub(crate) struct SystemStore {
     db: Arc<DB>,
@@ -54,6 +54,36 @@ impl SystemStore {
 
         Ok(counters)
     }
+
+    /// Returns up to `limit` sets of ledger counters recorded from `start_version` to
+    /// `end_version`, in ascending order of version.
+    ///
+    /// Counters are only recorded at the last version of each batch of transactions saved, so the
+    /// first set returned is the latest one recorded at or before `start_version`, which the
+    /// following batch builds on. It is the earliest one after it if there is none.
+    pub fn get_ledger_statistics(
+        &self,
+        start_version: Version,
+        end_version: Version,
+        limit: u64,
+    ) -> Result<Vec<LedgerStatistics>> {
+        let mut iter = self
+            .db
+            .iter::<LedgerCountersSchema>(ReadOptions::default())?;
+        if !iter.seek_for_prev(&start_version)? {
+            iter.seek(&start_version)?;
+        }
+
+        let mut statistics = Vec::new();
+        for item in iter {
+            let (version, counters) = item?;
+            if version > end_version || statistics.len() as u64 >= limit {
+                break;
+            }
+            statistics.push(counters.to_ledger_statistics(version));
+        }
+        Ok(statistics)
+    }
 }
 
 #[cfg(test)]
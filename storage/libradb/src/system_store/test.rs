@@ -72,3 +72,46 @@ fn test_inc_ledger_counters() {
         assert_eq!(counters.get(LedgerCounter::EventsCreated), 1);
     }
 }
+
+#[test]
+fn test_get_ledger_statistics() {
+    let tmp_dir = tempdir().unwrap();
+    let db = LibraDB::new(&tmp_dir);
+    let store = &db.system_store;
+
+    assert!(store.get_ledger_statistics(0, 100, 10).unwrap().is_empty());
+
+    // Counters recorded at versions 4, 9 and 14.
+    for (first_version, last_version) in &[(0, 4), (5, 9), (10, 14)] {
+        let mut bumps = LedgerCounterBumps::new();
+        bumps.bump(LedgerCounter::EventsCreated, 2);
+        bump_ledger_counters(store, *first_version, *last_version, bumps);
+    }
+    let versions_and_events = |statistics: Vec<LedgerStatistics>| {
+        statistics
+            .into_iter()
+            .map(|s| (s.version, s.events_created))
+            .collect::<Vec<_>>()
+    };
+
+    // Starts with the latest recorded at or before the start version.
+    assert_eq!(
+        versions_and_events(store.get_ledger_statistics(6, 14, 10).unwrap()),
+        vec![(4, 2), (9, 4), (14, 6)]
+    );
+    assert_eq!(
+        versions_and_events(store.get_ledger_statistics(9, 13, 10).unwrap()),
+        vec![(9, 4)]
+    );
+    // After the latest.
+    assert_eq!(
+        versions_and_events(store.get_ledger_statistics(20, 30, 10).unwrap()),
+        vec![(14, 6)]
+    );
+    // Limited.
+    assert_eq!(
+        versions_and_events(store.get_ledger_statistics(0, 14, 2).unwrap()),
+        vec![(4, 2), (9, 4)]
+    );
+    assert!(store.get_ledger_statistics(0, 14, 0).unwrap().is_empty());
+}
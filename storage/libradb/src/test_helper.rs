@@ -71,6 +71,7 @@ fn to_blocks_to_commit(
                 partial_ledger_info_with_sigs
                     .ledger_info()
                     .timestamp_usecs(),
+                None,
             );
             let ledger_info_with_sigs = LedgerInfoWithSignatures::new(
                 ledger_info,
@@ -3,12 +3,31 @@
 
 //! This file defines transaction store APIs that are related to committed signed transactions.
 
-use super::schema::signed_transaction::*;
+use super::schema::{signed_transaction::*, transaction_by_account::*};
 use crate::{change_set::ChangeSet, errors::LibraDbError};
 use failure::prelude::*;
-use schemadb::DB;
+use schemadb::{ReadOptions, SchemaBatch, DB};
 use std::sync::Arc;
-use types::transaction::{SignedTransaction, Version};
+use types::{
+    account_address::AccountAddress,
+    transaction::{SignedTransaction, TransactionPayload, Version},
+};
+
+/// The number of transactions read per batch written by
+/// [`TransactionStore::backfill_transactions_by_account`].
+const BACKFILL_BATCH_SIZE: u64 = 10_000;
+
+/// Returns the key a transaction is indexed with by [`TransactionByAccountSchema`], or `None` for a
+/// write set transaction, which doesn't bump the sequence number of the sender.
+fn account_key(signed_transaction: &SignedTransaction) -> Option<(AccountAddress, u64)> {
+    match signed_transaction.payload() {
+        TransactionPayload::WriteSet(_) => None,
+        _ => Some((
+            signed_transaction.sender(),
+            signed_transaction.sequence_number(),
+        )),
+    }
+}
 
 pub(crate) struct TransactionStore {
     db: Arc<DB>,
@@ -26,13 +45,133 @@ impl TransactionStore {
             .ok_or_else(|| LibraDbError::NotFound(format!("Txn {}", version)).into())
     }
 
-    /// Save signed transaction at `version`
+    /// Gets the version of the transaction sent by `address` with `seq_num`. Returns `None` if it
+    /// is not committed at `ledger_version`.
+    pub fn lookup_transaction_by_account(
+        &self,
+        address: AccountAddress,
+        seq_num: u64,
+        ledger_version: Version,
+    ) -> Result<Option<Version>> {
+        Ok(self
+            .db
+            .get::<TransactionByAccountSchema>(&(address, seq_num))?
+            .filter(|version| *version <= ledger_version))
+    }
+
+    /// Given account address and start sequence number, return the versions of up to `limit`
+    /// transactions sent by the account with consecutive sequence numbers. Result won't contain
+    /// records with a version > `ledger_version` and is in ascending order.
+    pub fn lookup_transactions_by_account(
+        &self,
+        address: AccountAddress,
+        start_seq_num: u64,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<
+        Vec<(
+            u64,     // sequence number
+            Version, // transaction version
+        )>,
+    > {
+        let mut iter = self
+            .db
+            .iter::<TransactionByAccountSchema>(ReadOptions::default())?;
+        iter.seek(&(address, start_seq_num))?;
+
+        let mut result = Vec::new();
+        let mut cur_seq = start_seq_num;
+        for res in iter.take(limit as usize) {
+            let ((txn_address, seq), ver) = res?;
+            if txn_address != address || ver > ledger_version {
+                break;
+            }
+            ensure!(
+                seq == cur_seq,
+                "DB corrupt: Sequence number not continuous, expected: {}, actual: {}.",
+                cur_seq,
+                seq
+            );
+            result.push((seq, ver));
+            cur_seq += 1;
+        }
+
+        Ok(result)
+    }
+
+    /// Indexes by sender and sequence number the transactions saved before the index existed, and
+    /// returns how many were indexed. Nothing is done if the oldest indexable transaction already
+    /// is. Batches are written from the latest version down to the oldest one, so that an
+    /// interrupted backfill is carried on the next time.
+    pub fn backfill_transactions_by_account(&self) -> Result<u64> {
+        let mut iter = self
+            .db
+            .iter::<SignedTransactionSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        let mut oldest = None;
+        for res in iter {
+            let (version, signed_transaction) = res?;
+            if let Some(key) = account_key(&signed_transaction) {
+                oldest = Some((version, key));
+                break;
+            }
+        }
+        let (oldest_version, (address, seq_num)) = match oldest {
+            Some(oldest) => oldest,
+            None => return Ok(0),
+        };
+        if self.lookup_transaction_by_account(address, seq_num, oldest_version)?
+            == Some(oldest_version)
+        {
+            return Ok(0);
+        }
+
+        let mut iter = self
+            .db
+            .iter::<SignedTransactionSchema>(ReadOptions::default())?;
+        iter.seek_to_last();
+        let latest_version = match iter.next().transpose()? {
+            Some((version, _)) => version,
+            None => return Ok(0),
+        };
+
+        let mut num_indexed = 0;
+        let mut end_version = latest_version + 1;
+        while end_version > oldest_version {
+            let start_version = end_version
+                .saturating_sub(BACKFILL_BATCH_SIZE)
+                .max(oldest_version);
+            let mut iter = self
+                .db
+                .iter::<SignedTransactionSchema>(ReadOptions::default())?;
+            iter.seek(&start_version)?;
+            let mut batch = SchemaBatch::new();
+            for res in iter.take((end_version - start_version) as usize) {
+                let (version, signed_transaction) = res?;
+                if let Some(key) = account_key(&signed_transaction) {
+                    batch.put::<TransactionByAccountSchema>(&key, &version)?;
+                    num_indexed += 1;
+                }
+            }
+            self.db.write_schemas(batch)?;
+            end_version = start_version;
+        }
+
+        Ok(num_indexed)
+    }
+
+    /// Save signed transaction at `version`, and index it by sender and sequence number. Write set
+    /// transactions, e.g. the genesis transaction, don't bump the sequence number of the sender
+    /// and are not indexed.
     pub fn put_transaction(
         &self,
         version: Version,
         signed_transaction: &SignedTransaction,
         cs: &mut ChangeSet,
     ) -> Result<()> {
+        if let Some(key) = account_key(signed_transaction) {
+            cs.batch.put::<TransactionByAccountSchema>(&key, &version)?;
+        }
         cs.batch
             .put::<SignedTransactionSchema>(&version, signed_transaction)
     }
@@ -3,8 +3,10 @@
 
 use super::*;
 use crate::LibraDB;
+use crypto::ed25519::compat;
 use proptest::{collection::vec, prelude::*};
 use tempfile::tempdir;
+use types::test_helpers::transaction_test_helpers::{get_test_unchecked_txn, get_write_set_txn};
 
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
@@ -30,3 +32,135 @@ proptest! {
         prop_assert!(store.get_transaction(txns.len() as u64).is_err());
     }
 }
+
+#[test]
+fn test_lookup_transactions_by_account() {
+    let tmp_dir = tempdir().unwrap();
+    let db = LibraDB::new(&tmp_dir);
+    let store = &db.transaction_store;
+    let (privkey, pubkey) = compat::generate_keypair(None);
+    let sender = AccountAddress::from_public_key(&pubkey);
+    let (other_privkey, other_pubkey) = compat::generate_keypair(None);
+    let other_sender = AccountAddress::from_public_key(&other_pubkey);
+
+    // Version 0 is a write set transaction, which is not indexed. Then both accounts send
+    // transactions in turn.
+    let mut txns =
+        vec![get_write_set_txn(sender, 0, privkey.clone(), pubkey.clone(), None).into_inner()];
+    for seq_num in 0..5 {
+        txns.push(get_test_unchecked_txn(
+            sender,
+            seq_num,
+            privkey.clone(),
+            pubkey.clone(),
+            None,
+        ));
+        txns.push(get_test_unchecked_txn(
+            other_sender,
+            seq_num,
+            other_privkey.clone(),
+            other_pubkey.clone(),
+            None,
+        ));
+    }
+    let mut cs = ChangeSet::new();
+    for (version, txn) in txns.iter().enumerate() {
+        store
+            .put_transaction(version as Version, &txn, &mut cs)
+            .unwrap();
+    }
+    store.db.write_schemas(cs.batch).unwrap();
+
+    assert_eq!(
+        store.lookup_transaction_by_account(sender, 0, 10).unwrap(),
+        Some(1)
+    );
+    assert_eq!(
+        store
+            .lookup_transaction_by_account(other_sender, 4, 10)
+            .unwrap(),
+        Some(10)
+    );
+    assert_eq!(
+        store
+            .lookup_transaction_by_account(other_sender, 4, 9)
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        store.lookup_transaction_by_account(sender, 5, 10).unwrap(),
+        None
+    );
+
+    assert_eq!(
+        store
+            .lookup_transactions_by_account(sender, 1, 10, 10)
+            .unwrap(),
+        vec![(1, 3), (2, 5), (3, 7), (4, 9)]
+    );
+    assert_eq!(
+        store
+            .lookup_transactions_by_account(sender, 1, 2, 10)
+            .unwrap(),
+        vec![(1, 3), (2, 5)]
+    );
+    assert_eq!(
+        store
+            .lookup_transactions_by_account(other_sender, 2, 10, 6)
+            .unwrap(),
+        vec![(2, 6)]
+    );
+    assert!(store
+        .lookup_transactions_by_account(other_sender, 5, 10, 10)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_backfill_transactions_by_account() {
+    let tmp_dir = tempdir().unwrap();
+    let db = LibraDB::new(&tmp_dir);
+    let store = &db.transaction_store;
+    let (privkey, pubkey) = compat::generate_keypair(None);
+    let sender = AccountAddress::from_public_key(&pubkey);
+
+    // Nothing to index in an empty DB.
+    assert_eq!(store.backfill_transactions_by_account().unwrap(), 0);
+
+    // Save the transactions the way a DB predating the index did.
+    let mut txns =
+        vec![get_write_set_txn(sender, 0, privkey.clone(), pubkey.clone(), None).into_inner()];
+    for seq_num in 0..5 {
+        txns.push(get_test_unchecked_txn(
+            sender,
+            seq_num,
+            privkey.clone(),
+            pubkey.clone(),
+            None,
+        ));
+    }
+    let mut batch = SchemaBatch::new();
+    for (version, txn) in txns.iter().enumerate() {
+        batch
+            .put::<SignedTransactionSchema>(&(version as Version), txn)
+            .unwrap();
+    }
+    // An interrupted backfill left the latest transaction indexed only.
+    batch
+        .put::<TransactionByAccountSchema>(&(sender, 4), &5)
+        .unwrap();
+    store.db.write_schemas(batch).unwrap();
+    assert_eq!(
+        store.lookup_transaction_by_account(sender, 0, 5).unwrap(),
+        None
+    );
+
+    assert_eq!(store.backfill_transactions_by_account().unwrap(), 5);
+    assert_eq!(
+        store
+            .lookup_transactions_by_account(sender, 0, 10, 5)
+            .unwrap(),
+        vec![(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)]
+    );
+    assert_eq!(store.backfill_transactions_by_account().unwrap(), 0);
+}
@@ -158,6 +158,12 @@ fn db_exists(path: &Path) -> bool {
     rocksdb_current_file.is_file()
 }
 
+/// RocksDB takes paths as strings.
+fn path_to_str(path: &Path) -> Result<&str> {
+    path.to_str()
+        .ok_or_else(|| format_err!("Path {:?} can not be converted to string.", path))
+}
+
 /// All the RocksDB methods return `std::result::Result<T, String>`. Since our methods return
 /// `failure::Result<T>`, manual conversion is needed.
 fn convert_rocksdb_err(msg: String) -> failure::Error {
@@ -203,19 +209,77 @@ impl DB {
         Ok(db)
     }
 
+    /// Opens the existing db at `path` with all the column families provided, for reads only.
+    ///
+    /// Unlike [`DB::open`], this doesn't lock the db, so it can be opened while another process
+    /// writes to it. The db is read as of the time it is opened: later writes are only seen after
+    /// opening it again. Any write fails.
+    pub fn open_readonly<P: AsRef<Path>>(
+        path: P,
+        cf_opts_map: ColumnFamilyOptionsMap,
+    ) -> Result<Self> {
+        ensure!(
+            db_exists(path.as_ref()),
+            "No DB found at {:?}.",
+            path.as_ref()
+        );
+        let inner = rocksdb::DB::open_cf_for_read_only(
+            DBOptions::new(),
+            path_to_str(path.as_ref())?,
+            cf_opts_map.into_iter().collect(),
+            false, /* error_if_log_file_exist */
+        )
+        .map_err(convert_rocksdb_err)?;
+
+        Ok(DB { inner })
+    }
+
+    /// Opens the existing db at `path` with all the column families provided as a secondary
+    /// instance, for reads only. The secondary instance keeps its own info logs under
+    /// `secondary_path`.
+    ///
+    /// Unlike [`DB::open`], this doesn't lock the db, so it can be opened while another process
+    /// writes to it. The db is read as of the time it is opened, until
+    /// [`DB::try_catch_up_with_primary`] makes it see the later writes. Any write fails.
+    pub fn open_as_secondary<P: AsRef<Path>, Q: AsRef<Path>>(
+        path: P,
+        secondary_path: Q,
+        cf_opts_map: ColumnFamilyOptionsMap,
+    ) -> Result<Self> {
+        ensure!(
+            db_exists(path.as_ref()),
+            "No DB found at {:?}.",
+            path.as_ref()
+        );
+        let mut db_opts = DBOptions::new();
+        // A secondary instance has to keep all the files of the primary open.
+        db_opts.set_max_open_files(-1);
+        let inner = rocksdb::DB::open_cf_as_secondary(
+            db_opts,
+            path_to_str(path.as_ref())?,
+            path_to_str(secondary_path.as_ref())?,
+            cf_opts_map.into_iter().collect(),
+        )
+        .map_err(convert_rocksdb_err)?;
+
+        Ok(DB { inner })
+    }
+
+    /// Makes a db opened with [`DB::open_as_secondary`] see what the primary instance wrote since
+    /// it was opened or last caught up.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.inner
+            .try_catch_up_with_primary()
+            .map_err(convert_rocksdb_err)
+    }
+
     fn open_cf<'a, P, T>(opts: DBOptions, path: P, cfds: Vec<T>) -> Result<DB>
     where
         P: AsRef<Path>,
         T: Into<ColumnFamilyDescriptor<'a>>,
     {
-        let inner = rocksdb::DB::open_cf(
-            opts,
-            path.as_ref().to_str().ok_or_else(|| {
-                format_err!("Path {:?} can not be converted to string.", path.as_ref())
-            })?,
-            cfds,
-        )
-        .map_err(convert_rocksdb_err)?;
+        let inner = rocksdb::DB::open_cf(opts, path_to_str(path.as_ref())?, cfds)
+            .map_err(convert_rocksdb_err)?;
 
         Ok(DB { inner })
     }
@@ -70,8 +70,8 @@ impl ValueCodec<TestSchema2> for TestField {
     }
 }
 
-fn open_db(dir: &tempfile::TempDir) -> DB {
-    let cf_opts_map: ColumnFamilyOptionsMap = [
+fn column_families() -> ColumnFamilyOptionsMap {
+    [
         (DEFAULT_CF_NAME, ColumnFamilyOptions::default()),
         (
             TestSchema1::COLUMN_FAMILY_NAME,
@@ -84,8 +84,11 @@ fn open_db(dir: &tempfile::TempDir) -> DB {
     ]
     .iter()
     .cloned()
-    .collect();
-    DB::open(&dir, cf_opts_map).expect("Failed to open DB.")
+    .collect()
+}
+
+fn open_db(dir: &tempfile::TempDir) -> DB {
+    DB::open(&dir, column_families()).expect("Failed to open DB.")
 }
 
 struct TestDB {
@@ -293,3 +296,67 @@ fn test_report_size() {
     assert!(*cf_sizes.get("TestCF2").unwrap() > 0);
     assert_eq!(*cf_sizes.get("default").unwrap(), 0);
 }
+
+#[test]
+fn test_open_readonly() {
+    let tmpdir = tempfile::tempdir().expect("Failed to create temporary directory.");
+    assert!(DB::open_readonly(&tmpdir, column_families()).is_err());
+
+    let db = open_db(&tmpdir);
+    db.put::<TestSchema1>(&TestField(0), &TestField(0)).unwrap();
+
+    // The db is locked by `db` but can still be read.
+    let open_readonly_db =
+        || DB::open_readonly(&tmpdir, column_families()).expect("Failed to open DB read-only.");
+    let readonly_db = open_readonly_db();
+    assert_eq!(
+        readonly_db.get::<TestSchema1>(&TestField(0)).unwrap(),
+        Some(TestField(0)),
+    );
+    assert!(readonly_db
+        .put::<TestSchema1>(&TestField(1), &TestField(1))
+        .is_err());
+
+    // Later writes are seen once opened again.
+    db.put::<TestSchema1>(&TestField(2), &TestField(2)).unwrap();
+    assert_eq!(readonly_db.get::<TestSchema1>(&TestField(2)).unwrap(), None);
+    assert_eq!(
+        open_readonly_db()
+            .get::<TestSchema1>(&TestField(2))
+            .unwrap(),
+        Some(TestField(2)),
+    );
+}
+
+#[test]
+fn test_open_as_secondary() {
+    let tmpdir = tempfile::tempdir().expect("Failed to create temporary directory.");
+    let secondary_dir = tempfile::tempdir().expect("Failed to create temporary directory.");
+    assert!(DB::open_as_secondary(&tmpdir, &secondary_dir, column_families()).is_err());
+
+    let db = open_db(&tmpdir);
+    db.put::<TestSchema1>(&TestField(0), &TestField(0)).unwrap();
+
+    // The db is locked by `db` but can still be read.
+    let secondary_db = DB::open_as_secondary(&tmpdir, &secondary_dir, column_families())
+        .expect("Failed to open DB as secondary.");
+    assert_eq!(
+        secondary_db.get::<TestSchema1>(&TestField(0)).unwrap(),
+        Some(TestField(0)),
+    );
+    assert!(secondary_db
+        .put::<TestSchema1>(&TestField(1), &TestField(1))
+        .is_err());
+
+    // Later writes are seen once caught up.
+    db.put::<TestSchema1>(&TestField(2), &TestField(2)).unwrap();
+    assert_eq!(
+        secondary_db.get::<TestSchema1>(&TestField(2)).unwrap(),
+        None
+    );
+    secondary_db.try_catch_up_with_primary().unwrap();
+    assert_eq!(
+        secondary_db.get::<TestSchema1>(&TestField(2)).unwrap(),
+        Some(TestField(2)),
+    );
+}
@@ -9,10 +9,10 @@
 
 mod state_view;
 
-use crypto::ed25519::*;
+use crypto::{ed25519::*, HashValue};
 use failure::prelude::*;
 use futures::{compat::Future01CompatExt, executor::block_on, prelude::*};
-use futures_01::future::Future as Future01;
+use futures_01::{future::Future as Future01, stream::Stream as Stream01};
 use grpcio::{ChannelBuilder, Environment};
 use metrics::counters::SVC_COUNTERS;
 use proto_conv::{FromProto, IntoProto};
@@ -20,10 +20,15 @@ use protobuf::Message;
 use rand::Rng;
 use std::{pin::Pin, sync::Arc};
 use storage_proto::{
-    proto::{storage::GetExecutorStartupInfoRequest, storage_grpc},
-    ExecutorStartupInfo, GetAccountStateWithProofByVersionRequest,
-    GetAccountStateWithProofByVersionResponse, GetExecutorStartupInfoResponse,
-    GetTransactionsRequest, GetTransactionsResponse, SaveTransactionsRequest,
+    proto::{
+        storage::{GetExecutorStartupInfoRequest, GetPruneWindowsRequest},
+        storage_grpc,
+    },
+    BackupAccountStateRequest, BackupAccountStateResponse, ExecutorStartupInfo,
+    GetAccountStateWithProofByVersionRequest, GetAccountStateWithProofByVersionResponse,
+    GetExecutorStartupInfoResponse, GetLedgerStatisticsRequest, GetLedgerStatisticsResponse,
+    GetPruneWindowsResponse, GetTransactionsRequest, GetTransactionsResponse, PruneWindows,
+    SaveTransactionsRequest, SetPruneWindowsRequest,
 };
 use types::{
     account_address::AccountAddress,
@@ -32,6 +37,7 @@ use types::{
         RequestItem, ResponseItem, UpdateToLatestLedgerRequest, UpdateToLatestLedgerResponse,
     },
     ledger_info::LedgerInfoWithSignatures,
+    ledger_statistics::LedgerStatistics,
     proof::SparseMerkleProof,
     transaction::{TransactionListWithProof, TransactionToCommit, Version},
     validator_change::ValidatorChangeEventWithProof,
@@ -96,6 +102,33 @@ impl StorageReadServiceClient {
     fn client(&self) -> &storage_grpc::StorageClient {
         pick(&self.clients)
     }
+
+    /// Streams all the account states at `version`, keyed by the hashes of the account addresses
+    /// and in their order. The returned iterator blocks until the next account state is received.
+    pub fn backup_account_state(
+        &self,
+        version: Version,
+    ) -> Result<impl Iterator<Item = Result<(HashValue, AccountStateBlob)>>> {
+        let req = BackupAccountStateRequest::new(version);
+        let stream = self
+            .client()
+            .backup_account_state(&log_and_convert(req))
+            .map_err(convert_grpc_err)?;
+        Ok(Stream01::wait(stream).map(|resp| {
+            let rust_resp =
+                BackupAccountStateResponse::from_proto(resp.map_err(convert_grpc_err)?)?;
+            Ok((rust_resp.account_key, rust_resp.account_state_blob))
+        }))
+    }
+
+    /// Returns how much history the storage keeps.
+    pub fn get_prune_windows(&self) -> Result<PruneWindows> {
+        let resp = self
+            .client()
+            .get_prune_windows(&GetPruneWindowsRequest::new())
+            .map_err(convert_grpc_err)?;
+        Ok(GetPruneWindowsResponse::from_proto(resp)?.prune_windows)
+    }
 }
 
 impl StorageRead for StorageReadServiceClient {
@@ -218,6 +251,33 @@ impl StorageRead for StorageReadServiceClient {
             })
             .boxed()
     }
+
+    fn get_ledger_statistics(
+        &self,
+        start_version: Version,
+        end_version: Version,
+        limit: u64,
+    ) -> Result<Vec<LedgerStatistics>> {
+        block_on(self.get_ledger_statistics_async(start_version, end_version, limit))
+    }
+
+    fn get_ledger_statistics_async(
+        &self,
+        start_version: Version,
+        end_version: Version,
+        limit: u64,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<LedgerStatistics>>> + Send>> {
+        let req = GetLedgerStatisticsRequest::new(start_version, end_version, limit);
+        convert_grpc_response(
+            self.client()
+                .get_ledger_statistics_async(&log_and_convert(req)),
+        )
+        .map(|resp| {
+            let resp = GetLedgerStatisticsResponse::from_proto(resp?)?;
+            Ok(resp.ledger_statistics)
+        })
+        .boxed()
+    }
 }
 
 /// This provides storage write interfaces backed by real storage service.
@@ -241,6 +301,15 @@ impl StorageWriteServiceClient {
     fn client(&self) -> &storage_grpc::StorageClient {
         pick(&self.clients)
     }
+
+    /// Changes how much history the storage keeps, until the storage service restarts.
+    pub fn set_prune_windows(&self, prune_windows: PruneWindows) -> Result<()> {
+        let req = SetPruneWindowsRequest { prune_windows };
+        self.client()
+            .set_prune_windows(&log_and_convert(req))
+            .map_err(convert_grpc_err)?;
+        Ok(())
+    }
 }
 
 impl StorageWrite for StorageWriteServiceClient {
@@ -362,6 +431,28 @@ pub trait StorageRead: Send + Sync {
     fn get_executor_startup_info_async(
         &self,
     ) -> Pin<Box<dyn Future<Output = Result<Option<ExecutorStartupInfo>>> + Send>>;
+
+    /// See [`LibraDB::get_ledger_statistics`].
+    ///
+    /// [`LibraDB::get_ledger_statistics`]:
+    /// ../libradb/struct.LibraDB.html#method.get_ledger_statistics
+    fn get_ledger_statistics(
+        &self,
+        start_version: Version,
+        end_version: Version,
+        limit: u64,
+    ) -> Result<Vec<LedgerStatistics>>;
+
+    /// See [`LibraDB::get_ledger_statistics`].
+    ///
+    /// [`LibraDB::get_ledger_statistics`]:
+    /// ../libradb/struct.LibraDB.html#method.get_ledger_statistics
+    fn get_ledger_statistics_async(
+        &self,
+        start_version: Version,
+        end_version: Version,
+        limit: u64,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<LedgerStatistics>>> + Send>>;
 }
 
 /// This trait defines interfaces to be implemented by a storage write client.
@@ -36,6 +36,7 @@ use types::{
     account_address::AccountAddress,
     account_state_blob::AccountStateBlob,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    ledger_statistics::LedgerStatistics,
     proof::SparseMerkleProof,
     transaction::{TransactionListWithProof, TransactionToCommit, Version},
 };
@@ -373,6 +374,320 @@ impl IntoProto for GetExecutorStartupInfoResponse {
     }
 }
 
+/// Helper to construct and parse [`proto::storage::BackupAccountStateRequest`]
+///
+/// It does so by implementing [`IntoProto`](#impl-IntoProto) and [`FromProto`](#impl-FromProto),
+/// providing [`into_proto`](IntoProto::into_proto) and [`from_proto`](FromProto::from_proto).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
+pub struct BackupAccountStateRequest {
+    pub version: Version,
+}
+
+impl BackupAccountStateRequest {
+    /// Constructor.
+    pub fn new(version: Version) -> Self {
+        Self { version }
+    }
+}
+
+impl FromProto for BackupAccountStateRequest {
+    type ProtoType = crate::proto::storage::BackupAccountStateRequest;
+
+    fn from_proto(object: Self::ProtoType) -> Result<Self> {
+        Ok(Self {
+            version: object.get_version(),
+        })
+    }
+}
+
+impl IntoProto for BackupAccountStateRequest {
+    type ProtoType = crate::proto::storage::BackupAccountStateRequest;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        proto.set_version(self.version);
+        proto
+    }
+}
+
+/// Helper to construct and parse [`proto::storage::BackupAccountStateResponse`]
+///
+/// It does so by implementing [`IntoProto`](#impl-IntoProto) and [`FromProto`](#impl-FromProto),
+/// providing [`into_proto`](IntoProto::into_proto) and [`from_proto`](FromProto::from_proto).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
+pub struct BackupAccountStateResponse {
+    pub account_key: HashValue,
+    pub account_state_blob: AccountStateBlob,
+}
+
+impl BackupAccountStateResponse {
+    /// Constructor.
+    pub fn new(account_key: HashValue, account_state_blob: AccountStateBlob) -> Self {
+        Self {
+            account_key,
+            account_state_blob,
+        }
+    }
+}
+
+impl FromProto for BackupAccountStateResponse {
+    type ProtoType = crate::proto::storage::BackupAccountStateResponse;
+
+    fn from_proto(mut object: Self::ProtoType) -> Result<Self> {
+        Ok(Self {
+            account_key: HashValue::from_proto(object.take_account_key())?,
+            account_state_blob: AccountStateBlob::from_proto(object.take_account_state_blob())?,
+        })
+    }
+}
+
+impl IntoProto for BackupAccountStateResponse {
+    type ProtoType = crate::proto::storage::BackupAccountStateResponse;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        proto.set_account_key(self.account_key.into_proto());
+        proto.set_account_state_blob(self.account_state_blob.into_proto());
+        proto
+    }
+}
+
+/// Helper to construct and parse [`proto::storage::GetLedgerStatisticsRequest`]
+///
+/// It does so by implementing [`IntoProto`](#impl-IntoProto) and [`FromProto`](#impl-FromProto),
+/// providing [`into_proto`](IntoProto::into_proto) and [`from_proto`](FromProto::from_proto).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
+pub struct GetLedgerStatisticsRequest {
+    pub start_version: Version,
+    pub end_version: Version,
+    pub limit: u64,
+}
+
+impl GetLedgerStatisticsRequest {
+    /// Constructor.
+    pub fn new(start_version: Version, end_version: Version, limit: u64) -> Self {
+        Self {
+            start_version,
+            end_version,
+            limit,
+        }
+    }
+}
+
+impl FromProto for GetLedgerStatisticsRequest {
+    type ProtoType = crate::proto::storage::GetLedgerStatisticsRequest;
+
+    fn from_proto(object: Self::ProtoType) -> Result<Self> {
+        Ok(Self {
+            start_version: object.get_start_version(),
+            end_version: object.get_end_version(),
+            limit: object.get_limit(),
+        })
+    }
+}
+
+impl IntoProto for GetLedgerStatisticsRequest {
+    type ProtoType = crate::proto::storage::GetLedgerStatisticsRequest;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        proto.set_start_version(self.start_version);
+        proto.set_end_version(self.end_version);
+        proto.set_limit(self.limit);
+        proto
+    }
+}
+
+/// Helper to construct and parse [`proto::storage::GetLedgerStatisticsResponse`]
+///
+/// It does so by implementing [`IntoProto`](#impl-IntoProto) and [`FromProto`](#impl-FromProto),
+/// providing [`into_proto`](IntoProto::into_proto) and [`from_proto`](FromProto::from_proto).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
+pub struct GetLedgerStatisticsResponse {
+    pub ledger_statistics: Vec<LedgerStatistics>,
+}
+
+impl GetLedgerStatisticsResponse {
+    /// Constructor.
+    pub fn new(ledger_statistics: Vec<LedgerStatistics>) -> Self {
+        Self { ledger_statistics }
+    }
+}
+
+impl FromProto for GetLedgerStatisticsResponse {
+    type ProtoType = crate::proto::storage::GetLedgerStatisticsResponse;
+
+    fn from_proto(mut object: Self::ProtoType) -> Result<Self> {
+        Ok(Self {
+            ledger_statistics: object
+                .take_ledger_statistics()
+                .into_iter()
+                .map(LedgerStatistics::from_proto)
+                .collect::<Result<Vec<_>>>()?,
+        })
+    }
+}
+
+impl IntoProto for GetLedgerStatisticsResponse {
+    type ProtoType = crate::proto::storage::GetLedgerStatisticsResponse;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        proto.set_ledger_statistics(::protobuf::RepeatedField::from_vec(
+            self.ledger_statistics
+                .into_iter()
+                .map(LedgerStatistics::into_proto)
+                .collect::<Vec<_>>(),
+        ));
+        proto
+    }
+}
+
+/// Helper to construct and parse [`proto::storage::PruneWindows`]
+///
+/// It does so by implementing [`IntoProto`](#impl-IntoProto) and [`FromProto`](#impl-FromProto),
+/// providing [`into_proto`](IntoProto::into_proto) and [`from_proto`](FromProto::from_proto).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
+pub struct PruneWindows {
+    /// Other than the latest version, how many historical versions of the state to keep readable.
+    /// The state is never pruned if `None`.
+    pub state_window: Option<u64>,
+    /// Other than the latest version, how many historical versions of the transactions,
+    /// transaction infos and events to keep. They are never pruned if `None`.
+    pub ledger_window: Option<u64>,
+}
+
+impl PruneWindows {
+    /// Keep the whole history.
+    pub fn archive() -> Self {
+        Self {
+            state_window: None,
+            ledger_window: None,
+        }
+    }
+
+    /// Ensures the state is not kept longer than the ledger: the transaction info of a version is
+    /// needed to prove the state at that version.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(ledger_window) = self.ledger_window {
+            match self.state_window {
+                Some(state_window) => ensure!(
+                    state_window <= ledger_window,
+                    "State window {} is larger than ledger window {}.",
+                    state_window,
+                    ledger_window
+                ),
+                None => bail!("The state must be pruned when the ledger is pruned."),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for PruneWindows {
+    fn default() -> Self {
+        Self {
+            state_window: Some(1_000_000),
+            ledger_window: None,
+        }
+    }
+}
+
+impl FromProto for PruneWindows {
+    type ProtoType = crate::proto::storage::PruneWindows;
+
+    fn from_proto(mut object: Self::ProtoType) -> Result<Self> {
+        Ok(Self {
+            state_window: object.state_window.take().map(|v| v.get_value()),
+            ledger_window: object.ledger_window.take().map(|v| v.get_value()),
+        })
+    }
+}
+
+impl IntoProto for PruneWindows {
+    type ProtoType = crate::proto::storage::PruneWindows;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        if let Some(state_window) = self.state_window {
+            let mut wrapped = protobuf::well_known_types::UInt64Value::new();
+            wrapped.set_value(state_window);
+            proto.set_state_window(wrapped);
+        }
+        if let Some(ledger_window) = self.ledger_window {
+            let mut wrapped = protobuf::well_known_types::UInt64Value::new();
+            wrapped.set_value(ledger_window);
+            proto.set_ledger_window(wrapped);
+        }
+        proto
+    }
+}
+
+/// Helper to construct and parse [`proto::storage::GetPruneWindowsResponse`]
+///
+/// It does so by implementing [`IntoProto`](#impl-IntoProto) and [`FromProto`](#impl-FromProto),
+/// providing [`into_proto`](IntoProto::into_proto) and [`from_proto`](FromProto::from_proto).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
+pub struct GetPruneWindowsResponse {
+    pub prune_windows: PruneWindows,
+}
+
+impl FromProto for GetPruneWindowsResponse {
+    type ProtoType = crate::proto::storage::GetPruneWindowsResponse;
+
+    fn from_proto(mut object: Self::ProtoType) -> Result<Self> {
+        Ok(Self {
+            prune_windows: PruneWindows::from_proto(object.take_prune_windows())?,
+        })
+    }
+}
+
+impl IntoProto for GetPruneWindowsResponse {
+    type ProtoType = crate::proto::storage::GetPruneWindowsResponse;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        proto.set_prune_windows(self.prune_windows.into_proto());
+        proto
+    }
+}
+
+/// Helper to construct and parse [`proto::storage::SetPruneWindowsRequest`]
+///
+/// It does so by implementing [`IntoProto`](#impl-IntoProto) and [`FromProto`](#impl-FromProto),
+/// providing [`into_proto`](IntoProto::into_proto) and [`from_proto`](FromProto::from_proto).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
+pub struct SetPruneWindowsRequest {
+    pub prune_windows: PruneWindows,
+}
+
+impl FromProto for SetPruneWindowsRequest {
+    type ProtoType = crate::proto::storage::SetPruneWindowsRequest;
+
+    fn from_proto(mut object: Self::ProtoType) -> Result<Self> {
+        Ok(Self {
+            prune_windows: PruneWindows::from_proto(object.take_prune_windows())?,
+        })
+    }
+}
+
+impl IntoProto for SetPruneWindowsRequest {
+    type ProtoType = crate::proto::storage::SetPruneWindowsRequest;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        proto.set_prune_windows(self.prune_windows.into_proto());
+        proto
+    }
+}
+
 pub mod prelude {
     pub use super::*;
 }
@@ -32,4 +32,34 @@ proptest! {
     fn test_get_executor_startup_info_response(res in any::<GetExecutorStartupInfoResponse>()) {
         assert_protobuf_encode_decode(&res);
     }
+
+    #[test]
+    fn test_backup_account_state_request(req in any::<BackupAccountStateRequest>()) {
+        assert_protobuf_encode_decode(&req);
+    }
+
+    #[test]
+    fn test_backup_account_state_response(resp in any::<BackupAccountStateResponse>()) {
+        assert_protobuf_encode_decode(&resp);
+    }
+
+    #[test]
+    fn test_get_ledger_statistics_request(req in any::<GetLedgerStatisticsRequest>()) {
+        assert_protobuf_encode_decode(&req);
+    }
+
+    #[test]
+    fn test_get_ledger_statistics_response(resp in any::<GetLedgerStatisticsResponse>()) {
+        assert_protobuf_encode_decode(&resp);
+    }
+
+    #[test]
+    fn test_get_prune_windows_response(resp in any::<GetPruneWindowsResponse>()) {
+        assert_protobuf_encode_decode(&resp);
+    }
+
+    #[test]
+    fn test_set_prune_windows_request(req in any::<SetPruneWindowsRequest>()) {
+        assert_protobuf_encode_decode(&req);
+    }
 }
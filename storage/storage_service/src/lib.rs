@@ -8,10 +8,16 @@
 //! [`StorageClient`](../storage_proto/proto/storage_grpc/struct.StorageClient.html) directly.
 
 pub mod mocks;
+pub mod read_only_storage;
 
-use config::config::NodeConfig;
+use config::config::{NodeConfig, StoragePruningConfig};
 use failure::prelude::*;
-use grpc_helpers::{provide_grpc_response, spawn_service_thread_with_drop_closure, ServerHandle};
+use futures_01::{future::Future, sink::Sink, stream::Stream, sync::mpsc as mpsc_01};
+use grpc_helpers::{
+    create_grpc_invalid_arg_status, default_reply_error_logger, provide_grpc_response,
+    spawn_service_thread_with_drop_closure, ServerHandle,
+};
+use grpcio::{RpcStatus, RpcStatusCode, WriteFlags};
 use libradb::LibraDB;
 use logger::prelude::*;
 use metrics::counters::SVC_COUNTERS;
@@ -20,12 +26,16 @@ use std::{
     ops::Deref,
     path::Path,
     sync::{mpsc, Arc, Mutex},
+    thread,
 };
 use storage_proto::proto::{
     storage::{
+        BackupAccountStateRequest, BackupAccountStateResponse,
         GetAccountStateWithProofByVersionRequest, GetAccountStateWithProofByVersionResponse,
-        GetExecutorStartupInfoRequest, GetExecutorStartupInfoResponse, GetTransactionsRequest,
-        GetTransactionsResponse, SaveTransactionsRequest, SaveTransactionsResponse,
+        GetExecutorStartupInfoRequest, GetExecutorStartupInfoResponse, GetLedgerStatisticsRequest,
+        GetLedgerStatisticsResponse, GetPruneWindowsRequest, GetPruneWindowsResponse,
+        GetTransactionsRequest, GetTransactionsResponse, SaveTransactionsRequest,
+        SaveTransactionsResponse, SetPruneWindowsRequest, SetPruneWindowsResponse,
     },
     storage_grpc::{create_storage, Storage},
 };
@@ -33,7 +43,10 @@ use types::proto::get_with_proof::{UpdateToLatestLedgerRequest, UpdateToLatestLe
 
 /// Starts storage service according to config.
 pub fn start_storage_service(config: &NodeConfig) -> ServerHandle {
-    let (storage_service, shutdown_receiver) = StorageService::new(&config.storage.get_dir());
+    let (storage_service, shutdown_receiver) = StorageService::new_with_prune_windows(
+        &config.storage.get_dir(),
+        prune_windows_from_config(&config.storage.pruning),
+    );
     spawn_service_thread_with_drop_closure(
         create_storage(storage_service),
         config.storage.address.clone(),
@@ -48,6 +61,17 @@ pub fn start_storage_service(config: &NodeConfig) -> ServerHandle {
     )
 }
 
+fn prune_windows_from_config(config: &StoragePruningConfig) -> storage_proto::PruneWindows {
+    if config.archive {
+        storage_proto::PruneWindows::archive()
+    } else {
+        storage_proto::PruneWindows {
+            state_window: Some(config.state_window),
+            ledger_window: config.ledger_window,
+        }
+    }
+}
+
 /// The implementation of the storage [GRPC](http://grpc.io) service.
 ///
 /// It serves [`LibraDB`] APIs over the network. See API documentation in [`storage_proto`] and
@@ -73,8 +97,11 @@ struct LibraDBWrapper {
 }
 
 impl LibraDBWrapper {
-    pub fn new<P: AsRef<Path>>(path: &P) -> (Self, mpsc::Receiver<()>) {
-        let db = LibraDB::new(path);
+    pub fn new<P: AsRef<Path>>(
+        path: &P,
+        prune_windows: storage_proto::PruneWindows,
+    ) -> (Self, mpsc::Receiver<()>) {
+        let db = LibraDB::new_with_prune_windows(path, prune_windows);
         let (shutdown_sender, shutdown_receiver) = mpsc::channel();
         (
             Self {
@@ -126,7 +153,15 @@ impl StorageService {
     ///    // LibraDB instance is guaranteed to be properly dropped at this point.
     /// ```
     pub fn new<P: AsRef<Path>>(path: &P) -> (Self, mpsc::Receiver<()>) {
-        let (db_wrapper, shutdown_receiver) = LibraDBWrapper::new(path);
+        Self::new_with_prune_windows(path, storage_proto::PruneWindows::default())
+    }
+
+    /// Same as `new`, keeping as much history as `prune_windows` tells.
+    pub fn new_with_prune_windows<P: AsRef<Path>>(
+        path: &P,
+        prune_windows: storage_proto::PruneWindows,
+    ) -> (Self, mpsc::Receiver<()>) {
+        let (db_wrapper, shutdown_receiver) = LibraDBWrapper::new(path, prune_windows);
         (
             Self {
                 db: Arc::new(db_wrapper),
@@ -210,6 +245,76 @@ impl StorageService {
         let rust_resp = storage_proto::GetExecutorStartupInfoResponse { info };
         Ok(rust_resp.into_proto())
     }
+
+    fn get_ledger_statistics_inner(
+        &self,
+        req: GetLedgerStatisticsRequest,
+    ) -> Result<GetLedgerStatisticsResponse> {
+        let rust_req = storage_proto::GetLedgerStatisticsRequest::from_proto(req)?;
+        let ledger_statistics = self.db.get_ledger_statistics(
+            rust_req.start_version,
+            rust_req.end_version,
+            rust_req.limit,
+        )?;
+        let rust_resp = storage_proto::GetLedgerStatisticsResponse::new(ledger_statistics);
+        Ok(rust_resp.into_proto())
+    }
+
+    fn get_prune_windows_inner(&self) -> Result<GetPruneWindowsResponse> {
+        let prune_windows = self.db.get_prune_windows();
+        let rust_resp = storage_proto::GetPruneWindowsResponse { prune_windows };
+        Ok(rust_resp.into_proto())
+    }
+
+    fn set_prune_windows_inner(
+        &self,
+        req: SetPruneWindowsRequest,
+    ) -> Result<SetPruneWindowsResponse> {
+        let rust_req = storage_proto::SetPruneWindowsRequest::from_proto(req)?;
+        self.db.set_prune_windows(rust_req.prune_windows)?;
+        Ok(SetPruneWindowsResponse::new())
+    }
+
+    /// Streams the account states from a separate thread, as they are read from the DB, so that
+    /// a state snapshot is never held in memory as a whole. The thread stops as soon as the
+    /// receiver is dropped, e.g. when the client goes away, or after sending an error.
+    fn backup_account_state_inner(
+        &self,
+        req: BackupAccountStateRequest,
+    ) -> Result<mpsc_01::Receiver<Result<BackupAccountStateResponse>>> {
+        let rust_req = storage_proto::BackupAccountStateRequest::from_proto(req)?;
+        let (mut sender, receiver) = mpsc_01::channel(1);
+        let db = Arc::clone(&self.db);
+        thread::Builder::new()
+            .name("storage-backup".to_string())
+            .spawn(move || {
+                let iter = match db.get_account_iter(rust_req.version) {
+                    Ok(iter) => iter,
+                    Err(e) => {
+                        let _ = sender.send(Err(e)).wait();
+                        return;
+                    }
+                };
+                for item in iter {
+                    let item = item.map(|(account_key, account_state_blob)| {
+                        storage_proto::BackupAccountStateResponse::new(
+                            account_key,
+                            account_state_blob,
+                        )
+                        .into_proto()
+                    });
+                    let is_err = item.is_err();
+                    sender = match sender.send(item).wait() {
+                        Ok(sender) => sender,
+                        Err(_) => return,
+                    };
+                    if is_err {
+                        return;
+                    }
+                }
+            })?;
+        Ok(receiver)
+    }
 }
 
 impl Storage for StorageService {
@@ -272,7 +377,76 @@ impl Storage for StorageService {
         let resp = self.get_executor_startup_info_inner();
         provide_grpc_response(resp, ctx, sink);
     }
+
+    fn backup_account_state(
+        &mut self,
+        ctx: grpcio::RpcContext,
+        req: BackupAccountStateRequest,
+        sink: grpcio::ServerStreamingSink<BackupAccountStateResponse>,
+    ) {
+        debug!("[GRPC] Storage::backup_account_state");
+        let _timer = SVC_COUNTERS.req(&ctx);
+        let receiver = match self.backup_account_state_inner(req) {
+            Ok(receiver) => receiver,
+            Err(e) => {
+                let status = create_grpc_invalid_arg_status("backup_account_state", e);
+                ctx.spawn(sink.fail(status).map_err(default_reply_error_logger));
+                return;
+            }
+        };
+        let responses = receiver.then(|item| match item {
+            Ok(Ok(response)) => Ok((response, WriteFlags::default())),
+            Ok(Err(e)) => Err(grpcio::Error::RpcFailure(RpcStatus::new(
+                RpcStatusCode::Internal,
+                Some(e.to_string()),
+            ))),
+            Err(()) => Err(grpcio::Error::RemoteStopped),
+        });
+        ctx.spawn(
+            sink.send_all(responses)
+                .map(|_| ())
+                .map_err(|e| debug!("[GRPC] Account state backup stream closed: {:?}", e)),
+        );
+    }
+
+    fn get_ledger_statistics(
+        &mut self,
+        ctx: grpcio::RpcContext,
+        req: GetLedgerStatisticsRequest,
+        sink: grpcio::UnarySink<GetLedgerStatisticsResponse>,
+    ) {
+        debug!("[GRPC] Storage::get_ledger_statistics");
+        let _timer = SVC_COUNTERS.req(&ctx);
+        let resp = self.get_ledger_statistics_inner(req);
+        provide_grpc_response(resp, ctx, sink);
+    }
+
+    fn get_prune_windows(
+        &mut self,
+        ctx: grpcio::RpcContext,
+        _req: GetPruneWindowsRequest,
+        sink: grpcio::UnarySink<GetPruneWindowsResponse>,
+    ) {
+        debug!("[GRPC] Storage::get_prune_windows");
+        let _timer = SVC_COUNTERS.req(&ctx);
+        let resp = self.get_prune_windows_inner();
+        provide_grpc_response(resp, ctx, sink);
+    }
+
+    fn set_prune_windows(
+        &mut self,
+        ctx: grpcio::RpcContext,
+        req: SetPruneWindowsRequest,
+        sink: grpcio::UnarySink<SetPruneWindowsResponse>,
+    ) {
+        debug!("[GRPC] Storage::set_prune_windows");
+        let _timer = SVC_COUNTERS.req(&ctx);
+        let resp = self.set_prune_windows_inner(req);
+        provide_grpc_response(resp, ctx, sink);
+    }
 }
 
+#[cfg(test)]
+mod read_only_storage_test;
 #[cfg(test)]
 mod storage_service_test;
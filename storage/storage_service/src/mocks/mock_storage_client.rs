@@ -21,13 +21,15 @@ use types::{
     account_state_blob::AccountStateBlob,
     get_with_proof::{RequestItem, ResponseItem},
     ledger_info::LedgerInfoWithSignatures,
+    ledger_statistics::LedgerStatistics,
     proof::SparseMerkleProof,
     proto::{
         account_state_blob::AccountStateWithProof,
         get_with_proof::{
-            GetAccountStateResponse, GetTransactionsResponse, RequestItem as ProtoRequestItem,
-            RequestItem_oneof_requested_items, ResponseItem as ProtoResponseItem,
-            UpdateToLatestLedgerRequest, UpdateToLatestLedgerResponse,
+            GetAccountStateResponse, GetEventsByEventAccessPathResponse, GetTransactionsResponse,
+            RequestItem as ProtoRequestItem, RequestItem_oneof_requested_items,
+            ResponseItem as ProtoResponseItem, UpdateToLatestLedgerRequest,
+            UpdateToLatestLedgerResponse,
         },
         ledger_info::LedgerInfoWithSignatures as ProtoLedgerInfoWithSignatures,
         proof::AccumulatorProof,
@@ -140,6 +142,41 @@ impl StorageRead for MockStorageReadClient {
     ) -> Pin<Box<dyn Future<Output = Result<Option<ExecutorStartupInfo>>> + Send>> {
         unimplemented!()
     }
+
+    fn get_ledger_statistics(
+        &self,
+        _start_version: Version,
+        _end_version: Version,
+        limit: u64,
+    ) -> Result<Vec<LedgerStatistics>> {
+        // The counters as of the version of the mock ledger info.
+        let ledger_statistics = LedgerStatistics {
+            version: 7,
+            events_created: 4,
+            new_state_leaves: 3,
+            stale_state_leaves: 1,
+            new_state_nodes: 9,
+            stale_state_nodes: 2,
+        };
+        Ok(if limit > 0 {
+            vec![ledger_statistics]
+        } else {
+            vec![]
+        })
+    }
+
+    fn get_ledger_statistics_async(
+        &self,
+        start_version: Version,
+        end_version: Version,
+        limit: u64,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<LedgerStatistics>>> + Send>> {
+        futures::future::ok(
+            self.get_ledger_statistics(start_version, end_version, limit)
+                .unwrap(),
+        )
+        .boxed()
+    }
 }
 
 fn get_mock_update_to_latest_ledger(
@@ -167,49 +204,17 @@ fn get_mock_response_item(request_item: &ProtoRequestItem) -> Result<ProtoRespon
         match requested_item {
             RequestItem_oneof_requested_items::get_account_state_request(_request) => {
                 let mut resp = GetAccountStateResponse::new();
-                let mut version_data = BTreeMap::new();
-
-                let account_resource = types::account_config::AccountResource::new(
-                    100,
-                    0,
-                    types::byte_array::ByteArray::new(vec![]),
-                    false,
-                    EventHandle::random_handle(0),
-                    EventHandle::random_handle(0),
-                );
-                version_data.insert(
-                    types::account_config::account_resource_path(),
-                    SimpleSerializer::serialize(&account_resource)?,
-                );
-                let mut account_state_with_proof = AccountStateWithProof::new();
-                let blob = AccountStateBlob::from(
-                    SimpleSerializer::<Vec<u8>>::serialize(&version_data)?
-                ).into_proto();
-                let proof = {
-                    let ledger_info_to_transaction_info_proof = types::proof::AccumulatorProof::new(vec![]);
-                    let transaction_info = types::transaction::TransactionInfo::new(
-                        HashValue::zero(),
-                        HashValue::zero(),
-                        HashValue::zero(),
-                        0,
-                    );
-                    let transaction_info_to_account_proof = types::proof::SparseMerkleProof::new(None, vec![]);
-                    types::proof::AccountStateProof::new(
-                        ledger_info_to_transaction_info_proof,
-                        transaction_info,
-                        transaction_info_to_account_proof,
-                    ).into_proto()
-                };
-                account_state_with_proof.set_blob(blob);
-                account_state_with_proof.set_proof(proof);
-                resp.set_account_state_with_proof(account_state_with_proof);
+                resp.set_account_state_with_proof(get_mock_account_state_with_proof()?);
                 response_item.set_get_account_state_response(resp);
             }
             RequestItem_oneof_requested_items::get_account_transaction_by_sequence_number_request(_request) => {
                 unimplemented!();
             }
             RequestItem_oneof_requested_items::get_events_by_event_access_path_request(_request) => {
-                unimplemented!();
+                // No event was ever emitted.
+                let mut resp = GetEventsByEventAccessPathResponse::new();
+                resp.set_proof_of_latest_event(get_mock_account_state_with_proof()?);
+                response_item.set_get_events_by_event_access_path_response(resp);
             }
             RequestItem_oneof_requested_items::get_transactions_request(request) => {
                 let mut ret = TransactionListWithProof::new();
@@ -236,6 +241,40 @@ fn get_mock_response_item(request_item: &ProtoRequestItem) -> Result<ProtoRespon
     Ok(response_item)
 }
 
+fn get_mock_account_state_with_proof() -> Result<AccountStateWithProof> {
+    let mut version_data = BTreeMap::new();
+
+    let account_resource = types::account_config::AccountResource::new(
+        100,
+        0,
+        types::byte_array::ByteArray::new(vec![]),
+        false,
+        EventHandle::random_handle(0),
+        EventHandle::random_handle(0),
+    );
+    version_data.insert(
+        types::account_config::account_resource_path(),
+        SimpleSerializer::serialize(&account_resource)?,
+    );
+    let mut account_state_with_proof = AccountStateWithProof::new();
+    let blob =
+        AccountStateBlob::from(SimpleSerializer::<Vec<u8>>::serialize(&version_data)?).into_proto();
+    let proof = {
+        let ledger_info_to_transaction_info_proof = types::proof::AccumulatorProof::new(vec![]);
+        let transaction_info = get_transaction_info();
+        let transaction_info_to_account_proof = types::proof::SparseMerkleProof::new(None, vec![]);
+        types::proof::AccountStateProof::new(
+            ledger_info_to_transaction_info_proof,
+            transaction_info,
+            transaction_info_to_account_proof,
+        )
+        .into_proto()
+    };
+    account_state_with_proof.set_blob(blob);
+    account_state_with_proof.set_proof(proof);
+    Ok(account_state_with_proof)
+}
+
 fn get_mock_txn_data(
     address: AccountAddress,
     start_seq: u64,
@@ -0,0 +1,202 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides [`ReadOnlyStorage`], which serves the [`StorageRead`] API right from the
+//! LibraDB of a running node, e.g. for analytics jobs, without going through the storage service
+//! of the node nor copying the DB.
+
+use crypto::ed25519::*;
+use failure::prelude::*;
+use futures::prelude::*;
+use libradb::LibraDB;
+use logger::prelude::*;
+use std::{
+    path::Path,
+    pin::Pin,
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+use storage_client::StorageRead;
+use storage_proto::ExecutorStartupInfo;
+use types::{
+    account_address::AccountAddress,
+    account_state_blob::AccountStateBlob,
+    get_with_proof::{RequestItem, ResponseItem},
+    ledger_info::LedgerInfoWithSignatures,
+    ledger_statistics::LedgerStatistics,
+    proof::SparseMerkleProof,
+    transaction::{TransactionListWithProof, Version},
+    validator_change::ValidatorChangeEventWithProof,
+};
+
+/// Reads a LibraDB opened as a secondary instance with [`LibraDB::open_as_secondary`], which
+/// serves the data as of the time it is opened. A background thread catches up with the node
+/// every `catch_up_interval` to serve what it wrote since. Nothing is ever written nor pruned.
+///
+/// The async APIs of [`StorageRead`] block until the data is read, like the sync ones.
+pub struct ReadOnlyStorage {
+    db: Arc<LibraDB>,
+    /// Dropped to stop the catch-up thread.
+    shutdown_sender: Option<mpsc::Sender<()>>,
+    catch_up_thread: Option<JoinHandle<()>>,
+}
+
+impl ReadOnlyStorage {
+    /// Opens the LibraDB under `db_root_path` as a secondary instance keeping its info logs under
+    /// `secondary_path`, and catches up with the node writing to it every `catch_up_interval`.
+    pub fn open<P: AsRef<Path>, Q: AsRef<Path>>(
+        db_root_path: P,
+        secondary_path: Q,
+        catch_up_interval: Duration,
+    ) -> Result<Self> {
+        let db = Arc::new(LibraDB::open_as_secondary(db_root_path, secondary_path)?);
+
+        let (shutdown_sender, shutdown_receiver) = mpsc::channel::<()>();
+        let thread_db = Arc::clone(&db);
+        let catch_up_thread = thread::Builder::new()
+            .name("storage-catch-up".to_string())
+            .spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) =
+                    shutdown_receiver.recv_timeout(catch_up_interval)
+                {
+                    if let Err(e) = thread_db.try_catch_up_with_primary() {
+                        warn!("Failed to catch up with the LibraDB writer: {}", e);
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            db,
+            shutdown_sender: Some(shutdown_sender),
+            catch_up_thread: Some(catch_up_thread),
+        })
+    }
+
+    /// Catches up with the node writing to the DB right away, instead of waiting for the
+    /// background thread.
+    pub fn catch_up(&self) -> Result<()> {
+        self.db.try_catch_up_with_primary()
+    }
+}
+
+impl Drop for ReadOnlyStorage {
+    fn drop(&mut self) {
+        self.shutdown_sender.take();
+        if let Some(catch_up_thread) = self.catch_up_thread.take() {
+            catch_up_thread
+                .join()
+                .expect("Failed to join the catch-up thread.");
+        }
+    }
+}
+
+impl StorageRead for ReadOnlyStorage {
+    fn update_to_latest_ledger(
+        &self,
+        client_known_version: Version,
+        request_items: Vec<RequestItem>,
+    ) -> Result<(
+        Vec<ResponseItem>,
+        LedgerInfoWithSignatures<Ed25519Signature>,
+        Vec<ValidatorChangeEventWithProof<Ed25519Signature>>,
+    )> {
+        self.db
+            .update_to_latest_ledger(client_known_version, request_items)
+    }
+
+    fn update_to_latest_ledger_async(
+        &self,
+        client_known_version: Version,
+        request_items: Vec<RequestItem>,
+    ) -> Pin<
+        Box<
+            dyn Future<
+                    Output = Result<(
+                        Vec<ResponseItem>,
+                        LedgerInfoWithSignatures<Ed25519Signature>,
+                        Vec<ValidatorChangeEventWithProof<Ed25519Signature>>,
+                    )>,
+                > + Send,
+        >,
+    > {
+        future::ready(self.update_to_latest_ledger(client_known_version, request_items)).boxed()
+    }
+
+    fn get_transactions(
+        &self,
+        start_version: Version,
+        batch_size: u64,
+        ledger_version: Version,
+        fetch_events: bool,
+    ) -> Result<TransactionListWithProof> {
+        self.db
+            .get_transactions(start_version, batch_size, ledger_version, fetch_events)
+    }
+
+    fn get_transactions_async(
+        &self,
+        start_version: Version,
+        batch_size: u64,
+        ledger_version: Version,
+        fetch_events: bool,
+    ) -> Pin<Box<dyn Future<Output = Result<TransactionListWithProof>> + Send>> {
+        future::ready(self.get_transactions(
+            start_version,
+            batch_size,
+            ledger_version,
+            fetch_events,
+        ))
+        .boxed()
+    }
+
+    fn get_account_state_with_proof_by_version(
+        &self,
+        address: AccountAddress,
+        version: Version,
+    ) -> Result<(Option<AccountStateBlob>, SparseMerkleProof)> {
+        self.db
+            .get_account_state_with_proof_by_version(address, version)
+    }
+
+    fn get_account_state_with_proof_by_version_async(
+        &self,
+        address: AccountAddress,
+        version: Version,
+    ) -> Pin<Box<dyn Future<Output = Result<(Option<AccountStateBlob>, SparseMerkleProof)>> + Send>>
+    {
+        future::ready(self.get_account_state_with_proof_by_version(address, version)).boxed()
+    }
+
+    fn get_executor_startup_info(&self) -> Result<Option<ExecutorStartupInfo>> {
+        self.db.get_executor_startup_info()
+    }
+
+    fn get_executor_startup_info_async(
+        &self,
+    ) -> Pin<Box<dyn Future<Output = Result<Option<ExecutorStartupInfo>>> + Send>> {
+        future::ready(self.get_executor_startup_info()).boxed()
+    }
+
+    fn get_ledger_statistics(
+        &self,
+        start_version: Version,
+        end_version: Version,
+        limit: u64,
+    ) -> Result<Vec<LedgerStatistics>> {
+        self.db
+            .get_ledger_statistics(start_version, end_version, limit)
+    }
+
+    fn get_ledger_statistics_async(
+        &self,
+        start_version: Version,
+        end_version: Version,
+        limit: u64,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<LedgerStatistics>>> + Send>> {
+        future::ready(self.get_ledger_statistics(start_version, end_version, limit)).boxed()
+    }
+}
@@ -0,0 +1,56 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::read_only_storage::ReadOnlyStorage;
+use libradb::{mock_genesis::db_with_mock_genesis, test_helper::arb_blocks_to_commit};
+use proptest::prelude::*;
+use std::time::Duration;
+use storage_client::StorageRead;
+
+fn latest_version(storage: &ReadOnlyStorage) -> u64 {
+    let (_, ledger_info_with_sigs, _) = storage
+        .update_to_latest_ledger(0 /* client_known_version */, vec![])
+        .unwrap();
+    ledger_info_with_sigs.ledger_info().version()
+}
+
+#[test]
+fn test_open_without_db() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let secondary_dir = tempfile::tempdir().unwrap();
+    assert!(ReadOnlyStorage::open(&tmp_dir, &secondary_dir, Duration::from_secs(3600)).is_err());
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(5))]
+
+    #[test]
+    fn test_catch_up(blocks in arb_blocks_to_commit().no_shrink()) {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let secondary_dir = tempfile::tempdir().unwrap();
+        let db = db_with_mock_genesis(&tmp_dir).unwrap();
+        let storage =
+            ReadOnlyStorage::open(&tmp_dir, &secondary_dir, Duration::from_secs(3600)).unwrap();
+        prop_assert_eq!(latest_version(&storage), 0);
+
+        let mut version = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in &blocks {
+            db.save_transactions(
+                txns_to_commit,
+                version + 1, /* first_version */
+                &Some(ledger_info_with_sigs.clone()),
+            )
+            .unwrap();
+            version += txns_to_commit.len() as u64;
+        }
+        // What the node writes is only served after a catch-up.
+        prop_assert_eq!(latest_version(&storage), 0);
+
+        storage.catch_up().unwrap();
+        prop_assert_eq!(latest_version(&storage), version);
+        prop_assert_eq!(
+            storage.get_executor_startup_info().unwrap(),
+            db.get_executor_startup_info().unwrap()
+        );
+    }
+}
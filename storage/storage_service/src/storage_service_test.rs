@@ -13,6 +13,7 @@ use std::collections::HashMap;
 use storage_client::{
     StorageRead, StorageReadServiceClient, StorageWrite, StorageWriteServiceClient,
 };
+use storage_proto::PruneWindows;
 use types::get_with_proof::{RequestItem, ResponseItem};
 
 fn start_test_storage_with_read_write_client(
@@ -107,3 +108,44 @@ proptest! {
          }
     }
 }
+
+#[test]
+fn test_storage_service_prune_windows() {
+    let (_tmp_dir, _server_handler, read_client, write_client) =
+        start_test_storage_with_read_write_client(/* need_to_use_genesis = */ true);
+    assert_eq!(
+        read_client.get_prune_windows().unwrap(),
+        PruneWindows::default()
+    );
+
+    let prune_windows = PruneWindows {
+        state_window: Some(10),
+        ledger_window: Some(100),
+    };
+    write_client.set_prune_windows(prune_windows).unwrap();
+    assert_eq!(read_client.get_prune_windows().unwrap(), prune_windows);
+
+    // The state can't be kept longer than the ledger.
+    assert!(write_client
+        .set_prune_windows(PruneWindows {
+            state_window: None,
+            ledger_window: Some(100),
+        })
+        .is_err());
+    assert_eq!(read_client.get_prune_windows().unwrap(), prune_windows);
+}
+
+#[test]
+fn test_storage_service_ledger_statistics() {
+    let (_tmp_dir, _server_handler, read_client, _write_client) =
+        start_test_storage_with_read_write_client(/* need_to_use_genesis = */ true);
+
+    // The mock genesis creates one account and no event.
+    let ledger_statistics = read_client.get_ledger_statistics(0, 10, 10).unwrap();
+    assert_eq!(ledger_statistics.len(), 1);
+    assert_eq!(ledger_statistics[0].version, 0);
+    assert_eq!(ledger_statistics[0].events_created, 0);
+    assert_eq!(ledger_statistics[0].num_accounts(), 1);
+
+    assert!(read_client.get_ledger_statistics(10, 0, 10).is_err());
+}
@@ -50,6 +50,11 @@ fn setup_env(
                 .unwrap()
                 .to_string(),
         ),
+        None,
+        None,
+        None,
+        None,
+        None,
     )
     .unwrap();
     (swarm, client_proxy)
@@ -151,6 +156,35 @@ fn test_execute_custom_module_and_script() {
     );
 }
 
+#[test]
+fn test_simulate_transaction() {
+    let (_swarm, mut client_proxy) = setup_swarm_and_client_proxy(1, 0);
+    client_proxy.create_next_account(false).unwrap();
+    client_proxy
+        .mint_coins(&["mintb", "0", "10"], true)
+        .unwrap();
+    client_proxy.create_next_account(false).unwrap();
+
+    let response = client_proxy
+        .simulate_transaction(&["simulate", "0", "transfer", "1", "3"])
+        .unwrap();
+    assert!(response.output.gas_used() > 0);
+    assert!(!response.output.write_set().is_empty());
+    // Nothing is committed.
+    assert_eq!(
+        Decimal::from_f64(10.0),
+        Decimal::from_str(&client_proxy.get_balance(&["b", "0"]).unwrap()).ok()
+    );
+    // The simulated transaction can be submitted as is.
+    client_proxy
+        .transfer_coins(&["tb", "0", "1", "3"], true)
+        .unwrap();
+    assert_eq!(
+        Decimal::from_f64(3.0),
+        Decimal::from_str(&client_proxy.get_balance(&["b", "1"]).unwrap()).ok()
+    );
+}
+
 #[test]
 fn smoke_test_single_node() {
     let (_swarm, mut client_proxy) = setup_swarm_and_client_proxy(1, 0);
@@ -309,6 +343,11 @@ fn test_basic_state_synchronization() {
                 .unwrap()
                 .to_string(),
         ),
+        None,
+        None,
+        None,
+        None,
+        None,
     )
     .unwrap();
     client_proxy2.set_accounts(client_proxy.copy_all_accounts());
@@ -444,3 +483,42 @@ fn test_external_transaction_signer() {
         _ => panic!("Signed transaction payload expected to be of struct Program"),
     }
 }
+
+#[test]
+fn test_offline_transaction_signing() {
+    let (_swarm, mut client_proxy) = setup_swarm_and_client_proxy(1, 0);
+    client_proxy.create_next_account(false).unwrap();
+    client_proxy.create_next_account(false).unwrap();
+    client_proxy
+        .mint_coins(&["mintb", "0", "10"], true)
+        .unwrap();
+
+    let unsigned_txn_path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let signed_txn_path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let unsigned_txn_file = unsigned_txn_path.to_str().unwrap();
+    let signed_txn_file = signed_txn_path.to_str().unwrap();
+
+    let raw_txn = client_proxy
+        .prepare_transfer_coins_to_file(&["prepare", "0", "1", "3", unsigned_txn_file])
+        .unwrap();
+    let signed_txn = client_proxy
+        .sign_transaction_file(&["sign", unsigned_txn_file, signed_txn_file])
+        .unwrap();
+    assert_eq!(signed_txn.sender(), raw_txn.sender());
+    // Submitting the unsigned file by mistake is rejected.
+    assert!(client_proxy
+        .submit_transaction_file(&["submitb", unsigned_txn_file], true)
+        .is_err());
+    client_proxy
+        .submit_transaction_file(&["submitb", signed_txn_file], true)
+        .unwrap();
+
+    assert_eq!(
+        Decimal::from_f64(7.0),
+        Decimal::from_str(&client_proxy.get_balance(&["b", "0"]).unwrap()).ok()
+    );
+    assert_eq!(
+        Decimal::from_f64(3.0),
+        Decimal::from_str(&client_proxy.get_balance(&["b", "1"]).unwrap()).ok()
+    );
+}
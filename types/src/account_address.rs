@@ -55,12 +55,18 @@ impl AccountAddress {
     }
 
     pub fn from_public_key<PublicKey: VerifyingKey>(public_key: &PublicKey) -> Self {
+        Self::from_authentication_key_preimage(&public_key.to_bytes())
+    }
+
+    /// Address of an account whose authentication key is the hash of `preimage`, e.g. the
+    /// encoding of a multi-signature public key.
+    pub fn from_authentication_key_preimage(preimage: &[u8]) -> Self {
         // TODO: using keccak directly instead of crypto::hash because we have to make sure we use
         // the same hash function that the Move transaction prologue is using.
         // TODO: keccak is just a placeholder, make a principled choice for the hash function
         let mut keccak = Keccak::new_sha3_256();
         let mut hash = [0u8; ADDRESS_LENGTH];
-        keccak.update(&public_key.to_bytes());
+        keccak.update(preimage);
         keccak.finalize(&mut hash);
         AccountAddress::new(hash)
     }
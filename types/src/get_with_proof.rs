@@ -11,10 +11,11 @@ use crate::{
     contract_event::EventWithProof,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     proto::get_with_proof::{
-        GetAccountStateRequest, GetAccountStateResponse,
-        GetAccountTransactionBySequenceNumberRequest,
+        GetAccountStateAtVersionRequest, GetAccountStateAtVersionResponse, GetAccountStateRequest,
+        GetAccountStateResponse, GetAccountTransactionBySequenceNumberRequest,
         GetAccountTransactionBySequenceNumberResponse, GetEventsByEventAccessPathRequest,
-        GetEventsByEventAccessPathResponse, GetTransactionsRequest, GetTransactionsResponse,
+        GetEventsByEventAccessPathResponse, GetEventsByVersionRangeRequest,
+        GetEventsByVersionRangeResponse, GetTransactionsRequest, GetTransactionsResponse,
     },
     transaction::{SignedTransactionWithProof, TransactionListWithProof, Version},
     validator_change::ValidatorChangeEventWithProof,
@@ -227,6 +228,33 @@ fn verify_response_item(
             *fetch_events,
             txn_list_with_proof,
         ),
+        // GetAccountStateAtVersion
+        (
+            RequestItem::GetAccountStateAtVersion { address, version },
+            ResponseItem::GetAccountStateAtVersion {
+                account_state_with_proof,
+            },
+        ) => verify_get_account_state_at_version_resp(
+            ledger_info,
+            *address,
+            *version,
+            account_state_with_proof,
+        ),
+        // GetEventsByVersionRange
+        (
+            RequestItem::GetEventsByVersionRange {
+                start_version,
+                limit,
+                access_path_prefix,
+            },
+            ResponseItem::GetEventsByVersionRange { events_with_proof },
+        ) => verify_get_events_by_version_range_resp(
+            ledger_info,
+            *start_version,
+            *limit,
+            access_path_prefix.as_ref(),
+            events_with_proof,
+        ),
         // Request-response item types mismatch.
         _ => bail!(
             "RequestItem/ResponseItem types mismatch. request: {:?}, response: {:?}",
@@ -371,6 +399,68 @@ fn verify_get_txns_resp(
     }
 }
 
+fn verify_get_account_state_at_version_resp(
+    ledger_info: &LedgerInfo,
+    req_address: AccountAddress,
+    req_version: Version,
+    account_state_with_proof: &AccountStateWithProof,
+) -> Result<()> {
+    ensure!(
+        req_version <= ledger_info.version(),
+        "Bad GetAccountStateAtVersion response. Version requested: {}, latest version: {}.",
+        req_version,
+        ledger_info.version(),
+    );
+    account_state_with_proof.verify(ledger_info, req_version, req_address)
+}
+
+fn verify_get_events_by_version_range_resp(
+    ledger_info: &LedgerInfo,
+    req_start_version: Version,
+    req_limit: u64,
+    req_access_path_prefix: Option<&AccessPath>,
+    events_with_proof: &[EventWithProof],
+) -> Result<()> {
+    let mut prev_event_position = None;
+    for e in events_with_proof {
+        let event_position = (e.transaction_version, e.event_index);
+        ensure!(
+            e.transaction_version >= req_start_version
+                && e.transaction_version - req_start_version < req_limit,
+            "Bad GetEventsByVersionRange response. Event at version {} out of range requested: \
+             start version: {}, limit: {}.",
+            e.transaction_version,
+            req_start_version,
+            req_limit,
+        );
+        ensure!(
+            prev_event_position.map_or(true, |prev| prev < event_position),
+            "Bad GetEventsByVersionRange response. Events not in order, (version, index): {:?} \
+             after {:?}.",
+            event_position,
+            prev_event_position,
+        );
+        if let Some(prefix) = req_access_path_prefix {
+            ensure!(
+                e.event.access_path().starts_with(prefix),
+                "Bad GetEventsByVersionRange response. Event access path {} doesn't start with {}.",
+                e.event.access_path(),
+                prefix,
+            );
+        }
+        e.verify(
+            ledger_info,
+            e.event.access_path(),
+            e.event.sequence_number(),
+            e.transaction_version,
+            e.event_index,
+        )?;
+        prev_event_position = Some(event_position);
+    }
+
+    Ok(())
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
 pub enum RequestItem {
@@ -394,6 +484,15 @@ pub enum RequestItem {
         limit: u64,
         fetch_events: bool,
     },
+    GetAccountStateAtVersion {
+        address: AccountAddress,
+        version: Version,
+    },
+    GetEventsByVersionRange {
+        start_version: Version,
+        limit: u64,
+        access_path_prefix: Option<AccessPath>,
+    },
 }
 
 impl FromProto for RequestItem {
@@ -440,6 +539,27 @@ impl FromProto for RequestItem {
                 limit,
                 fetch_events,
             }
+        } else if object.has_get_account_state_at_version_request() {
+            let mut req = object.take_get_account_state_at_version_request();
+            let address = AccountAddress::from_proto(req.take_address())?;
+            let version = req.get_version();
+
+            RequestItem::GetAccountStateAtVersion { address, version }
+        } else if object.has_get_events_by_version_range_request() {
+            let mut req = object.take_get_events_by_version_range_request();
+            let start_version = req.get_start_version();
+            let limit = req.get_limit();
+            let access_path_prefix = if req.has_access_path_prefix() {
+                Some(AccessPath::from_proto(req.take_access_path_prefix())?)
+            } else {
+                None
+            };
+
+            RequestItem::GetEventsByVersionRange {
+                start_version,
+                limit,
+                access_path_prefix,
+            }
         } else {
             bail!("Unknown RequestItem type.")
         })
@@ -495,6 +615,27 @@ impl IntoProto for RequestItem {
 
                 out.set_get_transactions_request(req);
             }
+            RequestItem::GetAccountStateAtVersion { address, version } => {
+                let mut req = GetAccountStateAtVersionRequest::new();
+                req.set_address(address.into_proto());
+                req.set_version(version);
+
+                out.set_get_account_state_at_version_request(req);
+            }
+            RequestItem::GetEventsByVersionRange {
+                start_version,
+                limit,
+                access_path_prefix,
+            } => {
+                let mut req = GetEventsByVersionRangeRequest::new();
+                req.set_start_version(start_version);
+                req.set_limit(limit);
+                if let Some(access_path_prefix) = access_path_prefix {
+                    req.set_access_path_prefix(access_path_prefix.into_proto());
+                }
+
+                out.set_get_events_by_version_range_request(req);
+            }
         }
         out
     }
@@ -520,6 +661,12 @@ pub enum ResponseItem {
     GetTransactions {
         txn_list_with_proof: TransactionListWithProof,
     },
+    GetAccountStateAtVersion {
+        account_state_with_proof: AccountStateWithProof,
+    },
+    GetEventsByVersionRange {
+        events_with_proof: Vec<EventWithProof>,
+    },
 }
 
 impl ResponseItem {
@@ -570,6 +717,22 @@ impl ResponseItem {
             _ => bail!("Not ResponseItem::GetTransactions."),
         }
     }
+
+    pub fn into_get_account_state_at_version_response(self) -> Result<AccountStateWithProof> {
+        match self {
+            ResponseItem::GetAccountStateAtVersion {
+                account_state_with_proof,
+            } => Ok(account_state_with_proof),
+            _ => bail!("Not ResponseItem::GetAccountStateAtVersion."),
+        }
+    }
+
+    pub fn into_get_events_by_version_range_response(self) -> Result<Vec<EventWithProof>> {
+        match self {
+            ResponseItem::GetEventsByVersionRange { events_with_proof } => Ok(events_with_proof),
+            _ => bail!("Not ResponseItem::GetEventsByVersionRange."),
+        }
+    }
 }
 
 impl FromProto for ResponseItem {
@@ -627,6 +790,25 @@ impl FromProto for ResponseItem {
             ResponseItem::GetTransactions {
                 txn_list_with_proof,
             }
+        } else if object.has_get_account_state_at_version_response() {
+            let account_state_with_proof = AccountStateWithProof::from_proto(
+                object
+                    .take_get_account_state_at_version_response()
+                    .take_account_state_with_proof(),
+            )?;
+
+            ResponseItem::GetAccountStateAtVersion {
+                account_state_with_proof,
+            }
+        } else if object.has_get_events_by_version_range_response() {
+            let events_with_proof = object
+                .take_get_events_by_version_range_response()
+                .take_events_with_proof()
+                .into_iter()
+                .map(EventWithProof::from_proto)
+                .collect::<Result<Vec<_>>>()?;
+
+            ResponseItem::GetEventsByVersionRange { events_with_proof }
         } else {
             bail!("Unknown ResponseItem type.")
         })
@@ -685,6 +867,25 @@ impl IntoProto for ResponseItem {
 
                 out.set_get_transactions_response(res)
             }
+            ResponseItem::GetAccountStateAtVersion {
+                account_state_with_proof,
+            } => {
+                let mut res = GetAccountStateAtVersionResponse::new();
+                res.set_account_state_with_proof(account_state_with_proof.into_proto());
+
+                out.set_get_account_state_at_version_response(res);
+            }
+            ResponseItem::GetEventsByVersionRange { events_with_proof } => {
+                let mut res = GetEventsByVersionRangeResponse::new();
+                res.set_events_with_proof(::protobuf::RepeatedField::from_vec(
+                    events_with_proof
+                        .into_iter()
+                        .map(EventWithProof::into_proto)
+                        .collect(),
+                ));
+
+                out.set_get_events_by_version_range_response(res);
+            }
         }
         out
     }
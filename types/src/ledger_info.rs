@@ -6,6 +6,7 @@
 use crate::{
     account_address::AccountAddress,
     transaction::Version,
+    validator_set::ValidatorSet,
     validator_verifier::{ValidatorVerifier, VerifyError},
 };
 use canonical_serialization::{CanonicalSerialize, CanonicalSerializer, SimpleSerializer};
@@ -40,9 +41,8 @@ use std::{
 /// LedgerInfo with the `version` being the latest version that will be committed if B gets 2f+1
 /// votes. It sets `consensus_data_hash` to represent B so that if those 2f+1 votes are gathered a
 /// QC is formed on B.
-#[derive(Clone, Debug, Eq, PartialEq, IntoProto, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
-#[ProtoType(crate::proto::ledger_info::LedgerInfo)]
 pub struct LedgerInfo {
     /// The version of latest transaction in the ledger.
     version: Version,
@@ -67,6 +67,11 @@ pub struct LedgerInfo {
     // they can be certain that their transaction will never be included in a block in the future
     // (assuming that their transaction has not yet been included)
     timestamp_usecs: u64,
+
+    /// An epoch change ledger info carries the set of validators of the next epoch: it is the
+    /// last ledger info signed by the validators of `epoch_num`.
+    #[serde(default)]
+    next_validator_set: Option<ValidatorSet>,
 }
 
 impl Display for LedgerInfo {
@@ -92,6 +97,7 @@ impl LedgerInfo {
         consensus_block_id: HashValue,
         epoch_num: u64,
         timestamp_usecs: u64,
+        next_validator_set: Option<ValidatorSet>,
     ) -> Self {
         LedgerInfo {
             version,
@@ -100,6 +106,7 @@ impl LedgerInfo {
             consensus_block_id,
             epoch_num,
             timestamp_usecs,
+            next_validator_set,
         }
     }
 
@@ -134,6 +141,11 @@ impl LedgerInfo {
         self.timestamp_usecs
     }
 
+    /// Returns the validator set of the next epoch if this `LedgerInfo` ends its epoch.
+    pub fn next_validator_set(&self) -> Option<&ValidatorSet> {
+        self.next_validator_set.as_ref()
+    }
+
     /// A ledger info is nominal if it's not certifying any real version.
     pub fn is_zero(&self) -> bool {
         self.version == 0
@@ -143,7 +155,12 @@ impl LedgerInfo {
 impl FromProto for LedgerInfo {
     type ProtoType = crate::proto::ledger_info::LedgerInfo;
 
-    fn from_proto(proto: Self::ProtoType) -> Result<Self> {
+    fn from_proto(mut proto: Self::ProtoType) -> Result<Self> {
+        let next_validator_set = if proto.has_next_validator_set() {
+            Some(ValidatorSet::from_proto(proto.take_next_validator_set())?)
+        } else {
+            None
+        };
         Ok(LedgerInfo::new(
             proto.get_version(),
             HashValue::from_slice(proto.get_transaction_accumulator_hash())?,
@@ -151,10 +168,29 @@ impl FromProto for LedgerInfo {
             HashValue::from_slice(proto.get_consensus_block_id())?,
             proto.get_epoch_num(),
             proto.get_timestamp_usecs(),
+            next_validator_set,
         ))
     }
 }
 
+impl IntoProto for LedgerInfo {
+    type ProtoType = crate::proto::ledger_info::LedgerInfo;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        proto.set_version(self.version);
+        proto.set_transaction_accumulator_hash(self.transaction_accumulator_hash.to_vec());
+        proto.set_consensus_data_hash(self.consensus_data_hash.to_vec());
+        proto.set_consensus_block_id(self.consensus_block_id.to_vec());
+        proto.set_epoch_num(self.epoch_num);
+        proto.set_timestamp_usecs(self.timestamp_usecs);
+        if let Some(next_validator_set) = self.next_validator_set {
+            proto.set_next_validator_set(next_validator_set.into_proto());
+        }
+        proto
+    }
+}
+
 impl CanonicalSerialize for LedgerInfo {
     fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
         serializer
@@ -164,6 +200,11 @@ impl CanonicalSerialize for LedgerInfo {
             .encode_raw_bytes(self.consensus_block_id.as_ref())?
             .encode_u64(self.epoch_num)?
             .encode_u64(self.timestamp_usecs)?;
+        // Only epoch change ledger infos serialize the next validator set, which keeps the hashes
+        // of the other ones, signed before the field existed, unchanged.
+        if let Some(next_validator_set) = &self.next_validator_set {
+            serializer.encode_struct(next_validator_set)?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,50 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::unit_arg)]
+
+#[cfg(any(test, feature = "testing"))]
+use proptest_derive::Arbitrary;
+use proto_conv::{FromProto, IntoProto};
+
+/// The values of the ledger counters of storage as of a version: how much data the transactions
+/// up to that version created or made stale. Storage records them at the last version of each
+/// batch of transactions it saves, so they hold for the versions up to the next record.
+///
+/// They are not part of the ledger, so they come without proof.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, FromProto, IntoProto)]
+#[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
+#[ProtoType(crate::proto::ledger_statistics::LedgerStatistics)]
+pub struct LedgerStatistics {
+    /// The version the counters are as of.
+    pub version: u64,
+    /// The number of events emitted.
+    pub events_created: u64,
+    /// The number of leaves, i.e. account states, added to the state tree.
+    pub new_state_leaves: u64,
+    /// The number of leaves of the state tree replaced by newer ones.
+    pub stale_state_leaves: u64,
+    /// The number of nodes added to the state tree.
+    pub new_state_nodes: u64,
+    /// The number of nodes of the state tree replaced by newer ones.
+    pub stale_state_nodes: u64,
+}
+
+impl LedgerStatistics {
+    /// The number of leaves of the state tree at `version`.
+    pub fn live_state_leaves(&self) -> u64 {
+        self.new_state_leaves
+            .saturating_sub(self.stale_state_leaves)
+    }
+
+    /// The number of nodes of the state tree at `version`, which is what storing the latest state
+    /// takes.
+    pub fn live_state_nodes(&self) -> u64 {
+        self.new_state_nodes.saturating_sub(self.stale_state_nodes)
+    }
+
+    /// The number of accounts at `version`: each is a leaf of the state tree.
+    pub fn num_accounts(&self) -> u64 {
+        self.live_state_leaves()
+    }
+}
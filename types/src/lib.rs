@@ -10,6 +10,7 @@ pub mod contract_event;
 pub mod get_with_proof;
 pub mod language_storage;
 pub mod ledger_info;
+pub mod ledger_statistics;
 pub mod proof;
 #[cfg(any(test, feature = "testing"))]
 pub mod proptest_types;
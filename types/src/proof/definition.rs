@@ -245,6 +245,60 @@ impl IntoProto for SparseMerkleProof {
     }
 }
 
+/// A proof that can be used to authenticate a range of consecutive leaves, from the leftmost leaf
+/// to a given one, in a Sparse Merkle Tree given trusted root hash. Whoever holds all the leaves in
+/// the range can compute the siblings on the left of the path from the root to the rightmost leaf
+/// of the range, so only the siblings on the right are provided.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
+pub struct SparseMerkleRangeProof {
+    /// The siblings on the right of the path from the root to the rightmost leaf, including the
+    /// default ones. Siblings near the root are at the beginning of the vector.
+    right_siblings: Vec<HashValue>,
+}
+
+impl SparseMerkleRangeProof {
+    /// Constructs a new `SparseMerkleRangeProof` using the siblings on the right.
+    pub fn new(right_siblings: Vec<HashValue>) -> Self {
+        Self { right_siblings }
+    }
+
+    /// Returns the siblings on the right.
+    pub fn right_siblings(&self) -> &[HashValue] {
+        &self.right_siblings
+    }
+}
+
+impl FromProto for SparseMerkleRangeProof {
+    type ProtoType = crate::proto::proof::SparseMerkleRangeProof;
+
+    fn from_proto(mut proto_proof: Self::ProtoType) -> Result<Self> {
+        let right_siblings = proto_proof
+            .take_right_siblings()
+            .into_iter()
+            .map(|hash_bytes| HashValue::from_slice(&hash_bytes))
+            .collect::<Result<Vec<_>>>()?;
+        ensure!(
+            right_siblings.len() <= HashValue::LENGTH_IN_BITS,
+            "Too many ({}) siblings in the proof.",
+            right_siblings.len(),
+        );
+        Ok(Self::new(right_siblings))
+    }
+}
+
+impl IntoProto for SparseMerkleRangeProof {
+    type ProtoType = crate::proto::proof::SparseMerkleRangeProof;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto_proof = Self::ProtoType::new();
+        for sibling in self.right_siblings {
+            proto_proof.mut_right_siblings().push(sibling.to_vec());
+        }
+        proto_proof
+    }
+}
+
 /// A proof that can be used to show that two Merkle accumulators are consistent -- the big one can
 /// be obtained by appending certain leaves to the small one. For example, at some point in time a
 /// client knows that the root hash of the ledger at version 10 is `old_root` (it could be a
@@ -29,7 +29,7 @@ use std::{collections::VecDeque, marker::PhantomData};
 
 pub use crate::proof::definition::{
     AccountStateProof, AccumulatorConsistencyProof, AccumulatorProof, EventProof,
-    SignedTransactionProof, SparseMerkleProof,
+    SignedTransactionProof, SparseMerkleProof, SparseMerkleRangeProof,
 };
 
 /// Verifies that a `SignedTransaction` with hash value of `signed_transaction_hash`
@@ -493,6 +493,66 @@ pub fn verify_sparse_merkle_element(
     Ok(())
 }
 
+/// Verifies that the leaves from the leftmost one to `rightmost_key` are all the leaves of a
+/// Sparse Merkle Tree on their side, using the provided proof. `left_siblings` are the siblings on
+/// the left of the path from the root to the leaf of `rightmost_key`, computed from the leaves in
+/// the range. Siblings near the root are at the beginning of the vector.
+pub fn verify_sparse_merkle_range(
+    expected_root_hash: HashValue,
+    rightmost_key: HashValue,
+    rightmost_value_hash: HashValue,
+    left_siblings: &[HashValue],
+    proof: &SparseMerkleRangeProof,
+) -> Result<()> {
+    let right_siblings = proof.right_siblings();
+    let num_siblings = left_siblings.len() + right_siblings.len();
+    ensure!(
+        num_siblings <= HashValue::LENGTH_IN_BITS,
+        "Sparse Merkle Tree range proof has more than {} ({}) siblings.",
+        HashValue::LENGTH_IN_BITS,
+        num_siblings
+    );
+    // The leaf of `rightmost_key` is a right child wherever there is a sibling on the left.
+    let num_right_children = rightmost_key
+        .iter_bits()
+        .take(num_siblings)
+        .filter(|bit| *bit)
+        .count();
+    ensure!(
+        num_right_children == left_siblings.len(),
+        "Expected {} siblings on the left. Got {}.",
+        num_right_children,
+        left_siblings.len()
+    );
+
+    let mut left_siblings = left_siblings.iter().rev();
+    let mut right_siblings = right_siblings.iter().rev();
+    let actual_root_hash = rightmost_key
+        .iter_bits()
+        .rev()
+        .skip(HashValue::LENGTH_IN_BITS - num_siblings)
+        .fold(
+            SparseMerkleLeafNode::new(rightmost_key, rightmost_value_hash).hash(),
+            |hash, bit| {
+                if bit {
+                    let sibling = left_siblings.next().expect("Counted above.");
+                    SparseMerkleInternalNode::new(*sibling, hash).hash()
+                } else {
+                    let sibling = right_siblings.next().expect("Counted above.");
+                    SparseMerkleInternalNode::new(hash, *sibling).hash()
+                }
+            },
+        );
+    ensure!(
+        actual_root_hash == expected_root_hash,
+        "Root hashes do not match. Actual root hash: {:x}. Expected root hash: {:x}.",
+        actual_root_hash,
+        expected_root_hash
+    );
+
+    Ok(())
+}
+
 pub struct MerkleTreeInternalNode<H> {
     left_child: HashValue,
     right_child: HashValue,
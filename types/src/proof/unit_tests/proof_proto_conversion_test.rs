@@ -4,7 +4,7 @@
 use crate::proof::{
     definition::bitmap::{AccumulatorBitmap, SparseMerkleBitmap},
     AccountStateProof, AccumulatorConsistencyProof, AccumulatorProof, EventProof,
-    SignedTransactionProof, SparseMerkleProof,
+    SignedTransactionProof, SparseMerkleProof, SparseMerkleRangeProof,
 };
 use crypto::{
     hash::{TestOnlyHash, ACCUMULATOR_PLACEHOLDER_HASH, SPARSE_MERKLE_PLACEHOLDER_HASH},
@@ -314,6 +314,13 @@ proptest! {
         assert_protobuf_encode_decode(&proof);
     }
 
+    #[test]
+    fn test_sparse_merkle_range_protobuf_conversion_roundtrip(
+        proof in any::<SparseMerkleRangeProof>(),
+    ) {
+        assert_protobuf_encode_decode(&proof);
+    }
+
     #[test]
     fn test_accumulator_consistency_protobuf_conversion_roundtrip(
         proof in any::<AccumulatorConsistencyProof>(),
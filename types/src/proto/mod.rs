@@ -9,6 +9,7 @@ pub mod events;
 pub mod get_with_proof;
 pub mod language_storage;
 pub mod ledger_info;
+pub mod ledger_statistics;
 pub mod proof;
 pub mod transaction;
 pub mod transaction_info;
@@ -26,6 +26,7 @@ use crypto::{
         CryptoHash, CryptoHasher, EventAccumulatorHasher, RawTransactionHasher,
         SignedTransactionHasher, TransactionInfoHasher,
     },
+    multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
     traits::*,
     HashValue,
 };
@@ -175,8 +176,10 @@ impl RawTransaction {
         let signature = private_key.sign_message(&hash);
         Ok(SignatureCheckedTransaction(SignedTransaction {
             raw_txn: self,
-            public_key,
-            signature,
+            authenticator: TransactionAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            },
             raw_txn_bytes,
         }))
     }
@@ -402,6 +405,73 @@ impl TransactionPayloadType {
 
 impl ::std::marker::Copy for TransactionPayloadType {}
 
+/// Proof that a transaction was authorized by the sender: a public key whose hash is the
+/// authentication key of the sender's account, and the signature of the transaction under this
+/// key.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum TransactionAuthenticator {
+    /// Single Ed25519 signature.
+    Ed25519 {
+        public_key: Ed25519PublicKey,
+        signature: Ed25519Signature,
+    },
+    /// K-of-n Ed25519 multi-signature.
+    MultiEd25519 {
+        public_key: MultiEd25519PublicKey,
+        signature: MultiEd25519Signature,
+    },
+}
+
+impl TransactionAuthenticator {
+    /// Decode an authenticator from the encoding of its public key and signature. The schemes are
+    /// told apart by the length of the public key: an Ed25519 public key is 32 bytes long, a
+    /// multi-signature one is `32 * n + 1` bytes long.
+    pub fn from_bytes(public_key: &[u8], signature: &[u8]) -> Result<Self> {
+        Ok(if public_key.len() == ED25519_PUBLIC_KEY_LENGTH {
+            TransactionAuthenticator::Ed25519 {
+                public_key: Ed25519PublicKey::try_from(public_key)?,
+                signature: Ed25519Signature::try_from(signature)?,
+            }
+        } else {
+            TransactionAuthenticator::MultiEd25519 {
+                public_key: MultiEd25519PublicKey::try_from(public_key)?,
+                signature: MultiEd25519Signature::try_from(signature)?,
+            }
+        })
+    }
+
+    /// Encoding of the public key. This is the preimage of the authentication key of the sender:
+    /// the transaction prologue checks that its sha3 matches the authentication key.
+    pub fn public_key_bytes(&self) -> Vec<u8> {
+        match self {
+            TransactionAuthenticator::Ed25519 { public_key, .. } => public_key.to_bytes().to_vec(),
+            TransactionAuthenticator::MultiEd25519 { public_key, .. } => public_key.to_bytes(),
+        }
+    }
+
+    /// Encoding of the signature.
+    pub fn signature_bytes(&self) -> Vec<u8> {
+        match self {
+            TransactionAuthenticator::Ed25519 { signature, .. } => signature.to_bytes().to_vec(),
+            TransactionAuthenticator::MultiEd25519 { signature, .. } => signature.to_bytes(),
+        }
+    }
+
+    /// Verify the signature of `message` under the public key.
+    pub fn verify_signature(&self, message: &HashValue) -> Result<()> {
+        match self {
+            TransactionAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            } => public_key.verify_signature(message, signature),
+            TransactionAuthenticator::MultiEd25519 {
+                public_key,
+                signature,
+            } => public_key.verify_signature(message, signature),
+        }
+    }
+}
+
 /// A transaction that has been signed.
 ///
 /// A `SignedTransaction` is a single transaction that can be atomically executed. Clients submit
@@ -415,12 +485,10 @@ pub struct SignedTransaction {
     /// The raw transaction
     raw_txn: RawTransaction,
 
-    /// Sender's public key. When checking the signature, we first need to check whether this key
-    /// is indeed the pre-image of the pubkey hash stored under sender's account.
-    public_key: Ed25519PublicKey,
-
-    /// Signature of the transaction that correspond to the public key
-    signature: Ed25519Signature,
+    /// Sender's public key and signature of the transaction that correspond to the public key.
+    /// When checking the signature, we first need to check whether this key is indeed the
+    /// pre-image of the pubkey hash stored under sender's account.
+    authenticator: TransactionAuthenticator,
 
     // The original raw bytes from the protobuf are also stored here so that we use
     // these bytes when generating the canonical serialization of the SignedTransaction struct
@@ -461,11 +529,10 @@ impl fmt::Debug for SignedTransaction {
             f,
             "SignedTransaction {{ \n \
              {{ raw_txn: {:#?}, \n \
-             public_key: {:#?}, \n \
-             signature: {:#?}, \n \
+             authenticator: {:#?}, \n \
              }} \n \
              }}",
-            self.raw_txn, self.public_key, self.signature,
+            self.raw_txn, self.authenticator,
         )
     }
 }
@@ -475,22 +542,91 @@ impl SignedTransaction {
         raw_txn: RawTransaction,
         public_key: Ed25519PublicKey,
         signature: Ed25519Signature,
+    ) -> SignedTransaction {
+        Self::craft_with_authenticator(
+            raw_txn,
+            TransactionAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            },
+        )
+    }
+
+    /// Crafts a transaction sent by an account controlled by a multi-signature key. The signature
+    /// may hold fewer signatures than the threshold of the key, for the co-signers to add theirs
+    /// with [`SignedTransaction::add_cosigner_signature`].
+    pub fn craft_multi_signed_transaction_for_client(
+        raw_txn: RawTransaction,
+        public_key: MultiEd25519PublicKey,
+        signature: MultiEd25519Signature,
+    ) -> SignedTransaction {
+        Self::craft_with_authenticator(
+            raw_txn,
+            TransactionAuthenticator::MultiEd25519 {
+                public_key,
+                signature,
+            },
+        )
+    }
+
+    fn craft_with_authenticator(
+        raw_txn: RawTransaction,
+        authenticator: TransactionAuthenticator,
     ) -> SignedTransaction {
         SignedTransaction {
             raw_txn: raw_txn.clone(),
-            public_key,
-            signature,
+            authenticator,
             // In real world raw_txn should be derived from raw_txn_bytes, not the opposite.
             raw_txn_bytes: raw_txn.into_proto_bytes().expect("Should convert."),
         }
     }
 
-    pub fn public_key(&self) -> Ed25519PublicKey {
-        self.public_key.clone()
+    pub fn authenticator(&self) -> &TransactionAuthenticator {
+        &self.authenticator
     }
 
-    pub fn signature(&self) -> Ed25519Signature {
-        self.signature.clone()
+    /// The preimage of the authentication key of the sender, see
+    /// [`TransactionAuthenticator::public_key_bytes`].
+    pub fn authentication_key_preimage(&self) -> Vec<u8> {
+        self.authenticator.public_key_bytes()
+    }
+
+    /// The hash of the raw transaction bytes, which is the message signed by the sender.
+    pub fn signing_message(&self) -> HashValue {
+        RawTransactionBytes(&self.raw_txn_bytes).hash()
+    }
+
+    /// Add the signature of a co-signer of a multi-signature transaction, identified by its
+    /// public key. The signature is checked against the transaction.
+    pub fn add_cosigner_signature(
+        &mut self,
+        cosigner_public_key: &Ed25519PublicKey,
+        cosigner_signature: Ed25519Signature,
+    ) -> Result<()> {
+        let message = self.signing_message();
+        match &mut self.authenticator {
+            TransactionAuthenticator::MultiEd25519 {
+                public_key,
+                signature,
+            } => {
+                let index = public_key
+                    .public_keys()
+                    .iter()
+                    .position(|key| key == cosigner_public_key)
+                    .ok_or_else(|| {
+                        format_err!(
+                            "{:?} is not a key of the multi-signature of the sender",
+                            cosigner_public_key
+                        )
+                    })?;
+                cosigner_public_key.verify_signature(&message, &cosigner_signature)?;
+                signature.add_signature(cosigner_signature, index as u8)?;
+                Ok(())
+            }
+            TransactionAuthenticator::Ed25519 { .. } => {
+                bail!("Transaction is not a multi-signature transaction")
+            }
+        }
     }
 
     pub fn sender(&self) -> AccountAddress {
@@ -528,8 +664,8 @@ impl SignedTransaction {
     /// Checks that the signature of given transaction. Returns `Ok(SignatureCheckedTransaction)` if
     /// the signature is valid.
     pub fn check_signature(self) -> Result<SignatureCheckedTransaction> {
-        let hash = RawTransactionBytes(&self.raw_txn_bytes).hash();
-        self.public_key.verify_signature(&hash, &self.signature)?;
+        self.authenticator
+            .verify_signature(&self.signing_message())?;
         Ok(SignatureCheckedTransaction(self))
     }
 
@@ -537,12 +673,10 @@ impl SignedTransaction {
         format!(
             "SignedTransaction {{ \n \
              raw_txn: {}, \n \
-             public_key: {:#?}, \n \
-             signature: {:#?}, \n \
+             authenticator: {:#?}, \n \
              }}",
             self.raw_txn.format_for_client(get_transaction_name),
-            self.public_key,
-            self.signature,
+            self.authenticator,
         )
     }
 }
@@ -578,8 +712,10 @@ impl FromProto for SignedTransaction {
 
         let t = SignedTransaction {
             raw_txn: RawTransaction::from_proto(proto_raw_transaction)?,
-            public_key: Ed25519PublicKey::try_from(txn.get_sender_public_key())?,
-            signature: Ed25519Signature::try_from(txn.get_sender_signature())?,
+            authenticator: TransactionAuthenticator::from_bytes(
+                txn.get_sender_public_key(),
+                txn.get_sender_signature(),
+            )?,
             raw_txn_bytes: txn.raw_txn_bytes,
         };
 
@@ -595,8 +731,8 @@ impl IntoProto for SignedTransaction {
     fn into_proto(self) -> Self::ProtoType {
         let mut transaction = Self::ProtoType::new();
         transaction.set_raw_txn_bytes(self.raw_txn_bytes);
-        transaction.set_sender_public_key(self.public_key.to_bytes().to_vec());
-        transaction.set_sender_signature(self.signature.to_bytes().to_vec());
+        transaction.set_sender_public_key(self.authenticator.public_key_bytes());
+        transaction.set_sender_signature(self.authenticator.signature_bytes());
         transaction
     }
 }
@@ -718,8 +854,8 @@ impl CanonicalSerialize for SignedTransaction {
     fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
         serializer
             .encode_variable_length_bytes(&self.raw_txn_bytes)?
-            .encode_variable_length_bytes(&self.public_key.to_bytes())?
-            .encode_variable_length_bytes(&self.signature.to_bytes())?;
+            .encode_variable_length_bytes(&self.authenticator.public_key_bytes())?
+            .encode_variable_length_bytes(&self.authenticator.signature_bytes())?;
         Ok(())
     }
 }
@@ -738,8 +874,10 @@ impl CanonicalDeserialize for SignedTransaction {
 
         Ok(SignedTransaction {
             raw_txn: RawTransaction::from_proto(proto_raw_transaction)?,
-            public_key: Ed25519PublicKey::try_from(&public_key_bytes[..]).unwrap(),
-            signature: Ed25519Signature::try_from(&signature_bytes[..]).unwrap(),
+            authenticator: TransactionAuthenticator::from_bytes(
+                &public_key_bytes,
+                &signature_bytes,
+            )?,
             raw_txn_bytes,
         })
     }
@@ -31,6 +31,20 @@ fn access_path_ord() {
     assert!(ap3 < ap4);
 }
 
+#[test]
+fn test_access_path_starts_with() {
+    let address = AccountAddress::new([1u8; ADDRESS_LENGTH]);
+    let ap = AccessPath::new(address, b"/foo/bar".to_vec());
+    assert!(ap.starts_with(&AccessPath::new(address, vec![])));
+    assert!(ap.starts_with(&AccessPath::new(address, b"/foo/".to_vec())));
+    assert!(ap.starts_with(&ap));
+    assert!(!ap.starts_with(&AccessPath::new(address, b"/foo/bar/".to_vec())));
+    assert!(!ap.starts_with(&AccessPath::new(
+        AccountAddress::new([2u8; ADDRESS_LENGTH]),
+        vec![]
+    )));
+}
+
 #[test]
 fn test_access_path_protobuf_conversion() {
     let address = AccountAddress::new([1u8; ADDRESS_LENGTH]);
@@ -1,8 +1,11 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::ledger_info::{LedgerInfo, LedgerInfoWithSignatures};
-use crypto::ed25519::*;
+use crate::{
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_set::ValidatorSet,
+};
+use crypto::{ed25519::*, hash::CryptoHash};
 use proptest::prelude::*;
 use proto_conv::test_helper::assert_protobuf_encode_decode;
 
@@ -18,6 +21,28 @@ proptest! {
     ) {
         assert_protobuf_encode_decode(&ledger_info_with_signatures);
     }
+
+    #[test]
+    fn test_ledger_info_hash_covers_next_validator_set(
+        ledger_info in any::<LedgerInfo>(),
+        next_validator_set in any::<ValidatorSet>(),
+    ) {
+        let with_next_validator_set = |next_validator_set| {
+            LedgerInfo::new(
+                ledger_info.version(),
+                ledger_info.transaction_accumulator_hash(),
+                ledger_info.consensus_data_hash(),
+                ledger_info.consensus_block_id(),
+                ledger_info.epoch_num(),
+                ledger_info.timestamp_usecs(),
+                next_validator_set,
+            )
+        };
+        prop_assert_ne!(
+            with_next_validator_set(None).hash(),
+            with_next_validator_set(Some(next_validator_set)).hash()
+        );
+    }
 }
 
 proptest! {
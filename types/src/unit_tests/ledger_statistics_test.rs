@@ -0,0 +1,28 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ledger_statistics::LedgerStatistics;
+use proptest::prelude::*;
+use proto_conv::test_helper::assert_protobuf_encode_decode;
+
+#[test]
+fn test_derived_totals() {
+    let statistics = LedgerStatistics {
+        version: 10,
+        events_created: 20,
+        new_state_leaves: 15,
+        stale_state_leaves: 5,
+        new_state_nodes: 60,
+        stale_state_nodes: 25,
+    };
+    assert_eq!(statistics.live_state_leaves(), 10);
+    assert_eq!(statistics.num_accounts(), 10);
+    assert_eq!(statistics.live_state_nodes(), 35);
+}
+
+proptest! {
+    #[test]
+    fn test_ledger_statistics(ledger_statistics in any::<LedgerStatistics>()) {
+        assert_protobuf_encode_decode(&ledger_statistics);
+    }
+}
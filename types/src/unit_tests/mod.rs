@@ -7,6 +7,7 @@ mod contract_event_proto_conversion_test;
 mod get_with_proof_proto_conversion_test;
 mod language_storage_test;
 mod ledger_info_proto_conversion_test;
+mod ledger_statistics_test;
 mod transaction_proto_conversion_test;
 mod transaction_test;
 mod validator_change_proto_conversion_test;
@@ -8,9 +8,14 @@ use crate::{
 use canonical_serialization::{
     CanonicalDeserializer, CanonicalSerializer, SimpleDeserializer, SimpleSerializer,
 };
-use crypto::ed25519::*;
+use crypto::{
+    ed25519::*,
+    multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+    traits::SigningKey,
+};
 use proptest::prelude::*;
 use proto_conv::{FromProto, IntoProto};
+use rand::{rngs::StdRng, SeedableRng};
 use std::convert::TryFrom;
 
 #[test]
@@ -36,6 +41,52 @@ fn test_invalid_signature() {
         .expect_err("signature checking should fail");
 }
 
+#[test]
+fn test_multi_signature_cosigning() {
+    let keypairs: Vec<_> = (0..3u8)
+        .map(|seed| compat::generate_keypair(&mut StdRng::from_seed([seed; 32])))
+        .collect();
+    let public_key = MultiEd25519PublicKey::new(
+        keypairs
+            .iter()
+            .map(|(_, public_key)| public_key.clone())
+            .collect(),
+        2,
+    )
+    .unwrap();
+    let raw_txn = RawTransaction::new(
+        AccountAddress::from_authentication_key_preimage(&public_key.to_bytes()),
+        0,
+        Program::new(vec![], vec![], vec![]),
+        0,
+        0,
+        std::time::Duration::new(0, 0),
+    );
+    let mut txn = SignedTransaction::craft_multi_signed_transaction_for_client(
+        raw_txn,
+        public_key.clone(),
+        MultiEd25519Signature::new(vec![]).unwrap(),
+    );
+    assert_eq!(txn.authentication_key_preimage(), public_key.to_bytes());
+
+    let message = txn.signing_message();
+    txn.add_cosigner_signature(&keypairs[2].1, keypairs[2].0.sign_message(&message))
+        .unwrap();
+    assert!(txn.clone().check_signature().is_err());
+    // A signature by another key is rejected.
+    assert!(txn
+        .add_cosigner_signature(&keypairs[1].1, keypairs[0].0.sign_message(&message))
+        .is_err());
+    txn.add_cosigner_signature(&keypairs[0].1, keypairs[0].0.sign_message(&message))
+        .unwrap();
+
+    // The multi-signature survives both encodings.
+    let txn = SignedTransaction::from_proto(txn.into_proto()).unwrap();
+    let serialized_bytes = SimpleSerializer::<Vec<u8>>::serialize(&txn).unwrap();
+    let txn: SignedTransaction = SimpleDeserializer::deserialize(&serialized_bytes).unwrap();
+    assert!(txn.check_signature().is_ok());
+}
+
 proptest! {
     #[test]
     fn test_sig(raw_txn in any::<RawTransaction>(), (sk1, pk1) in compat::keypair_strategy()) {
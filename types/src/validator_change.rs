@@ -3,14 +3,52 @@
 
 #![allow(clippy::unit_arg)]
 
-use crate::{contract_event::EventWithProof, ledger_info::LedgerInfoWithSignatures};
+use crate::{
+    ledger_info::LedgerInfoWithSignatures, validator_set::ValidatorSet,
+    validator_verifier::ValidatorVerifier,
+};
 use crypto::*;
+use failure::prelude::*;
 use proto_conv::{FromProto, IntoProto};
 
+/// A change of the validator set, proven by the ledger info ending the epoch: it is signed by the
+/// validators of the epoch it ends and carries the validator set of the next one, so that a client
+/// trusting those validators can learn the new ones.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ValidatorChangeEventWithProof<Sig> {
     ledger_info_with_sigs: LedgerInfoWithSignatures<Sig>,
-    event_with_proof: EventWithProof,
+}
+
+impl<Sig: Signature> ValidatorChangeEventWithProof<Sig> {
+    /// Constructor.
+    pub fn new(ledger_info_with_sigs: LedgerInfoWithSignatures<Sig>) -> Self {
+        ValidatorChangeEventWithProof {
+            ledger_info_with_sigs,
+        }
+    }
+
+    pub fn ledger_info_with_sigs(&self) -> &LedgerInfoWithSignatures<Sig> {
+        &self.ledger_info_with_sigs
+    }
+
+    /// Verifies the ledger info with the validators of the current epoch. Returns the validator
+    /// set of the next epoch carried by the ledger info.
+    pub fn verify(
+        &self,
+        validator_verifier: &ValidatorVerifier<Sig::VerifyingKeyMaterial>,
+    ) -> Result<ValidatorSet> {
+        let ledger_info = self.ledger_info_with_sigs.ledger_info();
+        // The genesis ledger info is not signed, it can't prove any change.
+        ensure!(
+            ledger_info.version() > 0,
+            "Validator change committed by the genesis ledger info."
+        );
+        let validator_set = ledger_info
+            .next_validator_set()
+            .ok_or_else(|| format_err!("{} doesn't end an epoch.", ledger_info))?;
+        self.ledger_info_with_sigs.verify(validator_verifier)?;
+        Ok(validator_set.clone())
+    }
 }
 
 impl<Sig: Signature> IntoProto for ValidatorChangeEventWithProof<Sig> {
@@ -19,7 +57,6 @@ impl<Sig: Signature> IntoProto for ValidatorChangeEventWithProof<Sig> {
     fn into_proto(self) -> Self::ProtoType {
         let mut out = crate::proto::validator_change::ValidatorChangeEventWithProof::new();
         out.set_ledger_info_with_sigs(self.ledger_info_with_sigs.into_proto());
-        out.set_event_with_proof(self.event_with_proof.into_proto());
         out
     }
 }
@@ -32,7 +69,6 @@ impl<Sig: Signature> FromProto for ValidatorChangeEventWithProof<Sig> {
             ledger_info_with_sigs: LedgerInfoWithSignatures::from_proto(
                 object.take_ledger_info_with_sigs(),
             )?,
-            event_with_proof: EventWithProof::from_proto(object.take_event_with_proof())?,
         })
     }
 }
@@ -46,10 +82,9 @@ use proptest::prelude::*;
 prop_compose! {
     fn arb_validator_change_event_with_proof()(
         ledger_info_with_sigs in any::<LedgerInfoWithSignatures<Ed25519Signature>>(),
-        event_with_proof in any::<EventWithProof>(),
     ) -> ValidatorChangeEventWithProof<Ed25519Signature> {
         ValidatorChangeEventWithProof{
-            ledger_info_with_sigs, event_with_proof
+            ledger_info_with_sigs
         }
     }
 }
@@ -6,14 +6,17 @@ use crate::{
     account_config::core_code_address,
     language_storage::StructTag,
     validator_public_keys::ValidatorPublicKeys,
+    validator_verifier::ValidatorVerifier,
 };
 use canonical_serialization::{
     CanonicalDeserialize, CanonicalDeserializer, CanonicalSerialize, CanonicalSerializer,
 };
+use crypto::ed25519::*;
 use failure::prelude::*;
 #[cfg(any(test, feature = "testing"))]
 use proptest_derive::Arbitrary;
 use proto_conv::{FromProto, IntoProto};
+use serde::{Deserialize, Serialize};
 
 pub const VALIDATOR_SET_MODULE_NAME: &str = "ValidatorSet";
 pub const VALIDATOR_SET_STRUCT_NAME: &str = "T";
@@ -31,7 +34,7 @@ pub(crate) fn validator_set_path() -> Vec<u8> {
     AccessPath::resource_access_vec(&validator_set_tag(), &Accesses::empty())
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
 pub struct ValidatorSet(Vec<ValidatorPublicKeys>);
 
@@ -46,6 +49,19 @@ impl ValidatorSet {
     }
 }
 
+impl From<&ValidatorSet> for ValidatorVerifier<Ed25519PublicKey> {
+    /// Verifies the signatures of the validators of the set with their consensus keys.
+    fn from(validator_set: &ValidatorSet) -> Self {
+        ValidatorVerifier::new(
+            validator_set
+                .payload()
+                .iter()
+                .map(|keys| (*keys.account_address(), keys.consensus_public_key().clone()))
+                .collect(),
+        )
+    }
+}
+
 impl CanonicalSerialize for ValidatorSet {
     fn serialize(&self, mut serializer: &mut impl CanonicalSerializer) -> Result<()> {
         // TODO: We do not use encode_vec and decode_vec because the VM serializes these
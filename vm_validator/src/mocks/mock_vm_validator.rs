@@ -7,8 +7,9 @@ use state_view::StateView;
 use std::convert::TryFrom;
 use types::{
     account_address::{AccountAddress, ADDRESS_LENGTH},
-    transaction::SignedTransaction,
-    vm_error::{VMStatus, VMValidationStatus},
+    transaction::{SignedTransaction, TransactionOutput, TransactionStatus, Version},
+    vm_error::{ExecutionStatus, VMStatus, VMValidationStatus},
+    write_set::WriteSet,
 };
 use vm_runtime::VMVerifier;
 
@@ -80,4 +81,19 @@ impl TransactionValidation for MockVMValidator {
         };
         Box::new(ok(ret))
     }
+    fn simulate_transaction(
+        &self,
+        txn: SignedTransaction,
+    ) -> Box<dyn Future<Item = (TransactionOutput, Version), Error = failure::Error> + Send> {
+        Box::new(self.validate_transaction(txn).map(|validation_status| {
+            let status = match validation_status {
+                Some(vm_status) => TransactionStatus::Discard(vm_status),
+                None => TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed)),
+            };
+            (
+                TransactionOutput::new(WriteSet::default(), vec![], 0, status),
+                0,
+            )
+        }))
+    }
 }
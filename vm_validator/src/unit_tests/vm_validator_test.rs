@@ -19,8 +19,13 @@ use storage_service::start_storage_service;
 use types::{
     account_address, account_config,
     test_helpers::transaction_test_helpers,
-    transaction::{Program, SignedTransaction, TransactionArgument, MAX_TRANSACTION_SIZE_IN_BYTES},
-    vm_error::{VMStatus, VMValidationStatus, VMVerificationError, VMVerificationStatus},
+    transaction::{
+        Program, SignedTransaction, TransactionArgument, TransactionStatus,
+        MAX_TRANSACTION_SIZE_IN_BYTES,
+    },
+    vm_error::{
+        ExecutionStatus, VMStatus, VMValidationStatus, VMVerificationError, VMVerificationStatus,
+    },
 };
 use vm_genesis::encode_transfer_program;
 
@@ -115,6 +120,35 @@ fn test_validate_transaction() {
     assert_eq!(ret, None);
 }
 
+#[test]
+fn test_simulate_transaction() {
+    let (config, keypair) = get_test_config();
+    let vm_validator = TestValidator::new(&config);
+
+    let address = account_config::association_address();
+    let receiver = account_address::AccountAddress::random();
+    let program = encode_transfer_program(&receiver, 100);
+    let signed_txn = transaction_test_helpers::get_test_signed_txn(
+        address,
+        0,
+        keypair.private_key,
+        keypair.public_key,
+        Some(program),
+    );
+    let (output, version) = vm_validator
+        .simulate_transaction(SignedTransaction::from_proto(signed_txn).unwrap())
+        .wait()
+        .unwrap();
+    assert_eq!(version, 0);
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed))
+    );
+    assert!(output.gas_used() > 0);
+    assert!(!output.write_set().is_empty());
+    assert_eq!(output.events().len(), 2);
+}
+
 #[test]
 fn test_validate_invalid_signature() {
     let (config, keypair) = get_test_config();
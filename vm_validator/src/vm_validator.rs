@@ -1,7 +1,7 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use config::config::NodeConfig;
+use config::config::{NodeConfig, VMConfig};
 use failure::prelude::*;
 use futures::future::{err, ok, Future};
 use scratchpad::SparseMerkleTree;
@@ -11,10 +11,10 @@ use types::{
     account_address::{AccountAddress, ADDRESS_LENGTH},
     account_config::get_account_resource_or_default,
     get_with_proof::{RequestItem, ResponseItem},
-    transaction::SignedTransaction,
+    transaction::{SignedTransaction, TransactionOutput, Version},
     vm_error::VMStatus,
 };
-use vm_runtime::{MoveVM, VMVerifier};
+use vm_runtime::{MoveVM, VMExecutor, VMVerifier};
 
 #[cfg(test)]
 #[path = "unit_tests/vm_validator_test.rs"]
@@ -27,12 +27,19 @@ pub trait TransactionValidation: Send + Sync {
         &self,
         _txn: SignedTransaction,
     ) -> Box<dyn Future<Item = Option<VMStatus>, Error = failure::Error> + Send>;
+    /// Execute a txn from client against the latest state without committing its output.
+    /// Returns the output along with the latest committed version.
+    fn simulate_transaction(
+        &self,
+        _txn: SignedTransaction,
+    ) -> Box<dyn Future<Item = (TransactionOutput, Version), Error = failure::Error> + Send>;
 }
 
 #[derive(Clone)]
 pub struct VMValidator {
     storage_read_client: Arc<dyn StorageRead>,
     vm: MoveVM,
+    vm_config: VMConfig,
 }
 
 impl VMValidator {
@@ -40,17 +47,15 @@ impl VMValidator {
         VMValidator {
             storage_read_client,
             vm: MoveVM::new(&config.vm_config),
+            vm_config: config.vm_config.clone(),
         }
     }
-}
-
-impl TransactionValidation for VMValidator {
-    type ValidationInstance = MoveVM;
 
-    fn validate_transaction(
+    /// Run `f` on a view of the latest state, along with the latest committed version.
+    fn with_latest_state_view<T>(
         &self,
-        txn: SignedTransaction,
-    ) -> Box<dyn Future<Item = Option<VMStatus>, Error = failure::Error> + Send> {
+        f: impl FnOnce(&VerifiedStateView, Version) -> T,
+    ) -> Result<T> {
         // TODO: For transaction validation, there are two options to go:
         // 1. Trust storage: there is no need to get root hash from storage here. We will
         // create another struct similar to `VerifiedStateView` that implements `StateView`
@@ -67,36 +72,60 @@ impl TransactionValidation for VMValidator {
         let address = AccountAddress::new([0xff; ADDRESS_LENGTH]);
         let item = RequestItem::GetAccountState { address };
 
-        match self
+        let (mut items, ledger_info_with_sigs, _) = self
             .storage_read_client
-            .update_to_latest_ledger(/* client_known_version = */ 0, vec![item])
-        {
-            Ok((mut items, ledger_info_with_sigs, _)) => {
-                if items.len() != 1 {
-                    return Box::new(err(format_err!(
-                        "Unexpected number of items ({}).",
-                        items.len()
-                    )));
-                }
+            .update_to_latest_ledger(/* client_known_version = */ 0, vec![item])?;
+        ensure!(
+            items.len() == 1,
+            "Unexpected number of items ({}).",
+            items.len()
+        );
 
-                match items.remove(0) {
-                    ResponseItem::GetAccountState {
-                        account_state_with_proof,
-                    } => {
-                        let transaction_info = account_state_with_proof.proof.transaction_info();
-                        let state_root = transaction_info.state_root_hash();
-                        let smt = SparseMerkleTree::new(state_root);
-                        let state_view = VerifiedStateView::new(
-                            Arc::clone(&self.storage_read_client),
-                            ledger_info_with_sigs.ledger_info().version() + 1,
-                            state_root,
-                            &smt,
-                        );
-                        Box::new(ok(self.vm.validate_transaction(txn, &state_view)))
-                    }
-                    _ => panic!("Unexpected item in response."),
-                }
+        match items.remove(0) {
+            ResponseItem::GetAccountState {
+                account_state_with_proof,
+            } => {
+                let transaction_info = account_state_with_proof.proof.transaction_info();
+                let state_root = transaction_info.state_root_hash();
+                let smt = SparseMerkleTree::new(state_root);
+                let version = ledger_info_with_sigs.ledger_info().version();
+                let state_view = VerifiedStateView::new(
+                    Arc::clone(&self.storage_read_client),
+                    version + 1,
+                    state_root,
+                    &smt,
+                );
+                Ok(f(&state_view, version))
             }
+            _ => panic!("Unexpected item in response."),
+        }
+    }
+}
+
+impl TransactionValidation for VMValidator {
+    type ValidationInstance = MoveVM;
+
+    fn validate_transaction(
+        &self,
+        txn: SignedTransaction,
+    ) -> Box<dyn Future<Item = Option<VMStatus>, Error = failure::Error> + Send> {
+        match self
+            .with_latest_state_view(|state_view, _| self.vm.validate_transaction(txn, state_view))
+        {
+            Ok(status) => Box::new(ok(status)),
+            Err(e) => Box::new(err(e)),
+        }
+    }
+
+    fn simulate_transaction(
+        &self,
+        txn: SignedTransaction,
+    ) -> Box<dyn Future<Item = (TransactionOutput, Version), Error = failure::Error> + Send> {
+        match self.with_latest_state_view(|state_view, version| {
+            let mut outputs = MoveVM::execute_block(vec![txn], &self.vm_config, state_view);
+            (outputs.remove(0), version)
+        }) {
+            Ok(output) => Box::new(ok(output)),
             Err(e) => Box::new(err(e)),
         }
     }